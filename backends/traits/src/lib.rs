@@ -212,6 +212,7 @@ pub trait OutgoingDataInterceptor: Send + Sync {
 
 /// A wrapper around the [`zenoh::net::Query`] allowing to call the
 /// OutgoingDataInterceptor (if any) before to send the reply
+#[derive(Clone)]
 pub struct Query {
     q: zenoh::net::Query,
     interceptor: Option<Arc<RwLock<Box<dyn OutgoingDataInterceptor>>>>,
@@ -248,6 +249,11 @@ impl Query {
         // Send reply
         self.q.reply_async(sample).await
     }
+
+    /// Sends an error as a reply to this Query
+    pub async fn reply_err(&self, value: Sample) {
+        self.q.reply_err_async(value).await
+    }
 }
 
 impl TryFrom<&Query> for Selector {
@@ -14,7 +14,11 @@
 
 //! Some useful functions for Backend/Storage implementations.
 
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use zenoh::net::utils::resource_name::*;
+use zenoh::net::{DataInfo, Sample};
+use zenoh::{Selector, Value, ZError, ZErrorKind, ZResult, PROP_STARTTIME, PROP_STOPTIME};
+use zenoh_util::{zerror, zerror2};
 
 /// Returns the longest prefix in a Path expressions that doesn't contain any '*' character.  
 /// This would be the common prefix of all keys stored in a storage using this Path expression.
@@ -105,6 +109,216 @@ pub fn get_sub_path_exprs<'a>(path_expr: &'a str, prefix: &str) -> Vec<&'a str>
     }
 }
 
+/// Returns the time range specified in a [`Selector`] via its `"starttime"` and `"stoptime"` properties,
+/// as a couple of start and stop bounds (both included). A bound is `None` if not specified.
+///
+/// Use this operation in [`Storage::on_query()`](crate::Storage::on_query()) implementation to select
+/// all the stored values with a timestamp in the time range, when [`Selector::has_time_range()`] is true.
+///
+/// # See also
+/// [`parse_time()`] for the accepted time formats.
+///
+/// # Examples:
+/// ```
+/// # use std::convert::TryFrom;
+/// # use std::time::{Duration, UNIX_EPOCH};
+/// # use zenoh::Selector;
+/// # use zenoh_backend_traits::utils::get_time_range;
+/// let selector = Selector::try_from("/demo/**?(starttime=0;stoptime=10)").unwrap();
+/// assert_eq!(
+///     (Some(UNIX_EPOCH), Some(UNIX_EPOCH + Duration::from_secs(10))),
+///     get_time_range(&selector).unwrap()
+/// );
+/// let selector = Selector::try_from("/demo/**?(starttime=now()-1h)").unwrap();
+/// assert!(get_time_range(&selector).unwrap().1.is_none());
+/// ```
+pub fn get_time_range(selector: &Selector) -> ZResult<(Option<SystemTime>, Option<SystemTime>)> {
+    let start = selector
+        .properties
+        .get(PROP_STARTTIME)
+        .map(|s| parse_time(s))
+        .transpose()?;
+    let stop = selector
+        .properties
+        .get(PROP_STOPTIME)
+        .map(|s| parse_time(s))
+        .transpose()?;
+    Ok((start, stop))
+}
+
+/// Parses a time as used in the `"starttime"` and `"stoptime"` properties of a [`Selector`].
+///
+/// The accepted formats are:
+///  - a number of seconds since UNIX EPOCH (e.g. `0` or `1625047200.5`)
+///  - an [RFC3339](https://tools.ietf.org/html/rfc3339) date (e.g. `2021-06-30T10:00:00Z`)
+///  - `now()`, optionally followed by `+` or `-` and a duration (e.g. `now()-1h` or `now()-1m 30s`)
+///
+/// # Examples:
+/// ```
+/// # use std::time::{Duration, SystemTime, UNIX_EPOCH};
+/// # use zenoh_backend_traits::utils::parse_time;
+/// assert_eq!(UNIX_EPOCH + Duration::from_millis(1500), parse_time("1.5").unwrap());
+/// assert_eq!(
+///     UNIX_EPOCH + Duration::from_secs(1625047200),
+///     parse_time("2021-06-30T10:00:00Z").unwrap()
+/// );
+/// assert!(parse_time("now()-1h").unwrap() < SystemTime::now());
+/// assert!(parse_time("yesterday").is_err());
+/// ```
+pub fn parse_time(s: &str) -> ZResult<SystemTime> {
+    let s = s.trim();
+    if let Some(offset) = s.strip_prefix("now()") {
+        let now = SystemTime::now();
+        let offset = offset.trim();
+        if offset.is_empty() {
+            return Ok(now);
+        }
+        let (sign, duration) = offset.split_at(1);
+        let duration = parse_duration(duration.trim(), s)?;
+        let time = match sign {
+            "+" => now.checked_add(duration),
+            "-" => now.checked_sub(duration),
+            _ => None,
+        };
+        return match time {
+            Some(time) => Ok(time),
+            None => invalid_time(s),
+        };
+    }
+    if let Ok(secs) = s.parse::<f64>() {
+        if secs.is_finite() && secs >= 0.0 {
+            return Ok(UNIX_EPOCH + Duration::from_secs_f64(secs));
+        }
+        return invalid_time(s);
+    }
+    humantime::parse_rfc3339_weak(s).or_else(|_| invalid_time(s))
+}
+
+fn parse_duration(duration: &str, time: &str) -> ZResult<Duration> {
+    humantime::parse_duration(duration).or_else(|_| invalid_time(time))
+}
+
+fn invalid_time<T>(time: &str) -> ZResult<T> {
+    zerror!(ZErrorKind::ValueDecodingFailed {
+        descr: format!("Invalid time: {}", time)
+    })
+}
+
+/// Merges a PATCH [`Sample`] into the currently stored [`Sample`] for the same path (if any),
+/// and returns the resulting [`Sample`] that should replace the stored one.
+///
+/// The merge depends on the encoding of the PATCH value:
+///  - [`Value::Json`]: the PATCH is applied as a [RFC 7396](https://tools.ietf.org/html/rfc7396) merge-patch
+///    (i.e. the `null` members of the PATCH remove the corresponding members of the stored value).
+///  - [`Value::Properties`]: the properties of the PATCH are added to the stored properties,
+///    replacing the existing ones with the same keys.
+///
+/// The stored value must have the same encoding than the PATCH. If there is no stored value, the PATCH
+/// is applied to an empty value. The returned [`Sample`] is a PUT that keeps the PATCH's timestamp.
+///
+/// Use this operation in [`Storage::on_sample()`](crate::Storage::on_sample()) implementation on
+/// reception of a [`ChangeKind::Patch`](zenoh::ChangeKind::Patch) sample.
+pub fn merge_patch(stored: Option<&Sample>, patch: &Sample) -> ZResult<Sample> {
+    let res_name = &patch.res_name;
+    let stored = match stored {
+        Some(sample) => Value::from_sample(sample, true)?,
+        None => None,
+    };
+    let merged = match (
+        stored,
+        Value::decode(get_encoding(patch), patch.payload.clone())?,
+    ) {
+        (None, Value::Json(patch)) => Value::Json(merge_json(serde_json::Value::Null, &patch)?),
+        (Some(Value::Json(stored)), Value::Json(patch)) => {
+            let stored = serde_json::from_str(&stored).map_err(|e| {
+                zerror2!(ZErrorKind::ValueDecodingFailed {
+                    descr: format!("Invalid stored JSON value: {}", e)
+                })
+            })?;
+            Value::Json(merge_json(stored, &patch)?)
+        }
+        (None, Value::Properties(patch)) => Value::Properties(patch),
+        (Some(Value::Properties(mut stored)), Value::Properties(patch)) => {
+            stored.extend(patch.0);
+            Value::Properties(stored)
+        }
+        (Some(stored), patch) if stored.encoding() != patch.encoding() => {
+            return zerror!(ZErrorKind::Other {
+                descr: format!(
+                    "PATCH on {} not applicable: stored value is {} but PATCH is {}",
+                    res_name,
+                    stored.encoding_descr(),
+                    patch.encoding_descr()
+                )
+            })
+        }
+        (_, patch) => {
+            return zerror!(ZErrorKind::Other {
+                descr: format!(
+                    "PATCH on {} not supported for {} values",
+                    res_name,
+                    patch.encoding_descr()
+                )
+            })
+        }
+    };
+
+    let (encoding, payload) = merged.encode();
+    let mut data_info = DataInfo::new();
+    data_info.encoding = Some(encoding);
+    if let Some(info) = patch.data_info.as_ref() {
+        data_info.timestamp = info.timestamp;
+        data_info.source_id = info.source_id;
+        data_info.source_sn = info.source_sn;
+        data_info.first_router_id = info.first_router_id;
+        data_info.first_router_sn = info.first_router_sn;
+    }
+    Ok(Sample {
+        res_name: res_name.clone(),
+        payload,
+        data_info: Some(data_info),
+        attachment: patch.attachment.clone(),
+    })
+}
+
+fn get_encoding(sample: &Sample) -> zenoh::net::ZInt {
+    sample
+        .data_info
+        .as_ref()
+        .and_then(|info| info.encoding)
+        .unwrap_or(zenoh::net::encoding::APP_OCTET_STREAM)
+}
+
+fn merge_json(mut target: serde_json::Value, patch: &str) -> ZResult<String> {
+    let patch = serde_json::from_str(patch).map_err(|e| {
+        zerror2!(ZErrorKind::ValueDecodingFailed {
+            descr: format!("Invalid JSON PATCH value: {}", e)
+        })
+    })?;
+    json_merge_patch(&mut target, patch);
+    Ok(target.to_string())
+}
+
+// The MergePatch algorithm of RFC 7396
+fn json_merge_patch(target: &mut serde_json::Value, patch: serde_json::Value) {
+    match patch {
+        serde_json::Value::Object(patch) => {
+            if !target.is_object() {
+                *target = serde_json::Value::Object(serde_json::Map::new());
+            }
+            let map = target.as_object_mut().unwrap();
+            for (key, value) in patch {
+                if value.is_null() {
+                    map.remove(&key);
+                } else {
+                    json_merge_patch(map.entry(key).or_insert(serde_json::Value::Null), value);
+                }
+            }
+        }
+        patch => *target = patch,
+    }
+}
+
 #[test]
 fn test_get_sub_path_exprs() {
     assert_eq!(
@@ -148,3 +362,51 @@ fn test_get_sub_path_exprs() {
         get_sub_path_exprs("/demo/example/test", "/demo/example/test/").as_slice()
     );
 }
+
+#[test]
+fn test_merge_patch() {
+    fn sample(value: Value) -> Sample {
+        let (encoding, payload) = value.encode();
+        let mut data_info = DataInfo::new();
+        data_info.encoding = Some(encoding);
+        Sample {
+            res_name: "/demo/example/test".to_string(),
+            payload,
+            data_info: Some(data_info),
+            attachment: None,
+        }
+    }
+    fn json(sample: &Sample) -> serde_json::Value {
+        match Value::from_sample(sample, true).unwrap() {
+            Some(Value::Json(s)) => serde_json::from_str(&s).unwrap(),
+            v => panic!("Unexpected value: {:?}", v),
+        }
+    }
+
+    let stored = sample(Value::Json(
+        r#"{"a":"b","c":{"d":"e","f":"g"},"h":[1,2]}"#.to_string(),
+    ));
+    let patch = sample(Value::Json(
+        r#"{"a":"z","c":{"f":null},"h":[3]}"#.to_string(),
+    ));
+    assert_eq!(
+        serde_json::json!({"a":"z","c":{"d":"e"},"h":[3]}),
+        json(&merge_patch(Some(&stored), &patch).unwrap())
+    );
+    assert_eq!(
+        serde_json::json!({"a":"z","c":{},"h":[3]}),
+        json(&merge_patch(None, &patch).unwrap())
+    );
+
+    let stored = sample(Value::Properties("a=1;b=2".into()));
+    let patch = sample(Value::Properties("b=3;c=4".into()));
+    match Value::from_sample(&merge_patch(Some(&stored), &patch).unwrap(), true).unwrap() {
+        Some(Value::Properties(props)) => {
+            assert_eq!(zenoh::Properties::from("a=1;b=3;c=4"), props)
+        }
+        v => panic!("Unexpected value: {:?}", v),
+    }
+
+    assert!(merge_patch(Some(&stored), &sample(Value::Json("{}".to_string()))).is_err());
+    assert!(merge_patch(None, &sample(Value::Integer(1))).is_err());
+}
@@ -66,7 +66,7 @@ impl Plugin for ExamplePlugin {
             let flag = Arc::new(AtomicBool::new(true));
             let stopper = ExamplePluginStopper { flag: flag.clone() };
             async_std::task::spawn(run(runtime.clone(), selector.into(), flag));
-            Ok(Box::new(stopper))
+            Ok(boxed_stopper(stopper))
         } else {
             Err(Box::new(zerror2!(ZErrorKind::Other {
                 descr: "storage-selector is a mandatory option for ExamplePlugin".into()
@@ -116,6 +116,7 @@ async fn run(runtime: Runtime, selector: ResKey, flag: Arc<AtomicBool>) {
                             res_name: rname.clone(),
                             payload: data.clone(),
                             data_info: data_info.clone(),
+                            attachment: None,
                         }).await;
                     }
                 }
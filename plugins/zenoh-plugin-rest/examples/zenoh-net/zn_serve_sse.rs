@@ -56,6 +56,7 @@ async fn main() {
                         res_name: path.to_string(),
                         payload: HTML.as_bytes().into(),
                         data_info: None,
+                        attachment: None,
                     })
                     .await;
             }),
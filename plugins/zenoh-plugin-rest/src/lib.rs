@@ -12,6 +12,8 @@
 //   ADLINK zenoh team, <zenoh@adlink-labs.tech>
 //
 
+use async_std::channel::{bounded, Receiver};
+use async_std::prelude::FutureExt as _;
 use async_std::sync::Arc;
 use clap::{Arg, ArgMatches};
 use futures::prelude::*;
@@ -19,6 +21,7 @@ use http_types::Method;
 use runtime::Runtime;
 use std::convert::TryFrom;
 use std::str::FromStr;
+use std::sync::Mutex;
 use tide::http::Mime;
 use tide::sse::Sender;
 use tide::{Request, Response, Server, StatusCode};
@@ -29,6 +32,8 @@ use zenoh_plugin_trait::prelude::*;
 const PORT_SEPARATOR: char = ':';
 const DEFAULT_HTTP_HOST: &str = "0.0.0.0";
 const DEFAULT_HTTP_PORT: &str = "8000";
+const METRICS_PATH: &str = "/metrics";
+const PROMETHEUS_MIME: &str = "text/plain; version=0.0.4";
 
 const SSE_SUB_INFO: SubInfo = SubInfo {
     reliability: Reliability::Reliable,
@@ -105,9 +110,19 @@ fn sample_to_json(sample: Sample) -> String {
     }
 }
 
+fn error_to_json(sample: Sample) -> String {
+    let error = serde_json::json!(String::from_utf8_lossy(&sample.payload.contiguous()));
+    format!(r#"{{ "key": "{}", "error": {} }}"#, sample.res_name, error)
+}
+
 async fn to_json(results: ReplyReceiver) -> String {
     let values = results
-        .filter_map(move |reply| async move { Some(sample_to_json(reply.data)) })
+        .filter_map(move |reply| async move {
+            match reply {
+                Reply::Ok(reply) => Some(sample_to_json(reply.data)),
+                Reply::Err(err) => Some(error_to_json(err.data)),
+            }
+        })
         .collect::<Vec<String>>()
         .await
         .join(",\n");
@@ -122,9 +137,22 @@ fn sample_to_html(sample: Sample) -> String {
     )
 }
 
+fn error_to_html(sample: Sample) -> String {
+    format!(
+        "<dt>{}</dt>\n<dd>ERROR: {}</dd>\n",
+        sample.res_name,
+        String::from_utf8_lossy(&sample.payload.contiguous())
+    )
+}
+
 async fn to_html(results: ReplyReceiver) -> String {
     let values = results
-        .filter_map(move |reply| async move { Some(sample_to_html(reply.data)) })
+        .filter_map(move |reply| async move {
+            match reply {
+                Reply::Ok(reply) => Some(sample_to_html(reply.data)),
+                Reply::Err(err) => Some(error_to_html(err.data)),
+            }
+        })
         .collect::<Vec<String>>()
         .await
         .join("\n");
@@ -190,6 +218,9 @@ impl Plugin for RestPlugin {
         vec![
             Arg::from_usage("--rest-http-port 'The REST plugin's http port'")
                 .default_value(DEFAULT_HTTP_PORT),
+            Arg::from_usage(
+                "--rest-metrics 'Serve the router metrics in the Prometheus text format on /metrics'",
+            ),
         ]
     }
 
@@ -201,13 +232,39 @@ impl Plugin for RestPlugin {
                 err: "No --rest-http-port argument found",
             })),
             Some(port) => {
-                async_std::task::spawn(run(runtime.clone(), port.to_owned()));
-                Ok(Box::new(()))
+                let (stop_tx, stop_rx) = bounded::<()>(1);
+                let (stopped_tx, stopped_rx) = std::sync::mpsc::channel::<()>();
+                async_std::task::spawn(run(
+                    runtime.clone(),
+                    port.to_owned(),
+                    args.is_present("rest-metrics"),
+                    stop_rx,
+                    stopped_tx,
+                ));
+                Ok(boxed_stopper(RestStopper {
+                    stop_tx,
+                    stopped_rx: Mutex::new(stopped_rx),
+                }))
             }
         }
     }
 }
 
+/// Stops the plugin, closing its http server and its session.
+struct RestStopper {
+    stop_tx: async_std::channel::Sender<()>,
+    stopped_rx: Mutex<std::sync::mpsc::Receiver<()>>,
+}
+
+impl PluginStopper for RestStopper {
+    fn stop(&self) {
+        if self.stop_tx.try_send(()).is_ok() {
+            // Wait for the plugin to terminate, dropping the sending side of the channel
+            let _ = self.stopped_rx.lock().unwrap().recv();
+        }
+    }
+}
+
 async fn query(req: Request<(Arc<Session>, String)>) -> tide::Result<Response> {
     log::trace!("Incoming GET request: {:?}", req);
     // Reconstruct Selector from req.url() (no easier way...)
@@ -331,6 +388,45 @@ async fn query(req: Request<(Arc<Session>, String)>) -> tide::Result<Response> {
     }
 }
 
+async fn metrics(req: Request<(Arc<Session>, String)>) -> tide::Result<Response> {
+    log::trace!("Incoming GET metrics request: {:?}", req);
+    let (session, pid) = req.state();
+
+    // The router metrics are exposed by the admin space
+    let mut body = String::new();
+    let admin_path = format!("/@/router/{}/metrics", pid);
+    match session
+        .query(
+            &ResKey::from(admin_path),
+            "",
+            QueryTarget::default(),
+            QueryConsolidation::default(),
+        )
+        .await
+    {
+        Ok(mut replies) => {
+            while let Some(reply) = replies.next().await {
+                if let Reply::Ok(reply) = reply {
+                    body.push_str(&String::from_utf8_lossy(&reply.data.payload.contiguous()));
+                }
+            }
+        }
+        Err(e) => {
+            return Ok(response(
+                StatusCode::InternalServerError,
+                Mime::from_str("text/plain").unwrap(),
+                &e.to_string(),
+            ))
+        }
+    }
+
+    Ok(response(
+        StatusCode::Ok,
+        Mime::from_str(PROMETHEUS_MIME).unwrap(),
+        &body,
+    ))
+}
+
 async fn write(mut req: Request<(Arc<Session>, String)>) -> tide::Result<Response> {
     log::trace!("Incoming PUT request: {:?}", req);
     match req.body_bytes().await {
@@ -364,7 +460,13 @@ async fn write(mut req: Request<(Arc<Session>, String)>) -> tide::Result<Respons
     }
 }
 
-pub async fn run(runtime: Runtime, port: String) {
+pub async fn run(
+    runtime: Runtime,
+    port: String,
+    with_metrics: bool,
+    stop_rx: Receiver<()>,
+    _stopped_tx: std::sync::mpsc::Sender<()>,
+) {
     // Try to initiate login.
     // Required in case of dynamic lib, otherwise no logs.
     // But cannot be done twice in case of static link.
@@ -387,6 +489,9 @@ pub async fn run(runtime: Runtime, port: String) {
             .allow_credentials(false),
     );
 
+    if with_metrics {
+        app.at(METRICS_PATH).get(metrics);
+    }
     app.at("/").get(query);
     app.at("*").get(query);
 
@@ -399,7 +504,13 @@ pub async fn run(runtime: Runtime, port: String) {
     app.at("/").delete(write);
     app.at("*").delete(write);
 
-    if let Err(e) = app.listen(http_port).await {
+    let stop = async {
+        let _ = stop_rx.recv().await;
+        log::debug!("Stopping REST plugin");
+        Ok(())
+    };
+    // Dropping the server once stopped closes the session
+    if let Err(e) = app.listen(http_port).race(stop).await {
         log::error!("Unable to start http server for REST : {:?}", e);
     }
 }
@@ -30,7 +30,7 @@ pub(crate) async fn start_backend(
     backend: Box<dyn zenoh_backend_traits::Backend>,
     admin_path: Path,
     zenoh: Arc<Zenoh>,
-) -> ZResult<Sender<bool>> {
+) -> ZResult<TaskHandle> {
     let backend_name = admin_path.clone();
     trace!("Starting backend {}", backend_name);
 
@@ -39,7 +39,7 @@ pub(crate) async fn start_backend(
     // Channel to stop the task
     let (stop_tx, stop_rx) = bounded::<bool>(1);
 
-    task::spawn(async move {
+    let task = task::spawn(async move {
         let workspace = zenoh.workspace(Some(admin_path.clone())).await.unwrap();
         // admin_path is "/@/.../backend/<beid>"
         // answer to GET on 'admin_path'
@@ -81,7 +81,7 @@ pub(crate) async fn start_backend(
         let mut backend = backend;
         // Map owning handles on alive storages for this backend.
         // Once dropped, a handle will release/stop the backend.
-        let mut storages_handles: HashMap<Path, Sender<bool>> = HashMap::new();
+        let mut storages_handles: HashMap<Path, TaskHandle> = HashMap::new();
         loop {
             select!(
                 // on get request on backend_admin
@@ -121,6 +121,10 @@ pub(crate) async fn start_backend(
                 },
                 _ = stop_rx.recv().fuse() => {
                     trace!("Dropping backend {}", admin_path);
+                    // Stop the storages first, so they are undeclared before the backend is dropped
+                    for (_, handle) in storages_handles.drain() {
+                        handle.stop().await;
+                    }
                     return
                 }
             );
@@ -131,7 +135,23 @@ pub(crate) async fn start_backend(
     let _ = ready_rx.recv().await;
     trace!("Backend {} ready", backend_name);
 
-    Ok(stop_tx)
+    Ok(TaskHandle { stop_tx, task })
+}
+
+/// An owning handle on the task of a backend or a storage.
+/// Once dropped, the handle releases/stops the task without waiting for it.
+pub(crate) struct TaskHandle {
+    pub(crate) stop_tx: Sender<bool>,
+    pub(crate) task: task::JoinHandle<()>,
+}
+
+impl TaskHandle {
+    /// Stops the task, waiting for it to terminate.
+    pub(crate) async fn stop(self) {
+        // The send fails if the task already terminated on its own
+        let _ = self.stop_tx.send(true).await;
+        self.task.await;
+    }
 }
 
 async fn create_and_start_storage(
@@ -141,7 +161,7 @@ async fn create_and_start_storage(
     in_interceptor: Option<Arc<RwLock<Box<dyn IncomingDataInterceptor>>>>,
     out_interceptor: Option<Arc<RwLock<Box<dyn OutgoingDataInterceptor>>>>,
     zenoh: Arc<Zenoh>,
-) -> ZResult<Sender<bool>> {
+) -> ZResult<TaskHandle> {
     trace!("Create storage {}", admin_path);
     if let Value::Properties(props) = value {
         let path_expr_str = props.get(PROP_STORAGE_PATH_EXPR).ok_or_else(|| {
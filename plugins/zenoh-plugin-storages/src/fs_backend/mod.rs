@@ -0,0 +1,717 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ADLINK zenoh team, <zenoh@adlink-labs.tech>
+//
+use async_std::fs;
+use async_std::path::{Path, PathBuf};
+use async_std::prelude::*;
+use async_trait::async_trait;
+use log::{debug, trace};
+use std::convert::TryFrom;
+use std::io::ErrorKind;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime};
+use zenoh::net::encoding::APP_OCTET_STREAM;
+use zenoh::net::utils::resource_name;
+use zenoh::net::{DataInfo, Sample, ZInt};
+use zenoh::{
+    utils, ChangeKind, Properties, Selector, Timestamp, Value, ZError, ZErrorKind, ZResult,
+};
+use zenoh_backend_traits::utils::{get_keys_prefix, get_time_range, merge_patch};
+use zenoh_backend_traits::*;
+use zenoh_util::{zerror, zerror2};
+
+/// The "root" property of the filesystem backend: the directory under which
+/// all the storages directories are created.
+/// Default: `$ZBACKEND_FS_ROOT` if set, otherwise `$HOME/.zenoh/zbackend_fs`.
+pub const PROP_BACKEND_FS_ROOT: &str = "root";
+/// The "dir" property of a filesystem storage: the directory (relative to the backend's root)
+/// where the storage's files are written.
+/// Default: the storage's path prefix (e.g. `demo/example` for `/demo/example/**`).
+pub const PROP_STORAGE_DIR: &str = "dir";
+
+const ROOT_ENV_VAR: &str = "ZBACKEND_FS_ROOT";
+const DEFAULT_ROOT_DIR: &str = ".zenoh/zbackend_fs";
+
+// The suffixes of the files storing the value and the metadata of each key. A key
+// can thus also be the prefix of other keys (e.g. /demo/a and /demo/a/b), its files
+// being next to the directory of the other keys.
+const VALUE_SUFFIX: &str = ".zval";
+const METADATA_SUFFIX: &str = ".zmeta";
+// The delay after which the tombstone of a deleted key is removed
+const TOMBSTONE_CLEANUP_DELAY: Duration = Duration::from_secs(5);
+const METADATA_ENCODING: &str = "encoding";
+const METADATA_TIMESTAMP: &str = "timestamp";
+const METADATA_DELETED: &str = "deleted";
+
+pub fn create_backend(properties: Properties) -> ZResult<Box<dyn Backend>> {
+    let root = match properties.get(PROP_BACKEND_FS_ROOT) {
+        Some(root) => PathBuf::from(root),
+        None => match std::env::var_os(ROOT_ENV_VAR) {
+            Some(root) => PathBuf::from(root),
+            None => match std::env::var_os("HOME") {
+                Some(home) => PathBuf::from(home).join(DEFAULT_ROOT_DIR),
+                None => {
+                    return zerror!(ZErrorKind::Other {
+                        descr: format!(
+                            "Can't create filesystem backend: no {} property nor {} or HOME environment variable",
+                            PROP_BACKEND_FS_ROOT, ROOT_ENV_VAR
+                        )
+                    })
+                }
+            },
+        },
+    };
+    let mut p = properties;
+    p.insert(PROP_BACKEND_TYPE.into(), "fs".into());
+    p.insert(PROP_BACKEND_FS_ROOT.into(), root.to_string_lossy().into());
+    let admin_status = utils::properties_to_json_value(&p);
+    Ok(Box::new(FsBackend { admin_status, root }))
+}
+
+pub struct FsBackend {
+    admin_status: Value,
+    root: PathBuf,
+}
+
+#[async_trait]
+impl Backend for FsBackend {
+    async fn get_admin_status(&self) -> Value {
+        self.admin_status.clone()
+    }
+
+    async fn create_storage(&mut self, properties: Properties) -> ZResult<Box<dyn Storage>> {
+        debug!("Create Filesystem Storage with properties: {}", properties);
+        Ok(Box::new(FsStorage::new(&self.root, properties).await?))
+    }
+
+    fn incoming_data_interceptor(&self) -> Option<Box<dyn IncomingDataInterceptor>> {
+        None
+    }
+
+    fn outgoing_data_interceptor(&self) -> Option<Box<dyn OutgoingDataInterceptor>> {
+        None
+    }
+}
+
+// The metadata stored in the sidecar file of each value
+struct Metadata {
+    encoding: ZInt,
+    timestamp: Timestamp,
+    deleted: bool,
+}
+
+impl Metadata {
+    fn to_properties(&self) -> Properties {
+        let mut props = Properties::default();
+        props.insert(METADATA_ENCODING.into(), self.encoding.to_string());
+        props.insert(METADATA_TIMESTAMP.into(), self.timestamp.to_string());
+        props.insert(METADATA_DELETED.into(), self.deleted.to_string());
+        props
+    }
+
+    fn from_properties(props: &Properties) -> Option<Metadata> {
+        Some(Metadata {
+            encoding: props.get(METADATA_ENCODING)?.parse().ok()?,
+            timestamp: Timestamp::from_str(props.get(METADATA_TIMESTAMP)?).ok()?,
+            deleted: props.get(METADATA_DELETED)?.parse().ok()?,
+        })
+    }
+}
+
+struct FsStorage {
+    admin_status: Value,
+    path_prefix: String,
+    dir: PathBuf,
+    // The paths of the deleted keys whose tombstones are to be removed
+    tombstones: Vec<PathBuf>,
+}
+
+impl FsStorage {
+    async fn new(root: &Path, properties: Properties) -> ZResult<FsStorage> {
+        let path_expr = properties.get(PROP_STORAGE_PATH_EXPR).ok_or_else(|| {
+            zerror2!(ZErrorKind::Other {
+                descr: format!(
+                    "Can't create filesystem storage: no {} property",
+                    PROP_STORAGE_PATH_EXPR
+                )
+            })
+        })?;
+        let path_prefix = match properties.get(PROP_STORAGE_PATH_PREFIX) {
+            Some(prefix) => {
+                // the keys are stored relatively to the prefix, which always ends with '/'
+                let prefix = if prefix.ends_with('/') {
+                    prefix.clone()
+                } else {
+                    format!("{}/", prefix)
+                };
+                if !path_expr.starts_with(prefix.as_str()) {
+                    return zerror!(ZErrorKind::Other {
+                        descr: format!(
+                            "Can't create filesystem storage: {} '{}' is not a prefix of {} '{}'",
+                            PROP_STORAGE_PATH_PREFIX, prefix, PROP_STORAGE_PATH_EXPR, path_expr
+                        )
+                    });
+                }
+                prefix
+            }
+            // by default, strip all the segments before the first one with a wildcard
+            None => {
+                let prefix = get_keys_prefix(path_expr);
+                prefix[..prefix.rfind('/').map_or(0, |i| i + 1)].to_string()
+            }
+        };
+        let dir = match properties.get(PROP_STORAGE_DIR) {
+            Some(dir) => dir.clone(),
+            None => path_prefix.trim_matches('/').to_string(),
+        };
+        if dir.is_empty() || dir.split('/').any(|s| s == "..") || dir.starts_with('/') {
+            return zerror!(ZErrorKind::Other {
+                descr: format!(
+                    "Can't create filesystem storage: invalid {} '{}' (must be a relative path under the backend's root)",
+                    PROP_STORAGE_DIR, dir
+                )
+            });
+        }
+        let dir = root.join(dir);
+        fs::create_dir_all(&dir).await.map_err(|e| {
+            zerror2!(ZErrorKind::IoError {
+                descr: format!("Can't create directory {}: {}", dir.display(), e)
+            })
+        })?;
+
+        let mut p = properties.clone();
+        p.insert(PROP_STORAGE_PATH_PREFIX.into(), path_prefix.clone());
+        p.insert(PROP_STORAGE_DIR.into(), dir.to_string_lossy().into());
+        let admin_status = utils::properties_to_json_value(&p);
+
+        let mut storage = FsStorage {
+            admin_status,
+            path_prefix,
+            dir,
+            tombstones: vec![],
+        };
+        // The tombstones left by a previous run are removed as well
+        for key_path in storage.list_keys().await? {
+            if let Some(metadata) = storage.read_metadata(&key_path).await? {
+                if metadata.deleted {
+                    storage.tombstones.push(key_path);
+                }
+            }
+        }
+        storage.clean_tombstones().await?;
+        Ok(storage)
+    }
+
+    // Returns the path of the files where `res_name` is stored, without their suffix
+    fn get_key_path(&self, res_name: &str) -> ZResult<PathBuf> {
+        let key = res_name.strip_prefix(&self.path_prefix).ok_or_else(|| {
+            zerror2!(ZErrorKind::Other {
+                descr: format!(
+                    "Received a Sample for {} not starting with {}",
+                    res_name, self.path_prefix
+                )
+            })
+        })?;
+        if key.is_empty()
+            || key.split('/').any(|s| {
+                s.is_empty()
+                    || s == "."
+                    || s == ".."
+                    || s.ends_with(VALUE_SUFFIX)
+                    || s.ends_with(METADATA_SUFFIX)
+            })
+        {
+            return zerror!(ZErrorKind::Other {
+                descr: format!("Can't store {} in a filesystem storage", res_name)
+            });
+        }
+        Ok(self.dir.join(key))
+    }
+
+    fn get_res_name(&self, key_path: &Path) -> Option<String> {
+        let key = key_path.strip_prefix(&self.dir).ok()?.to_str()?;
+        Some(format!("{}{}", self.path_prefix, key))
+    }
+
+    async fn read_metadata(&self, key_path: &Path) -> ZResult<Option<Metadata>> {
+        let path = with_suffix(key_path, METADATA_SUFFIX);
+        match fs::read_to_string(&path).await {
+            Ok(s) => Ok(Metadata::from_properties(&Properties::from(s))),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => io_error(&path, e),
+        }
+    }
+
+    async fn read_sample(&self, key_path: &Path) -> ZResult<Option<(Timestamp, Sample)>> {
+        let metadata = match self.read_metadata(key_path).await? {
+            Some(metadata) if !metadata.deleted => metadata,
+            _ => return Ok(None),
+        };
+        let res_name = match self.get_res_name(key_path) {
+            Some(res_name) => res_name,
+            None => return Ok(None),
+        };
+        let path = with_suffix(key_path, VALUE_SUFFIX);
+        let payload = match fs::read(&path).await {
+            Ok(payload) => payload,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return io_error(&path, e),
+        };
+        let mut data_info = DataInfo::new();
+        data_info.encoding = Some(metadata.encoding);
+        data_info.timestamp = Some(metadata.timestamp);
+        let sample = Sample {
+            res_name,
+            payload: payload.into(),
+            data_info: Some(data_info),
+            attachment: None,
+        };
+        Ok(Some((metadata.timestamp, sample)))
+    }
+
+    async fn write_sample(
+        &self,
+        key_path: &Path,
+        timestamp: Timestamp,
+        sample: &Sample,
+    ) -> ZResult<()> {
+        if let Some(parent) = key_path.parent() {
+            fs::create_dir_all(parent)
+                .await
+                .or_else(|e| io_error(parent, e))?;
+        }
+        let path = with_suffix(key_path, VALUE_SUFFIX);
+        fs::write(&path, sample.payload.to_vec())
+            .await
+            .or_else(|e| io_error(&path, e))?;
+        let metadata = Metadata {
+            encoding: sample
+                .data_info
+                .as_ref()
+                .and_then(|info| info.encoding)
+                .unwrap_or(APP_OCTET_STREAM),
+            timestamp,
+            deleted: false,
+        };
+        self.write_metadata(key_path, &metadata).await
+    }
+
+    async fn write_metadata(&self, key_path: &Path, metadata: &Metadata) -> ZResult<()> {
+        if let Some(parent) = key_path.parent() {
+            fs::create_dir_all(parent)
+                .await
+                .or_else(|e| io_error(parent, e))?;
+        }
+        let path = with_suffix(key_path, METADATA_SUFFIX);
+        fs::write(&path, metadata.to_properties().to_string())
+            .await
+            .or_else(|e| io_error(&path, e))
+    }
+
+    async fn delete_sample(&mut self, key_path: &Path, timestamp: Timestamp) -> ZResult<()> {
+        // NOTE: the metadata file is kept as a tombstone with the removal time:
+        // if ever a put with a lower timestamp arrive (e.g. msg inversion between put and remove)
+        // we must drop the put. It's removed after TOMBSTONE_CLEANUP_DELAY.
+        let path = with_suffix(key_path, VALUE_SUFFIX);
+        match fs::remove_file(&path).await {
+            Ok(()) => (),
+            Err(e) if e.kind() == ErrorKind::NotFound => (),
+            Err(e) => return io_error(&path, e),
+        }
+        let metadata = Metadata {
+            encoding: APP_OCTET_STREAM,
+            timestamp,
+            deleted: true,
+        };
+        self.write_metadata(key_path, &metadata).await?;
+        self.tombstones.push(key_path.to_path_buf());
+        Ok(())
+    }
+
+    // Removes the tombstones older than TOMBSTONE_CLEANUP_DELAY, and then the directories
+    // left empty. The tombstones overwritten by a put since are just forgotten.
+    async fn clean_tombstones(&mut self) -> ZResult<()> {
+        let now = SystemTime::now();
+        let mut tombstones = vec![];
+        for key_path in std::mem::take(&mut self.tombstones) {
+            match self.read_metadata(&key_path).await? {
+                Some(metadata) if metadata.deleted => {
+                    let time = metadata.timestamp.get_time().to_system_time();
+                    if time + TOMBSTONE_CLEANUP_DELAY > now {
+                        tombstones.push(key_path);
+                        continue;
+                    }
+                    trace!("Remove tombstone {}", key_path.display());
+                    let path = with_suffix(&key_path, METADATA_SUFFIX);
+                    match fs::remove_file(&path).await {
+                        Ok(()) => (),
+                        Err(e) if e.kind() == ErrorKind::NotFound => (),
+                        Err(e) => return io_error(&path, e),
+                    }
+                    let mut dir = key_path.parent();
+                    while let Some(d) = dir {
+                        if d == self.dir.as_path() || fs::remove_dir(d).await.is_err() {
+                            break;
+                        }
+                        dir = d.parent();
+                    }
+                }
+                _ => (),
+            }
+        }
+        self.tombstones = tombstones;
+        Ok(())
+    }
+
+    // Returns the stored samples matching the selector
+    async fn get_samples(&self, selector: &Selector) -> ZResult<Vec<Sample>> {
+        let res_name = selector.path_expr.as_str();
+        let (start, stop) = get_time_range(selector)?;
+        let is_in_time_range = |ts: &Timestamp| {
+            let time = ts.get_time().to_system_time();
+            !matches!(start, Some(start) if start > time)
+                && !matches!(stop, Some(stop) if stop < time)
+        };
+        let keys = if !res_name.contains('*') {
+            match self.get_key_path(res_name) {
+                Ok(key_path) => vec![key_path],
+                Err(_) => vec![],
+            }
+        } else {
+            self.list_keys().await?
+        };
+        let mut samples = vec![];
+        for key_path in keys {
+            if let Some((ts, sample)) = self.read_sample(&key_path).await? {
+                if resource_name::intersect(res_name, &sample.res_name) && is_in_time_range(&ts) {
+                    samples.push(sample);
+                }
+            }
+        }
+        Ok(samples)
+    }
+
+    // Returns the paths of all the keys (i.e. with a metadata file) in the storage directory
+    async fn list_keys(&self) -> ZResult<Vec<PathBuf>> {
+        let mut keys = vec![];
+        let mut dirs = vec![self.dir.clone()];
+        while let Some(dir) = dirs.pop() {
+            let mut entries = fs::read_dir(&dir).await.or_else(|e| io_error(&dir, e))?;
+            while let Some(entry) = entries.next().await {
+                let path = entry.or_else(|e| io_error(&dir, e))?.path();
+                if path.is_dir().await {
+                    dirs.push(path);
+                } else if let Some(key) =
+                    path.to_str().and_then(|s| s.strip_suffix(METADATA_SUFFIX))
+                {
+                    keys.push(PathBuf::from(key));
+                }
+            }
+        }
+        Ok(keys)
+    }
+}
+
+fn with_suffix(key_path: &Path, suffix: &str) -> PathBuf {
+    let mut path = key_path.as_os_str().to_owned();
+    path.push(suffix);
+    path.into()
+}
+
+fn io_error<T>(path: &Path, e: std::io::Error) -> ZResult<T> {
+    zerror!(ZErrorKind::IoError {
+        descr: format!("{}: {}", path.display(), e)
+    })
+}
+
+#[async_trait]
+impl Storage for FsStorage {
+    async fn get_admin_status(&self) -> Value {
+        self.admin_status.clone()
+    }
+
+    async fn on_sample(&mut self, sample: Sample) -> ZResult<()> {
+        trace!("on_sample for {}", sample.res_name);
+        let (kind, timestamp) = if let Some(ref info) = sample.data_info {
+            (
+                info.kind.map_or(ChangeKind::Put, ChangeKind::from),
+                match &info.timestamp {
+                    Some(ts) => *ts,
+                    None => utils::new_reception_timestamp(),
+                },
+            )
+        } else {
+            (ChangeKind::Put, utils::new_reception_timestamp())
+        };
+        self.clean_tombstones().await?;
+        let key_path = self.get_key_path(&sample.res_name)?;
+        if let Some(metadata) = self.read_metadata(&key_path).await? {
+            if metadata.timestamp >= timestamp {
+                debug!("{} on {} dropped: out-of-date", kind, sample.res_name);
+                return Ok(());
+            }
+        }
+        match kind {
+            ChangeKind::Put => self.write_sample(&key_path, timestamp, &sample).await,
+            ChangeKind::Delete => self.delete_sample(&key_path, timestamp).await,
+            ChangeKind::Patch => {
+                let stored = self.read_sample(&key_path).await?;
+                let merged = merge_patch(stored.as_ref().map(|(_, s)| s), &sample)?;
+                self.write_sample(&key_path, timestamp, &merged).await
+            }
+        }
+    }
+
+    async fn on_query(&mut self, query: Query) -> ZResult<()> {
+        trace!("on_query for {}", query.res_name());
+        self.clean_tombstones().await?;
+        let selector = Selector::try_from(&query)?;
+        for sample in self.get_samples(&selector).await? {
+            query.reply(sample).await;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for FsStorage {
+    fn drop(&mut self) {
+        // nothing to do: the files are kept on disk
+        trace!("FsStorage::drop()");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_std::task;
+    use std::time::UNIX_EPOCH;
+    use zenoh::TimestampId;
+
+    async fn new_storage(name: &str, clean: bool) -> FsStorage {
+        let root = PathBuf::from(std::env::temp_dir()).join(format!("zenoh-test-fs-{}", name));
+        if clean {
+            let _ = fs::remove_dir_all(&root).await;
+        }
+        let mut properties = Properties::default();
+        properties.insert(PROP_STORAGE_PATH_EXPR.into(), "/demo/fs/**".into());
+        FsStorage::new(&root, properties).await.unwrap()
+    }
+
+    // Returns a timestamp `secs` seconds after now (before if negative)
+    fn timestamp(secs: i64) -> Timestamp {
+        let now = SystemTime::now();
+        let time = if secs < 0 {
+            now - Duration::from_secs(-secs as u64)
+        } else {
+            now + Duration::from_secs(secs as u64)
+        };
+        Timestamp::new(
+            time.duration_since(UNIX_EPOCH).unwrap().into(),
+            TimestampId::new(1, [0u8; TimestampId::MAX_SIZE]),
+        )
+    }
+
+    fn sample(res_name: &str, kind: ChangeKind, payload: &[u8], timestamp: Timestamp) -> Sample {
+        let mut info = DataInfo::new();
+        info.kind = Some(kind as ZInt);
+        info.timestamp = Some(timestamp);
+        Sample {
+            res_name: res_name.into(),
+            payload: payload.into(),
+            data_info: Some(info),
+            attachment: None,
+        }
+    }
+
+    async fn get(storage: &FsStorage, selector: &str) -> Vec<(String, Vec<u8>)> {
+        let selector = Selector::try_from(selector).unwrap();
+        let mut samples: Vec<(String, Vec<u8>)> = storage
+            .get_samples(&selector)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|s| (s.res_name, s.payload.to_vec()))
+            .collect();
+        samples.sort();
+        samples
+    }
+
+    fn kv(res_name: &str, payload: &[u8]) -> (String, Vec<u8>) {
+        (res_name.to_string(), payload.to_vec())
+    }
+
+    #[test]
+    fn fs_put_get_delete() {
+        task::block_on(async {
+            let mut storage = new_storage("put-get-delete", true).await;
+            let s = sample("/demo/fs/a", ChangeKind::Put, b"1", timestamp(-2));
+            storage.on_sample(s).await.unwrap();
+            assert_eq!(
+                get(&storage, "/demo/fs/a").await,
+                vec![kv("/demo/fs/a", b"1")]
+            );
+
+            // an out-of-date put is dropped
+            let s = sample("/demo/fs/a", ChangeKind::Put, b"0", timestamp(-3));
+            storage.on_sample(s).await.unwrap();
+            assert_eq!(
+                get(&storage, "/demo/fs/a").await,
+                vec![kv("/demo/fs/a", b"1")]
+            );
+
+            let s = sample("/demo/fs/a", ChangeKind::Delete, b"", timestamp(0));
+            storage.on_sample(s).await.unwrap();
+            assert!(get(&storage, "/demo/fs/a").await.is_empty());
+
+            // a put older than the delete is dropped
+            let s = sample("/demo/fs/a", ChangeKind::Put, b"2", timestamp(-1));
+            storage.on_sample(s).await.unwrap();
+            assert!(get(&storage, "/demo/fs/**").await.is_empty());
+
+            // while a newer one is stored
+            let s = sample("/demo/fs/a", ChangeKind::Put, b"3", timestamp(1));
+            storage.on_sample(s).await.unwrap();
+            assert_eq!(
+                get(&storage, "/demo/fs/**").await,
+                vec![kv("/demo/fs/a", b"3")]
+            );
+        });
+    }
+
+    #[test]
+    fn fs_nested_keys() {
+        task::block_on(async {
+            let mut storage = new_storage("nested-keys", true).await;
+            for res_name in &["/demo/fs/a", "/demo/fs/a/b", "/demo/fs/a/b/c"] {
+                let s = sample(
+                    res_name,
+                    ChangeKind::Put,
+                    res_name.as_bytes(),
+                    timestamp(-1),
+                );
+                storage.on_sample(s).await.unwrap();
+            }
+            assert_eq!(
+                get(&storage, "/demo/fs/a").await,
+                vec![kv("/demo/fs/a", b"/demo/fs/a")]
+            );
+            assert_eq!(
+                get(&storage, "/demo/fs/a/*").await,
+                vec![kv("/demo/fs/a/b", b"/demo/fs/a/b")]
+            );
+            assert_eq!(
+                get(&storage, "/demo/fs/**").await,
+                vec![
+                    kv("/demo/fs/a", b"/demo/fs/a"),
+                    kv("/demo/fs/a/b", b"/demo/fs/a/b"),
+                    kv("/demo/fs/a/b/c", b"/demo/fs/a/b/c")
+                ]
+            );
+
+            let s = sample("/demo/fs/a/b", ChangeKind::Delete, b"", timestamp(0));
+            storage.on_sample(s).await.unwrap();
+            assert_eq!(
+                get(&storage, "/demo/fs/**").await,
+                vec![
+                    kv("/demo/fs/a", b"/demo/fs/a"),
+                    kv("/demo/fs/a/b/c", b"/demo/fs/a/b/c")
+                ]
+            );
+
+            // the keys that can't be mapped to a file are refused
+            for res_name in &["/demo/fs/a.zval", "/demo/fs/x/a.zmeta/b", "/demo/fs/../x"] {
+                let s = sample(res_name, ChangeKind::Put, b"x", timestamp(0));
+                assert!(storage.on_sample(s).await.is_err());
+            }
+        });
+    }
+
+    #[test]
+    fn fs_path_prefix() {
+        task::block_on(async {
+            let root = PathBuf::from(std::env::temp_dir()).join("zenoh-test-fs-path-prefix");
+            let _ = fs::remove_dir_all(&root).await;
+            let mut properties = Properties::default();
+            properties.insert(PROP_STORAGE_PATH_EXPR.into(), "/demo/fs/**".into());
+            properties.insert(PROP_STORAGE_PATH_PREFIX.into(), "/demo/fs".into());
+            let mut storage = FsStorage::new(&root, properties).await.unwrap();
+            for res_name in &["/demo/fs/a", "/demo/fs/b/c"] {
+                let s = sample(res_name, ChangeKind::Put, b"x", timestamp(-1));
+                storage.on_sample(s).await.unwrap();
+            }
+            assert_eq!(
+                get(&storage, "/demo/fs/**").await,
+                vec![kv("/demo/fs/a", b"x"), kv("/demo/fs/b/c", b"x")]
+            );
+            assert!(root.join("demo/fs/b/c.zval").exists().await);
+
+            // the prefix must be made of whole segments of the path expression
+            let mut properties = Properties::default();
+            properties.insert(PROP_STORAGE_PATH_EXPR.into(), "/demo/fsx/**".into());
+            properties.insert(PROP_STORAGE_PATH_PREFIX.into(), "/demo/fs".into());
+            assert!(FsStorage::new(&root, properties).await.is_err());
+        });
+    }
+
+    #[test]
+    fn fs_restart() {
+        task::block_on(async {
+            let mut storage = new_storage("restart", true).await;
+            let s = sample("/demo/fs/a/b", ChangeKind::Put, b"b", timestamp(-1));
+            storage.on_sample(s).await.unwrap();
+            let s = sample("/demo/fs/c", ChangeKind::Put, b"c", timestamp(-1));
+            storage.on_sample(s).await.unwrap();
+            let s = sample("/demo/fs/c", ChangeKind::Delete, b"", timestamp(0));
+            storage.on_sample(s).await.unwrap();
+            drop(storage);
+
+            let mut storage = new_storage("restart", false).await;
+            assert_eq!(
+                get(&storage, "/demo/fs/**").await,
+                vec![kv("/demo/fs/a/b", b"b")]
+            );
+            // the tombstone of /demo/fs/c is still there to drop an out-of-date put
+            let s = sample("/demo/fs/c", ChangeKind::Put, b"c", timestamp(-1));
+            storage.on_sample(s).await.unwrap();
+            assert_eq!(
+                get(&storage, "/demo/fs/**").await,
+                vec![kv("/demo/fs/a/b", b"b")]
+            );
+        });
+    }
+
+    #[test]
+    fn fs_tombstones_cleanup() {
+        task::block_on(async {
+            let mut storage = new_storage("tombstones", true).await;
+            let s = sample("/demo/fs/x/y", ChangeKind::Delete, b"", timestamp(-60));
+            storage.on_sample(s).await.unwrap();
+            let key_path = storage.get_key_path("/demo/fs/x/y").unwrap();
+            assert!(with_suffix(&key_path, METADATA_SUFFIX).exists().await);
+
+            // the tombstone is older than TOMBSTONE_CLEANUP_DELAY: removed with its directory
+            storage.clean_tombstones().await.unwrap();
+            assert!(!with_suffix(&key_path, METADATA_SUFFIX).exists().await);
+            assert!(!storage.dir.join("x").exists().await);
+            assert!(storage.tombstones.is_empty());
+
+            // a recent tombstone is kept
+            let s = sample("/demo/fs/z", ChangeKind::Delete, b"", timestamp(0));
+            storage.on_sample(s).await.unwrap();
+            storage.clean_tombstones().await.unwrap();
+            let key_path = storage.get_key_path("/demo/fs/z").unwrap();
+            assert!(with_suffix(&key_path, METADATA_SUFFIX).exists().await);
+            assert_eq!(storage.tombstones.len(), 1);
+        });
+    }
+}
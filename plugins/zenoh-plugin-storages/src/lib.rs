@@ -13,22 +13,27 @@
 //
 #![recursion_limit = "512"]
 
-use async_std::channel::Sender;
+use async_std::channel::{bounded, Receiver, Sender};
 use async_std::sync::Arc;
 use clap::{Arg, ArgMatches};
 use futures::prelude::*;
+use futures::select;
 use libloading::Symbol;
 use log::{debug, error, warn};
 use std::collections::HashMap;
 use std::convert::TryFrom;
+use std::sync::Mutex;
 use zenoh::net::runtime::Runtime;
 use zenoh::{ChangeKind, Path, Properties, Selector, Value, ZError, ZErrorKind, ZResult, Zenoh};
 use zenoh_backend_traits::{Backend, PROP_STORAGE_PATH_EXPR};
 use zenoh_plugin_trait::prelude::*;
+use zenoh_util::properties::config::ZN_TRUE;
+use zenoh_util::properties::config_file::*;
 use zenoh_util::{zerror, LibLoader};
 
 mod backends_mgt;
 use backends_mgt::*;
+mod fs_backend;
 mod memory_backend;
 mod storages_mgt;
 
@@ -79,23 +84,193 @@ impl Plugin for StoragesPlugin {
     fn start(
         (runtime, args): &Self::StartArgs,
     ) -> Result<Box<dyn std::any::Any + Send + Sync>, Box<dyn std::error::Error>> {
-        async_std::task::spawn(run(runtime.clone(), args.to_owned()));
-        Ok(Box::new(()))
+        let (stop_tx, stop_rx) = bounded::<()>(1);
+        let (stopped_tx, stopped_rx) = std::sync::mpsc::channel::<()>();
+        async_std::task::spawn(run(runtime.clone(), args.to_owned(), stop_rx, stopped_tx));
+        Ok(boxed_stopper(StoragesStopper {
+            stop_tx,
+            stopped_rx: Mutex::new(stopped_rx),
+        }))
+    }
+}
+
+/// Stops the plugin, undeclaring all the storages and dropping all the backends.
+struct StoragesStopper {
+    stop_tx: Sender<()>,
+    stopped_rx: Mutex<std::sync::mpsc::Receiver<()>>,
+}
+
+impl PluginStopper for StoragesStopper {
+    fn stop(&self) {
+        if self.stop_tx.try_send(()).is_ok() {
+            // Wait for the plugin to terminate, dropping the sending side of the channel
+            let _ = self.stopped_rx.lock().unwrap().recv();
+        }
     }
 }
 
 const BACKEND_LIB_PREFIX: &str = "zbackend_";
 const MEMORY_BACKEND_NAME: &str = "memory";
 const MEMORY_STORAGE_NAME: &str = "mem-storage";
+const STORAGES_SECTION: &str = "storages";
+const FS_BACKEND_NAME: &str = "fs";
+
+/// The storages configuration, from the command line arguments and
+/// the `storages` section of the zenohd configuration file:
+/// ```text
+/// storages: {
+///   backend_search_dirs: ["/usr/lib/zenoh"],   // --backend-search-dir
+///   no_backend: false,                         // --no-backend
+///   memory: ["/demo/**"],                      // --mem-storage
+///   backends: {
+///     influxdb: {                              // the backend name
+///       lib: "/usr/lib/libzbackend_influxdb.so",
+///       url: "http://localhost:8086",          // the backend properties
+///       storages: {
+///         demo: {                              // the storage name
+///           path_expr: "/demo/influxdb/**",
+///           path_prefix: "/demo/influxdb",
+///           db: "zenoh_demo",                  // the storage properties
+///         },
+///       },
+///     },
+///   },
+/// }
+/// ```
+#[derive(Default)]
+struct StoragesConfig {
+    backend_search_dirs: Vec<String>,
+    no_backend: bool,
+    mem_storages: Vec<String>,
+    backends: Vec<BackendConfig>,
+}
+
+/// A backend and its storages, declared in the `storages.backends` section.
+struct BackendConfig {
+    name: String,
+    properties: Properties,
+    storages: Vec<(String, Properties)>,
+}
+
+impl StoragesConfig {
+    fn from_args(args: &ArgMatches<'_>) -> ZResult<StoragesConfig> {
+        let mut config = StoragesConfig {
+            backend_search_dirs: args
+                .values_of("backend-search-dir")
+                .map(|values| values.map(String::from).collect())
+                .unwrap_or_default(),
+            no_backend: args.is_present("no-backend"),
+            mem_storages: args
+                .values_of("mem-storage")
+                .map(|values| values.map(String::from).collect())
+                .unwrap_or_default(),
+            backends: vec![],
+        };
+        if let Some(conf_file) = args.value_of("config") {
+            let conf_file = ConfigFile::try_from(std::path::Path::new(conf_file))?;
+            if let Some(section) = conf_file.section(STORAGES_SECTION) {
+                config.load_section(section)?;
+            }
+        }
+        if config.no_backend && (!config.mem_storages.is_empty() || !config.backends.is_empty()) {
+            return zerror!(ZErrorKind::Other {
+                descr: "Backends and storages cannot be created without backend".to_string()
+            });
+        }
+        Ok(config)
+    }
+
+    fn load_section(&mut self, section: &serde_json::Value) -> ZResult<()> {
+        let section = expect_section(
+            STORAGES_SECTION,
+            section,
+            &["backend_search_dirs", "no_backend", "memory", "backends"],
+        )?;
+        for (key, value) in section {
+            let path = join_path(STORAGES_SECTION, key);
+            match key.as_str() {
+                "backend_search_dirs" => {
+                    self.backend_search_dirs
+                        .extend(expect_strings(&path, value)?);
+                }
+                "no_backend" => {
+                    self.no_backend |= expect_string(&path, value)?.as_deref() == Some(ZN_TRUE);
+                }
+                "memory" => self.mem_storages.extend(expect_strings(&path, value)?),
+                _ => {
+                    for (name, value) in expect_map(&path, value)? {
+                        self.backends.push(BackendConfig::load(
+                            name,
+                            &join_path(&path, name),
+                            value,
+                        )?);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl BackendConfig {
+    fn load(name: &str, path: &str, value: &serde_json::Value) -> ZResult<BackendConfig> {
+        let mut backend = BackendConfig {
+            name: name.to_string(),
+            properties: Properties::default(),
+            storages: vec![],
+        };
+        for (key, value) in expect_map(path, value)? {
+            let path = join_path(path, key);
+            if key == STORAGES_SECTION {
+                for (name, value) in expect_map(&path, value)? {
+                    let path = join_path(&path, name);
+                    let props = load_properties(&path, value)?;
+                    if !props.contains_key(PROP_STORAGE_PATH_EXPR) {
+                        return zerror!(ZErrorKind::Other {
+                            descr: format!("Missing '{}.{}'", path, PROP_STORAGE_PATH_EXPR)
+                        });
+                    }
+                    backend.storages.push((name.clone(), props));
+                }
+            } else if let Some(v) = expect_string(&path, value)? {
+                backend.properties.insert(key.clone(), v);
+            }
+        }
+        Ok(backend)
+    }
+}
+
+fn load_properties(path: &str, value: &serde_json::Value) -> ZResult<Properties> {
+    let mut props = Properties::default();
+    for (key, value) in expect_map(path, value)? {
+        if let Some(v) = expect_string(&join_path(path, key), value)? {
+            props.insert(key.clone(), v);
+        }
+    }
+    Ok(props)
+}
 
-async fn run(runtime: Runtime, args: ArgMatches<'_>) {
+async fn run(
+    runtime: Runtime,
+    args: ArgMatches<'_>,
+    stop_rx: Receiver<()>,
+    _stopped_tx: std::sync::mpsc::Sender<()>,
+) {
     // Try to initiate login.
     // Required in case of dynamic lib, otherwise no logs.
     // But cannot be done twice in case of static link.
     let _ = env_logger::try_init();
 
-    let lib_loader = if let Some(values) = args.values_of("backend-search-dir") {
-        LibLoader::new(&values.collect::<Vec<&str>>(), false)
+    let config = match StoragesConfig::from_args(&args) {
+        Ok(config) => config,
+        Err(e) => {
+            error!("Invalid storages configuration: {}", e);
+            return;
+        }
+    };
+
+    let lib_loader = if !config.backend_search_dirs.is_empty() {
+        LibLoader::new(&config.backend_search_dirs, false)
     } else {
         LibLoader::default()
     };
@@ -112,12 +287,18 @@ async fn run(runtime: Runtime, args: ArgMatches<'_>) {
         .unwrap();
 
     // Map owning handles on alive backends. Once dropped, a handle will release/stop the backend.
-    let mut backend_handles: HashMap<Path, Sender<bool>> = HashMap::new();
+    let mut backend_handles: HashMap<Path, TaskHandle> = HashMap::new();
 
-    // Start Memory Backend and storages if configured via args
-    if !args.is_present("no-backend") {
+    // Start Memory Backend and storages if configured via args or config file
+    if !config.no_backend {
         debug!("Memory backend enabled");
-        let mem_backend = memory_backend::create_backend(Properties::default()).unwrap();
+        let mem_props = config
+            .backends
+            .iter()
+            .find(|backend| backend.name == MEMORY_BACKEND_NAME)
+            .map(|backend| backend.properties.clone())
+            .unwrap_or_default();
+        let mem_backend = memory_backend::create_backend(mem_props).unwrap();
         let mem_backend_path =
             Path::try_from(format!("{}/{}", backends_prefix, MEMORY_BACKEND_NAME)).unwrap();
         let handle = start_backend(mem_backend, mem_backend_path.clone(), zenoh.clone())
@@ -125,69 +306,132 @@ async fn run(runtime: Runtime, args: ArgMatches<'_>) {
             .unwrap();
         backend_handles.insert(mem_backend_path.clone(), handle);
 
-        if let Some(values) = args.values_of("mem-storage") {
-            let mut i: u32 = 1;
-            for path_expr in values {
-                debug!(
-                    "Add memory storage {}-{} on {}",
-                    MEMORY_STORAGE_NAME, i, path_expr
-                );
-                let storage_admin_path = Path::try_from(format!(
-                    "{}/storage/{}-{}",
-                    mem_backend_path, MEMORY_STORAGE_NAME, i
-                ))
+        for (i, path_expr) in (1u32..).zip(config.mem_storages.iter()) {
+            debug!(
+                "Add memory storage {}-{} on {}",
+                MEMORY_STORAGE_NAME, i, path_expr
+            );
+            let storage_admin_path = Path::try_from(format!(
+                "{}/storage/{}-{}",
+                mem_backend_path, MEMORY_STORAGE_NAME, i
+            ))
+            .unwrap();
+            let props = Properties::from([(PROP_STORAGE_PATH_EXPR, path_expr.as_str())].as_ref());
+            workspace
+                .put(&storage_admin_path, Value::Properties(props))
+                .await
                 .unwrap();
-                let props = Properties::from([(PROP_STORAGE_PATH_EXPR, path_expr)].as_ref());
-                workspace
-                    .put(&storage_admin_path, Value::Properties(props))
-                    .await
-                    .unwrap();
-                i += 1
+        }
+    }
+
+    // Start the backends and storages declared in the config file
+    for backend in config.backends {
+        let backend_path = match Path::try_from(format!("{}/{}", backends_prefix, backend.name)) {
+            Ok(path) => path,
+            Err(e) => {
+                warn!("Invalid backend name {}: {}", backend.name, e);
+                continue;
+            }
+        };
+        // The Memory backend is already started
+        if backend.name != MEMORY_BACKEND_NAME {
+            match load_and_start_backend(
+                &backend_path,
+                Value::Properties(backend.properties),
+                zenoh.clone(),
+                &lib_loader,
+            )
+            .await
+            {
+                Ok(handle) => {
+                    let _ = backend_handles.insert(backend_path.clone(), handle);
+                }
+                Err(e) => {
+                    warn!("{}", e);
+                    continue;
+                }
+            }
+        }
+        for (name, props) in backend.storages {
+            debug!("Add storage {} on backend {}", name, backend.name);
+            let storage_admin_path =
+                match Path::try_from(format!("{}/storage/{}", backend_path, name)) {
+                    Ok(path) => path,
+                    Err(e) => {
+                        warn!("Invalid storage name {}: {}", name, e);
+                        continue;
+                    }
+                };
+            if let Err(e) = workspace
+                .put(&storage_admin_path, Value::Properties(props))
+                .await
+            {
+                warn!("Failed to create storage {}: {}", storage_admin_path, e);
             }
         }
     }
 
     // subscribe to PUT/DELETE on 'backends_prefix'/*
     let backends_admin_selector = Selector::try_from(format!("{}/*", backends_prefix)).unwrap();
-    if let Ok(mut backends_admin) = workspace.subscribe(&backends_admin_selector).await {
-        while let Some(change) = backends_admin.next().await {
-            debug!("Received change: {:?}", change);
-            match change.kind {
-                ChangeKind::Put => {
-                    #[allow(clippy::map_entry)]
-                    // Disable clippy check because no way to log the warn using map.entry().or_insert()
-                    if !backend_handles.contains_key(&change.path) {
-                        if let Some(value) = change.value {
-                            match load_and_start_backend(
-                                &change.path,
-                                value,
-                                zenoh.clone(),
-                                &lib_loader,
-                            )
-                            .await
-                            {
-                                Ok(handle) => {
-                                    let _ = backend_handles.insert(change.path, handle);
+    let mut backends_admin = match workspace.subscribe(&backends_admin_selector).await {
+        Ok(backends_admin) => backends_admin,
+        Err(_) => {
+            error!("Failed to subscribe on {}", backends_admin_selector);
+            return;
+        }
+    };
+    loop {
+        select!(
+            change = backends_admin.next().fuse() => {
+                let change = match change {
+                    Some(change) => change,
+                    None => break,
+                };
+                debug!("Received change: {:?}", change);
+                match change.kind {
+                    ChangeKind::Put => {
+                        #[allow(clippy::map_entry)]
+                        // Disable clippy check because no way to log the warn using map.entry().or_insert()
+                        if !backend_handles.contains_key(&change.path) {
+                            if let Some(value) = change.value {
+                                match load_and_start_backend(
+                                    &change.path,
+                                    value,
+                                    zenoh.clone(),
+                                    &lib_loader,
+                                )
+                                .await
+                                {
+                                    Ok(handle) => {
+                                        let _ = backend_handles.insert(change.path, handle);
+                                    }
+                                    Err(e) => warn!("{}", e),
                                 }
-                                Err(e) => warn!("{}", e),
+                            } else {
+                                warn!("Received a PUT on {} without value", change.path);
                             }
                         } else {
-                            warn!("Received a PUT on {} without value", change.path);
+                            warn!("Backend {} already exists", change.path);
                         }
-                    } else {
-                        warn!("Backend {} already exists", change.path);
                     }
+                    ChangeKind::Delete => {
+                        debug!("Delete backend {}", change.path);
+                        let _ = backend_handles.remove(&change.path);
+                    }
+                    ChangeKind::Patch => warn!("PATCH not supported on {}", change.path),
                 }
-                ChangeKind::Delete => {
-                    debug!("Delete backend {}", change.path);
-                    let _ = backend_handles.remove(&change.path);
-                }
-                ChangeKind::Patch => warn!("PATCH not supported on {}", change.path),
+            },
+            _ = stop_rx.recv().fuse() => {
+                debug!("Stopping storages plugin");
+                break;
             }
-        }
-    } else {
-        error!("Failed to subscribe on {}", backends_admin_selector);
-    };
+        );
+    }
+
+    // Stop the backends, each one undeclaring its storages before terminating
+    for (_, handle) in backend_handles.drain() {
+        handle.stop().await;
+    }
 }
 
 /// Signature of the `create_backend` operation to be implemented in the library as an entrypoint.
@@ -200,9 +444,15 @@ async fn load_and_start_backend(
     value: Value,
     zenoh: Arc<Zenoh>,
     lib_loader: &LibLoader,
-) -> ZResult<Sender<bool>> {
+) -> ZResult<TaskHandle> {
     if let Value::Properties(props) = value {
         let name = path.last_segment();
+        // The filesystem backend is built-in, unless another library is explicitly specified
+        if name == FS_BACKEND_NAME && props.get("lib").is_none() {
+            debug!("Create built-in filesystem backend {}", name);
+            let backend = fs_backend::create_backend(props)?;
+            return start_backend(backend, path.clone(), zenoh).await;
+        }
         let (lib, lib_path) = unsafe {
             if let Some(filename) = props.get("lib") {
                 LibLoader::load_file(filename)?
@@ -244,3 +494,101 @@ async fn load_and_start_backend(
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const PATH: &str = "storages.backends.influxdb";
+
+    #[test]
+    fn backend_config_load() {
+        let value = json!({
+            "lib": "/usr/lib/libzbackend_influxdb.so",
+            "url": "http://localhost:8086",
+            "port": 8086,
+            "tags": ["a", "b"],
+            "unset": null,
+            "storages": {
+                "demo": {
+                    "path_expr": "/demo/influxdb/**",
+                    "db": "zenoh_demo",
+                },
+                "test": {
+                    "path_expr": "/test/**",
+                },
+            },
+        });
+        let backend = BackendConfig::load("influxdb", PATH, &value).unwrap();
+        assert_eq!(backend.name, "influxdb");
+        assert_eq!(backend.properties.len(), 4);
+        assert_eq!(
+            backend.properties.get("lib").unwrap(),
+            "/usr/lib/libzbackend_influxdb.so"
+        );
+        assert_eq!(backend.properties.get("port").unwrap(), "8086");
+        assert_eq!(backend.properties.get("tags").unwrap(), "a,b");
+        assert!(!backend.properties.contains_key("unset"));
+        assert!(!backend.properties.contains_key(STORAGES_SECTION));
+
+        let mut storages = backend.storages;
+        storages.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(storages.len(), 2);
+        assert_eq!(storages[0].0, "demo");
+        assert_eq!(
+            storages[0].1.get(PROP_STORAGE_PATH_EXPR).unwrap(),
+            "/demo/influxdb/**"
+        );
+        assert_eq!(storages[0].1.get("db").unwrap(), "zenoh_demo");
+        assert_eq!(storages[1].0, "test");
+        assert_eq!(storages[1].1.len(), 1);
+
+        let backend =
+            BackendConfig::load("memory", "storages.backends.memory", &json!({})).unwrap();
+        assert!(backend.properties.is_empty());
+        assert!(backend.storages.is_empty());
+    }
+
+    #[test]
+    fn backend_config_load_invalid() {
+        let invalid = [
+            (json!("influxdb"), "storages.backends.influxdb"),
+            (
+                json!({ "url": { "host": "localhost" } }),
+                "storages.backends.influxdb.url",
+            ),
+            (
+                json!({ "tags": [["a"]] }),
+                "storages.backends.influxdb.tags[0]",
+            ),
+            (
+                json!({ "storages": ["demo"] }),
+                "storages.backends.influxdb.storages",
+            ),
+            (
+                json!({ "storages": { "demo": "/demo/**" } }),
+                "storages.backends.influxdb.storages.demo",
+            ),
+            (
+                json!({ "storages": { "demo": { "db": "zenoh_demo" } } }),
+                "storages.backends.influxdb.storages.demo.path_expr",
+            ),
+            (
+                json!({ "storages": { "demo": { "path_expr": "/demo/**", "db": {} } } }),
+                "storages.backends.influxdb.storages.demo.db",
+            ),
+        ];
+        for (value, path) in invalid.iter() {
+            match BackendConfig::load("influxdb", PATH, value) {
+                Ok(_) => panic!("{} should be invalid", value),
+                Err(e) => assert!(
+                    e.to_string().contains(&format!("'{}'", path)),
+                    "{} not reported in: {}",
+                    path,
+                    e
+                ),
+            }
+        }
+    }
+}
@@ -13,15 +13,31 @@
 //
 use async_std::sync::{Arc, RwLock};
 use async_trait::async_trait;
-use log::{debug, trace, warn};
+use log::{debug, trace};
 use std::collections::hash_map::Entry;
-use std::collections::HashMap;
-use std::time::{Duration, Instant};
+use std::collections::{BTreeMap, HashMap};
+use std::convert::TryFrom;
+use std::time::{Duration, Instant, SystemTime};
 use zenoh::net::utils::resource_name;
 use zenoh::net::Sample;
-use zenoh::{utils, ChangeKind, Properties, Timestamp, Value, ZResult};
+use zenoh::{
+    utils, ChangeKind, Properties, Selector, Timestamp, Value, ZError, ZErrorKind, ZResult,
+};
+use zenoh_backend_traits::utils::merge_patch;
 use zenoh_backend_traits::*;
 use zenoh_util::collections::{Timed, TimedEvent, TimedHandle, Timer};
+use zenoh_util::{zerror, zerror2};
+
+/// The "history" property of a memory storage. If `"all"`, all the timestamped versions of each key
+/// (including its deletions) are kept and returned to time-range queries.
+/// Default: `"latest"` (only the latest version is kept).
+pub const PROP_STORAGE_HISTORY: &str = "history";
+/// The "history_max_samples" property of a memory storage in history mode: the maximum number of
+/// versions kept per key. Default: unlimited.
+pub const PROP_STORAGE_HISTORY_MAX_SAMPLES: &str = "history_max_samples";
+/// The "history_max_age" property of a memory storage in history mode: the maximum age (in seconds)
+/// of the versions kept per key. Default: unlimited.
+pub const PROP_STORAGE_HISTORY_MAX_AGE: &str = "history_max_age";
 
 pub fn create_backend(_unused: Properties) -> ZResult<Box<dyn Backend>> {
     // For now admin status is static and only contains a PROP_BACKEND_TYPE entry
@@ -90,7 +106,7 @@ impl Drop for MemoryBackend {
 enum StoredValue {
     Present {
         ts: Timestamp,
-        sample: Sample,
+        sample: Box<Sample>,
     },
     Removed {
         ts: Timestamp,
@@ -112,19 +128,111 @@ impl StoredValue {
 }
 use StoredValue::{Present, Removed};
 
+// The timestamped versions of each key (including the deletions), kept by a storage in history mode
+struct History {
+    max_samples: Option<usize>,
+    max_age: Option<Duration>,
+    versions: HashMap<String, BTreeMap<Timestamp, Sample>>,
+}
+
+impl History {
+    fn from_properties(properties: &Properties) -> ZResult<Option<History>> {
+        match properties.get(PROP_STORAGE_HISTORY).map(|s| s.as_str()) {
+            None | Some("latest") => return Ok(None),
+            Some("all") => (),
+            Some(other) => {
+                return zerror!(ZErrorKind::Other {
+                    descr: format!(
+                        "Invalid value for {} property: '{}' (expected 'latest' or 'all')",
+                        PROP_STORAGE_HISTORY, other
+                    )
+                })
+            }
+        }
+        let max_samples = properties
+            .get(PROP_STORAGE_HISTORY_MAX_SAMPLES)
+            .map(|s| {
+                s.parse::<usize>().map_err(|_| {
+                    zerror2!(ZErrorKind::Other {
+                        descr: format!(
+                            "Invalid value for {} property: '{}'",
+                            PROP_STORAGE_HISTORY_MAX_SAMPLES, s
+                        )
+                    })
+                })
+            })
+            .transpose()?;
+        let max_age = properties
+            .get(PROP_STORAGE_HISTORY_MAX_AGE)
+            .map(|s| match s.parse::<f64>() {
+                Ok(secs) if secs.is_finite() && secs >= 0.0 => Ok(Duration::from_secs_f64(secs)),
+                _ => zerror!(ZErrorKind::Other {
+                    descr: format!(
+                        "Invalid value for {} property: '{}'",
+                        PROP_STORAGE_HISTORY_MAX_AGE, s
+                    )
+                }),
+            })
+            .transpose()?;
+        Ok(Some(History {
+            max_samples,
+            max_age,
+            versions: HashMap::new(),
+        }))
+    }
+
+    fn insert(&mut self, timestamp: Timestamp, sample: Sample) {
+        let versions = self.versions.entry(sample.res_name.clone()).or_default();
+        versions.insert(timestamp, sample);
+        // apply the retention policy, removing the oldest versions first
+        if let Some(max_samples) = self.max_samples {
+            while versions.len() > max_samples {
+                let oldest = *versions.keys().next().unwrap();
+                versions.remove(&oldest);
+            }
+        }
+        self.prune();
+    }
+
+    // Removes the versions older than max_age, and the keys left without any version
+    fn prune(&mut self) {
+        if let Some(limit) = self
+            .max_age
+            .and_then(|max_age| SystemTime::now().checked_sub(max_age))
+        {
+            self.versions.retain(|_, versions| {
+                versions.retain(|ts, _| ts.get_time().to_system_time() >= limit);
+                !versions.is_empty()
+            });
+        }
+    }
+}
+
+fn is_in_time_range(
+    timestamp: &Timestamp,
+    start: &Option<SystemTime>,
+    stop: &Option<SystemTime>,
+) -> bool {
+    let time = timestamp.get_time().to_system_time();
+    !matches!(start, Some(start) if *start > time) && !matches!(stop, Some(stop) if *stop < time)
+}
+
 struct MemoryStorage {
     admin_status: Value,
     map: Arc<RwLock<HashMap<String, StoredValue>>>,
+    history: Option<History>,
     timer: Timer,
 }
 
 impl MemoryStorage {
     async fn new(properties: Properties) -> ZResult<MemoryStorage> {
+        let history = History::from_properties(&properties)?;
         let admin_status = utils::properties_to_json_value(&properties);
 
         Ok(MemoryStorage {
             admin_status,
             map: Arc::new(RwLock::new(HashMap::new())),
+            history,
             timer: Timer::new(),
         })
     }
@@ -164,11 +272,17 @@ impl Storage for MemoryStorage {
         } else {
             (ChangeKind::Put, utils::new_reception_timestamp())
         };
+        if let Some(history) = self.history.as_mut() {
+            if !matches!(kind, ChangeKind::Patch) {
+                // in history mode, all versions are kept, even the out-of-date ones
+                history.insert(timestamp, sample.clone());
+            }
+        }
         match kind {
             ChangeKind::Put => match self.map.write().await.entry(sample.res_name.clone()) {
                 Entry::Vacant(v) => {
                     v.insert(Present {
-                        sample,
+                        sample: Box::new(sample),
                         ts: timestamp,
                     });
                 }
@@ -184,7 +298,7 @@ impl Storage for MemoryStorage {
                             cleanup_handle.clone().defuse();
                         }
                         o.insert(Present {
-                            sample,
+                            sample: Box::new(sample),
                             ts: timestamp,
                         });
                     } else {
@@ -224,30 +338,106 @@ impl Storage for MemoryStorage {
                     }
                 }
             },
-            ChangeKind::Patch => {
-                warn!("Received PATCH for {}: not yet supported", sample.res_name);
-            }
+            ChangeKind::Patch => match self.map.write().await.entry(sample.res_name.clone()) {
+                Entry::Vacant(v) => {
+                    let merged = merge_patch(None, &sample)?;
+                    if let Some(history) = self.history.as_mut() {
+                        history.insert(timestamp, merged.clone());
+                    }
+                    v.insert(Present {
+                        sample: Box::new(merged),
+                        ts: timestamp,
+                    });
+                }
+                Entry::Occupied(mut o) => {
+                    let old_val = o.get();
+                    if old_val.ts() < &timestamp {
+                        let merged = match old_val {
+                            Present {
+                                sample: stored,
+                                ts: _,
+                            } => merge_patch(Some(stored.as_ref()), &sample)?,
+                            Removed {
+                                ts: _,
+                                cleanup_handle,
+                            } => {
+                                let merged = merge_patch(None, &sample)?;
+                                // cancel timed cleanup
+                                cleanup_handle.clone().defuse();
+                                merged
+                            }
+                        };
+                        if let Some(history) = self.history.as_mut() {
+                            history.insert(timestamp, merged.clone());
+                        }
+                        o.insert(Present {
+                            sample: Box::new(merged),
+                            ts: timestamp,
+                        });
+                    } else {
+                        debug!("PATCH on {} dropped: out-of-date", sample.res_name);
+                    }
+                }
+            },
         }
         Ok(())
     }
 
     async fn on_query(&mut self, query: Query) -> ZResult<()> {
         trace!("on_query for {}", query.res_name());
-        if !query.res_name().contains('*') {
-            if let Some(Present { sample, ts: _ }) = self.map.read().await.get(query.res_name()) {
-                query.reply(sample.clone()).await;
+        let selector = Selector::try_from(&query)?;
+        for sample in self.get_samples(&selector).await? {
+            query.reply(sample).await;
+        }
+        Ok(())
+    }
+}
+
+impl MemoryStorage {
+    // Returns the stored samples matching the selector
+    async fn get_samples(&mut self, selector: &Selector) -> ZResult<Vec<Sample>> {
+        let res_name = selector.path_expr.as_str();
+        let mut samples = vec![];
+        if selector.has_time_range() {
+            let (start, stop) = zenoh_backend_traits::utils::get_time_range(selector)?;
+            if let Some(history) = self.history.as_mut() {
+                history.prune();
+                // reply with every version inside the time range
+                for (name, versions) in history.versions.iter() {
+                    if resource_name::intersect(res_name, name) {
+                        for (ts, sample) in versions.iter() {
+                            if is_in_time_range(ts, &start, &stop) {
+                                samples.push(sample.clone());
+                            }
+                        }
+                    }
+                }
+            } else {
+                // reply with the latest version if inside the time range
+                for (_, stored_value) in self.map.read().await.iter() {
+                    if let Present { sample, ts } = stored_value {
+                        if resource_name::intersect(res_name, &sample.res_name)
+                            && is_in_time_range(ts, &start, &stop)
+                        {
+                            samples.push(sample.as_ref().clone());
+                        }
+                    }
+                }
+            }
+        } else if !res_name.contains('*') {
+            if let Some(Present { sample, ts: _ }) = self.map.read().await.get(res_name) {
+                samples.push(sample.as_ref().clone());
             }
         } else {
             for (_, stored_value) in self.map.read().await.iter() {
                 if let Present { sample, ts: _ } = stored_value {
-                    if resource_name::intersect(query.res_name(), &sample.res_name) {
-                        let s: Sample = sample.clone();
-                        query.reply(s).await;
+                    if resource_name::intersect(res_name, &sample.res_name) {
+                        samples.push(sample.as_ref().clone());
                     }
                 }
             }
         }
-        Ok(())
+        Ok(samples)
     }
 }
 
@@ -271,3 +461,227 @@ impl Timed for TimedCleanup {
         self.map.write().await.remove(&self.path);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_std::task;
+    use std::time::UNIX_EPOCH;
+    use zenoh::net::{DataInfo, ZInt};
+    use zenoh::TimestampId;
+
+    async fn new_storage(props: &[(&str, &str)]) -> MemoryStorage {
+        let mut properties = Properties::from(props);
+        properties.insert(PROP_STORAGE_PATH_EXPR.into(), "/demo/mem/**".into());
+        MemoryStorage::new(properties).await.unwrap()
+    }
+
+    fn timestamp(time: SystemTime) -> Timestamp {
+        Timestamp::new(
+            time.duration_since(UNIX_EPOCH).unwrap().into(),
+            TimestampId::new(1, [0u8; TimestampId::MAX_SIZE]),
+        )
+    }
+
+    fn sample(res_name: &str, kind: ChangeKind, payload: &str, time: SystemTime) -> Sample {
+        let mut info = DataInfo::new();
+        info.kind = Some(kind as ZInt);
+        info.timestamp = Some(timestamp(time));
+        Sample {
+            res_name: res_name.into(),
+            payload: payload.as_bytes().into(),
+            data_info: Some(info),
+            attachment: None,
+        }
+    }
+
+    fn secs(secs: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(secs)
+    }
+
+    // Returns the (res_name, kind, payload) of the samples matching the selector
+    async fn get(storage: &mut MemoryStorage, selector: &str) -> Vec<(String, ChangeKind, String)> {
+        let selector = Selector::try_from(selector).unwrap();
+        let mut samples: Vec<(String, ChangeKind, String)> = storage
+            .get_samples(&selector)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|s| {
+                let kind = s
+                    .data_info
+                    .and_then(|info| info.kind)
+                    .map_or(ChangeKind::Put, ChangeKind::from);
+                (
+                    s.res_name,
+                    kind,
+                    String::from_utf8(s.payload.to_vec()).unwrap(),
+                )
+            })
+            .collect();
+        samples.sort_by(|a, b| (&a.0, &a.2).cmp(&(&b.0, &b.2)));
+        samples
+    }
+
+    fn put(res_name: &str, payload: &str) -> (String, ChangeKind, String) {
+        (res_name.into(), ChangeKind::Put, payload.into())
+    }
+
+    fn del(res_name: &str) -> (String, ChangeKind, String) {
+        (res_name.into(), ChangeKind::Delete, "".into())
+    }
+
+    #[test]
+    fn memory_history_time_range() {
+        task::block_on(async {
+            let mut storage = new_storage(&[(PROP_STORAGE_HISTORY, "all")]).await;
+            for (res_name, kind, payload, t) in &[
+                ("/demo/mem/a", ChangeKind::Put, "a1", 10),
+                ("/demo/mem/a", ChangeKind::Put, "a2", 20),
+                ("/demo/mem/b", ChangeKind::Put, "b1", 15),
+                ("/demo/mem/a", ChangeKind::Delete, "", 30),
+                ("/demo/mem/a", ChangeKind::Put, "a3", 40),
+            ] {
+                let s = sample(res_name, kind.clone(), payload, secs(*t));
+                storage.on_sample(s).await.unwrap();
+            }
+
+            // without time range, only the latest value is returned
+            assert_eq!(
+                get(&mut storage, "/demo/mem/**").await,
+                vec![put("/demo/mem/a", "a3"), put("/demo/mem/b", "b1")]
+            );
+            assert_eq!(
+                get(&mut storage, "/demo/mem/**?(starttime=0)").await,
+                vec![
+                    del("/demo/mem/a"),
+                    put("/demo/mem/a", "a1"),
+                    put("/demo/mem/a", "a2"),
+                    put("/demo/mem/a", "a3"),
+                    put("/demo/mem/b", "b1")
+                ]
+            );
+            assert_eq!(
+                get(&mut storage, "/demo/mem/a?(starttime=15;stoptime=30)").await,
+                vec![del("/demo/mem/a"), put("/demo/mem/a", "a2")]
+            );
+            assert_eq!(
+                get(&mut storage, "/demo/mem/*?(stoptime=15)").await,
+                vec![put("/demo/mem/a", "a1"), put("/demo/mem/b", "b1")]
+            );
+            assert!(get(&mut storage, "/demo/mem/**?(starttime=50)")
+                .await
+                .is_empty());
+        });
+    }
+
+    #[test]
+    fn memory_latest_time_range() {
+        task::block_on(async {
+            let mut storage = new_storage(&[]).await;
+            let s = sample("/demo/mem/a", ChangeKind::Put, "a1", secs(10));
+            storage.on_sample(s).await.unwrap();
+            let s = sample("/demo/mem/a", ChangeKind::Put, "a2", secs(20));
+            storage.on_sample(s).await.unwrap();
+            assert_eq!(
+                get(&mut storage, "/demo/mem/**?(starttime=0)").await,
+                vec![put("/demo/mem/a", "a2")]
+            );
+            assert!(get(&mut storage, "/demo/mem/**?(stoptime=15)")
+                .await
+                .is_empty());
+        });
+    }
+
+    #[test]
+    fn memory_history_max_samples() {
+        task::block_on(async {
+            let mut storage = new_storage(&[
+                (PROP_STORAGE_HISTORY, "all"),
+                (PROP_STORAGE_HISTORY_MAX_SAMPLES, "2"),
+            ])
+            .await;
+            for t in 1..=4 {
+                let s = sample("/demo/mem/a", ChangeKind::Put, &format!("a{}", t), secs(t));
+                storage.on_sample(s).await.unwrap();
+            }
+            // an out-of-date version is kept if among the latest ones
+            let s = sample("/demo/mem/a", ChangeKind::Put, "a0", secs(0));
+            storage.on_sample(s).await.unwrap();
+            let s = sample("/demo/mem/a", ChangeKind::Delete, "", secs(5));
+            storage.on_sample(s).await.unwrap();
+            assert_eq!(
+                get(&mut storage, "/demo/mem/a?(starttime=0)").await,
+                vec![del("/demo/mem/a"), put("/demo/mem/a", "a4")]
+            );
+        });
+    }
+
+    #[test]
+    fn memory_history_max_age() {
+        task::block_on(async {
+            let mut storage = new_storage(&[
+                (PROP_STORAGE_HISTORY, "all"),
+                (PROP_STORAGE_HISTORY_MAX_AGE, "3"),
+            ])
+            .await;
+            let now = SystemTime::now();
+            let s = sample(
+                "/demo/mem/a",
+                ChangeKind::Put,
+                "old",
+                now - Duration::from_secs(10),
+            );
+            storage.on_sample(s).await.unwrap();
+            // the expired version is pruned on insertion
+            assert!(storage.history.as_ref().unwrap().versions.is_empty());
+
+            let s = sample(
+                "/demo/mem/a",
+                ChangeKind::Put,
+                "a1",
+                now - Duration::from_secs(2),
+            );
+            storage.on_sample(s).await.unwrap();
+            let s = sample("/demo/mem/b", ChangeKind::Put, "b1", now);
+            storage.on_sample(s).await.unwrap();
+            assert_eq!(
+                get(&mut storage, "/demo/mem/**?(starttime=0)").await,
+                vec![put("/demo/mem/a", "a1"), put("/demo/mem/b", "b1")]
+            );
+
+            // the versions expiring without any new insertion are pruned on query
+            task::sleep(Duration::from_millis(1500)).await;
+            assert_eq!(
+                get(&mut storage, "/demo/mem/**?(starttime=0)").await,
+                vec![put("/demo/mem/b", "b1")]
+            );
+            assert!(!storage
+                .history
+                .as_ref()
+                .unwrap()
+                .versions
+                .contains_key("/demo/mem/a"));
+        });
+    }
+
+    #[test]
+    fn memory_history_invalid_properties() {
+        task::block_on(async {
+            for props in &[
+                vec![(PROP_STORAGE_HISTORY, "some")],
+                vec![
+                    (PROP_STORAGE_HISTORY, "all"),
+                    (PROP_STORAGE_HISTORY_MAX_SAMPLES, "-1"),
+                ],
+                vec![
+                    (PROP_STORAGE_HISTORY, "all"),
+                    (PROP_STORAGE_HISTORY_MAX_AGE, "forever"),
+                ],
+            ] {
+                let properties = Properties::from(&props[..]);
+                assert!(MemoryStorage::new(properties).await.is_err());
+            }
+        });
+    }
+}
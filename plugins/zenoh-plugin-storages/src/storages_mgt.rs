@@ -11,7 +11,8 @@
 // Contributors:
 //   ADLINK zenoh team, <zenoh@adlink-labs.tech>
 //
-use async_std::channel::{bounded, Sender};
+use super::backends_mgt::TaskHandle;
+use async_std::channel::bounded;
 use async_std::sync::{Arc, RwLock};
 use async_std::task;
 use futures::select;
@@ -19,7 +20,8 @@ use futures::stream::StreamExt;
 use futures::FutureExt;
 use log::{debug, error, trace, warn};
 use zenoh::net::{
-    queryable, QueryConsolidation, QueryTarget, Reliability, SubInfo, SubMode, Target,
+    encoding, queryable, DataInfo, QueryConsolidation, QueryTarget, Reliability, Reply, Sample,
+    SubInfo, SubMode, Target,
 };
 use zenoh::{Path, PathExpr, ZResult, Zenoh};
 use zenoh_backend_traits::{IncomingDataInterceptor, OutgoingDataInterceptor, Query};
@@ -31,11 +33,11 @@ pub(crate) async fn start_storage(
     in_interceptor: Option<Arc<RwLock<Box<dyn IncomingDataInterceptor>>>>,
     out_interceptor: Option<Arc<RwLock<Box<dyn OutgoingDataInterceptor>>>>,
     zenoh: Arc<Zenoh>,
-) -> ZResult<Sender<bool>> {
+) -> ZResult<TaskHandle> {
     debug!("Start storage {} on {}", admin_path, path_expr);
 
-    let (tx, rx) = bounded::<bool>(1);
-    task::spawn(async move {
+    let (stop_tx, rx) = bounded::<bool>(1);
+    let task = task::spawn(async move {
         let workspace = zenoh.workspace(Some(admin_path.clone())).await.unwrap();
 
         // subscribe on path_expr
@@ -79,6 +81,16 @@ pub(crate) async fn start_storage(
             }
         };
         while let Some(reply) = replies.next().await {
+            let reply = match reply {
+                Reply::Ok(reply) => reply,
+                Reply::Err(err) => {
+                    warn!(
+                        "Storage {} received an error reply aligning {}",
+                        admin_path, err.data.res_name
+                    );
+                    continue;
+                }
+            };
             log::trace!("Storage {} aligns data {}", admin_path, reply.data.res_name);
             // Call incoming data interceptor (if any)
             let sample = if let Some(ref interceptor) = in_interceptor {
@@ -144,8 +156,16 @@ pub(crate) async fn start_storage(
                     // wrap zenoh::net::Query in zenoh_backend_traits::Query
                     // with outgoing interceptor
                     let query = Query::new(q, out_interceptor.clone());
-                    if let Err(e) = storage.on_query(query).await {
+                    if let Err(e) = storage.on_query(query.clone()).await {
                         warn!("Storage {} raised an error receiving a query: {}", admin_path, e);
+                        let mut data_info = DataInfo::new();
+                        data_info.encoding = Some(encoding::TEXT_PLAIN);
+                        query.reply_err(Sample {
+                            res_name: query.res_name().to_string(),
+                            payload: e.to_string().as_bytes().into(),
+                            data_info: Some(data_info),
+                            attachment: None,
+                        }).await;
                     }
                 },
                 // on storage handle drop
@@ -157,5 +177,5 @@ pub(crate) async fn start_storage(
         }
     });
 
-    Ok(tx)
+    Ok(TaskHandle { stop_tx, task })
 }
@@ -17,6 +17,7 @@
 //! To build a plugin, up to 2 types may be constructed :
 //! * A [`Plugin`] type.
 //! * [`PluginLaunch::start`] should be non-blocking, and return a boxed instance of your stoppage type, which should implement [`PluginStopper`].
+//!   Box it with [`boxed_stopper`] to let the host stop your plugin.
 
 use std::any::Any;
 use std::error::Error;
@@ -24,7 +25,7 @@ pub mod loading;
 pub mod vtable;
 
 pub mod prelude {
-    pub use crate::{loading::*, vtable::*, Plugin, PluginStopper};
+    pub use crate::{boxed_stopper, loading::*, vtable::*, Plugin, PluginStopper};
 }
 
 /// Your plugin's identifier.
@@ -106,3 +107,9 @@ pub trait PluginStopper: Send + Sync {
 impl PluginStopper for () {
     fn stop(&self) {}
 }
+
+/// Boxes a [`PluginStopper`] as the control structure to be returned by [`Plugin::start`],
+/// so that the host can stop the plugin with [`PluginsHandles::stop`](loading::PluginsHandles::stop).
+pub fn boxed_stopper<S: PluginStopper + 'static>(stopper: S) -> BoxedAny {
+    Box::new(Box::new(stopper) as Box<dyn PluginStopper>)
+}
@@ -364,6 +364,16 @@ impl<A, B> PluginsHandles<A, B> {
             }))
             .collect()
     }
+
+    /// Stops the plugins which control structure was built with [`boxed_stopper`](crate::boxed_stopper).
+    /// The other plugins are left running.
+    pub fn stop(&self) {
+        for stopper in self.stopper.stoppers.iter() {
+            if let Some(stopper) = stopper.downcast_ref::<Box<dyn PluginStopper>>() {
+                stopper.stop();
+            }
+        }
+    }
 }
 
 impl<A, B> AsRef<Vec<BoxedAny>> for PluginsHandles<A, B> {
@@ -9,8 +9,8 @@ use std::ops::Add;
 use std::time::{Duration, Instant};
 use zenoh::net::queryable::EVAL;
 use zenoh::net::{
-    CongestionControl, ConsolidationMode, QueryConsolidation, QueryTarget, Reliability, ResKey,
-    Sample, Session, SubInfo, SubMode, ZFuture,
+    CongestionControl, ConsolidationMode, QueryConsolidation, QueryTarget, Reliability, Reply,
+    ResKey, Sample, Session, SubInfo, SubMode, ZFuture,
 };
 use zenoh_util::sync::Condition;
 
@@ -192,6 +192,7 @@ async fn query_handler(z: Arc<Session>, state: Arc<GroupState>) {
             res_name: qres.clone(),
             payload: buf.clone().into(),
             data_info: None,
+            attachment: None,
         })
     }
 }
@@ -296,7 +297,17 @@ async fn net_event_handler(z: Arc<Session>, state: Arc<GroupState>) {
                                     .await
                                     .unwrap();
 
-                                while let Some(sample) = receiver.next().await {
+                                while let Some(reply) = receiver.next().await {
+                                    let sample = match reply {
+                                        Reply::Ok(sample) => sample,
+                                        Reply::Err(err) => {
+                                            log::debug!(
+                                                "Error reply received for {}",
+                                                err.data.res_name
+                                            );
+                                            continue;
+                                        }
+                                    };
                                     match bincode::deserialize::<Member>(
                                         &sample.data.payload.to_vec(),
                                     ) {
@@ -259,6 +259,19 @@ impl Receiver<Sample> for QueryingSubscriberReceiver {
     }
 }
 
+fn merge_reply(merge_queue: &mut Vec<Sample>, reply: Reply) {
+    match reply {
+        Reply::Ok(mut reply) => {
+            log::trace!("Reply received: {}", reply.data.res_name);
+            reply.data.ensure_timestamp();
+            merge_queue.push(reply.data);
+        }
+        Reply::Err(err) => {
+            log::warn!("Error reply received: {}", err.data.res_name);
+        }
+    }
+}
+
 struct InnerState {
     subscriber_recv: SampleReceiver,
     replies_recv_queue: Vec<ReplyReceiver>,
@@ -278,10 +291,8 @@ impl Stream for InnerState {
             while i < mself.replies_recv_queue.len() {
                 loop {
                     match mself.replies_recv_queue[i].poll_next(cx) {
-                        Poll::Ready(Some(mut reply)) => {
-                            log::trace!("Reply received: {}", reply.data.res_name);
-                            reply.data.ensure_timestamp();
-                            mself.merge_queue.push(reply.data);
+                        Poll::Ready(Some(reply)) => {
+                            merge_reply(&mut mself.merge_queue, reply);
                         }
                         Poll::Ready(None) => {
                             // query completed - remove the receiver and break loop
@@ -345,10 +356,8 @@ impl InnerState {
         if !self.replies_recv_queue.is_empty() {
             // get all replies and add them to merge_queue
             for recv in self.replies_recv_queue.drain(..) {
-                while let Ok(mut reply) = recv.recv() {
-                    log::trace!("Reply received: {}", reply.data.res_name);
-                    reply.data.ensure_timestamp();
-                    self.merge_queue.push(reply.data);
+                while let Ok(reply) = recv.recv() {
+                    merge_reply(&mut self.merge_queue, reply);
                 }
             }
             log::debug!(
@@ -397,10 +406,8 @@ impl InnerState {
             while i < self.replies_recv_queue.len() {
                 loop {
                     match self.replies_recv_queue[i].try_recv() {
-                        Ok(mut reply) => {
-                            log::trace!("Reply received: {}", reply.data.res_name);
-                            reply.data.ensure_timestamp();
-                            self.merge_queue.push(reply.data);
+                        Ok(reply) => {
+                            merge_reply(&mut self.merge_queue, reply);
                         }
                         Err(TryRecvError::Disconnected) => {
                             // query completed - remove the receiver and break loop
@@ -468,10 +475,8 @@ impl InnerState {
             while i < self.replies_recv_queue.len() {
                 loop {
                     match self.replies_recv_queue[i].recv_deadline(deadline) {
-                        Ok(mut reply) => {
-                            log::trace!("Reply received: {}", reply.data.res_name);
-                            reply.data.ensure_timestamp();
-                            self.merge_queue.push(reply.data);
+                        Ok(reply) => {
+                            merge_reply(&mut self.merge_queue, reply);
                         }
                         Err(RecvTimeoutError::Disconnected) => {
                             // query completed - remove the receiver and break loop
@@ -38,6 +38,7 @@ mod consts {
     pub const ZN_PEER_STR: &str = "peer";
 
     /// A locator to listen on.
+    /// A multicast locator (ex: `"udp/224.0.0.225:7447"`) joins the multicast group instead.
     /// String key : `"listener"`.
     /// Accepted values : `<locator>` (ex: `"tcp/10.10.10.10:7447"`).
     /// Default value : None.
@@ -307,6 +308,126 @@ mod consts {
     pub const ZN_MULTICAST_IPV6_ADDRESS_KEY: u64 = 0x77;
     pub const ZN_MULTICAST_IPV6_ADDRESS_STR: &str = "multicast_ipv6_address";
     pub const ZN_MULTICAST_IPV6_ADDRESS_DEFAULT: &str = "[ff24::224]:7447";
+
+    /// Configures the default timeout in milliseconds for queries.
+    /// When the timeout expires the query is finalized with the replies received so far.
+    /// String key : `"query_timeout"`.
+    /// Accepted values : `<unsigned integer>`.
+    /// Default value : `10000`.
+    pub const ZN_QUERY_TIMEOUT_KEY: u64 = 0x78;
+    pub const ZN_QUERY_TIMEOUT_STR: &str = "query_timeout";
+    pub const ZN_QUERY_TIMEOUT_DEFAULT: &str = "10000";
+
+    /// The file path containing the TLS client private key.
+    /// String key : `"tls_client_private_key"`.
+    /// Accepted values : `<file path>`.
+    /// Default value : None.
+    pub const ZN_TLS_CLIENT_PRIVATE_KEY_KEY: u64 = 0x79;
+    pub const ZN_TLS_CLIENT_PRIVATE_KEY_STR: &str = "tls_client_private_key";
+
+    /// The file path containing the TLS client certificate.
+    /// String key : `"tls_client_certificate"`.
+    /// Accepted values : `<file path>`.
+    /// Default value : None.
+    pub const ZN_TLS_CLIENT_CERTIFICATE_KEY: u64 = 0x7A;
+    pub const ZN_TLS_CLIENT_CERTIFICATE_STR: &str = "tls_client_certificate";
+
+    /// The file path containing the CA certificate used by a TLS listener to verify the
+    /// client certificates. When set, clients are required to present a valid certificate
+    /// whose subject common name is their PeerId.
+    /// String key : `"tls_client_ca_certificate"`.
+    /// Accepted values : `<file path>`.
+    /// Default value : None.
+    pub const ZN_TLS_CLIENT_CA_CERTIFICATE_KEY: u64 = 0x7B;
+    pub const ZN_TLS_CLIENT_CA_CERTIFICATE_STR: &str = "tls_client_ca_certificate";
+
+    /// Configures the compression of the batches sent on the unicast links.
+    /// When enabled, compression is requested on the opened links and it is used
+    /// only if the remote peer supports it. It can be overridden per endpoint.
+    /// String key : `"compression"`.
+    /// Accepted values : `"true"`, `"false"`.
+    /// Default value : `"false"`.
+    pub const ZN_COMPRESSION_KEY: u64 = 0x7C;
+    pub const ZN_COMPRESSION_STR: &str = "compression";
+    pub const ZN_COMPRESSION_DEFAULT: &str = ZN_FALSE;
+
+    /// The file path containing the Ed25519 private key used for public key authentication,
+    /// either PEM or DER encoded in PKCS#8 format.
+    /// String key : `"auth_private_key"`.
+    /// Accepted values : `<file path>`.
+    /// Default value : None.
+    pub const ZN_AUTH_PRIVATE_KEY_KEY: u64 = 0x7D;
+    pub const ZN_AUTH_PRIVATE_KEY_STR: &str = "auth_private_key";
+
+    /// The file path containing the Ed25519 public keys of the trusted peers, one per line,
+    /// either as base64 raw keys or as PEM encoded SubjectPublicKeyInfo.
+    /// String key : `"auth_trusted_keys"`.
+    /// Accepted values : `<file path>`.
+    /// Default value : None.
+    pub const ZN_AUTH_TRUSTED_KEYS_KEY: u64 = 0x7E;
+    pub const ZN_AUTH_TRUSTED_KEYS_STR: &str = "auth_trusted_keys";
+
+    /// The file path containing the access control rules applied by the router,
+    /// one `<allow|deny> <actions> <subject> <key expression>` rule per line.
+    /// String key : `"acl_rules"`.
+    /// Accepted values : `<file path>`.
+    /// Default value : None.
+    pub const ZN_ACL_RULES_KEY: u64 = 0x7F;
+    pub const ZN_ACL_RULES_STR: &str = "acl_rules";
+
+    /// Configures the maximum time in milliseconds a router waits, when shutting down,
+    /// for its plugins to stop and for its transports to be flushed and closed.
+    /// String key : `"drain_timeout"`.
+    /// Accepted values : `<unsigned integer>`.
+    /// Default value : `5000`.
+    pub const ZN_DRAIN_TIMEOUT_KEY: u64 = 0x80;
+    pub const ZN_DRAIN_TIMEOUT_STR: &str = "drain_timeout";
+    pub const ZN_DRAIN_TIMEOUT_DEFAULT: &str = "5000";
+
+    /// Configures the delay in milliseconds before retrying to connect to a configured peer
+    /// after a first failed attempt.
+    /// String key : `"connect_retry_initial_delay"`.
+    /// Accepted values : `<unsigned integer>`.
+    /// Default value : `1000`.
+    pub const ZN_CONNECT_RETRY_INITIAL_DELAY_KEY: u64 = 0x81;
+    pub const ZN_CONNECT_RETRY_INITIAL_DELAY_STR: &str = "connect_retry_initial_delay";
+    pub const ZN_CONNECT_RETRY_INITIAL_DELAY_DEFAULT: &str = "1000";
+
+    /// Configures the maximum delay in milliseconds between two attempts to connect
+    /// to a configured peer.
+    /// String key : `"connect_retry_max_delay"`.
+    /// Accepted values : `<unsigned integer>`.
+    /// Default value : `4000`.
+    pub const ZN_CONNECT_RETRY_MAX_DELAY_KEY: u64 = 0x82;
+    pub const ZN_CONNECT_RETRY_MAX_DELAY_STR: &str = "connect_retry_max_delay";
+    pub const ZN_CONNECT_RETRY_MAX_DELAY_DEFAULT: &str = "4000";
+
+    /// Configures the factor applied to the delay between two attempts to connect
+    /// to a configured peer after each failed attempt.
+    /// String key : `"connect_retry_multiplier"`.
+    /// Accepted values : `<float greater or equal to 1>`.
+    /// Default value : `2`.
+    pub const ZN_CONNECT_RETRY_MULTIPLIER_KEY: u64 = 0x83;
+    pub const ZN_CONNECT_RETRY_MULTIPLIER_STR: &str = "connect_retry_multiplier";
+    pub const ZN_CONNECT_RETRY_MULTIPLIER_DEFAULT: &str = "2";
+
+    /// Configures the fraction of the delay between two attempts to connect to a configured
+    /// peer that is randomized, so that the peers of a restarted router don't retry all at once.
+    /// String key : `"connect_retry_jitter"`.
+    /// Accepted values : `<float between 0 and 1>`.
+    /// Default value : `0`.
+    pub const ZN_CONNECT_RETRY_JITTER_KEY: u64 = 0x84;
+    pub const ZN_CONNECT_RETRY_JITTER_STR: &str = "connect_retry_jitter";
+    pub const ZN_CONNECT_RETRY_JITTER_DEFAULT: &str = "0";
+
+    /// Configures the number of attempts to connect to a configured peer after which
+    /// zenoh gives up. `0` means that zenoh never gives up.
+    /// String key : `"connect_retry_max_attempts"`.
+    /// Accepted values : `<unsigned integer>`.
+    /// Default value : `0`.
+    pub const ZN_CONNECT_RETRY_MAX_ATTEMPTS_KEY: u64 = 0x85;
+    pub const ZN_CONNECT_RETRY_MAX_ATTEMPTS_STR: &str = "connect_retry_max_attempts";
+    pub const ZN_CONNECT_RETRY_MAX_ATTEMPTS_DEFAULT: &str = "0";
 }
 
 pub use consts::*;
@@ -356,6 +477,20 @@ impl KeyTranscoder for ConfigTranscoder {
             ZN_DEFRAG_BUFF_SIZE_STR => Some(ZN_DEFRAG_BUFF_SIZE_KEY),
             ZN_LINK_RX_BUFF_SIZE_STR => Some(ZN_LINK_RX_BUFF_SIZE_KEY),
             ZN_MULTICAST_IPV6_ADDRESS_STR => Some(ZN_MULTICAST_IPV6_ADDRESS_KEY),
+            ZN_QUERY_TIMEOUT_STR => Some(ZN_QUERY_TIMEOUT_KEY),
+            ZN_TLS_CLIENT_PRIVATE_KEY_STR => Some(ZN_TLS_CLIENT_PRIVATE_KEY_KEY),
+            ZN_TLS_CLIENT_CERTIFICATE_STR => Some(ZN_TLS_CLIENT_CERTIFICATE_KEY),
+            ZN_TLS_CLIENT_CA_CERTIFICATE_STR => Some(ZN_TLS_CLIENT_CA_CERTIFICATE_KEY),
+            ZN_COMPRESSION_STR => Some(ZN_COMPRESSION_KEY),
+            ZN_AUTH_PRIVATE_KEY_STR => Some(ZN_AUTH_PRIVATE_KEY_KEY),
+            ZN_AUTH_TRUSTED_KEYS_STR => Some(ZN_AUTH_TRUSTED_KEYS_KEY),
+            ZN_ACL_RULES_STR => Some(ZN_ACL_RULES_KEY),
+            ZN_DRAIN_TIMEOUT_STR => Some(ZN_DRAIN_TIMEOUT_KEY),
+            ZN_CONNECT_RETRY_INITIAL_DELAY_STR => Some(ZN_CONNECT_RETRY_INITIAL_DELAY_KEY),
+            ZN_CONNECT_RETRY_MAX_DELAY_STR => Some(ZN_CONNECT_RETRY_MAX_DELAY_KEY),
+            ZN_CONNECT_RETRY_MULTIPLIER_STR => Some(ZN_CONNECT_RETRY_MULTIPLIER_KEY),
+            ZN_CONNECT_RETRY_JITTER_STR => Some(ZN_CONNECT_RETRY_JITTER_KEY),
+            ZN_CONNECT_RETRY_MAX_ATTEMPTS_STR => Some(ZN_CONNECT_RETRY_MAX_ATTEMPTS_KEY),
             _ => None,
         }
     }
@@ -403,6 +538,24 @@ impl KeyTranscoder for ConfigTranscoder {
             ZN_DEFRAG_BUFF_SIZE_KEY => Some(ZN_DEFRAG_BUFF_SIZE_STR.to_string()),
             ZN_LINK_RX_BUFF_SIZE_KEY => Some(ZN_LINK_RX_BUFF_SIZE_STR.to_string()),
             ZN_MULTICAST_IPV6_ADDRESS_KEY => Some(ZN_MULTICAST_IPV6_ADDRESS_STR.to_string()),
+            ZN_QUERY_TIMEOUT_KEY => Some(ZN_QUERY_TIMEOUT_STR.to_string()),
+            ZN_TLS_CLIENT_PRIVATE_KEY_KEY => Some(ZN_TLS_CLIENT_PRIVATE_KEY_STR.to_string()),
+            ZN_TLS_CLIENT_CERTIFICATE_KEY => Some(ZN_TLS_CLIENT_CERTIFICATE_STR.to_string()),
+            ZN_TLS_CLIENT_CA_CERTIFICATE_KEY => Some(ZN_TLS_CLIENT_CA_CERTIFICATE_STR.to_string()),
+            ZN_COMPRESSION_KEY => Some(ZN_COMPRESSION_STR.to_string()),
+            ZN_AUTH_PRIVATE_KEY_KEY => Some(ZN_AUTH_PRIVATE_KEY_STR.to_string()),
+            ZN_AUTH_TRUSTED_KEYS_KEY => Some(ZN_AUTH_TRUSTED_KEYS_STR.to_string()),
+            ZN_ACL_RULES_KEY => Some(ZN_ACL_RULES_STR.to_string()),
+            ZN_DRAIN_TIMEOUT_KEY => Some(ZN_DRAIN_TIMEOUT_STR.to_string()),
+            ZN_CONNECT_RETRY_INITIAL_DELAY_KEY => {
+                Some(ZN_CONNECT_RETRY_INITIAL_DELAY_STR.to_string())
+            }
+            ZN_CONNECT_RETRY_MAX_DELAY_KEY => Some(ZN_CONNECT_RETRY_MAX_DELAY_STR.to_string()),
+            ZN_CONNECT_RETRY_MULTIPLIER_KEY => Some(ZN_CONNECT_RETRY_MULTIPLIER_STR.to_string()),
+            ZN_CONNECT_RETRY_JITTER_KEY => Some(ZN_CONNECT_RETRY_JITTER_STR.to_string()),
+            ZN_CONNECT_RETRY_MAX_ATTEMPTS_KEY => {
+                Some(ZN_CONNECT_RETRY_MAX_ATTEMPTS_STR.to_string())
+            }
             _ => None,
         }
     }
@@ -0,0 +1,423 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ADLINK zenoh team, <zenoh@adlink-labs.tech>
+//
+//! Structured (JSON5 or YAML) configuration files.
+//!
+//! A structured configuration file is organized in sections, e.g.:
+//! ```text
+//! {
+//!   listeners: ["tcp/0.0.0.0:7447"],
+//!   peers: ["tcp/192.168.1.1:7447"],
+//!   transport: { lease: 10000, auth: { user: "user", password: "password" } },
+//!   scouting: { multicast: false },
+//!   routing: { acl_rules: "/etc/zenoh/acl.rules" },
+//!   plugins: { search_dirs: ["/usr/lib/zenoh"] },
+//!   storages: { memory: ["/demo/**"] },
+//! }
+//! ```
+//! The entries of the `transport`, `scouting` and `routing` sections, as well as the top-level
+//! ones, are mapped to the [`ConfigProperties`] keys. The other sections (`plugins` and `storages`)
+//! are kept as is and are interpreted by their consumer.
+//!
+//! Any file that does not have a `.json`, `.json5`, `.yaml` or `.yml` extension is parsed
+//! as a flat [`Properties`] file.
+use super::config::*;
+use super::Properties;
+use crate::core::{ZError, ZErrorKind, ZResult};
+use crate::{zerror, zerror2};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::path::Path;
+
+enum Entry {
+    Key(u64),
+    Section(&'static [(&'static str, Entry)]),
+    Raw,
+}
+
+const AUTH_SECTION: &[(&str, Entry)] = &[
+    ("user", Entry::Key(ZN_USER_KEY)),
+    ("password", Entry::Key(ZN_PASSWORD_KEY)),
+    ("dictionary", Entry::Key(ZN_USER_PASSWORD_DICTIONARY_KEY)),
+    ("private_key", Entry::Key(ZN_AUTH_PRIVATE_KEY_KEY)),
+    ("trusted_keys", Entry::Key(ZN_AUTH_TRUSTED_KEYS_KEY)),
+];
+
+const TLS_SECTION: &[(&str, Entry)] = &[
+    (
+        "server_private_key",
+        Entry::Key(ZN_TLS_SERVER_PRIVATE_KEY_KEY),
+    ),
+    (
+        "server_certificate",
+        Entry::Key(ZN_TLS_SERVER_CERTIFICATE_KEY),
+    ),
+    (
+        "root_ca_certificate",
+        Entry::Key(ZN_TLS_ROOT_CA_CERTIFICATE_KEY),
+    ),
+    (
+        "client_private_key",
+        Entry::Key(ZN_TLS_CLIENT_PRIVATE_KEY_KEY),
+    ),
+    (
+        "client_certificate",
+        Entry::Key(ZN_TLS_CLIENT_CERTIFICATE_KEY),
+    ),
+    (
+        "client_ca_certificate",
+        Entry::Key(ZN_TLS_CLIENT_CA_CERTIFICATE_KEY),
+    ),
+];
+
+const TRANSPORT_SECTION: &[(&str, Entry)] = &[
+    ("sn_resolution", Entry::Key(ZN_SEQ_NUM_RESOLUTION_KEY)),
+    ("batch_size", Entry::Key(ZN_BATCH_SIZE_KEY)),
+    ("lease", Entry::Key(ZN_LINK_LEASE_KEY)),
+    ("keep_alive", Entry::Key(ZN_LINK_KEEP_ALIVE_KEY)),
+    ("open_timeout", Entry::Key(ZN_OPEN_TIMEOUT_KEY)),
+    ("open_pending", Entry::Key(ZN_OPEN_INCOMING_PENDING_KEY)),
+    ("max_sessions", Entry::Key(ZN_MAX_SESSIONS_KEY)),
+    ("max_links", Entry::Key(ZN_MAX_LINKS_KEY)),
+    ("join_interval", Entry::Key(ZN_JOIN_INTERVAL_KEY)),
+    ("defrag_buff_size", Entry::Key(ZN_DEFRAG_BUFF_SIZE_KEY)),
+    ("link_rx_buff_size", Entry::Key(ZN_LINK_RX_BUFF_SIZE_KEY)),
+    ("qos", Entry::Key(ZN_QOS_KEY)),
+    ("shm", Entry::Key(ZN_SHM_KEY)),
+    ("compression", Entry::Key(ZN_COMPRESSION_KEY)),
+    ("drain_timeout", Entry::Key(ZN_DRAIN_TIMEOUT_KEY)),
+    ("auth", Entry::Section(AUTH_SECTION)),
+    ("tls", Entry::Section(TLS_SECTION)),
+];
+
+const SCOUTING_SECTION: &[(&str, Entry)] = &[
+    ("multicast", Entry::Key(ZN_MULTICAST_SCOUTING_KEY)),
+    ("interface", Entry::Key(ZN_MULTICAST_INTERFACE_KEY)),
+    ("ipv4_address", Entry::Key(ZN_MULTICAST_IPV4_ADDRESS_KEY)),
+    ("ipv6_address", Entry::Key(ZN_MULTICAST_IPV6_ADDRESS_KEY)),
+    ("timeout", Entry::Key(ZN_SCOUTING_TIMEOUT_KEY)),
+    ("delay", Entry::Key(ZN_SCOUTING_DELAY_KEY)),
+    ("peers_autoconnect", Entry::Key(ZN_PEERS_AUTOCONNECT_KEY)),
+    (
+        "routers_autoconnect_multicast",
+        Entry::Key(ZN_ROUTERS_AUTOCONNECT_MULTICAST_KEY),
+    ),
+    (
+        "routers_autoconnect_gossip",
+        Entry::Key(ZN_ROUTERS_AUTOCONNECT_GOSSIP_KEY),
+    ),
+];
+
+const ROUTING_SECTION: &[(&str, Entry)] = &[
+    ("link_state", Entry::Key(ZN_LINK_STATE_KEY)),
+    ("add_timestamp", Entry::Key(ZN_ADD_TIMESTAMP_KEY)),
+    ("local_routing", Entry::Key(ZN_LOCAL_ROUTING_KEY)),
+    ("join_subscriptions", Entry::Key(ZN_JOIN_SUBSCRIPTIONS_KEY)),
+    ("join_publications", Entry::Key(ZN_JOIN_PUBLICATIONS_KEY)),
+    ("query_timeout", Entry::Key(ZN_QUERY_TIMEOUT_KEY)),
+    ("acl_rules", Entry::Key(ZN_ACL_RULES_KEY)),
+];
+
+const CONNECT_RETRY_SECTION: &[(&str, Entry)] = &[
+    (
+        "initial_delay",
+        Entry::Key(ZN_CONNECT_RETRY_INITIAL_DELAY_KEY),
+    ),
+    ("max_delay", Entry::Key(ZN_CONNECT_RETRY_MAX_DELAY_KEY)),
+    ("multiplier", Entry::Key(ZN_CONNECT_RETRY_MULTIPLIER_KEY)),
+    ("jitter", Entry::Key(ZN_CONNECT_RETRY_JITTER_KEY)),
+    (
+        "max_attempts",
+        Entry::Key(ZN_CONNECT_RETRY_MAX_ATTEMPTS_KEY),
+    ),
+];
+
+const ROOT_SECTION: &[(&str, Entry)] = &[
+    ("mode", Entry::Key(ZN_MODE_KEY)),
+    ("id", Entry::Key(ZN_PEER_ID_KEY)),
+    ("listeners", Entry::Key(ZN_LISTENER_KEY)),
+    ("peers", Entry::Key(ZN_PEER_KEY)),
+    ("connect_retry", Entry::Section(CONNECT_RETRY_SECTION)),
+    ("transport", Entry::Section(TRANSPORT_SECTION)),
+    ("scouting", Entry::Section(SCOUTING_SECTION)),
+    ("routing", Entry::Section(ROUTING_SECTION)),
+    ("plugins", Entry::Raw),
+    ("storages", Entry::Raw),
+];
+
+/// The content of a configuration file.
+#[derive(Clone, Debug, Default)]
+pub struct ConfigFile {
+    /// The configuration properties declared in the file.
+    pub properties: ConfigProperties,
+    /// The sections that are not mapped to configuration properties, by name.
+    pub sections: HashMap<String, Value>,
+}
+
+impl ConfigFile {
+    /// Parses a JSON5 (or JSON) configuration.
+    pub fn from_json5(s: &str) -> ZResult<ConfigFile> {
+        let value: Value = json5::from_str(s).map_err(|e| {
+            zerror2!(ZErrorKind::ValueDecodingFailed {
+                descr: format!("Invalid JSON5 configuration: {}", e)
+            })
+        })?;
+        ConfigFile::from_value(&value)
+    }
+
+    /// Parses a YAML configuration.
+    pub fn from_yaml(s: &str) -> ZResult<ConfigFile> {
+        let value: Value = serde_yaml::from_str(s).map_err(|e| {
+            zerror2!(ZErrorKind::ValueDecodingFailed {
+                descr: format!("Invalid YAML configuration: {}", e)
+            })
+        })?;
+        ConfigFile::from_value(&value)
+    }
+
+    /// Builds a configuration from an already parsed document.
+    pub fn from_value(value: &Value) -> ZResult<ConfigFile> {
+        let mut config = ConfigFile::default();
+        config.load_section("", ROOT_SECTION, value)?;
+        Ok(config)
+    }
+
+    /// Returns the section with the given name, if present in the file.
+    pub fn section(&self, name: &str) -> Option<&Value> {
+        self.sections.get(name)
+    }
+
+    fn load_section(&mut self, path: &str, schema: &[(&str, Entry)], value: &Value) -> ZResult<()> {
+        let map = expect_section(path, value, &keys_of(schema))?;
+        for (key, value) in map {
+            let entry_path = join_path(path, key);
+            // expect_section() already checked the key is part of the schema
+            let (_, entry) = schema.iter().find(|(k, _)| k == key).unwrap();
+            match entry {
+                Entry::Key(k) => {
+                    if let Some(s) = expect_string(&entry_path, value)? {
+                        self.properties.insert(*k, s);
+                    }
+                }
+                Entry::Section(s) => self.load_section(&entry_path, s, value)?,
+                Entry::Raw => {
+                    self.sections.insert(entry_path, value.clone());
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl TryFrom<&Path> for ConfigFile {
+    type Error = ZError;
+
+    /// Reads a configuration file, choosing the format according to its extension.
+    /// Files that are neither JSON5 nor YAML are parsed as flat [`Properties`].
+    fn try_from(p: &Path) -> ZResult<ConfigFile> {
+        let s = std::fs::read_to_string(p).map_err(|e| {
+            zerror2!(ZErrorKind::IoError {
+                descr: format!("Failed to read config file {} : {}", p.display(), e)
+            })
+        })?;
+        let extension = p.extension().and_then(|e| e.to_str()).unwrap_or("");
+        let result = match extension {
+            "json" | "json5" => ConfigFile::from_json5(&s),
+            "yaml" | "yml" => ConfigFile::from_yaml(&s),
+            _ => Ok(ConfigFile {
+                properties: Properties::from(s).into(),
+                sections: HashMap::new(),
+            }),
+        };
+        result.map_err(|e| {
+            zerror2!(ZErrorKind::Other {
+                descr: format!("Failed to parse config file {} : {}", p.display(), e)
+            })
+        })
+    }
+}
+
+fn keys_of<'a>(schema: &[(&'a str, Entry)]) -> Vec<&'a str> {
+    schema.iter().map(|(k, _)| *k).collect()
+}
+
+/// Joins a section path and a key into a dotted path (e.g. `"transport.auth"`).
+pub fn join_path(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+/// Checks that `value` is a section only containing some of the `expected` keys,
+/// and returns its entries. `path` is the dotted path of the section, used in error messages.
+pub fn expect_section<'a>(
+    path: &str,
+    value: &'a Value,
+    expected: &[&str],
+) -> ZResult<&'a Map<String, Value>> {
+    let map = expect_map(path, value)?;
+    for key in map.keys() {
+        if !expected.contains(&key.as_str()) {
+            return zerror!(ZErrorKind::Other {
+                descr: format!(
+                    "Unknown key '{}' (expected one of: {})",
+                    join_path(path, key),
+                    expected.join(", ")
+                )
+            });
+        }
+    }
+    Ok(map)
+}
+
+/// Checks that `value` is a section with arbitrary keys, and returns its entries.
+pub fn expect_map<'a>(path: &str, value: &'a Value) -> ZResult<&'a Map<String, Value>> {
+    match value {
+        Value::Object(map) => Ok(map),
+        _ => zerror!(ZErrorKind::Other {
+            descr: format!(
+                "Invalid value for '{}': expected a section",
+                if path.is_empty() { "<root>" } else { path }
+            )
+        }),
+    }
+}
+
+/// Converts a value to the string format of the [`Properties`]. Lists are converted
+/// into comma-separated strings and `null` into `None`.
+pub fn expect_string(path: &str, value: &Value) -> ZResult<Option<String>> {
+    match value {
+        Value::Null => Ok(None),
+        Value::Array(values) => {
+            let mut strings = vec![];
+            for (i, v) in values.iter().enumerate() {
+                match v {
+                    Value::Array(_) | Value::Object(_) | Value::Null => {
+                        return zerror!(ZErrorKind::Other {
+                            descr: format!(
+                                "Invalid value for '{}[{}]': expected a scalar",
+                                path, i
+                            )
+                        })
+                    }
+                    v => strings.push(scalar_to_string(v)),
+                }
+            }
+            Ok(Some(strings.join(",")))
+        }
+        Value::Object(_) => zerror!(ZErrorKind::Other {
+            descr: format!(
+                "Invalid value for '{}': expected a scalar or a list of scalars",
+                path
+            )
+        }),
+        v => Ok(Some(scalar_to_string(v))),
+    }
+}
+
+/// Converts a value that may be either a scalar or a list of scalars into a list of strings.
+pub fn expect_strings(path: &str, value: &Value) -> ZResult<Vec<String>> {
+    match value {
+        Value::Array(values) => values
+            .iter()
+            .enumerate()
+            .map(|(i, v)| match v {
+                Value::Array(_) | Value::Object(_) | Value::Null => zerror!(ZErrorKind::Other {
+                    descr: format!("Invalid value for '{}[{}]': expected a scalar", path, i)
+                }),
+                v => Ok(scalar_to_string(v)),
+            })
+            .collect(),
+        v => Ok(expect_string(path, v)?.into_iter().collect()),
+    }
+}
+
+fn scalar_to_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        v => v.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_file() {
+        let config = ConfigFile::from_json5(
+            r#"{
+                // a comment
+                listeners: ["tcp/0.0.0.0:7447", "udp/0.0.0.0:7447"],
+                transport: { lease: 10000, qos: false, auth: { user: "foo" } },
+                scouting: { multicast: false },
+                connect_retry: { max_delay: 30000, jitter: 0.5 },
+                storages: { memory: ["/demo/**"] },
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.properties.get(&ZN_LISTENER_KEY).unwrap(),
+            "tcp/0.0.0.0:7447,udp/0.0.0.0:7447"
+        );
+        assert_eq!(config.properties.get(&ZN_LINK_LEASE_KEY).unwrap(), "10000");
+        assert_eq!(config.properties.get(&ZN_QOS_KEY).unwrap(), ZN_FALSE);
+        assert_eq!(config.properties.get(&ZN_USER_KEY).unwrap(), "foo");
+        assert_eq!(
+            config.properties.get(&ZN_MULTICAST_SCOUTING_KEY).unwrap(),
+            ZN_FALSE
+        );
+        assert_eq!(
+            config
+                .properties
+                .get(&ZN_CONNECT_RETRY_MAX_DELAY_KEY)
+                .unwrap(),
+            "30000"
+        );
+        assert_eq!(
+            config.properties.get(&ZN_CONNECT_RETRY_JITTER_KEY).unwrap(),
+            "0.5"
+        );
+        assert!(config.section("storages").is_some());
+        assert!(config.section("plugins").is_none());
+
+        let yaml = ConfigFile::from_yaml(
+            "listeners:\n  - tcp/0.0.0.0:7447\n  - udp/0.0.0.0:7447\n\
+            transport:\n  lease: 10000\n  qos: false\n  auth:\n    user: foo\n\
+            scouting:\n  multicast: false\n",
+        )
+        .unwrap();
+        for key in &[
+            ZN_LISTENER_KEY,
+            ZN_LINK_LEASE_KEY,
+            ZN_QOS_KEY,
+            ZN_USER_KEY,
+            ZN_MULTICAST_SCOUTING_KEY,
+        ] {
+            assert_eq!(yaml.properties.get(key), config.properties.get(key));
+        }
+
+        let err = ConfigFile::from_json5("{ transport: { auth: { usr: 'foo' } } }")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("'transport.auth.usr'"));
+        let err = ConfigFile::from_json5("{ scouting: true }")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("'scouting'"));
+        assert!(ConfigFile::from_json5("{ listeners: ").is_err());
+    }
+}
@@ -12,6 +12,7 @@
 //   ADLINK zenoh team, <zenoh@adlink-labs.tech>
 //
 pub mod config;
+pub mod config_file;
 
 use crate::core::*;
 use std::collections::HashMap;
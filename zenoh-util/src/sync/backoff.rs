@@ -11,9 +11,14 @@
 // Contributors:
 //   ADLINK zenoh team, <zenoh@adlink-labs.tech>
 //
+use crate::core::{ZError, ZErrorKind, ZResult};
+use crate::properties::config::*;
+use crate::{zerror, zerror2, zparse};
 use async_std::task;
+use rand::Rng;
 use std::fmt;
 use std::hint::spin_loop;
+use std::time::Duration;
 
 const SPIN_LIMIT: usize = 6;
 const YIELD_LIMIT: usize = 10;
@@ -22,7 +27,6 @@ const YIELD_LIMIT: usize = 10;
 ///
 /// This is the async version of the Backoff implementation provided by
 /// [`Crossebam utils`]: https://docs.rs/crossbeam/0.7.3/crossbeam/utils/struct.Backoff.html
-
 pub struct Backoff {
     step: usize,
 }
@@ -84,3 +88,185 @@ impl Default for Backoff {
         Backoff::new()
     }
 }
+
+/// Policy of the exponential backoff between the attempts of an operation that may fail,
+/// e.g. the connection to a peer.
+///
+/// The delay between two attempts starts at `initial_delay` and is multiplied by `multiplier`
+/// after each failed attempt, up to `max_delay`. A random fraction of at most `jitter` is
+/// then removed from each delay so that the retries of several processes get spread in time.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RetryPolicy {
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+    pub multiplier: f64,
+    pub jitter: f64,
+    pub max_attempts: Option<usize>,
+}
+
+impl RetryPolicy {
+    /// Builds the policy from the `connect_retry_*` configuration properties.
+    pub fn from_config(config: &ConfigProperties) -> ZResult<RetryPolicy> {
+        let initial_delay: u64 = zparse!(config.get_or(
+            &ZN_CONNECT_RETRY_INITIAL_DELAY_KEY,
+            ZN_CONNECT_RETRY_INITIAL_DELAY_DEFAULT
+        ))?;
+        // The default maximum delay is raised to the initial delay when only the latter is set
+        let max_delay: u64 = match config.get(&ZN_CONNECT_RETRY_MAX_DELAY_KEY) {
+            Some(max_delay) => zparse!(max_delay)?,
+            None => {
+                let max_delay: u64 = zparse!(ZN_CONNECT_RETRY_MAX_DELAY_DEFAULT)?;
+                max_delay.max(initial_delay)
+            }
+        };
+        let multiplier: f64 = zparse!(config.get_or(
+            &ZN_CONNECT_RETRY_MULTIPLIER_KEY,
+            ZN_CONNECT_RETRY_MULTIPLIER_DEFAULT
+        ))?;
+        let jitter: f64 = zparse!(config.get_or(
+            &ZN_CONNECT_RETRY_JITTER_KEY,
+            ZN_CONNECT_RETRY_JITTER_DEFAULT
+        ))?;
+        let max_attempts: usize = zparse!(config.get_or(
+            &ZN_CONNECT_RETRY_MAX_ATTEMPTS_KEY,
+            ZN_CONNECT_RETRY_MAX_ATTEMPTS_DEFAULT
+        ))?;
+
+        if max_delay < initial_delay {
+            let e = format!(
+                "Invalid configuration: {} ({}) is lower than {} ({})",
+                ZN_CONNECT_RETRY_MAX_DELAY_STR,
+                max_delay,
+                ZN_CONNECT_RETRY_INITIAL_DELAY_STR,
+                initial_delay
+            );
+            return zerror!(ZErrorKind::ValueDecodingFailed { descr: e });
+        }
+        if multiplier.is_nan() || multiplier < 1.0 {
+            let e = format!(
+                "Invalid configuration: {} must be greater or equal to 1: {}",
+                ZN_CONNECT_RETRY_MULTIPLIER_STR, multiplier
+            );
+            return zerror!(ZErrorKind::ValueDecodingFailed { descr: e });
+        }
+        if !(0.0..=1.0).contains(&jitter) {
+            let e = format!(
+                "Invalid configuration: {} must be between 0 and 1: {}",
+                ZN_CONNECT_RETRY_JITTER_STR, jitter
+            );
+            return zerror!(ZErrorKind::ValueDecodingFailed { descr: e });
+        }
+
+        Ok(RetryPolicy {
+            initial_delay: Duration::from_millis(initial_delay),
+            max_delay: Duration::from_millis(max_delay),
+            multiplier,
+            jitter,
+            max_attempts: if max_attempts == 0 {
+                None
+            } else {
+                Some(max_attempts)
+            },
+        })
+    }
+
+    /// Starts a new sequence of attempts following this policy.
+    pub fn retry(&self) -> Retry {
+        Retry {
+            policy: self.clone(),
+            attempts: 0,
+            delay: self.initial_delay,
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> RetryPolicy {
+        RetryPolicy::from_config(&ConfigProperties::default()).unwrap()
+    }
+}
+
+/// A sequence of attempts following a [`RetryPolicy`].
+#[derive(Clone, Debug)]
+pub struct Retry {
+    policy: RetryPolicy,
+    attempts: usize,
+    delay: Duration,
+}
+
+impl Retry {
+    /// Records a failed attempt and returns the delay to wait before the next one,
+    /// or `None` if the maximum number of attempts has been reached.
+    pub fn next_delay(&mut self) -> Option<Duration> {
+        self.attempts += 1;
+        if let Some(max_attempts) = self.policy.max_attempts {
+            if self.attempts >= max_attempts {
+                return None;
+            }
+        }
+
+        let delay = self.delay;
+        self.delay = self
+            .delay
+            .mul_f64(self.policy.multiplier)
+            .min(self.policy.max_delay);
+
+        if self.policy.jitter > 0.0 {
+            let jitter = self.policy.jitter * rand::thread_rng().gen::<f64>();
+            Some(delay.mul_f64(1.0 - jitter))
+        } else {
+            Some(delay)
+        }
+    }
+
+    /// The number of failed attempts recorded so far.
+    #[inline]
+    pub fn attempts(&self) -> usize {
+        self.attempts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retry_policy() {
+        let mut config = ConfigProperties::default();
+        let mut retry = RetryPolicy::from_config(&config).unwrap().retry();
+        let delays: Vec<u64> = (0..4)
+            .map(|_| retry.next_delay().unwrap().as_millis() as u64)
+            .collect();
+        assert_eq!(delays, vec![1000, 2000, 4000, 4000]);
+        assert_eq!(retry.attempts(), 4);
+        // By default the attempts never stop
+        assert_eq!(RetryPolicy::default().max_attempts, None);
+
+        // Setting only an initial delay above the default maximum raises the maximum
+        config.insert(ZN_CONNECT_RETRY_INITIAL_DELAY_KEY, "5000".to_string());
+        let policy = RetryPolicy::from_config(&config).unwrap();
+        assert_eq!(policy.max_delay, Duration::from_millis(5000));
+
+        config.insert(ZN_CONNECT_RETRY_INITIAL_DELAY_KEY, "100".to_string());
+        config.insert(ZN_CONNECT_RETRY_MAX_DELAY_KEY, "1000".to_string());
+        config.insert(ZN_CONNECT_RETRY_MULTIPLIER_KEY, "1.5".to_string());
+        config.insert(ZN_CONNECT_RETRY_JITTER_KEY, "0.5".to_string());
+        config.insert(ZN_CONNECT_RETRY_MAX_ATTEMPTS_KEY, "3".to_string());
+        let mut retry = RetryPolicy::from_config(&config).unwrap().retry();
+        let delay = retry.next_delay().unwrap();
+        assert!(delay > Duration::from_millis(50) && delay <= Duration::from_millis(100));
+        let delay = retry.next_delay().unwrap();
+        assert!(delay > Duration::from_millis(75) && delay <= Duration::from_millis(150));
+        assert!(retry.next_delay().is_none());
+        assert_eq!(retry.attempts(), 3);
+
+        config.insert(ZN_CONNECT_RETRY_JITTER_KEY, "2".to_string());
+        assert!(RetryPolicy::from_config(&config).is_err());
+        config.insert(ZN_CONNECT_RETRY_JITTER_KEY, "0".to_string());
+        config.insert(ZN_CONNECT_RETRY_MULTIPLIER_KEY, "0.5".to_string());
+        assert!(RetryPolicy::from_config(&config).is_err());
+        config.insert(ZN_CONNECT_RETRY_MULTIPLIER_KEY, "2".to_string());
+        config.insert(ZN_CONNECT_RETRY_MAX_DELAY_KEY, "10".to_string());
+        assert!(RetryPolicy::from_config(&config).is_err());
+    }
+}
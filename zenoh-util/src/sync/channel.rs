@@ -183,7 +183,9 @@ macro_rules! ztranscoder {
                     Poll::Ready(Some(src)) => match self.transcode(src) {
                         Ok(dst) => Poll::Ready(Some(dst)),
                         Err(err) => {
-                            warn!("Received an invalid Reply (drop it): {}", err);
+                            warn!("Received an invalid {} (drop it): {}", stringify!($srctype), err);
+                            // poll again to get the next item
+                            cx.waker().wake_by_ref();
                             Poll::Pending
                         }
                     },
@@ -15,6 +15,7 @@
 extern crate criterion;
 use async_std::sync::Arc;
 use criterion::{BenchmarkId, Criterion};
+use std::time::Duration;
 use zenoh::net::protocol::core::{
     whatami, Channel, CongestionControl, PeerId, Reliability, SubInfo, SubMode,
 };
@@ -25,7 +26,12 @@ use zenoh::net::routing::router::Tables;
 use zenoh::net::transport::DummyPrimitives;
 
 fn tables_bench(c: &mut Criterion) {
-    let mut tables = Tables::new(PeerId::new(0, [0; 16]), whatami::ROUTER, None);
+    let mut tables = Tables::new(
+        PeerId::new(0, [0; 16]),
+        whatami::ROUTER,
+        None,
+        Duration::from_millis(1000),
+    );
     let primitives = Arc::new(DummyPrimitives {});
 
     let face0 = tables.open_face(PeerId::new(0, [0; 16]), whatami::CLIENT, primitives.clone());
@@ -86,6 +92,7 @@ fn tables_bench(c: &mut Criterion) {
                     None,
                     payload.clone(),
                     None,
+                    None,
                 );
             })
         });
@@ -102,6 +109,7 @@ fn tables_bench(c: &mut Criterion) {
                     None,
                     payload.clone(),
                     None,
+                    None,
                 );
             })
         });
@@ -118,6 +126,7 @@ fn tables_bench(c: &mut Criterion) {
                     None,
                     payload.clone(),
                     None,
+                    None,
                 );
             })
         });
@@ -45,6 +45,7 @@ async fn main() {
                     res_name: path.clone(),
                     payload: value.as_bytes().into(),
                     data_info: None,
+                    attachment: None,
                 });
             },
 
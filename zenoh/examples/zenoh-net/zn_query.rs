@@ -40,11 +40,18 @@ async fn main() {
         .await
         .unwrap();
     while let Some(reply) = replies.next().await {
-        println!(
-            ">> [Reply handler] received ('{}': '{}')",
-            reply.data.res_name,
-            String::from_utf8_lossy(&reply.data.payload.contiguous())
-        )
+        match reply {
+            Reply::Ok(reply) => println!(
+                ">> [Reply handler] received ('{}': '{}')",
+                reply.data.res_name,
+                String::from_utf8_lossy(&reply.data.payload.contiguous())
+            ),
+            Reply::Err(err) => println!(
+                ">> [Reply handler] received error ('{}': '{}')",
+                err.data.res_name,
+                String::from_utf8_lossy(&err.data.payload.contiguous())
+            ),
+        }
     }
 }
 
@@ -72,6 +72,7 @@ async fn main() {
                             res_name: stored_name.clone(),
                             payload: data.clone(),
                             data_info: data_info.clone(),
+                            attachment: None,
                         });
                     }
                 }
@@ -12,13 +12,20 @@
 //   ADLINK zenoh team, <zenoh@adlink-labs.tech>
 //
 use async_std::future;
+use async_std::prelude::*;
+use async_std::sync::Arc;
 use async_std::task;
 use clap::{App, Arg, Values};
 use git_version::git_version;
+use serde_json::Value;
+use std::convert::TryFrom;
+use std::time::{Duration, Instant};
 use zenoh::net::plugins::*;
 use zenoh::net::runtime::{AdminSpace, Runtime};
+use zenoh_plugin_trait::loading::PluginsHandles;
+use zenoh_util::core::ZResult;
 use zenoh_util::properties::config::*;
-use zenoh_util::properties::Properties;
+use zenoh_util::properties::config_file::*;
 use zenoh_util::LibLoader;
 
 const GIT_VERSION: &str = git_version!(prefix = "v", cargo_prefix = "v");
@@ -29,13 +36,74 @@ lazy_static::lazy_static!(
 
 const DEFAULT_LISTENER: &str = "tcp/0.0.0.0:7447";
 
-fn get_plugin_search_dirs_from_args() -> Vec<String> {
-    let mut result: Vec<String> = vec![];
+fn get_config_file_from_args() -> Option<String> {
     let mut iter = std::env::args();
+    while let Some(arg) = iter.next() {
+        if arg == "-c" || arg == "--config" {
+            return iter.next();
+        } else if let Some(name) = arg.strip_prefix("--config=") {
+            return Some(name.to_string());
+        }
+    }
+    None
+}
+
+// Converts the "plugins" section of the configuration file into command line arguments:
+//   plugins: {
+//     search_dirs: ["/usr/lib/zenoh"],           // --plugin-search-dir
+//     load: ["/path/to/libzplugin_foo.so"],      // --plugin
+//     nolookup: false,                           // --plugin-nolookup
+//     args: { "rest-http-port": 8000 },          // the arguments expected by the plugins
+//   }
+fn get_plugins_args_from_config(config: &ConfigFile) -> ZResult<Vec<String>> {
+    let mut args = vec![];
+    let section = match config.section("plugins") {
+        Some(section) => section,
+        None => return Ok(args),
+    };
+    let section = expect_section(
+        "plugins",
+        section,
+        &["search_dirs", "load", "nolookup", "args"],
+    )?;
+    for (key, value) in section {
+        let path = join_path("plugins", key);
+        match key.as_str() {
+            "search_dirs" => push_arg_values(&mut args, "plugin-search-dir", &path, value)?,
+            "load" => push_arg_values(&mut args, "plugin", &path, value)?,
+            "nolookup" => push_arg_values(&mut args, "plugin-nolookup", &path, value)?,
+            _ => {
+                // The plugins arguments are checked when parsing the command line
+                for (name, value) in expect_map(&path, value)? {
+                    push_arg_values(&mut args, name, &join_path(&path, name), value)?;
+                }
+            }
+        }
+    }
+    Ok(args)
+}
+
+fn push_arg_values(args: &mut Vec<String>, name: &str, path: &str, value: &Value) -> ZResult<()> {
+    match value {
+        Value::Bool(true) => args.push(format!("--{}", name)),
+        Value::Bool(false) => (),
+        value => {
+            for v in expect_strings(path, value)? {
+                args.push(format!("--{}", name));
+                args.push(v);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn get_plugin_search_dirs_from_args(args: &[String]) -> Vec<String> {
+    let mut result: Vec<String> = vec![];
+    let mut iter = args.iter();
     while let Some(arg) = iter.next() {
         if arg == "--plugin-search-dir" {
             if let Some(arg2) = iter.next() {
-                result.push(arg2);
+                result.push(arg2.clone());
             }
         } else if let Some(name) = arg.strip_prefix("--plugin-search-dir=") {
             result.push(name.to_string());
@@ -44,13 +112,13 @@ fn get_plugin_search_dirs_from_args() -> Vec<String> {
     result
 }
 
-fn get_plugins_from_args() -> Vec<String> {
+fn get_plugins_from_args(args: &[String]) -> Vec<String> {
     let mut result: Vec<String> = vec![];
-    let mut iter = std::env::args();
+    let mut iter = args.iter();
     while let Some(arg) = iter.next() {
         if arg == "-P" || arg == "--plugin" {
             if let Some(arg2) = iter.next() {
-                result.push(arg2);
+                result.push(arg2.clone());
             }
         } else if let Some(name) = arg.strip_prefix("--plugin=") {
             result.push(name.to_string());
@@ -59,6 +127,11 @@ fn get_plugins_from_args() -> Vec<String> {
     result
 }
 
+fn exit_with_error(e: impl std::fmt::Display) -> ! {
+    println!("{}. Exiting...", e);
+    std::process::exit(-1);
+}
+
 fn main() {
     task::block_on(async {
         #[cfg(feature = "stats")]
@@ -81,7 +154,8 @@ fn main() {
             .long_version(LONG_VERSION.as_str())
             .arg(Arg::from_usage(
                 "-c, --config=[FILE] \
-             'The configuration file.'",
+             'The configuration file. Files with a .json5 or .yaml extension are read as structured \
+             configurations, any other file as a list of key=value properties.'",
             ))
             .arg(Arg::from_usage(
                 "-l, --listener=[LOCATOR]... \
@@ -120,8 +194,24 @@ fn main() {
               This option disables this feature.'",
         ));
 
+        // Load the configuration file first, as it may configure the plugins
+        let config_file = get_config_file_from_args().map(|conf_file| {
+            ConfigFile::try_from(std::path::Path::new(&conf_file))
+                .unwrap_or_else(|e| exit_with_error(e))
+        });
+        let config_args = config_file
+            .as_ref()
+            .map(get_plugins_args_from_config)
+            .transpose()
+            .unwrap_or_else(|e| exit_with_error(e))
+            .unwrap_or_default();
+        // The plugins arguments from the configuration file come after the command line ones
+        let cmd_args: Vec<String> = std::env::args()
+            .chain(config_args.iter().cloned())
+            .collect();
+
         // Get plugins search directories from the command line, and create LibLoader
-        let plugin_search_dirs = get_plugin_search_dirs_from_args();
+        let plugin_search_dirs = get_plugin_search_dirs_from_args(&cmd_args);
         let lib_loader = if !plugin_search_dirs.is_empty() {
             LibLoader::new(plugin_search_dirs.as_slice(), false)
         } else {
@@ -131,21 +221,40 @@ fn main() {
         let mut plugins = PluginsManager::builder()
             // Static plugins are to be added here, with `.add_static::<PluginType>()`
             .into_dynamic(lib_loader)
-            .load_plugins(&get_plugins_from_args(), &PLUGIN_PREFIX);
+            .load_plugins(&get_plugins_from_args(&cmd_args), &PLUGIN_PREFIX);
         // Also search for plugins if no "--plugin-nolookup" arg
-        if !std::env::args().any(|arg| arg == "--plugin-nolookup") {
+        if !cmd_args.iter().any(|arg| arg == "--plugin-nolookup") {
             plugins = plugins.search_and_load_plugins(Some(&PLUGIN_PREFIX));
         }
         let (plugins, expected_args) = plugins.get_requirements();
+        let app = app.args(&expected_args);
+
+        // Check the arguments coming from the configuration file on their own,
+        // to report errors against the configuration file rather than the command line
+        if let Err(e) = app
+            .clone()
+            .get_matches_from_safe(std::iter::once("zenohd".to_string()).chain(config_args))
+        {
+            match (e.kind, e.info) {
+                (clap::ErrorKind::UnknownArgument, Some(info)) if !info.is_empty() => {
+                    exit_with_error(format!(
+                        "Unknown plugin argument '{}' in the 'plugins' section of the configuration file",
+                        info[0].trim_start_matches('-')
+                    ))
+                }
+                _ => exit_with_error(format!(
+                    "Invalid 'plugins' section in the configuration file: {}",
+                    e.message.lines().next().unwrap_or("")
+                )),
+            }
+        }
 
         // Add plugins' expected args and parse command line
-        let args = app.args(&expected_args).get_matches();
+        let args = app.get_matches_from(cmd_args);
 
-        let mut config = if let Some(conf_file) = args.value_of("config") {
-            Properties::from(std::fs::read_to_string(conf_file).unwrap()).into()
-        } else {
-            ConfigProperties::default()
-        };
+        let mut config = config_file
+            .map(|config_file| config_file.properties)
+            .unwrap_or_default();
 
         config.insert(ZN_MODE_KEY, "router".to_string());
 
@@ -176,32 +285,40 @@ fn main() {
         }
         config.insert(ZN_LISTENER_KEY, listener);
 
-        config.insert(
-            ZN_ADD_TIMESTAMP_KEY,
-            if args.is_present("no-timestamp") {
-                ZN_FALSE.to_string()
-            } else {
-                ZN_TRUE.to_string()
-            },
-        );
+        // The command line flags override the configuration file, which overrides the defaults
+        if args.is_present("no-timestamp") {
+            config.insert(ZN_ADD_TIMESTAMP_KEY, ZN_FALSE.to_string());
+        } else {
+            config
+                .entry(ZN_ADD_TIMESTAMP_KEY)
+                .or_insert_with(|| ZN_TRUE.to_string());
+        }
 
-        config.insert(
-            ZN_MULTICAST_SCOUTING_KEY,
-            if args.is_present("no-multicast-scouting") {
-                ZN_FALSE.to_string()
-            } else {
-                ZN_TRUE.to_string()
-            },
-        );
+        if args.is_present("no-multicast-scouting") {
+            config.insert(ZN_MULTICAST_SCOUTING_KEY, ZN_FALSE.to_string());
+        } else {
+            config
+                .entry(ZN_MULTICAST_SCOUTING_KEY)
+                .or_insert_with(|| ZN_TRUE.to_string());
+        }
 
         log::debug!("Config: {:?}", &config);
 
-        let runtime = match Runtime::new(0, config, args.value_of("id")).await {
+        let drain_timeout = config
+            .get_or(&ZN_DRAIN_TIMEOUT_KEY, ZN_DRAIN_TIMEOUT_DEFAULT)
+            .parse()
+            .map(Duration::from_millis)
+            .unwrap_or_else(|e| {
+                exit_with_error(format!("Invalid {}: {}", ZN_DRAIN_TIMEOUT_STR, e))
+            });
+
+        let id = args
+            .value_of("id")
+            .map(String::from)
+            .or_else(|| config.get(&ZN_PEER_ID_KEY).cloned());
+        let runtime = match Runtime::new(0, config, id.as_deref()).await {
             Ok(runtime) => runtime,
-            Err(e) => {
-                println!("{}. Exiting...", e);
-                std::process::exit(-1);
-            }
+            Err(e) => exit_with_error(e),
         };
 
         let (handles, failures) = plugins.start(&(runtime.clone(), args));
@@ -212,8 +329,57 @@ fn main() {
             log::debug!("plugin_failure: {}", f);
         }
 
-        AdminSpace::start(&runtime, handles, LONG_VERSION.clone()).await;
+        let handles = Arc::new(handles);
+        AdminSpace::start(&runtime, handles.clone(), LONG_VERSION.clone()).await;
 
-        future::pending::<()>().await;
+        wait_for_termination().await;
+        shutdown(&runtime, handles, drain_timeout).await;
     });
 }
+
+// Waits for a SIGTERM or a SIGINT
+#[cfg(unix)]
+async fn wait_for_termination() {
+    use signal_hook::consts::{SIGINT, SIGTERM};
+    match signal_hook::iterator::Signals::new([SIGTERM, SIGINT]) {
+        Ok(mut signals) => {
+            if let Some(signal) = task::spawn_blocking(move || signals.forever().next()).await {
+                log::info!("Received signal {}: shutting down", signal);
+            }
+        }
+        Err(e) => {
+            log::warn!("Failed to register the termination signals: {}", e);
+            future::pending::<()>().await;
+        }
+    }
+}
+
+#[cfg(not(unix))]
+async fn wait_for_termination() {
+    future::pending::<()>().await;
+}
+
+async fn shutdown(
+    runtime: &Runtime,
+    plugins: Arc<PluginsHandles<Requirements, StartArgs>>,
+    drain_timeout: Duration,
+) {
+    let deadline = Instant::now() + drain_timeout;
+
+    // Stop the plugins first, for them to undeclare their resources (e.g. the storages)
+    if task::spawn_blocking(move || plugins.stop())
+        .timeout(drain_timeout)
+        .await
+        .is_err()
+    {
+        log::warn!("Plugins not stopped after {:?}", drain_timeout);
+    }
+
+    // Send a CLOSE on every transport, flushing their transmission pipelines
+    let remaining = deadline.saturating_duration_since(Instant::now());
+    match runtime.close().timeout(remaining).await {
+        Ok(Ok(())) => log::info!("zenohd stopped"),
+        Ok(Err(e)) => log::warn!("Error closing the transports: {}", e),
+        Err(_) => log::warn!("Transports not closed after {:?}", drain_timeout),
+    }
+}
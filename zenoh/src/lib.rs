@@ -109,7 +109,7 @@ pub use path::{path, Path};
 mod pathexpr;
 pub use pathexpr::{pathexpr, PathExpr};
 mod selector;
-pub use selector::{selector, Selector};
+pub use selector::{selector, Selector, PROP_STARTTIME, PROP_STOPTIME};
 mod values;
 pub use values::*;
 
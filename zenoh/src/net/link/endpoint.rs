@@ -11,6 +11,8 @@
 // Contributors:
 //   ADLINK zenoh team, <zenoh@adlink-labs.tech>
 //
+#[cfg(feature = "transport_inproc")]
+use super::inproc::{LocatorConfigInproc, LocatorInproc};
 #[cfg(feature = "transport_quic")]
 use super::quic::{LocatorConfigQuic, LocatorQuic};
 #[cfg(feature = "transport_tcp")]
@@ -19,12 +21,17 @@ use super::tcp::{LocatorConfigTcp, LocatorTcp};
 use super::tls::{LocatorConfigTls, LocatorTls};
 #[cfg(feature = "transport_udp")]
 use super::udp::{LocatorConfigUdp, LocatorUdp};
+#[cfg(all(feature = "transport_unixsock-dgram", target_family = "unix"))]
+use super::unixsock_dgram::{LocatorConfigUnixSocketDgram, LocatorUnixSocketDgram};
 #[cfg(all(feature = "transport_unixsock-stream", target_family = "unix"))]
 use super::unixsock_stream::{LocatorConfigUnixSocketStream, LocatorUnixSocketStream};
+#[cfg(feature = "transport_ws")]
+use super::ws::{LocatorConfigWs, LocatorWs};
 use std::cmp::PartialEq;
 use std::collections::HashMap;
 use std::fmt;
 use std::hash::{Hash, Hasher};
+use std::net::IpAddr;
 use std::str::FromStr;
 use std::sync::Arc;
 use zenoh_util::core::{ZError, ZErrorKind, ZResult};
@@ -46,6 +53,12 @@ pub const STR_TLS: &str = "tls";
 pub const STR_QUIC: &str = "quic";
 #[cfg(all(feature = "transport_unixsock-stream", target_family = "unix"))]
 pub const STR_UNIXSOCK_STREAM: &str = "unixsock-stream";
+#[cfg(all(feature = "transport_unixsock-dgram", target_family = "unix"))]
+pub const STR_UNIXSOCK_DGRAM: &str = "unixsock-dgram";
+#[cfg(feature = "transport_ws")]
+pub const STR_WS: &str = "ws";
+#[cfg(feature = "transport_inproc")]
+pub const STR_INPROC: &str = "inproc";
 
 // Parsing chars
 pub const PROTO_SEPARATOR: char = '/';
@@ -175,6 +188,12 @@ pub enum LocatorProtocol {
     Quic,
     #[cfg(all(feature = "transport_unixsock-stream", target_family = "unix"))]
     UnixSocketStream,
+    #[cfg(all(feature = "transport_unixsock-dgram", target_family = "unix"))]
+    UnixSocketDgram,
+    #[cfg(feature = "transport_ws")]
+    Ws,
+    #[cfg(feature = "transport_inproc")]
+    Inproc,
 }
 
 impl fmt::Display for LocatorProtocol {
@@ -190,6 +209,12 @@ impl fmt::Display for LocatorProtocol {
             LocatorProtocol::Quic => write!(f, "{}", STR_QUIC)?,
             #[cfg(all(feature = "transport_unixsock-stream", target_family = "unix"))]
             LocatorProtocol::UnixSocketStream => write!(f, "{}", STR_UNIXSOCK_STREAM)?,
+            #[cfg(all(feature = "transport_unixsock-dgram", target_family = "unix"))]
+            LocatorProtocol::UnixSocketDgram => write!(f, "{}", STR_UNIXSOCK_DGRAM)?,
+            #[cfg(feature = "transport_ws")]
+            LocatorProtocol::Ws => write!(f, "{}", STR_WS)?,
+            #[cfg(feature = "transport_inproc")]
+            LocatorProtocol::Inproc => write!(f, "{}", STR_INPROC)?,
         }
         Ok(())
     }
@@ -238,6 +263,27 @@ impl LocatorConfig {
                 ps.insert(LocatorProtocol::UnixSocketStream, p);
             }
         }
+        #[cfg(all(feature = "transport_unixsock-dgram", target_family = "unix"))]
+        {
+            let mut res = LocatorConfigUnixSocketDgram::from_config(config)?;
+            if let Some(p) = res.take() {
+                ps.insert(LocatorProtocol::UnixSocketDgram, p);
+            }
+        }
+        #[cfg(feature = "transport_ws")]
+        {
+            let mut res = LocatorConfigWs::from_config(config)?;
+            if let Some(p) = res.take() {
+                ps.insert(LocatorProtocol::Ws, p);
+            }
+        }
+        #[cfg(feature = "transport_inproc")]
+        {
+            let mut res = LocatorConfigInproc::from_config(config)?;
+            if let Some(p) = res.take() {
+                ps.insert(LocatorProtocol::Inproc, p);
+            }
+        }
         Ok(ps)
     }
 }
@@ -325,6 +371,12 @@ pub enum LocatorAddress {
     Quic(LocatorQuic),
     #[cfg(all(feature = "transport_unixsock-stream", target_family = "unix"))]
     UnixSocketStream(LocatorUnixSocketStream),
+    #[cfg(all(feature = "transport_unixsock-dgram", target_family = "unix"))]
+    UnixSocketDgram(LocatorUnixSocketDgram),
+    #[cfg(feature = "transport_ws")]
+    Ws(LocatorWs),
+    #[cfg(feature = "transport_inproc")]
+    Inproc(LocatorInproc),
 }
 
 impl FromStr for LocatorAddress {
@@ -349,6 +401,12 @@ impl FromStr for LocatorAddress {
             STR_QUIC => addr.parse().map(LocatorAddress::Quic),
             #[cfg(all(feature = "transport_unixsock-stream", target_family = "unix"))]
             STR_UNIXSOCK_STREAM => addr.parse().map(LocatorAddress::UnixSocketStream),
+            #[cfg(all(feature = "transport_unixsock-dgram", target_family = "unix"))]
+            STR_UNIXSOCK_DGRAM => addr.parse().map(LocatorAddress::UnixSocketDgram),
+            #[cfg(feature = "transport_ws")]
+            STR_WS => addr.parse().map(LocatorAddress::Ws),
+            #[cfg(feature = "transport_inproc")]
+            STR_INPROC => addr.parse().map(LocatorAddress::Inproc),
             unknown => {
                 let e = format!(
                     "Invalid locator address: {}. Unknown protocol: {}.",
@@ -373,6 +431,12 @@ impl LocatorAddress {
             LocatorAddress::Quic(..) => LocatorProtocol::Quic,
             #[cfg(all(feature = "transport_unixsock-stream", target_family = "unix"))]
             LocatorAddress::UnixSocketStream(..) => LocatorProtocol::UnixSocketStream,
+            #[cfg(all(feature = "transport_unixsock-dgram", target_family = "unix"))]
+            LocatorAddress::UnixSocketDgram(..) => LocatorProtocol::UnixSocketDgram,
+            #[cfg(feature = "transport_ws")]
+            LocatorAddress::Ws(..) => LocatorProtocol::Ws,
+            #[cfg(feature = "transport_inproc")]
+            LocatorAddress::Inproc(..) => LocatorProtocol::Inproc,
         }
     }
 
@@ -388,6 +452,30 @@ impl LocatorAddress {
             LocatorAddress::Quic(l) => l.is_multicast(),
             #[cfg(all(feature = "transport_unixsock-stream", target_family = "unix"))]
             LocatorAddress::UnixSocketStream(l) => l.is_multicast(),
+            #[cfg(all(feature = "transport_unixsock-dgram", target_family = "unix"))]
+            LocatorAddress::UnixSocketDgram(l) => l.is_multicast(),
+            #[cfg(feature = "transport_ws")]
+            LocatorAddress::Ws(l) => l.is_multicast(),
+            #[cfg(feature = "transport_inproc")]
+            LocatorAddress::Inproc(l) => l.is_multicast(),
+        }
+    }
+
+    /// The IP address of the locator, if it is resolved to a socket address.
+    pub fn get_ip(&self) -> Option<IpAddr> {
+        match self {
+            #[cfg(feature = "transport_tcp")]
+            LocatorAddress::Tcp(LocatorTcp::SocketAddr(addr)) => Some(addr.ip()),
+            #[cfg(feature = "transport_udp")]
+            LocatorAddress::Udp(LocatorUdp::SocketAddr(addr)) => Some(addr.ip()),
+            #[cfg(feature = "transport_tls")]
+            LocatorAddress::Tls(LocatorTls::SocketAddr(addr)) => Some(addr.ip()),
+            #[cfg(feature = "transport_quic")]
+            LocatorAddress::Quic(LocatorQuic::SocketAddr(addr)) => Some(addr.ip()),
+            #[cfg(feature = "transport_ws")]
+            LocatorAddress::Ws(LocatorWs::SocketAddr(addr)) => Some(addr.ip()),
+            #[allow(unreachable_patterns)]
+            _ => None,
         }
     }
 }
@@ -407,6 +495,14 @@ impl fmt::Display for LocatorAddress {
             LocatorAddress::UnixSocketStream(addr) => {
                 write!(f, "{}{}{}", STR_UNIXSOCK_STREAM, PROTO_SEPARATOR, addr)?
             }
+            #[cfg(all(feature = "transport_unixsock-dgram", target_family = "unix"))]
+            LocatorAddress::UnixSocketDgram(addr) => {
+                write!(f, "{}{}{}", STR_UNIXSOCK_DGRAM, PROTO_SEPARATOR, addr)?
+            }
+            #[cfg(feature = "transport_ws")]
+            LocatorAddress::Ws(addr) => write!(f, "{}{}{}", STR_WS, PROTO_SEPARATOR, addr)?,
+            #[cfg(feature = "transport_inproc")]
+            LocatorAddress::Inproc(addr) => write!(f, "{}{}{}", STR_INPROC, PROTO_SEPARATOR, addr)?,
         }
         Ok(())
     }
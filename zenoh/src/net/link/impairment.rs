@@ -0,0 +1,444 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ADLINK zenoh team, <zenoh@adlink-labs.tech>
+//
+//! A link decorator injecting network faults on the writes of any unicast link.
+//!
+//! The faults are configured on the endpoint a link is opened with, either in its
+//! configuration (`#`) or in its locator metadata (`?`), e.g.:
+//! `udp/127.0.0.1:7447#loss=0.05;delay=20ms;jitter=5ms;reorder=0.01;seed=42`.
+//!
+//! Each write on the link is subject to the faults as a whole. Since zenoh always writes
+//! complete batches, faults never break the framing of streamed links. The faults are
+//! drawn from a pseudo-random generator initialized with the configured seed, hence the
+//! same sequence of writes is always impaired in the same way.
+//!
+//! The impairment is one-directional: only the links opened towards an impaired endpoint
+//! are decorated, so only the messages sent by the opening side are impaired. The links
+//! accepted by a listener are never impaired, the same configuration on a listener
+//! endpoint has no effect. Both sides need to open an impaired link to impair both
+//! directions.
+use super::*;
+use async_std::channel::{bounded, Sender};
+use async_std::sync::Mutex as AsyncMutex;
+use async_std::task;
+use async_std::task::JoinHandle;
+use async_trait::async_trait;
+use rand::{Rng, SeedableRng};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use zenoh_util::crypto::PseudoRng;
+use zenoh_util::properties::Properties;
+use zenoh_util::{zconfigurable, zlock};
+
+zconfigurable! {
+    // Number of delayed writes that can be queued on an impaired link before the writer
+    // is blocked.
+    static ref IMPAIRMENT_QUEUE_CAPACITY: usize = 256;
+}
+
+pub mod config {
+    // The probability for a write to be lost, between 0 and 1
+    pub const IMPAIRMENT_LOSS: &str = "loss";
+    // The delay applied to each write, e.g. 20ms
+    pub const IMPAIRMENT_DELAY: &str = "delay";
+    // The maximum random variation of the delay, e.g. 5ms
+    pub const IMPAIRMENT_JITTER: &str = "jitter";
+    // The probability for a write to be sent after the following one, between 0 and 1
+    pub const IMPAIRMENT_REORDER: &str = "reorder";
+    // The seed of the pseudo-random generator drawing the faults
+    pub const IMPAIRMENT_SEED: &str = "seed";
+
+    pub const IMPAIRMENT_KEYS: [&str; 5] = [
+        IMPAIRMENT_LOSS,
+        IMPAIRMENT_DELAY,
+        IMPAIRMENT_JITTER,
+        IMPAIRMENT_REORDER,
+        IMPAIRMENT_SEED,
+    ];
+}
+use config::*;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct ImpairmentConfig {
+    pub(crate) loss: f64,
+    pub(crate) delay: Duration,
+    pub(crate) jitter: Duration,
+    pub(crate) reorder: f64,
+    pub(crate) seed: u64,
+}
+
+impl ImpairmentConfig {
+    /// The impairment configured on the endpoint, if any. The endpoint configuration
+    /// takes precedence over the locator metadata.
+    pub(crate) fn from_endpoint(endpoint: &EndPoint) -> ZResult<Option<ImpairmentConfig>> {
+        let mut props = Properties::default();
+        for p in [endpoint.locator.metadata.as_ref(), endpoint.config.as_ref()]
+            .iter()
+            .flatten()
+        {
+            for k in IMPAIRMENT_KEYS.iter() {
+                if let Some(v) = p.get(*k) {
+                    props.insert(k.to_string(), v.clone());
+                }
+            }
+        }
+        if props.is_empty() {
+            return Ok(None);
+        }
+        ImpairmentConfig::from_properties(&props).map(Some)
+    }
+
+    pub(crate) fn from_properties(props: &Properties) -> ZResult<ImpairmentConfig> {
+        fn probability(props: &Properties, key: &str) -> ZResult<f64> {
+            match props.get(key) {
+                Some(v) => match v.parse::<f64>() {
+                    Ok(p) if (0.0..=1.0).contains(&p) => Ok(p),
+                    _ => {
+                        let e = format!("Invalid link impairment {}: {}", key, v);
+                        zerror!(ZErrorKind::InvalidLocator { descr: e })
+                    }
+                },
+                None => Ok(0.0),
+            }
+        }
+
+        fn duration(props: &Properties, key: &str) -> ZResult<Duration> {
+            match props.get(key) {
+                Some(v) => humantime::parse_duration(v).map_err(|e| {
+                    let e = format!("Invalid link impairment {}: {} ({})", key, v, e);
+                    zerror2!(ZErrorKind::InvalidLocator { descr: e })
+                }),
+                None => Ok(Duration::from_millis(0)),
+            }
+        }
+
+        let seed = match props.get(IMPAIRMENT_SEED) {
+            Some(v) => v.parse::<u64>().map_err(|_| {
+                let e = format!("Invalid link impairment {}: {}", IMPAIRMENT_SEED, v);
+                zerror2!(ZErrorKind::InvalidLocator { descr: e })
+            })?,
+            None => rand::random::<u64>(),
+        };
+
+        Ok(ImpairmentConfig {
+            loss: probability(props, IMPAIRMENT_LOSS)?,
+            delay: duration(props, IMPAIRMENT_DELAY)?,
+            jitter: duration(props, IMPAIRMENT_JITTER)?,
+            reorder: probability(props, IMPAIRMENT_REORDER)?,
+            seed,
+        })
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) enum Fault {
+    // The write is lost
+    Loss,
+    // The write is sent after the following one
+    Reorder,
+    // The write is sent after the given delay
+    Delay(Duration),
+}
+
+pub(crate) struct Impairment {
+    config: ImpairmentConfig,
+    prng: PseudoRng,
+}
+
+impl Impairment {
+    pub(crate) fn new(config: ImpairmentConfig) -> Impairment {
+        let prng = PseudoRng::seed_from_u64(config.seed);
+        Impairment { config, prng }
+    }
+
+    /// Draws the fault to apply on the next write.
+    pub(crate) fn next_fault(&mut self) -> Fault {
+        // Always draw all the values to keep the sequence independent from the configuration
+        let loss = self.prng.gen::<f64>() < self.config.loss;
+        let reorder = self.prng.gen::<f64>() < self.config.reorder;
+        let jitter = self.prng.gen_range(-1.0..=1.0) * self.config.jitter.as_secs_f64();
+        if loss {
+            Fault::Loss
+        } else if reorder {
+            Fault::Reorder
+        } else {
+            let delay = (self.config.delay.as_secs_f64() + jitter).max(0.0);
+            Fault::Delay(Duration::from_secs_f64(delay))
+        }
+    }
+}
+
+struct ImpairmentState {
+    impairment: Impairment,
+    // The write held back by a reorder
+    held: Option<Vec<u8>>,
+    // The instant the last write is going to be sent at
+    last: Instant,
+}
+
+pub(crate) struct LinkUnicastImpaired {
+    inner: LinkUnicast,
+    // Held across the queueing of the writes to keep them in order
+    state: AsyncMutex<ImpairmentState>,
+    sender: Sender<(Instant, Vec<u8>)>,
+    // The error of the last delayed write, reported on the following write
+    error: Arc<Mutex<Option<String>>>,
+    handle: AsyncMutex<Option<JoinHandle<()>>>,
+}
+
+impl LinkUnicastImpaired {
+    fn new(inner: LinkUnicast, config: ImpairmentConfig) -> LinkUnicastImpaired {
+        log::debug!("Impairing link {}: {:?}", inner, config);
+        // The delayed writes are sent in order by a dedicated task
+        let (sender, receiver) = bounded::<(Instant, Vec<u8>)>(*IMPAIRMENT_QUEUE_CAPACITY);
+        let error = Arc::new(Mutex::new(None));
+        let c_inner = inner.clone();
+        let c_error = error.clone();
+        let handle = task::spawn(async move {
+            while let Ok((instant, buffer)) = receiver.recv().await {
+                let now = Instant::now();
+                if instant > now {
+                    task::sleep(instant - now).await;
+                }
+                if let Err(e) = c_inner.write_all(&buffer).await {
+                    log::debug!("Impaired link {}: {}", c_inner, e);
+                    *zlock!(c_error) = Some(e.to_string());
+                    break;
+                }
+            }
+        });
+
+        LinkUnicastImpaired {
+            inner,
+            state: AsyncMutex::new(ImpairmentState {
+                impairment: Impairment::new(config),
+                held: None,
+                last: Instant::now(),
+            }),
+            sender,
+            error,
+            handle: AsyncMutex::new(Some(handle)),
+        }
+    }
+
+    async fn push(
+        &self,
+        state: &mut ImpairmentState,
+        delay: Duration,
+        buffer: Vec<u8>,
+    ) -> ZResult<()> {
+        // Writes never overtake each other, the reordering is only due to the reorder faults
+        let instant = (Instant::now() + delay).max(state.last);
+        state.last = instant;
+        match self.sender.send((instant, buffer)).await {
+            Ok(()) => Ok(()),
+            Err(_) => {
+                self.check()?;
+                let e = format!("Write error on impaired link {}: link closed", self);
+                log::trace!("{}", e);
+                zerror!(ZErrorKind::IoError { descr: e })
+            }
+        }
+    }
+
+    fn check(&self) -> ZResult<()> {
+        match zlock!(self.error).as_ref() {
+            Some(e) => {
+                let e = format!("Write error on impaired link {}: {}", self, e);
+                log::trace!("{}", e);
+                zerror!(ZErrorKind::IoError { descr: e })
+            }
+            None => Ok(()),
+        }
+    }
+
+    async fn schedule(
+        &self,
+        state: &mut ImpairmentState,
+        delay: Duration,
+        buffer: &[u8],
+    ) -> ZResult<()> {
+        // The write held back by a reorder, if any, is sent after the current one
+        let held = state.held.take();
+        self.push(state, delay, buffer.to_vec()).await?;
+        match held {
+            Some(held) => self.push(state, delay, held).await,
+            None => Ok(()),
+        }
+    }
+}
+
+/// Decorates the link with the impairment configured on the endpoint, if any.
+pub(crate) fn impair(link: LinkUnicast, endpoint: &EndPoint) -> ZResult<LinkUnicast> {
+    match ImpairmentConfig::from_endpoint(endpoint)? {
+        Some(config) => Ok(LinkUnicast(Arc::new(LinkUnicastImpaired::new(
+            link, config,
+        )))),
+        None => Ok(link),
+    }
+}
+
+#[async_trait]
+impl LinkUnicastTrait for LinkUnicastImpaired {
+    async fn close(&self) -> ZResult<()> {
+        // Let the pending writes, including the one held back by a reorder, be sent
+        // before closing the link
+        let mut state = self.state.lock().await;
+        if let Some(held) = state.held.take() {
+            let delay = state.impairment.config.delay;
+            let _ = self.push(&mut state, delay, held).await;
+        }
+        drop(state);
+        self.sender.close();
+        if let Some(handle) = self.handle.lock().await.take() {
+            handle.await;
+        }
+        self.inner.close().await
+    }
+
+    async fn write(&self, buffer: &[u8]) -> ZResult<usize> {
+        self.write_all(buffer).await?;
+        Ok(buffer.len())
+    }
+
+    async fn write_all(&self, buffer: &[u8]) -> ZResult<()> {
+        self.check()?;
+        let mut guard = self.state.lock().await;
+        let state = &mut *guard;
+        match state.impairment.next_fault() {
+            Fault::Loss => {
+                log::trace!("Impaired link {}: lost {} bytes", self, buffer.len());
+                Ok(())
+            }
+            Fault::Reorder if state.held.is_none() => {
+                log::trace!("Impaired link {}: held {} bytes", self, buffer.len());
+                state.held = Some(buffer.to_vec());
+                Ok(())
+            }
+            Fault::Reorder => {
+                let delay = state.impairment.config.delay;
+                self.schedule(state, delay, buffer).await
+            }
+            Fault::Delay(delay) => self.schedule(state, delay, buffer).await,
+        }
+    }
+
+    async fn read(&self, buffer: &mut [u8]) -> ZResult<usize> {
+        self.inner.read(buffer).await
+    }
+
+    async fn read_exact(&self, buffer: &mut [u8]) -> ZResult<()> {
+        self.inner.read_exact(buffer).await
+    }
+
+    #[inline(always)]
+    fn get_src(&self) -> Locator {
+        self.inner.get_src()
+    }
+
+    #[inline(always)]
+    fn get_dst(&self) -> Locator {
+        self.inner.get_dst()
+    }
+
+    #[inline(always)]
+    fn get_mtu(&self) -> u16 {
+        self.inner.get_mtu()
+    }
+
+    #[inline(always)]
+    fn is_reliable(&self) -> bool {
+        self.inner.is_reliable()
+    }
+
+    #[inline(always)]
+    fn is_streamed(&self) -> bool {
+        self.inner.is_streamed()
+    }
+
+    fn get_peer_id(&self) -> Option<PeerId> {
+        self.inner.get_peer_id()
+    }
+}
+
+impl fmt::Display for LinkUnicastImpaired {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(s: &str) -> ImpairmentConfig {
+        ImpairmentConfig::from_properties(&Properties::from(s)).unwrap()
+    }
+
+    #[test]
+    fn impairment_config() {
+        let c = config("loss=0.05;delay=20ms;jitter=5ms;reorder=0.01;seed=42");
+        assert_eq!(
+            c,
+            ImpairmentConfig {
+                loss: 0.05,
+                delay: Duration::from_millis(20),
+                jitter: Duration::from_millis(5),
+                reorder: 0.01,
+                seed: 42,
+            }
+        );
+
+        for s in ["loss=1.5", "loss=x", "reorder=-0.1", "delay=20", "seed=-1"].iter() {
+            assert!(ImpairmentConfig::from_properties(&Properties::from(*s)).is_err());
+        }
+
+        let endpoint: EndPoint = "tcp/127.0.0.1:7447".parse().unwrap();
+        assert!(ImpairmentConfig::from_endpoint(&endpoint)
+            .unwrap()
+            .is_none());
+        let endpoint: EndPoint = "tcp/127.0.0.1:7447?loss=0.1;seed=1#loss=0.2"
+            .parse()
+            .unwrap();
+        let c = ImpairmentConfig::from_endpoint(&endpoint).unwrap().unwrap();
+        assert_eq!(c.loss, 0.2);
+        assert_eq!(c.seed, 1);
+    }
+
+    #[test]
+    fn impairment_determinism() {
+        let c = config("loss=0.2;delay=20ms;jitter=5ms;reorder=0.1;seed=42");
+        let mut a = Impairment::new(c.clone());
+        let mut b = Impairment::new(c);
+        let faults: Vec<Fault> = (0..10_000).map(|_| a.next_fault()).collect();
+        for f in faults.iter() {
+            assert_eq!(*f, b.next_fault());
+        }
+
+        let lost = faults.iter().filter(|f| **f == Fault::Loss).count();
+        assert!((1_800..2_200).contains(&lost));
+        let reordered = faults.iter().filter(|f| **f == Fault::Reorder).count();
+        assert!((600..1_000).contains(&reordered));
+        for f in faults.iter() {
+            if let Fault::Delay(d) = f {
+                assert!(*d >= Duration::from_millis(15) && *d <= Duration::from_millis(25));
+            }
+        }
+
+        let mut c = Impairment::new(config("loss=0.2;seed=43"));
+        assert!(faults
+            .iter()
+            .take(100)
+            .any(|f| (*f == Fault::Loss) != (c.next_fault() == Fault::Loss)));
+    }
+}
@@ -0,0 +1,88 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ADLINK zenoh team, <zenoh@adlink-labs.tech>
+//
+use super::config::*;
+use super::*;
+use std::fmt;
+use std::str::FromStr;
+use zenoh_util::core::{ZError, ZErrorKind, ZResult};
+use zenoh_util::properties::config::ConfigProperties;
+use zenoh_util::properties::Properties;
+
+#[allow(unreachable_patterns)]
+pub(super) fn get_inproc_name(locator: &Locator) -> ZResult<String> {
+    match &locator.address {
+        LocatorAddress::Inproc(addr) => Ok(addr.name.clone()),
+        _ => {
+            let e = format!("Not an Inproc locator: {:?}", locator);
+            log::debug!("{}", e);
+            zerror!(ZErrorKind::InvalidLocator { descr: e })
+        }
+    }
+}
+
+pub(super) fn get_inproc_mtu(endpoint: &EndPoint) -> ZResult<u16> {
+    match endpoint.config.as_ref().and_then(|c| c.get(INPROC_MTU)) {
+        Some(mtu) => match mtu.parse::<u16>() {
+            Ok(mtu) if mtu > 0 => Ok(mtu),
+            _ => {
+                let e = format!("Invalid Inproc MTU for {}: {}", endpoint, mtu);
+                zerror!(ZErrorKind::InvalidLocator { descr: e })
+            }
+        },
+        None => Ok(*INPROC_DEFAULT_MTU),
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct LocatorInproc {
+    pub(super) name: String,
+}
+
+impl LocatorInproc {
+    pub fn is_multicast(&self) -> bool {
+        false
+    }
+}
+
+impl FromStr for LocatorInproc {
+    type Err = ZError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            let e = format!("Invalid Inproc locator: {:?}", s);
+            return zerror!(ZErrorKind::InvalidLocator { descr: e });
+        }
+        Ok(LocatorInproc {
+            name: s.to_string(),
+        })
+    }
+}
+
+impl fmt::Display for LocatorInproc {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+        Ok(())
+    }
+}
+
+/*************************************/
+/*          LOCATOR CONFIG           */
+/*************************************/
+pub struct LocatorConfigInproc;
+
+impl LocatorConfigInproc {
+    pub fn from_config(_config: &ConfigProperties) -> ZResult<Option<Properties>> {
+        Ok(None)
+    }
+}
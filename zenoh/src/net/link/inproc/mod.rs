@@ -0,0 +1,40 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ADLINK zenoh team, <zenoh@adlink-labs.tech>
+//
+mod endpoint;
+mod unicast;
+
+use super::*;
+pub use endpoint::*;
+pub use unicast::*;
+
+// Default MTU (INPROC PDU) in bytes.
+// NOTE: Since the in-process link is a byte-stream oriented transport, theoretically it has
+//       no limit regarding the MTU. However, given the batching strategy
+//       adopted in Zenoh and the usage of 16 bits in Zenoh to encode the
+//       payload length in byte-streamed, the INPROC MTU is constrained to
+//       2^16 - 1 bytes (i.e., 65535).
+const INPROC_MAX_MTU: u16 = u16::MAX;
+
+zconfigurable! {
+    // Default MTU (INPROC PDU) in bytes.
+    static ref INPROC_DEFAULT_MTU: u16 = INPROC_MAX_MTU;
+    // Number of writes that can be buffered in each direction of an in-process link
+    // before the writer is blocked.
+    static ref INPROC_CHANNEL_CAPACITY: usize = 16;
+}
+
+pub mod config {
+    // The MTU of the in-process link. The smallest of the listener and connector values is used.
+    pub const INPROC_MTU: &str = "mtu";
+}
@@ -0,0 +1,429 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ADLINK zenoh team, <zenoh@adlink-labs.tech>
+//
+use super::*;
+use crate::net::transport::TransportManager;
+use async_std::channel::{bounded, Receiver, Sender};
+use async_std::prelude::*;
+use async_std::sync::Mutex as AsyncMutex;
+use async_std::task;
+use async_std::task::JoinHandle;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use uuid::Uuid;
+use zenoh_util::core::{ZError, ZErrorKind, ZResult};
+use zenoh_util::sync::Signal;
+use zenoh_util::{zerror, zerror2, zread, zwrite};
+
+// A connection request sent by a connector to the listener it targets
+struct InprocConnection {
+    // The name of the connector end of the link
+    name: String,
+    // The MTU agreed by the connector and the listener
+    mtu: u16,
+    // The channel to send bytes to the connector
+    tx: Sender<Vec<u8>>,
+    // The channel to receive bytes from the connector
+    rx: Receiver<Vec<u8>>,
+}
+
+struct InprocAcceptor {
+    sender: Sender<InprocConnection>,
+    mtu: u16,
+}
+
+lazy_static! {
+    // The in-process listeners, shared by all the transport managers of the process
+    static ref INPROC_LISTENERS: RwLock<HashMap<String, InprocAcceptor>> =
+        RwLock::new(HashMap::new());
+}
+
+struct InprocReader {
+    // The chunk of bytes being currently read
+    chunk: Vec<u8>,
+    // The read offset in the current chunk
+    offset: usize,
+}
+
+pub struct LinkUnicastInproc {
+    // The name of the local end of this link
+    src_name: String,
+    // The name of the remote end of this link
+    dst_name: String,
+    // The MTU of this link
+    mtu: u16,
+    // The channel to send bytes to the remote end
+    tx: Sender<Vec<u8>>,
+    // The channel to receive bytes from the remote end
+    rx: Receiver<Vec<u8>>,
+    // The chunk being read, if partially consumed
+    reader: AsyncMutex<InprocReader>,
+}
+
+impl LinkUnicastInproc {
+    fn new(
+        src_name: String,
+        dst_name: String,
+        mtu: u16,
+        tx: Sender<Vec<u8>>,
+        rx: Receiver<Vec<u8>>,
+    ) -> LinkUnicastInproc {
+        LinkUnicastInproc {
+            src_name,
+            dst_name,
+            mtu,
+            tx,
+            rx,
+            reader: AsyncMutex::new(InprocReader {
+                chunk: vec![],
+                offset: 0,
+            }),
+        }
+    }
+}
+
+#[async_trait]
+impl LinkUnicastTrait for LinkUnicastInproc {
+    async fn close(&self) -> ZResult<()> {
+        log::trace!("Closing Inproc link: {}", self);
+        // Close both directions, the bytes already sent can still be read by the remote end
+        self.tx.close();
+        self.rx.close();
+        Ok(())
+    }
+
+    async fn write(&self, buffer: &[u8]) -> ZResult<usize> {
+        self.write_all(buffer).await?;
+        Ok(buffer.len())
+    }
+
+    async fn write_all(&self, buffer: &[u8]) -> ZResult<()> {
+        self.tx.send(buffer.to_vec()).await.map_err(|_| {
+            let e = format!("Write error on Inproc link {}: link closed", self);
+            log::trace!("{}", e);
+            zerror2!(ZErrorKind::IoError { descr: e })
+        })
+    }
+
+    async fn read(&self, buffer: &mut [u8]) -> ZResult<usize> {
+        let mut guard = self.reader.lock().await;
+        let reader = &mut *guard;
+        // Wait for a new chunk if the current one has been consumed
+        while reader.offset == reader.chunk.len() {
+            match self.rx.recv().await {
+                Ok(chunk) => {
+                    reader.chunk = chunk;
+                    reader.offset = 0;
+                }
+                // The link has been closed
+                Err(_) => return Ok(0),
+            }
+        }
+
+        let n = buffer.len().min(reader.chunk.len() - reader.offset);
+        buffer[..n].copy_from_slice(&reader.chunk[reader.offset..reader.offset + n]);
+        reader.offset += n;
+        Ok(n)
+    }
+
+    async fn read_exact(&self, buffer: &mut [u8]) -> ZResult<()> {
+        let mut read = 0;
+        while read < buffer.len() {
+            let n = self.read(&mut buffer[read..]).await?;
+            if n == 0 {
+                let e = format!("Read error on Inproc link {}: link closed", self);
+                log::trace!("{}", e);
+                return zerror!(ZErrorKind::IoError { descr: e });
+            }
+            read += n;
+        }
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn get_src(&self) -> Locator {
+        Locator {
+            address: LocatorAddress::Inproc(LocatorInproc {
+                name: self.src_name.clone(),
+            }),
+            metadata: None,
+        }
+    }
+
+    #[inline(always)]
+    fn get_dst(&self) -> Locator {
+        Locator {
+            address: LocatorAddress::Inproc(LocatorInproc {
+                name: self.dst_name.clone(),
+            }),
+            metadata: None,
+        }
+    }
+
+    #[inline(always)]
+    fn get_mtu(&self) -> u16 {
+        self.mtu
+    }
+
+    #[inline(always)]
+    fn is_reliable(&self) -> bool {
+        true
+    }
+
+    #[inline(always)]
+    fn is_streamed(&self) -> bool {
+        true
+    }
+}
+
+impl Drop for LinkUnicastInproc {
+    fn drop(&mut self) {
+        // Close both directions of the link
+        self.tx.close();
+        self.rx.close();
+    }
+}
+
+impl fmt::Display for LinkUnicastInproc {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} => {}", self.src_name, self.dst_name)?;
+        Ok(())
+    }
+}
+
+impl fmt::Debug for LinkUnicastInproc {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Inproc")
+            .field("src", &self.src_name)
+            .field("dst", &self.dst_name)
+            .field("mtu", &self.mtu)
+            .finish()
+    }
+}
+
+/*************************************/
+/*          LISTENER                 */
+/*************************************/
+struct ListenerUnicastInproc {
+    endpoint: EndPoint,
+    active: Arc<AtomicBool>,
+    signal: Signal,
+    handle: JoinHandle<ZResult<()>>,
+}
+
+impl ListenerUnicastInproc {
+    fn new(
+        endpoint: EndPoint,
+        active: Arc<AtomicBool>,
+        signal: Signal,
+        handle: JoinHandle<ZResult<()>>,
+    ) -> ListenerUnicastInproc {
+        ListenerUnicastInproc {
+            endpoint,
+            active,
+            signal,
+            handle,
+        }
+    }
+}
+
+pub struct LinkManagerUnicastInproc {
+    manager: TransportManager,
+    listeners: Arc<RwLock<HashMap<String, ListenerUnicastInproc>>>,
+}
+
+impl LinkManagerUnicastInproc {
+    pub(crate) fn new(manager: TransportManager) -> Self {
+        Self {
+            manager,
+            listeners: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+}
+
+#[async_trait]
+impl LinkManagerUnicastTrait for LinkManagerUnicastInproc {
+    async fn new_link(&self, endpoint: EndPoint) -> ZResult<LinkUnicast> {
+        let dst_name = get_inproc_name(&endpoint.locator)?;
+        let mtu = get_inproc_mtu(&endpoint)?;
+
+        // Look for the listener
+        let (sender, listener_mtu) = zread!(INPROC_LISTENERS)
+            .get(&dst_name)
+            .map(|a| (a.sender.clone(), a.mtu))
+            .ok_or_else(|| {
+                let e = format!(
+                    "Can not create a new Inproc link bound to {}: no listener",
+                    dst_name
+                );
+                zerror2!(ZErrorKind::Other { descr: e })
+            })?;
+        let mtu = mtu.min(listener_mtu);
+
+        // Create the channels for both directions of the link
+        let (c_tx, l_rx) = bounded(*INPROC_CHANNEL_CAPACITY);
+        let (l_tx, c_rx) = bounded(*INPROC_CHANNEL_CAPACITY);
+        let src_name = format!("{}", Uuid::new_v4());
+        let connection = InprocConnection {
+            name: src_name.clone(),
+            mtu,
+            tx: l_tx,
+            rx: l_rx,
+        };
+        sender.send(connection).await.map_err(|_| {
+            let e = format!(
+                "Can not create a new Inproc link bound to {}: listener closed",
+                dst_name
+            );
+            zerror2!(ZErrorKind::InvalidLink { descr: e })
+        })?;
+
+        let link = Arc::new(LinkUnicastInproc::new(src_name, dst_name, mtu, c_tx, c_rx));
+
+        Ok(LinkUnicast(link))
+    }
+
+    async fn new_listener(&self, endpoint: EndPoint) -> ZResult<Locator> {
+        let name = get_inproc_name(&endpoint.locator)?;
+        let mtu = get_inproc_mtu(&endpoint)?;
+
+        // Register the listener in the process
+        let (sender, receiver) = bounded(*INPROC_CHANNEL_CAPACITY);
+        {
+            let mut guard = zwrite!(INPROC_LISTENERS);
+            if guard.contains_key(&name) {
+                let e = format!(
+                    "Can not create a new Inproc listener on {}: name already in use",
+                    name
+                );
+                return zerror!(ZErrorKind::InvalidLink { descr: e });
+            }
+            guard.insert(name.clone(), InprocAcceptor { sender, mtu });
+        }
+
+        // Spawn the accept loop for the listener
+        let active = Arc::new(AtomicBool::new(true));
+        let signal = Signal::new();
+
+        let c_active = active.clone();
+        let c_signal = signal.clone();
+        let c_manager = self.manager.clone();
+        let c_listeners = self.listeners.clone();
+        let c_name = name.clone();
+        let handle = task::spawn(async move {
+            // Wait for the accept loop to terminate
+            let res = accept_task(c_name.clone(), receiver, c_active, c_signal, c_manager).await;
+            zwrite!(INPROC_LISTENERS).remove(&c_name);
+            zwrite!(c_listeners).remove(&c_name);
+            res
+        });
+
+        let locator = endpoint.locator.clone();
+        let listener = ListenerUnicastInproc::new(endpoint, active, signal, handle);
+        // Update the list of active listeners on the manager
+        zwrite!(self.listeners).insert(name, listener);
+
+        Ok(locator)
+    }
+
+    async fn del_listener(&self, endpoint: &EndPoint) -> ZResult<()> {
+        let name = get_inproc_name(&endpoint.locator)?;
+
+        // Stop the listener
+        let listener = zwrite!(self.listeners).remove(&name).ok_or_else(|| {
+            let e = format!(
+                "Can not delete the Inproc listener because it has not been found: {}",
+                name
+            );
+            log::trace!("{}", e);
+            zerror2!(ZErrorKind::InvalidLink { descr: e })
+        })?;
+
+        // Send the stop signal
+        listener.active.store(false, Ordering::Release);
+        listener.signal.trigger();
+        listener.handle.await
+    }
+
+    fn get_listeners(&self) -> Vec<EndPoint> {
+        zread!(self.listeners)
+            .values()
+            .map(|l| l.endpoint.clone())
+            .collect()
+    }
+
+    fn get_locators(&self) -> Vec<Locator> {
+        zread!(self.listeners)
+            .values()
+            .map(|l| l.endpoint.locator.clone())
+            .collect()
+    }
+}
+
+async fn accept_task(
+    name: String,
+    receiver: Receiver<InprocConnection>,
+    active: Arc<AtomicBool>,
+    signal: Signal,
+    manager: TransportManager,
+) -> ZResult<()> {
+    enum Action {
+        Accept(InprocConnection),
+        Stop,
+    }
+
+    async fn accept(receiver: &Receiver<InprocConnection>) -> ZResult<Action> {
+        let res = receiver.recv().await.map_err(|e| {
+            zerror2!(ZErrorKind::IoError {
+                descr: e.to_string()
+            })
+        })?;
+        Ok(Action::Accept(res))
+    }
+
+    async fn stop(signal: Signal) -> ZResult<Action> {
+        signal.wait().await;
+        Ok(Action::Stop)
+    }
+
+    log::trace!("Ready to accept Inproc connections on: {}", name);
+    while active.load(Ordering::Acquire) {
+        // Wait for incoming connections
+        let connection = match accept(&receiver).race(stop(signal.clone())).await? {
+            Action::Accept(connection) => connection,
+            Action::Stop => break,
+        };
+
+        log::debug!(
+            "Accepted Inproc connection on {}: {}",
+            name,
+            connection.name
+        );
+        // Create the new link object
+        let link = Arc::new(LinkUnicastInproc::new(
+            name.clone(),
+            connection.name,
+            connection.mtu,
+            connection.tx,
+            connection.rx,
+        ));
+
+        // Communicate the new link to the initial transport manager
+        manager.handle_new_link_unicast(LinkUnicast(link)).await;
+    }
+
+    Ok(())
+}
@@ -11,6 +11,8 @@
 // Contributors:
 //   ADLINK zenoh team, <zenoh@adlink-labs.tech>
 //
+#[cfg(feature = "transport_inproc")]
+use super::inproc::LinkManagerUnicastInproc;
 #[cfg(feature = "transport_quic")]
 use super::quic::LinkManagerUnicastQuic;
 #[cfg(feature = "transport_tcp")]
@@ -19,8 +21,12 @@ use super::tcp::LinkManagerUnicastTcp;
 use super::tls::LinkManagerUnicastTls;
 #[cfg(feature = "transport_udp")]
 use super::udp::{LinkManagerMulticastUdp, LinkManagerUnicastUdp};
+#[cfg(all(feature = "transport_unixsock-dgram", target_family = "unix"))]
+use super::unixsock_dgram::LinkManagerUnicastUnixSocketDgram;
 #[cfg(all(feature = "transport_unixsock-stream", target_family = "unix"))]
 use super::unixsock_stream::LinkManagerUnicastUnixSocketStream;
+#[cfg(feature = "transport_ws")]
+use super::ws::LinkManagerUnicastWs;
 use super::{EndPoint, LinkMulticast, LinkUnicast, Locator, LocatorProtocol};
 use crate::net::transport::TransportManager;
 use async_std::sync::Arc;
@@ -61,6 +67,14 @@ impl LinkManagerBuilderUnicast {
             LocatorProtocol::UnixSocketStream => {
                 Ok(Arc::new(LinkManagerUnicastUnixSocketStream::new(manager)))
             }
+            #[cfg(all(feature = "transport_unixsock-dgram", target_family = "unix"))]
+            LocatorProtocol::UnixSocketDgram => {
+                Ok(Arc::new(LinkManagerUnicastUnixSocketDgram::new(manager)))
+            }
+            #[cfg(feature = "transport_ws")]
+            LocatorProtocol::Ws => Ok(Arc::new(LinkManagerUnicastWs::new(manager))),
+            #[cfg(feature = "transport_inproc")]
+            LocatorProtocol::Inproc => Ok(Arc::new(LinkManagerUnicastInproc::new(manager))),
         }
     }
 }
@@ -12,6 +12,9 @@
 //   ADLINK zenoh team, <zenoh@adlink-labs.tech>
 //
 mod endpoint;
+pub mod impairment;
+#[cfg(feature = "transport_inproc")]
+pub mod inproc;
 mod manager;
 #[cfg(feature = "transport_quic")]
 pub mod quic;
@@ -21,9 +24,22 @@ pub mod tcp;
 pub mod tls;
 #[cfg(feature = "transport_udp")]
 pub mod udp;
+#[cfg(all(
+    any(
+        feature = "transport_unixsock-stream",
+        feature = "transport_unixsock-dgram"
+    ),
+    target_family = "unix"
+))]
+mod unixsock;
+#[cfg(all(feature = "transport_unixsock-dgram", target_family = "unix"))]
+pub mod unixsock_dgram;
 #[cfg(all(feature = "transport_unixsock-stream", target_family = "unix"))]
 pub mod unixsock_stream;
+#[cfg(feature = "transport_ws")]
+pub mod ws;
 
+use crate::net::protocol::core::PeerId;
 use crate::net::protocol::io::{WBuf, ZBuf};
 use crate::net::protocol::proto::TransportMessage;
 use async_std::sync::Arc;
@@ -109,6 +125,10 @@ pub(crate) trait LinkUnicastTrait: Send + Sync {
     fn get_dst(&self) -> Locator;
     fn is_reliable(&self) -> bool;
     fn is_streamed(&self) -> bool;
+    // The PeerId the remote end has been authenticated with at link level, if any
+    fn get_peer_id(&self) -> Option<PeerId> {
+        None
+    }
     async fn write(&self, buffer: &[u8]) -> ZResult<usize>;
     async fn write_all(&self, buffer: &[u8]) -> ZResult<()>;
     async fn read(&self, buffer: &mut [u8]) -> ZResult<usize>;
@@ -145,6 +145,24 @@ impl LocatorConfigTls {
                 tls_server_certificate.into(),
             );
         }
+        if let Some(tls_client_private_key) = config.get(&ZN_TLS_CLIENT_PRIVATE_KEY_KEY) {
+            properties.insert(
+                TLS_CLIENT_PRIVATE_KEY_FILE.into(),
+                tls_client_private_key.into(),
+            );
+        }
+        if let Some(tls_client_certificate) = config.get(&ZN_TLS_CLIENT_CERTIFICATE_KEY) {
+            properties.insert(
+                TLS_CLIENT_CERTIFICATE_FILE.into(),
+                tls_client_certificate.into(),
+            );
+        }
+        if let Some(tls_client_ca_certificate) = config.get(&ZN_TLS_CLIENT_CA_CERTIFICATE_KEY) {
+            properties.insert(
+                TLS_CLIENT_CA_CERTIFICATE_FILE.into(),
+                tls_client_ca_certificate.into(),
+            );
+        }
 
         if properties.is_empty() {
             Ok(None)
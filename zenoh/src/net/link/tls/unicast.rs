@@ -13,6 +13,7 @@
 //
 use super::config::*;
 use super::*;
+use crate::net::protocol::core::PeerId;
 use crate::net::transport::TransportManager;
 use async_rustls::rustls::internal::pemfile;
 pub use async_rustls::rustls::*;
@@ -31,10 +32,12 @@ use std::convert::TryInto;
 use std::fmt;
 use std::io::Cursor;
 use std::net::Shutdown;
+use std::str::FromStr;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, RwLock};
 use std::time::Duration;
 use zenoh_util::core::{ZError, ZErrorKind, ZResult};
+use zenoh_util::properties::Properties;
 use zenoh_util::sync::Signal;
 use zenoh_util::{zerror2, zread, zwrite};
 
@@ -52,6 +55,8 @@ pub struct LinkUnicastTls {
     src_addr: SocketAddr,
     // The destination socket address of this link (address used on the local host)
     dst_addr: SocketAddr,
+    // The PeerId of the remote end as stated in its client certificate, if any
+    peer_id: Option<PeerId>,
     // Make sure there are no concurrent read or writes
     write_mtx: AsyncMutex<()>,
     read_mtx: AsyncMutex<()>,
@@ -65,6 +70,7 @@ impl LinkUnicastTls {
         socket: TlsStream<TcpStream>,
         src_addr: SocketAddr,
         dst_addr: SocketAddr,
+        peer_id: Option<PeerId>,
     ) -> LinkUnicastTls {
         let (tcp_stream, _) = socket.get_ref();
         // Set the TLS nodelay option
@@ -97,6 +103,7 @@ impl LinkUnicastTls {
             inner: UnsafeCell::new(socket),
             src_addr,
             dst_addr,
+            peer_id,
             write_mtx: AsyncMutex::new(()),
             read_mtx: AsyncMutex::new(()),
         }
@@ -201,6 +208,11 @@ impl LinkUnicastTrait for LinkUnicastTls {
     fn is_streamed(&self) -> bool {
         true
     }
+
+    #[inline(always)]
+    fn get_peer_id(&self) -> Option<PeerId> {
+        self.peer_id
+    }
 }
 
 impl Drop for LinkUnicastTls {
@@ -308,8 +320,8 @@ impl LinkManagerUnicastTrait for LinkManagerUnicastTls {
             None => vec![],
         };
 
-        let config = if bytes.is_empty() {
-            Arc::new(ClientConfig::new())
+        let mut cc = if bytes.is_empty() {
+            ClientConfig::new()
         } else {
             let mut cc = ClientConfig::new();
             let _ = cc
@@ -320,9 +332,50 @@ impl LinkManagerUnicastTrait for LinkManagerUnicastTls {
                         descr: "Invalid TLS CA certificate file".to_string()
                     })
                 })?;
-            Arc::new(cc)
+            cc
         };
 
+        // Configure the client certificate to authenticate with, if any
+        if let Some(config) = endpoint.config.as_ref() {
+            let key = read_tls_config(
+                config,
+                TLS_CLIENT_PRIVATE_KEY_RAW,
+                TLS_CLIENT_PRIVATE_KEY_FILE,
+            )
+            .await?;
+            let cert = read_tls_config(
+                config,
+                TLS_CLIENT_CERTIFICATE_RAW,
+                TLS_CLIENT_CERTIFICATE_FILE,
+            )
+            .await?;
+            match (key, cert) {
+                (Some(key), Some(cert)) => {
+                    let key = load_private_key(&key)?;
+                    let certs =
+                        pemfile::certs(&mut Cursor::new(cert.as_slice())).map_err(|_| {
+                            zerror2!(ZErrorKind::Other {
+                                descr: "Invalid TLS client certificate".to_string()
+                            })
+                        })?;
+                    cc.set_single_client_cert(certs, key).map_err(|e| {
+                        zerror2!(ZErrorKind::Other {
+                            descr: format!("Invalid TLS client certificate: {}", e)
+                        })
+                    })?;
+                }
+                (None, None) => {}
+                _ => {
+                    let e = format!(
+                        "Can not create a new TLS link bound to {}: both {} and {} must be provided",
+                        host, TLS_CLIENT_PRIVATE_KEY_FILE, TLS_CLIENT_CERTIFICATE_FILE
+                    );
+                    return zerror!(ZErrorKind::InvalidLink { descr: e });
+                }
+            }
+        }
+        let config = Arc::new(cc);
+
         let connector = TlsConnector::from(config);
         let tls_stream = connector
             .connect(domain.as_ref(), tcp_stream)
@@ -333,7 +386,7 @@ impl LinkManagerUnicastTrait for LinkManagerUnicastTls {
             })?;
         let tls_stream = TlsStream::Client(tls_stream);
 
-        let link = Arc::new(LinkUnicastTls::new(tls_stream, src_addr, dst_addr));
+        let link = Arc::new(LinkUnicastTls::new(tls_stream, src_addr, dst_addr, None));
 
         Ok(LinkUnicast(link))
     }
@@ -369,7 +422,7 @@ impl LinkManagerUnicastTrait for LinkManagerUnicastTls {
                 }
             },
         };
-        let mut keys = pemfile::rsa_private_keys(&mut Cursor::new(bytes.as_slice())).unwrap();
+        let key = load_private_key(&bytes)?;
 
         // Configure the server certificate
         let bytes = match config.get(TLS_SERVER_CERTIFICATE_RAW) {
@@ -392,8 +445,34 @@ impl LinkManagerUnicastTrait for LinkManagerUnicastTls {
         };
         let certs = pemfile::certs(&mut Cursor::new(bytes.as_slice())).unwrap();
 
-        let mut sc = ServerConfig::new(NoClientAuth::new());
-        sc.set_single_cert(certs, keys.remove(0)).unwrap();
+        // Configure the client CA certificate, if any, to require and verify client certificates
+        let client_auth = match read_tls_config(
+            config,
+            TLS_CLIENT_CA_CERTIFICATE_RAW,
+            TLS_CLIENT_CA_CERTIFICATE_FILE,
+        )
+        .await?
+        {
+            Some(bytes) => {
+                let mut root_store = RootCertStore::empty();
+                let _ = root_store
+                    .add_pem_file(&mut Cursor::new(&bytes))
+                    .map_err(|_| {
+                        zerror2!(ZErrorKind::Other {
+                            descr: "Invalid TLS client CA certificate file".to_string()
+                        })
+                    })?;
+                Some(AllowAnyAuthenticatedClient::new(root_store))
+            }
+            None => None,
+        };
+        let is_client_auth = client_auth.is_some();
+
+        let mut sc = match client_auth {
+            Some(client_auth) => ServerConfig::new(client_auth),
+            None => ServerConfig::new(NoClientAuth::new()),
+        };
+        sc.set_single_cert(certs, key).unwrap();
 
         // Initialize the TcpListener
         let socket = TcpListener::bind(addr).await.map_err(|e| {
@@ -422,7 +501,15 @@ impl LinkManagerUnicastTrait for LinkManagerUnicastTls {
         let c_addr = local_addr;
         let handle = task::spawn(async move {
             // Wait for the accept loop to terminate
-            let res = accept_task(socket, acceptor, c_active, c_signal, c_manager).await;
+            let res = accept_task(
+                socket,
+                acceptor,
+                is_client_auth,
+                c_active,
+                c_signal,
+                c_manager,
+            )
+            .await;
             zwrite!(c_listeners).remove(&c_addr);
             res
         });
@@ -513,6 +600,7 @@ impl LinkManagerUnicastTrait for LinkManagerUnicastTls {
 async fn accept_task(
     socket: TcpListener,
     acceptor: TlsAcceptor,
+    client_auth: bool,
     active: Arc<AtomicBool>,
     signal: Signal,
     manager: TransportManager,
@@ -564,7 +652,7 @@ async fn accept_task(
         };
         // Accept the TLS connection
         let tls_stream = match acceptor.accept(tcp_stream).await {
-            Ok(stream) => TlsStream::Server(stream),
+            Ok(stream) => stream,
             Err(e) => {
                 let e = format!("Can not accept TLS connection: {}", e);
                 log::warn!("{}", e);
@@ -572,9 +660,24 @@ async fn accept_task(
             }
         };
 
+        // Retrieve the PeerId from the verified client certificate
+        let peer_id = if client_auth {
+            let (_, session) = tls_stream.get_ref();
+            match get_tls_peer_id(session) {
+                Ok(pid) => Some(pid),
+                Err(e) => {
+                    log::warn!("Can not accept TLS connection from {}: {}", dst_addr, e);
+                    continue;
+                }
+            }
+        } else {
+            None
+        };
+        let tls_stream = TlsStream::Server(tls_stream);
+
         log::debug!("Accepted TLS connection on {:?}: {:?}", src_addr, dst_addr);
         // Create the new link object
-        let link = Arc::new(LinkUnicastTls::new(tls_stream, src_addr, dst_addr));
+        let link = Arc::new(LinkUnicastTls::new(tls_stream, src_addr, dst_addr, peer_id));
 
         // Communicate the new link to the initial transport manager
         manager.handle_new_link_unicast(LinkUnicast(link)).await;
@@ -582,3 +685,67 @@ async fn accept_task(
 
     Ok(())
 }
+
+async fn read_tls_config(config: &Properties, raw: &str, file: &str) -> ZResult<Option<Vec<u8>>> {
+    match config.get(raw) {
+        Some(value) => Ok(Some(value.as_bytes().to_vec())),
+        None => match config.get(file) {
+            Some(path) => {
+                let bytes = fs::read(path).await.map_err(|e| {
+                    let e = format!("Invalid TLS file {}: {}", path, e);
+                    zerror2!(ZErrorKind::IoError { descr: e })
+                })?;
+                Ok(Some(bytes))
+            }
+            None => Ok(None),
+        },
+    }
+}
+
+// Both RSA and PKCS8 encoded private keys are accepted
+fn load_private_key(bytes: &[u8]) -> ZResult<PrivateKey> {
+    let mut keys = pemfile::rsa_private_keys(&mut Cursor::new(bytes)).unwrap_or_default();
+    if keys.is_empty() {
+        keys = pemfile::pkcs8_private_keys(&mut Cursor::new(bytes)).unwrap_or_default();
+    }
+    if keys.is_empty() {
+        return zerror!(ZErrorKind::Other {
+            descr: "Invalid TLS private key".to_string()
+        });
+    }
+    Ok(keys.remove(0))
+}
+
+// The PeerId of a client is the common name of its certificate subject
+fn get_tls_peer_id(session: &ServerSession) -> ZResult<PeerId> {
+    let certs = session.get_peer_certificates().ok_or_else(|| {
+        zerror2!(ZErrorKind::InvalidLink {
+            descr: "No client certificate provided".to_string()
+        })
+    })?;
+    let cert = certs.first().ok_or_else(|| {
+        zerror2!(ZErrorKind::InvalidLink {
+            descr: "No client certificate provided".to_string()
+        })
+    })?;
+    let (_, x509) = x509_parser::parse_x509_certificate(&cert.0).map_err(|e| {
+        zerror2!(ZErrorKind::InvalidLink {
+            descr: format!("Invalid client certificate: {}", e)
+        })
+    })?;
+    let cn = x509
+        .subject()
+        .iter_common_name()
+        .next()
+        .and_then(|cn| cn.as_str().ok())
+        .ok_or_else(|| {
+            zerror2!(ZErrorKind::InvalidLink {
+                descr: "No common name in client certificate subject".to_string()
+            })
+        })?;
+    PeerId::from_str(cn).map_err(|e| {
+        zerror2!(ZErrorKind::InvalidLink {
+            descr: format!("Invalid PeerId in client certificate subject: {}", e)
+        })
+    })
+}
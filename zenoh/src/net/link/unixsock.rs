@@ -0,0 +1,130 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ADLINK zenoh team, <zenoh@adlink-labs.tech>
+//
+//! Helpers shared by the Unix domain socket links.
+//!
+//! On Linux, a socket path starting with `@` designates a socket in the abstract namespace
+//! (e.g. `unixsock-stream/@zenoh`). Abstract sockets have no file on the filesystem and their
+//! name is released by the kernel as soon as the socket is closed, therefore no stale socket
+//! file has to be cleaned up after a crash and no lock file is needed.
+use std::fs::remove_file;
+use std::io;
+use std::os::unix::io::RawFd;
+use std::os::unix::net::SocketAddr;
+use zenoh_util::core::{ZError, ZErrorKind, ZResult};
+use zenoh_util::zerror2;
+
+pub(crate) const ABSTRACT_PREFIX: char = '@';
+
+// Returns true if the path designates a socket in the Linux abstract namespace
+pub(crate) fn is_abstract(path: &str) -> bool {
+    cfg!(target_os = "linux") && path.starts_with(ABSTRACT_PREFIX)
+}
+
+// Converts a socket path into a socket address, taking the abstract namespace into account
+pub(crate) fn to_socket_addr(path: &str) -> io::Result<SocketAddr> {
+    #[cfg(target_os = "linux")]
+    {
+        use std::os::linux::net::SocketAddrExt;
+        if let Some(name) = path.strip_prefix(ABSTRACT_PREFIX) {
+            return SocketAddr::from_abstract_name(name.as_bytes());
+        }
+    }
+    SocketAddr::from_pathname(path)
+}
+
+// Converts a socket address into a socket path, returns None if the socket is unnamed
+pub(crate) fn to_socket_path(addr: &SocketAddr) -> Option<String> {
+    if let Some(path) = addr.as_pathname() {
+        return path.to_str().map(|p| p.to_string());
+    }
+    #[cfg(target_os = "linux")]
+    {
+        use std::os::linux::net::SocketAddrExt;
+        if let Some(name) = addr.as_abstract_name() {
+            return std::str::from_utf8(name)
+                .ok()
+                .map(|n| format!("{}{}", ABSTRACT_PREFIX, n));
+        }
+    }
+    None
+}
+
+// Because of the lack of SO_REUSEADDR we have to check if the
+// file is still there and if it is not used by another process.
+// In order to do so we use a separate lock file.
+// If the lock CAN NOT be acquired means that another process is
+// holding the lock NOW, therefore we cannot use the socket.
+// Kernel guarantees that the lock is release if the owner exists
+// or crashes.
+//
+// If the lock CAN be acquired means no one is using the socket.
+// Therefore we can unlink the socket file and create the new one with
+// bind(2)
+pub(crate) fn lock(path: &str) -> ZResult<RawFd> {
+    // We generate the path for the lock file, by adding .lock
+    // to the socket file
+    let lock_file_path = format!("{}.lock", path);
+
+    // We try to open the lock file, with O_RDONLY | O_CREAT
+    // and mode S_IRUSR | S_IWUSR, user read-write permissions
+    let mut open_flags = nix::fcntl::OFlag::empty();
+
+    open_flags.insert(nix::fcntl::OFlag::O_CREAT);
+    open_flags.insert(nix::fcntl::OFlag::O_RDONLY);
+
+    let mut open_mode = nix::sys::stat::Mode::empty();
+    open_mode.insert(nix::sys::stat::Mode::S_IRUSR);
+    open_mode.insert(nix::sys::stat::Mode::S_IWUSR);
+
+    let lock_fd = nix::fcntl::open(std::path::Path::new(&lock_file_path), open_flags, open_mode)
+        .map_err(|e| {
+            let e = format!(
+                "Can not lock the Unix domain socket {} - Unable to open lock file: {}",
+                path, e
+            );
+            log::warn!("{}", e);
+            zerror2!(ZErrorKind::InvalidLink { descr: e })
+        })?;
+
+    // We try to acquire the lock
+    nix::fcntl::flock(lock_fd, nix::fcntl::FlockArg::LockExclusiveNonblock).map_err(|e| {
+        let _ = nix::unistd::close(lock_fd);
+        let e = format!(
+            "Can not lock the Unix domain socket {} - Unable to acquire lock: {}",
+            path, e
+        );
+        log::warn!("{}", e);
+        zerror2!(ZErrorKind::InvalidLink { descr: e })
+    })?;
+
+    // Lock is acquired we can remove the socket file
+    // If the file does not exist this would return an error.
+    // We are not interested if the file was not existing.
+    let _ = remove_file(path);
+
+    Ok(lock_fd)
+}
+
+// Releases the lock acquired with lock() and removes both the socket and the lock files
+pub(crate) fn unlock(path: &str, lock_fd: RawFd) {
+    // Release the lock
+    let _ = nix::fcntl::flock(lock_fd, nix::fcntl::FlockArg::UnlockNonblock);
+    let _ = nix::unistd::close(lock_fd);
+    let _ = remove_file(path);
+
+    // Remove the lock file
+    let lock_file_path = format!("{}.lock", path);
+    let tmp = remove_file(lock_file_path);
+    log::trace!("Unix domain socket lock file removal result: {:?}", tmp);
+}
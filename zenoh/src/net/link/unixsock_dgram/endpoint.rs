@@ -0,0 +1,102 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ADLINK zenoh team, <zenoh@adlink-labs.tech>
+//
+use super::config::*;
+use super::*;
+use async_std::path::PathBuf;
+use std::fmt;
+use std::str::FromStr;
+use zenoh_util::core::{ZError, ZErrorKind, ZResult};
+use zenoh_util::properties::config::ConfigProperties;
+use zenoh_util::properties::Properties;
+
+#[allow(unreachable_patterns)]
+pub(super) fn get_unix_path_as_string(locator: &Locator) -> ZResult<String> {
+    match &locator.address {
+        LocatorAddress::UnixSocketDgram(addr) => match addr.path.to_str() {
+            Some(path) => Ok(path.to_string()),
+            None => {
+                let e = format!("Invalid UnixSocketDgram locator: {:?}", locator);
+                log::debug!("{}", e);
+                zerror!(ZErrorKind::InvalidLocator { descr: e })
+            }
+        },
+        _ => {
+            let e = format!("Not a UnixSocketDgram locator: {:?}", locator);
+            log::debug!("{}", e);
+            zerror!(ZErrorKind::InvalidLocator { descr: e })
+        }
+    }
+}
+
+pub(super) fn get_unix_mtu(endpoint: &EndPoint) -> ZResult<u16> {
+    match endpoint
+        .config
+        .as_ref()
+        .and_then(|c| c.get(UNIXSOCKDGRAM_MTU))
+    {
+        Some(mtu) => match mtu.parse::<u16>() {
+            Ok(mtu) if mtu > 0 => Ok(mtu),
+            _ => {
+                let e = format!("Invalid UnixSocketDgram MTU for {}: {}", endpoint, mtu);
+                zerror!(ZErrorKind::InvalidLocator { descr: e })
+            }
+        },
+        None => Ok(*UNIXSOCKDGRAM_DEFAULT_MTU),
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct LocatorUnixSocketDgram {
+    pub(super) path: PathBuf,
+}
+
+impl LocatorUnixSocketDgram {
+    pub fn is_multicast(&self) -> bool {
+        false
+    }
+}
+
+impl FromStr for LocatorUnixSocketDgram {
+    type Err = ZError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            let e = format!("Invalid UnixSocketDgram locator: {:?}", s);
+            return zerror!(ZErrorKind::InvalidLocator { descr: e });
+        }
+        Ok(LocatorUnixSocketDgram {
+            path: PathBuf::from(s),
+        })
+    }
+}
+
+impl fmt::Display for LocatorUnixSocketDgram {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = self.path.to_str().unwrap_or("None");
+        write!(f, "{}", path)?;
+        Ok(())
+    }
+}
+
+/*************************************/
+/*          LOCATOR CONFIG           */
+/*************************************/
+#[derive(Clone)]
+pub struct LocatorConfigUnixSocketDgram;
+
+impl LocatorConfigUnixSocketDgram {
+    pub fn from_config(_config: &ConfigProperties) -> ZResult<Option<Properties>> {
+        Ok(None)
+    }
+}
@@ -0,0 +1,52 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ADLINK zenoh team, <zenoh@adlink-labs.tech>
+//
+mod endpoint;
+mod unicast;
+
+use super::*;
+pub use endpoint::*;
+pub use unicast::*;
+
+// Maximum MTU (UnixSocketDgram PDU) in bytes.
+// NOTE: The size of a Unix datagram is only limited by the socket send buffer. However, given the
+//       usage of 16 bits in Zenoh to encode the MTU, the UNIXSOCKDGRAM MTU is constrained to
+//       2^16 - 1 bytes (i.e., 65535).
+const UNIXSOCKDGRAM_MAX_MTU: u16 = u16::MAX;
+
+#[cfg(target_os = "linux")]
+// Linux default send buffer size for Unix datagram sockets is large enough to hold the MAX MTU.
+const UNIXSOCKDGRAM_MTU_LIMIT: u16 = UNIXSOCKDGRAM_MAX_MTU;
+
+#[cfg(target_os = "macos")]
+// Mac OS X default value of a maximum Unix datagram size (net.local.dgram.maxdgram) is 2048 bytes.
+const UNIXSOCKDGRAM_MTU_LIMIT: u16 = 2_048;
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+const UNIXSOCKDGRAM_MTU_LIMIT: u16 = 8_192;
+
+zconfigurable! {
+    // Default MTU (UNIXSOCKDGRAM PDU) in bytes.
+    static ref UNIXSOCKDGRAM_DEFAULT_MTU: u16 = UNIXSOCKDGRAM_MTU_LIMIT;
+    // Amount of time in microseconds to wait for the listener to answer the MTU negotiation.
+    // Default set to 10 s.
+    static ref UNIXSOCKDGRAM_OPEN_TIMEOUT: u64 = 10_000_000;
+    // Amount of time in microseconds to throttle the accept loop upon an error.
+    // Default set to 100 ms.
+    static ref UNIXSOCKDGRAM_ACCEPT_THROTTLE_TIME: u64 = 100_000;
+}
+
+pub mod config {
+    // The MTU of the Unix datagram link. The smallest of the listener and connector values is used.
+    pub const UNIXSOCKDGRAM_MTU: &str = "mtu";
+}
@@ -0,0 +1,622 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ADLINK zenoh team, <zenoh@adlink-labs.tech>
+//
+use super::super::unixsock::{is_abstract, lock, to_socket_addr, to_socket_path, unlock};
+use super::*;
+use crate::net::transport::TransportManager;
+use async_io::Async;
+use async_std::path::PathBuf;
+use async_std::prelude::*;
+use async_std::sync::Mutex as AsyncMutex;
+use async_std::task;
+use async_std::task::JoinHandle;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::remove_file;
+use std::os::unix::io::RawFd;
+use std::os::unix::net::{SocketAddr, UnixDatagram};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, RwLock, Weak};
+use std::time::Duration;
+use uuid::Uuid;
+use zenoh_util::collections::{RecyclingObject, RecyclingObjectPool};
+use zenoh_util::core::{ZError, ZErrorKind, ZResult};
+use zenoh_util::sync::{Mvar, Signal};
+use zenoh_util::{zasynclock, zerror, zerror2, zlock, zread, zwrite};
+
+type LinkHashMap = Arc<Mutex<HashMap<String, Weak<LinkUnicastUnixSocketDgramUnconnected>>>>;
+type LinkInput = (RecyclingObject<Box<[u8]>>, usize);
+type LinkLeftOver = (RecyclingObject<Box<[u8]>>, usize, usize);
+
+// The MTU is negotiated when opening the link: the connector sends its MTU to the listener
+// in a datagram of INIT_LEN bytes and the listener answers with the smallest of the two MTUs.
+const INIT_LEN: usize = 2;
+
+struct LinkUnicastUnixSocketDgramConnected {
+    socket: Async<UnixDatagram>,
+    // The socket file bound by the connector, None if in the abstract namespace
+    file: Option<String>,
+}
+
+impl LinkUnicastUnixSocketDgramConnected {
+    async fn read(&self, buffer: &mut [u8]) -> ZResult<usize> {
+        self.socket.recv(buffer).await.map_err(|e| {
+            zerror2!(ZErrorKind::IoError {
+                descr: e.to_string()
+            })
+        })
+    }
+
+    async fn write(&self, buffer: &[u8]) -> ZResult<usize> {
+        self.socket.send(buffer).await.map_err(|e| {
+            zerror2!(ZErrorKind::IoError {
+                descr: e.to_string()
+            })
+        })
+    }
+
+    async fn close(&self) -> ZResult<()> {
+        if let Some(file) = self.file.as_ref() {
+            let _ = remove_file(file);
+        }
+        Ok(())
+    }
+}
+
+impl Drop for LinkUnicastUnixSocketDgramConnected {
+    fn drop(&mut self) {
+        // Remove the socket file bound by the connector
+        if let Some(file) = self.file.as_ref() {
+            let _ = remove_file(file);
+        }
+    }
+}
+
+struct LinkUnicastUnixSocketDgramUnconnected {
+    socket: Weak<Async<UnixDatagram>>,
+    links: LinkHashMap,
+    dst_addr: SocketAddr,
+    input: Mvar<LinkInput>,
+    leftover: AsyncMutex<Option<LinkLeftOver>>,
+}
+
+impl LinkUnicastUnixSocketDgramUnconnected {
+    async fn received(&self, buffer: RecyclingObject<Box<[u8]>>, len: usize) {
+        self.input.put((buffer, len)).await;
+    }
+
+    async fn read(&self, buffer: &mut [u8]) -> ZResult<usize> {
+        let mut guard = zasynclock!(self.leftover);
+        let (slice, start, len) = match guard.take() {
+            Some(tuple) => tuple,
+            None => {
+                let (slice, len) = self.input.take().await;
+                (slice, 0, len)
+            }
+        };
+        // Copy the read bytes into the target buffer
+        let len_min = (len - start).min(buffer.len());
+        let end = start + len_min;
+        buffer[0..len_min].copy_from_slice(&slice[start..end]);
+        if end < len {
+            // Store the leftover
+            *guard = Some((slice, end, len));
+        } else {
+            // Recycle the buffer
+            slice.recycle().await;
+        }
+        // Return the amount read
+        Ok(len_min)
+    }
+
+    async fn write(&self, buffer: &[u8]) -> ZResult<usize> {
+        match self.socket.upgrade() {
+            Some(socket) => socket
+                .write_with(|s| s.send_to_addr(buffer, &self.dst_addr))
+                .await
+                .map_err(|e| {
+                    zerror2!(ZErrorKind::IoError {
+                        descr: e.to_string()
+                    })
+                }),
+            None => zerror!(ZErrorKind::IoError {
+                descr: "UnixSocketDgram listener has been dropped".to_string()
+            }),
+        }
+    }
+
+    async fn close(&self, dst_path: &str) -> ZResult<()> {
+        // Delete the link from the list of links
+        zlock!(self.links).remove(dst_path);
+        Ok(())
+    }
+}
+
+enum LinkUnicastUnixSocketDgramVariant {
+    Connected(LinkUnicastUnixSocketDgramConnected),
+    Unconnected(Arc<LinkUnicastUnixSocketDgramUnconnected>),
+}
+
+pub struct LinkUnicastUnixSocketDgram {
+    // The Unix domain socket source path
+    src_path: String,
+    // The Unix domain socket destination path
+    dst_path: String,
+    // The MTU negotiated between the connector and the listener
+    mtu: u16,
+    // The socket is connected to the peer
+    variant: LinkUnicastUnixSocketDgramVariant,
+}
+
+impl LinkUnicastUnixSocketDgram {
+    fn new(
+        src_path: String,
+        dst_path: String,
+        mtu: u16,
+        variant: LinkUnicastUnixSocketDgramVariant,
+    ) -> LinkUnicastUnixSocketDgram {
+        LinkUnicastUnixSocketDgram {
+            src_path,
+            dst_path,
+            mtu,
+            variant,
+        }
+    }
+}
+
+#[async_trait]
+impl LinkUnicastTrait for LinkUnicastUnixSocketDgram {
+    async fn close(&self) -> ZResult<()> {
+        log::trace!("Closing UnixSocketDgram link: {}", self);
+        match &self.variant {
+            LinkUnicastUnixSocketDgramVariant::Connected(link) => link.close().await,
+            LinkUnicastUnixSocketDgramVariant::Unconnected(link) => {
+                link.close(&self.dst_path).await
+            }
+        }
+    }
+
+    async fn write(&self, buffer: &[u8]) -> ZResult<usize> {
+        match &self.variant {
+            LinkUnicastUnixSocketDgramVariant::Connected(link) => link.write(buffer).await,
+            LinkUnicastUnixSocketDgramVariant::Unconnected(link) => link.write(buffer).await,
+        }
+    }
+
+    async fn write_all(&self, buffer: &[u8]) -> ZResult<()> {
+        let mut written: usize = 0;
+        while written < buffer.len() {
+            written += self.write(&buffer[written..]).await?;
+        }
+        Ok(())
+    }
+
+    async fn read(&self, buffer: &mut [u8]) -> ZResult<usize> {
+        match &self.variant {
+            LinkUnicastUnixSocketDgramVariant::Connected(link) => link.read(buffer).await,
+            LinkUnicastUnixSocketDgramVariant::Unconnected(link) => link.read(buffer).await,
+        }
+    }
+
+    async fn read_exact(&self, buffer: &mut [u8]) -> ZResult<()> {
+        let mut read: usize = 0;
+        while read < buffer.len() {
+            let n = self.read(&mut buffer[read..]).await?;
+            read += n;
+        }
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn get_src(&self) -> Locator {
+        Locator {
+            address: LocatorAddress::UnixSocketDgram(LocatorUnixSocketDgram {
+                path: PathBuf::from(self.src_path.clone()),
+            }),
+            metadata: None,
+        }
+    }
+
+    #[inline(always)]
+    fn get_dst(&self) -> Locator {
+        Locator {
+            address: LocatorAddress::UnixSocketDgram(LocatorUnixSocketDgram {
+                path: PathBuf::from(self.dst_path.clone()),
+            }),
+            metadata: None,
+        }
+    }
+
+    #[inline(always)]
+    fn get_mtu(&self) -> u16 {
+        self.mtu
+    }
+
+    #[inline(always)]
+    fn is_reliable(&self) -> bool {
+        false
+    }
+
+    #[inline(always)]
+    fn is_streamed(&self) -> bool {
+        false
+    }
+}
+
+impl fmt::Display for LinkUnicastUnixSocketDgram {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} => {}", self.src_path, self.dst_path)?;
+        Ok(())
+    }
+}
+
+impl fmt::Debug for LinkUnicastUnixSocketDgram {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UnixSocketDgram")
+            .field("src", &self.src_path)
+            .field("dst", &self.dst_path)
+            .field("mtu", &self.mtu)
+            .finish()
+    }
+}
+
+/*************************************/
+/*          LISTENER                 */
+/*************************************/
+struct ListenerUnixSocketDgram {
+    endpoint: EndPoint,
+    active: Arc<AtomicBool>,
+    signal: Signal,
+    handle: JoinHandle<ZResult<()>>,
+    lock_fd: Option<RawFd>,
+}
+
+impl ListenerUnixSocketDgram {
+    fn new(
+        endpoint: EndPoint,
+        active: Arc<AtomicBool>,
+        signal: Signal,
+        handle: JoinHandle<ZResult<()>>,
+        lock_fd: Option<RawFd>,
+    ) -> ListenerUnixSocketDgram {
+        ListenerUnixSocketDgram {
+            endpoint,
+            active,
+            signal,
+            handle,
+            lock_fd,
+        }
+    }
+}
+
+pub struct LinkManagerUnicastUnixSocketDgram {
+    manager: TransportManager,
+    listeners: Arc<RwLock<HashMap<String, ListenerUnixSocketDgram>>>,
+}
+
+impl LinkManagerUnicastUnixSocketDgram {
+    pub(crate) fn new(manager: TransportManager) -> Self {
+        Self {
+            manager,
+            listeners: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+}
+
+#[async_trait]
+impl LinkManagerUnicastTrait for LinkManagerUnicastUnixSocketDgram {
+    async fn new_link(&self, endpoint: EndPoint) -> ZResult<LinkUnicast> {
+        let path = get_unix_path_as_string(&endpoint.locator)?;
+        let mtu = get_unix_mtu(&endpoint)?;
+
+        macro_rules! zerrlink {
+            ($e:expr) => {{
+                let e = format!(
+                    "Can not create a new UnixSocketDgram link bound to {}: {}",
+                    path, $e
+                );
+                log::warn!("{}", e);
+                zerror2!(ZErrorKind::InvalidLink { descr: e })
+            }};
+        }
+
+        // The listener answers on the source address of the connector, therefore the
+        // connector socket needs to be bound to a path as well. On Linux the abstract
+        // namespace is used so that no socket file is left behind.
+        let name = format!("zenoh-{}", Uuid::new_v4());
+        let (src_path, file) = if cfg!(target_os = "linux") {
+            (format!("@{}", name), None)
+        } else {
+            let file = std::env::temp_dir().join(format!("{}.sock", name));
+            let file = file
+                .to_str()
+                .ok_or_else(|| zerrlink!(format!("invalid path {:?}", file)))?
+                .to_string();
+            (file.clone(), Some(file))
+        };
+
+        let socket = to_socket_addr(&src_path)
+            .and_then(|addr| UnixDatagram::bind_addr(&addr))
+            .and_then(Async::new)
+            .map_err(|e| zerrlink!(e))?;
+        let link = LinkUnicastUnixSocketDgramConnected { socket, file };
+
+        // Connect the socket to the listener
+        let dst_addr = to_socket_addr(&path).map_err(|e| zerrlink!(e))?;
+        link.socket
+            .get_ref()
+            .connect_addr(&dst_addr)
+            .map_err(|e| zerrlink!(e))?;
+
+        // Negotiate the MTU with the listener
+        link.socket
+            .send(&mtu.to_le_bytes())
+            .await
+            .map_err(|e| zerrlink!(e))?;
+        let mut buffer = [0u8; INIT_LEN];
+        let n = link
+            .socket
+            .recv(&mut buffer)
+            .timeout(Duration::from_micros(*UNIXSOCKDGRAM_OPEN_TIMEOUT))
+            .await
+            .map_err(|e| zerrlink!(e))?
+            .map_err(|e| zerrlink!(e))?;
+        let mtu = match u16::from_le_bytes(buffer) {
+            m if n == INIT_LEN && m > 0 && m <= mtu => m,
+            m => return Err(zerrlink!(format!("invalid MTU from listener: {}", m))),
+        };
+
+        let link = Arc::new(LinkUnicastUnixSocketDgram::new(
+            src_path,
+            path,
+            mtu,
+            LinkUnicastUnixSocketDgramVariant::Connected(link),
+        ));
+
+        Ok(LinkUnicast(link))
+    }
+
+    async fn new_listener(&self, mut endpoint: EndPoint) -> ZResult<Locator> {
+        let path = get_unix_path_as_string(&endpoint.locator)?;
+        let mtu = get_unix_mtu(&endpoint)?;
+
+        // Sockets in the abstract namespace do not need to be locked since they
+        // are released by the kernel when closed
+        let lock_fd = if is_abstract(&path) {
+            None
+        } else {
+            Some(lock(&path)?)
+        };
+
+        // Bind the Unix socket
+        let socket = to_socket_addr(&path)
+            .and_then(|addr| UnixDatagram::bind_addr(&addr))
+            .and_then(Async::new)
+            .and_then(|s| {
+                let local_addr = s.get_ref().local_addr()?;
+                Ok((s, local_addr))
+            });
+        let (socket, local_addr) = socket.map_err(|e| {
+            if let Some(lock_fd) = lock_fd {
+                unlock(&path, lock_fd);
+            }
+            let e = format!(
+                "Can not create a new UnixSocketDgram listener on {}: {}",
+                path, e
+            );
+            log::warn!("{}", e);
+            zerror2!(ZErrorKind::InvalidLink { descr: e })
+        })?;
+
+        let local_path_str = to_socket_path(&local_addr).unwrap_or_else(|| path.clone());
+
+        // Update the endpoint with the actual local path
+        endpoint.locator.address = LocatorAddress::UnixSocketDgram(LocatorUnixSocketDgram {
+            path: PathBuf::from(local_path_str.clone()),
+        });
+
+        // Spawn the accept loop for the listener
+        let active = Arc::new(AtomicBool::new(true));
+        let signal = Signal::new();
+
+        let c_active = active.clone();
+        let c_signal = signal.clone();
+        let c_manager = self.manager.clone();
+        let c_listeners = self.listeners.clone();
+        let c_path = local_path_str.clone();
+        let handle = task::spawn(async move {
+            // Wait for the accept loop to terminate
+            let res =
+                accept_read_task(socket, c_path.clone(), mtu, c_active, c_signal, c_manager).await;
+            zwrite!(c_listeners).remove(&c_path);
+            res
+        });
+
+        let locator = endpoint.locator.clone();
+        let listener = ListenerUnixSocketDgram::new(endpoint, active, signal, handle, lock_fd);
+        zwrite!(self.listeners).insert(local_path_str, listener);
+
+        Ok(locator)
+    }
+
+    async fn del_listener(&self, endpoint: &EndPoint) -> ZResult<()> {
+        let path = get_unix_path_as_string(&endpoint.locator)?;
+
+        // Stop the listener
+        let listener = zwrite!(self.listeners).remove(&path).ok_or_else(|| {
+            let e = format!(
+                "Can not delete the UnixSocketDgram listener because it has not been found: {}",
+                path
+            );
+            log::trace!("{}", e);
+            zerror2!(ZErrorKind::InvalidLink { descr: e })
+        })?;
+
+        // Send the stop signal
+        listener.active.store(false, Ordering::Release);
+        listener.signal.trigger();
+        let res = listener.handle.await;
+
+        // Release the lock and remove the socket file
+        if let Some(lock_fd) = listener.lock_fd {
+            unlock(&path, lock_fd);
+        }
+        res
+    }
+
+    fn get_listeners(&self) -> Vec<EndPoint> {
+        zread!(self.listeners)
+            .values()
+            .map(|x| x.endpoint.clone())
+            .collect()
+    }
+
+    fn get_locators(&self) -> Vec<Locator> {
+        zread!(self.listeners)
+            .values()
+            .map(|x| x.endpoint.locator.clone())
+            .collect()
+    }
+}
+
+async fn accept_read_task(
+    socket: Async<UnixDatagram>,
+    src_path: String,
+    mtu: u16,
+    active: Arc<AtomicBool>,
+    signal: Signal,
+    manager: TransportManager,
+) -> ZResult<()> {
+    let socket = Arc::new(socket);
+    let links: LinkHashMap = Arc::new(Mutex::new(HashMap::new()));
+
+    enum Action {
+        Receive((usize, SocketAddr)),
+        Stop,
+    }
+
+    async fn receive(socket: Arc<Async<UnixDatagram>>, buffer: &mut [u8]) -> ZResult<Action> {
+        let res = socket.recv_from(buffer).await.map_err(|e| {
+            zerror2!(ZErrorKind::IoError {
+                descr: e.to_string()
+            })
+        })?;
+        Ok(Action::Receive(res))
+    }
+
+    async fn stop(signal: Signal) -> ZResult<Action> {
+        signal.wait().await;
+        Ok(Action::Stop)
+    }
+
+    log::trace!(
+        "Ready to accept UnixSocketDgram connections on: {}",
+        src_path
+    );
+    // Buffers for deserialization
+    let pool = RecyclingObjectPool::new(1, || vec![0u8; mtu as usize].into_boxed_slice());
+    while active.load(Ordering::Acquire) {
+        let mut buff = pool.take().await;
+        // Wait for incoming datagrams
+        let (n, dst_addr) = match receive(socket.clone(), &mut buff)
+            .race(stop(signal.clone()))
+            .await
+        {
+            Ok(action) => match action {
+                Action::Receive((n, addr)) => (n, addr),
+                Action::Stop => break,
+            },
+            Err(e) => {
+                log::warn!("{}. Hint: increase the system open file limit.", e);
+                // Throttle the accept loop upon an error
+                // NOTE: This might be due to various factors. However, the most common case is that
+                //       the process has reached the maximum number of open files in the system. On
+                //       Linux systems this limit can be changed by using the "ulimit" command line
+                //       tool. In case of systemd-based systems, this can be changed by using the
+                //       "sysctl" command line tool.
+                task::sleep(Duration::from_micros(*UNIXSOCKDGRAM_ACCEPT_THROTTLE_TIME)).await;
+                continue;
+            }
+        };
+
+        // Datagrams from unnamed sockets can not be answered
+        let dst_path = match to_socket_path(&dst_addr) {
+            Some(dst_path) => dst_path,
+            None => {
+                log::debug!(
+                    "Discarding datagram from an unnamed socket on UnixSocketDgram: {}",
+                    src_path
+                );
+                continue;
+            }
+        };
+
+        let link = zlock!(links).get(&dst_path).and_then(|link| link.upgrade());
+        match link {
+            Some(link) => link.received(buff, n).await,
+            None => {
+                // A new peer is negotiating the MTU
+                let link_mtu = match n {
+                    INIT_LEN => u16::from_le_bytes([buff[0], buff[1]]).min(mtu),
+                    _ => 0,
+                };
+                if link_mtu == 0 {
+                    log::debug!(
+                        "Discarding datagram from unknown peer on UnixSocketDgram {}: {}",
+                        src_path,
+                        dst_path
+                    );
+                    continue;
+                }
+                let res = socket
+                    .write_with(|s| s.send_to_addr(&link_mtu.to_le_bytes(), &dst_addr))
+                    .await;
+                if let Err(e) = res {
+                    log::warn!(
+                        "Can not accept UnixSocketDgram connection on {} from {}: {}",
+                        src_path,
+                        dst_path,
+                        e
+                    );
+                    continue;
+                }
+
+                log::debug!(
+                    "Accepted UnixSocketDgram connection on {}: {}",
+                    src_path,
+                    dst_path
+                );
+                let unconnected = Arc::new(LinkUnicastUnixSocketDgramUnconnected {
+                    socket: Arc::downgrade(&socket),
+                    links: links.clone(),
+                    dst_addr,
+                    input: Mvar::new(),
+                    leftover: AsyncMutex::new(None),
+                });
+                zlock!(links).insert(dst_path.clone(), Arc::downgrade(&unconnected));
+                // Create the new link object
+                let link = Arc::new(LinkUnicastUnixSocketDgram::new(
+                    src_path.clone(),
+                    dst_path,
+                    link_mtu,
+                    LinkUnicastUnixSocketDgramVariant::Unconnected(unconnected),
+                ));
+                // Communicate the new link to the initial transport manager
+                manager.handle_new_link_unicast(LinkUnicast(link)).await;
+            }
+        }
+    }
+
+    Ok(())
+}
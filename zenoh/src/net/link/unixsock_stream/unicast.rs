@@ -11,6 +11,7 @@
 // Contributors:
 //   ADLINK zenoh team, <zenoh@adlink-labs.tech>
 //
+use super::super::unixsock::{is_abstract, lock, to_socket_addr, to_socket_path, unlock};
 use super::*;
 use crate::net::transport::TransportManager;
 use async_std::os::unix::net::{UnixListener, UnixStream};
@@ -21,9 +22,9 @@ use async_std::task::JoinHandle;
 use async_trait::async_trait;
 use std::collections::HashMap;
 use std::fmt;
-use std::fs::remove_file;
 use std::net::Shutdown;
 use std::os::unix::io::RawFd;
+use std::os::unix::net::{UnixListener as StdUnixListener, UnixStream as StdUnixStream};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, RwLock};
 use std::time::Duration;
@@ -164,7 +165,7 @@ struct ListenerUnixSocketStream {
     active: Arc<AtomicBool>,
     signal: Signal,
     handle: JoinHandle<ZResult<()>>,
-    lock_fd: RawFd,
+    lock_fd: Option<RawFd>,
 }
 
 impl ListenerUnixSocketStream {
@@ -173,7 +174,7 @@ impl ListenerUnixSocketStream {
         active: Arc<AtomicBool>,
         signal: Signal,
         handle: JoinHandle<ZResult<()>>,
-        lock_fd: RawFd,
+        lock_fd: Option<RawFd>,
     ) -> ListenerUnixSocketStream {
         ListenerUnixSocketStream {
             endpoint,
@@ -205,7 +206,18 @@ impl LinkManagerUnicastTrait for LinkManagerUnicastUnixSocketStream {
         let path = get_unix_path(&endpoint.locator)?;
 
         // Create the UnixSocketStream connection
-        let stream = UnixStream::connect(&path).await.map_err(|e| {
+        let stream = match path.to_str() {
+            Some(p) if is_abstract(p) => {
+                let addr = to_socket_addr(p);
+                task::spawn_blocking(move || {
+                    addr.and_then(|addr| StdUnixStream::connect_addr(&addr))
+                        .map(UnixStream::from)
+                })
+                .await
+            }
+            _ => UnixStream::connect(&path).await,
+        };
+        let stream = stream.map_err(|e| {
             let e = format!(
                 "Can not create a new UnixSocketStream link bound to {:?}: {}",
                 path, e
@@ -281,64 +293,26 @@ impl LinkManagerUnicastTrait for LinkManagerUnicastUnixSocketStream {
     async fn new_listener(&self, mut endpoint: EndPoint) -> ZResult<Locator> {
         let path = get_unix_path_as_string(&endpoint.locator);
 
-        // Because of the lack of SO_REUSEADDR we have to check if the
-        // file is still there and if it is not used by another process.
-        // In order to do so we use a separate lock file.
-        // If the lock CAN NOT be acquired means that another process is
-        // holding the lock NOW, therefore we cannot use the socket.
-        // Kernel guarantees that the lock is release if the owner exists
-        // or crashes.
-
-        // If the lock CAN be acquired means no one is using the socket.
-        // Therefore we can unlink the socket file and create the new one with
-        // bind(2)
-
-        // We generate the path for the lock file, by adding .lock
-        // to the socket file
-        let lock_file_path = format!("{}.lock", path);
-
-        // We try to open the lock file, with O_RDONLY | O_CREAT
-        // and mode S_IRUSR | S_IWUSR, user read-write permissions
-        let mut open_flags = nix::fcntl::OFlag::empty();
-
-        open_flags.insert(nix::fcntl::OFlag::O_CREAT);
-        open_flags.insert(nix::fcntl::OFlag::O_RDONLY);
-
-        let mut open_mode = nix::sys::stat::Mode::empty();
-        open_mode.insert(nix::sys::stat::Mode::S_IRUSR);
-        open_mode.insert(nix::sys::stat::Mode::S_IWUSR);
-
-        let lock_fd = nix::fcntl::open(
-            std::path::Path::new(&lock_file_path),
-            open_flags,
-            open_mode,
-        ).map_err(|e| {
-            let e = format!(
-                "Can not create a new UnixSocketStream listener on {} - Unable to open lock file: {}",
-                path, e
-            );
-            log::warn!("{}", e);
-            zerror2!(ZErrorKind::InvalidLink { descr: e })
-        })?;
-
-        // We try to acquire the lock
-        nix::fcntl::flock(lock_fd, nix::fcntl::FlockArg::LockExclusiveNonblock).map_err(|e| {
-            let _ = nix::unistd::close(lock_fd);
-            let e = format!(
-                "Can not create a new UnixSocketStream listener on {} - Unable to acquire look: {}",
-                path, e
-            );
-            log::warn!("{}", e);
-            zerror2!(ZErrorKind::InvalidLink { descr: e })
-        })?;
-
-        //Lock is acquired we can remove the socket file
-        // If the file does not exist this would return an error.
-        // We are not interested if the file was not existing.
-        let _ = remove_file(path.clone());
+        // Sockets in the abstract namespace do not need to be locked since they
+        // are released by the kernel when closed
+        let lock_fd = if is_abstract(&path) {
+            None
+        } else {
+            Some(lock(&path)?)
+        };
 
         // Bind the Unix socket
-        let socket = UnixListener::bind(&path).await.map_err(|e| {
+        let socket = if is_abstract(&path) {
+            to_socket_addr(&path)
+                .and_then(|addr| StdUnixListener::bind_addr(&addr))
+                .map(UnixListener::from)
+        } else {
+            UnixListener::bind(&path).await
+        };
+        let socket = socket.map_err(|e| {
+            if let Some(lock_fd) = lock_fd {
+                unlock(&path, lock_fd);
+            }
             let e = format!(
                 "Can not create a new UnixSocketStream listener on {}: {}",
                 path, e
@@ -356,24 +330,16 @@ impl LinkManagerUnicastTrait for LinkManagerUnicastUnixSocketStream {
             zerror2!(ZErrorKind::InvalidLink { descr: e })
         })?;
 
-        let local_path = PathBuf::from(local_addr.as_pathname().ok_or_else(|| {
+        let local_path_str = to_socket_path(&local_addr).ok_or_else(|| {
             let e = format!("Can not create a new UnixSocketStream listener on {}", path);
             log::warn!("{}", e);
             zerror2!(ZErrorKind::InvalidLink { descr: e })
-        })?);
-
-        let local_path_str = local_path
-            .to_str()
-            .ok_or_else(|| {
-                let e = format!("Can not create a new UnixSocketStream listener on {}", path);
-                log::warn!("{}", e);
-                zerror2!(ZErrorKind::InvalidLink { descr: e })
-            })?
-            .to_string();
+        })?;
 
         // Update the endpoint with the acutal local path
-        endpoint.locator.address =
-            LocatorAddress::UnixSocketStream(LocatorUnixSocketStream { path: local_path });
+        endpoint.locator.address = LocatorAddress::UnixSocketStream(LocatorUnixSocketStream {
+            path: PathBuf::from(local_path_str.clone()),
+        });
 
         // Spawn the accept loop for the listener
         let active = Arc::new(AtomicBool::new(true));
@@ -416,15 +382,10 @@ impl LinkManagerUnicastTrait for LinkManagerUnicastUnixSocketStream {
         listener.signal.trigger();
         let res = listener.handle.await;
 
-        //Release the lock
-        let _ = nix::fcntl::flock(listener.lock_fd, nix::fcntl::FlockArg::UnlockNonblock);
-        let _ = nix::unistd::close(listener.lock_fd);
-        let _ = remove_file(path.clone());
-
-        // Remove the Unix Domain Socket file
-        let lock_file_path = format!("{}.lock", path);
-        let tmp = remove_file(lock_file_path);
-        log::trace!("UnixSocketStream Domain Socket removal result: {:?}", tmp);
+        // Release the lock and remove the Unix Domain Socket file
+        if let Some(lock_fd) = listener.lock_fd {
+            unlock(&path, lock_fd);
+        }
         res
     }
 
@@ -474,26 +435,14 @@ async fn accept_task(
         zerror2!(ZErrorKind::IoError { descr: e })
     })?;
 
-    let local_path = PathBuf::from(src_addr.as_pathname().ok_or_else(|| {
+    let src_path = to_socket_path(&src_addr).ok_or_else(|| {
         let e = format!(
             "Can not create a new UnixSocketStream link bound to {:?}",
             src_addr
         );
         log::warn!("{}", e);
         zerror2!(ZErrorKind::IoError { descr: e })
-    })?);
-
-    let src_path = local_path
-        .to_str()
-        .ok_or_else(|| {
-            let e = format!(
-                "Can not create a new UnixSocketStream link bound to {:?}",
-                src_addr
-            );
-            log::warn!("{}", e);
-            zerror2!(ZErrorKind::IoError { descr: e })
-        })?
-        .to_string();
+    })?;
 
     // The accept future
     log::trace!(
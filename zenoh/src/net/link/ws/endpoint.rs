@@ -0,0 +1,103 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ADLINK zenoh team, <zenoh@adlink-labs.tech>
+//
+use super::*;
+use async_std::net::{SocketAddr, ToSocketAddrs};
+use std::fmt;
+use std::str::FromStr;
+use zenoh_util::core::{ZError, ZErrorKind, ZResult};
+use zenoh_util::properties::config::ConfigProperties;
+use zenoh_util::properties::Properties;
+
+#[allow(unreachable_patterns)]
+pub(super) async fn get_ws_addr(address: &LocatorAddress) -> ZResult<SocketAddr> {
+    match address {
+        LocatorAddress::Ws(addr) => match addr {
+            LocatorWs::SocketAddr(addr) => Ok(*addr),
+            LocatorWs::DnsName(addr) => match addr.to_socket_addrs().await {
+                Ok(mut addr_iter) => {
+                    if let Some(addr) = addr_iter.next() {
+                        Ok(addr)
+                    } else {
+                        let e = format!("Couldn't resolve WS locator address: {}", addr);
+                        zerror!(ZErrorKind::InvalidLocator { descr: e })
+                    }
+                }
+                Err(e) => {
+                    let e = format!("{}: {}", e, addr);
+                    zerror!(ZErrorKind::InvalidLocator { descr: e })
+                }
+            },
+        },
+        _ => {
+            let e = format!("Not a WS locator address: {}", address);
+            zerror!(ZErrorKind::InvalidLocator { descr: e })
+        }
+    }
+}
+
+#[allow(unreachable_patterns)]
+pub(super) fn get_ws_url(address: &LocatorAddress) -> ZResult<String> {
+    match address {
+        // Keep the DNS name, if any, so that it is carried by the Host header of the handshake
+        LocatorAddress::Ws(addr) => Ok(format!("ws://{}/", addr)),
+        _ => {
+            let e = format!("Not a WS locator address: {}", address);
+            zerror!(ZErrorKind::InvalidLocator { descr: e })
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum LocatorWs {
+    SocketAddr(SocketAddr),
+    DnsName(String),
+}
+
+impl LocatorWs {
+    pub fn is_multicast(&self) -> bool {
+        false
+    }
+}
+
+impl FromStr for LocatorWs {
+    type Err = ZError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.parse() {
+            Ok(addr) => Ok(LocatorWs::SocketAddr(addr)),
+            Err(_) => Ok(LocatorWs::DnsName(s.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for LocatorWs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LocatorWs::SocketAddr(addr) => write!(f, "{}", addr)?,
+            LocatorWs::DnsName(addr) => write!(f, "{}", addr)?,
+        }
+        Ok(())
+    }
+}
+
+/*************************************/
+/*          LOCATOR CONFIG           */
+/*************************************/
+pub struct LocatorConfigWs;
+
+impl LocatorConfigWs {
+    pub fn from_config(_config: &ConfigProperties) -> ZResult<Option<Properties>> {
+        Ok(None)
+    }
+}
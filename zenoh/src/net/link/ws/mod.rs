@@ -0,0 +1,34 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ADLINK zenoh team, <zenoh@adlink-labs.tech>
+//
+mod endpoint;
+mod unicast;
+
+use super::*;
+pub use endpoint::*;
+pub use unicast::*;
+
+// Default MTU (WebSocket PDU) in bytes.
+// NOTE: Each zenoh batch is carried in a single binary WebSocket message, hence
+//       WebSocket provides its own framing and no length needs to be prepended.
+//       However, given the usage of 16 bits in Zenoh to encode the batch size,
+//       the WS MTU is constrained to 2^16 - 1 bytes (i.e., 65535).
+const WS_MAX_MTU: u16 = u16::MAX;
+
+zconfigurable! {
+    // Default MTU (WS PDU) in bytes.
+    static ref WS_DEFAULT_MTU: u16 = WS_MAX_MTU;
+    // Amount of time in microseconds to throttle the accept loop upon an error.
+    // Default set to 100 ms.
+    static ref WS_ACCEPT_THROTTLE_TIME: u64 = 100_000;
+}
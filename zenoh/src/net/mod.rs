@@ -61,7 +61,7 @@
 //!         QueryConsolidation::default()
 //!     ).await.unwrap();
 //!     while let Some(reply) = replies.next().await {
-//!         println!(">> Received {:?}", reply.data);
+//!         println!(">> Received {:?}", reply);
 //!     }
 //! }
 //! ```
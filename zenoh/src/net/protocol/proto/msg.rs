@@ -126,6 +126,7 @@ pub mod tmsg {
         use super::ZInt;
 
         pub const QOS: ZInt = 1 << 0; // 0x01 QoS       if PRIORITY==1 then the transport supports QoS
+        pub const COMPRESSION: ZInt = 1 << 1; // 0x02 Compression if COMPRESSION==1 then the batches are compressed
     }
 
     pub mod join_options {
@@ -183,6 +184,7 @@ pub mod zmsg {
     // Zenoh message flags
     pub mod flag {
         pub const D: u8 = 1 << 5; // 0x20 Drop          if D==1 then the message can be dropped
+        pub const E: u8 = 1 << 6; // 0x40 Error         if E==1 then the reply is an error (e.g., ReplyContext)
         pub const F: u8 = 1 << 5; // 0x20 Final         if F==1 then this is the final message (e.g., ReplyContext, Pull)
         pub const I: u8 = 1 << 6; // 0x40 DataInfo      if I==1 then DataInfo is present
         pub const K: u8 = 1 << 7; // 0x80 ResourceKey   if K==1 then resource key has name
@@ -225,11 +227,19 @@ pub mod zmsg {
             pub const PUBLISHER: u8 = 0x02;
             pub const SUBSCRIBER: u8 = 0x03;
             pub const QUERYABLE: u8 = 0x04;
+            // A liveliness token: the presence of an application on a resource key,
+            // withdrawn by FORGET_LIVELINESS_TOKEN or when its face closes
+            pub const LIVELINESS_TOKEN: u8 = 0x05;
+            // The interest of a client for the liveliness tokens matching a resource key:
+            // the tokens are only declared to the clients interested in them
+            pub const LIVELINESS_SUBSCRIBER: u8 = 0x06;
 
             pub const FORGET_RESOURCE: u8 = 0x11;
             pub const FORGET_PUBLISHER: u8 = 0x12;
             pub const FORGET_SUBSCRIBER: u8 = 0x13;
             pub const FORGET_QUERYABLE: u8 = 0x14;
+            pub const FORGET_LIVELINESS_TOKEN: u8 = 0x15;
+            pub const FORGET_LIVELINESS_SUBSCRIBER: u8 = 0x16;
 
             // SubModes
             pub const MODE_PUSH: u8 = 0x00;
@@ -384,7 +394,7 @@ impl Attachment {
 ///
 ///  7 6 5 4 3 2 1 0
 /// +-+-+-+-+-+-+-+-+
-/// |X|X|F|  R_CTX  |
+/// |X|E|F|  R_CTX  |
 /// +-+-+-+---------+
 /// ~      qid      ~
 /// +---------------+
@@ -394,6 +404,7 @@ impl Attachment {
 /// +---------------+
 ///
 /// - if F==1 then the message is a REPLY_FINAL
+/// - if E==1 then the Data message is an error reply (only valid if F==0)
 /// ```
 #[derive(Debug, Clone, PartialEq)]
 pub struct ReplierInfo {
@@ -404,6 +415,7 @@ pub struct ReplierInfo {
 pub struct ReplyContext {
     pub qid: ZInt,
     pub replier: Option<ReplierInfo>,
+    pub is_error: bool,
 }
 
 impl Header for ReplyContext {
@@ -412,6 +424,8 @@ impl Header for ReplyContext {
         let mut header = zmsg::id::REPLY_CONTEXT;
         if self.is_final() {
             header |= zmsg::flag::F;
+        } else if self.is_error {
+            header |= zmsg::flag::E;
         }
         header
     }
@@ -421,7 +435,20 @@ impl ReplyContext {
     // Note: id replier_id=None flag F is set, meaning it's a REPLY_FINAL
     #[inline(always)]
     pub fn new(qid: ZInt, replier: Option<ReplierInfo>) -> ReplyContext {
-        ReplyContext { qid, replier }
+        ReplyContext {
+            qid,
+            replier,
+            is_error: false,
+        }
+    }
+
+    #[inline(always)]
+    pub fn new_error(qid: ZInt, replier: ReplierInfo) -> ReplyContext {
+        ReplyContext {
+            qid,
+            replier: Some(replier),
+            is_error: true,
+        }
     }
 
     #[inline(always)]
@@ -699,6 +726,10 @@ pub enum Declaration {
     ForgetSubscriber(ForgetSubscriber),
     Queryable(Queryable),
     ForgetQueryable(ForgetQueryable),
+    LivelinessToken(LivelinessToken),
+    ForgetLivelinessToken(ForgetLivelinessToken),
+    LivelinessSubscriber(LivelinessSubscriber),
+    ForgetLivelinessSubscriber(ForgetLivelinessSubscriber),
 }
 
 /// ```text
@@ -909,6 +940,102 @@ impl Header for ForgetQueryable {
     }
 }
 
+/// ```text
+///  7 6 5 4 3 2 1 0
+/// +-+-+-+-+-+-+-+-+
+/// |K|X|X|  TOKEN  |
+/// +---------------+
+/// ~    ResKey     ~ if K==1 then resource key has name
+/// +---------------+
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct LivelinessToken {
+    pub key: ResKey,
+}
+
+impl Header for LivelinessToken {
+    #[inline(always)]
+    fn header(&self) -> u8 {
+        let mut header = zmsg::declaration::id::LIVELINESS_TOKEN;
+        if self.key.is_string() {
+            header |= zmsg::flag::K;
+        }
+        header
+    }
+}
+
+/// ```text
+///  7 6 5 4 3 2 1 0
+/// +-+-+-+-+-+-+-+-+
+/// |K|X|X| F_TOKEN |
+/// +---------------+
+/// ~    ResKey     ~ if K==1 then resource key has name
+/// +---------------+
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ForgetLivelinessToken {
+    pub key: ResKey,
+}
+
+impl Header for ForgetLivelinessToken {
+    #[inline(always)]
+    fn header(&self) -> u8 {
+        let mut header = zmsg::declaration::id::FORGET_LIVELINESS_TOKEN;
+        if self.key.is_string() {
+            header |= zmsg::flag::K;
+        }
+        header
+    }
+}
+
+/// ```text
+///  7 6 5 4 3 2 1 0
+/// +-+-+-+-+-+-+-+-+
+/// |K|X|X| L_SUB   |
+/// +---------------+
+/// ~    ResKey     ~ if K==1 then resource key has name
+/// +---------------+
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct LivelinessSubscriber {
+    pub key: ResKey,
+}
+
+impl Header for LivelinessSubscriber {
+    #[inline(always)]
+    fn header(&self) -> u8 {
+        let mut header = zmsg::declaration::id::LIVELINESS_SUBSCRIBER;
+        if self.key.is_string() {
+            header |= zmsg::flag::K;
+        }
+        header
+    }
+}
+
+/// ```text
+///  7 6 5 4 3 2 1 0
+/// +-+-+-+-+-+-+-+-+
+/// |K|X|X| F_L_SUB |
+/// +---------------+
+/// ~    ResKey     ~ if K==1 then resource key has name
+/// +---------------+
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ForgetLivelinessSubscriber {
+    pub key: ResKey,
+}
+
+impl Header for ForgetLivelinessSubscriber {
+    #[inline(always)]
+    fn header(&self) -> u8 {
+        let mut header = zmsg::declaration::id::FORGET_LIVELINESS_SUBSCRIBER;
+        if self.key.is_string() {
+            header |= zmsg::flag::K;
+        }
+        header
+    }
+}
+
 /// ```text
 ///  7 6 5 4 3 2 1 0
 /// +-+-+-+-+-+-+-+-+
@@ -1418,7 +1545,7 @@ impl fmt::Display for Hello {
 /// +-+-+-+-+-+-+-+-+
 /// |O|S|A|   INIT  |
 /// +-+-+-+-+-------+
-/// ~             |Q~ if O==1
+/// ~           |C|Q~ if O==1
 /// +---------------+
 /// | v_maj | v_min | if A==0 -- Protocol Version VMaj.VMin
 /// +-------+-------+
@@ -1435,6 +1562,7 @@ impl fmt::Display for Hello {
 ///     if A==1 and S==0 then the agreed resolution is the one communicated by the initiator.
 ///
 /// - if Q==1 then the initiator/responder support QoS.
+/// - if C==1 then the initiator requests/the responder accepts the compression of the batches.
 /// ```
 #[derive(Debug, Clone, PartialEq)]
 pub struct InitSyn {
@@ -1443,6 +1571,7 @@ pub struct InitSyn {
     pub pid: PeerId,
     pub sn_resolution: ZInt,
     pub is_qos: bool,
+    pub is_compression: bool,
 }
 
 impl Header for InitSyn {
@@ -1465,11 +1594,14 @@ impl Options for InitSyn {
         if self.is_qos {
             options |= tmsg::init_options::QOS;
         }
+        if self.is_compression {
+            options |= tmsg::init_options::COMPRESSION;
+        }
         options
     }
 
     fn has_options(&self) -> bool {
-        self.is_qos
+        self.is_qos || self.is_compression
     }
 }
 
@@ -1479,6 +1611,7 @@ pub struct InitAck {
     pub pid: PeerId,
     pub sn_resolution: Option<ZInt>,
     pub is_qos: bool,
+    pub is_compression: bool,
     pub cookie: ZSlice,
 }
 
@@ -1503,11 +1636,14 @@ impl Options for InitAck {
         if self.is_qos {
             options |= tmsg::init_options::QOS;
         }
+        if self.is_compression {
+            options |= tmsg::init_options::COMPRESSION;
+        }
         options
     }
 
     fn has_options(&self) -> bool {
-        self.is_qos
+        self.is_qos || self.is_compression
     }
 }
 
@@ -2039,6 +2175,7 @@ impl TransportMessage {
         pid: PeerId,
         sn_resolution: ZInt,
         is_qos: bool,
+        is_compression: bool,
         attachment: Option<Attachment>,
     ) -> TransportMessage {
         TransportMessage {
@@ -2048,6 +2185,7 @@ impl TransportMessage {
                 pid,
                 sn_resolution,
                 is_qos,
+                is_compression,
             }),
             attachment,
             #[cfg(feature = "stats")]
@@ -2060,6 +2198,7 @@ impl TransportMessage {
         pid: PeerId,
         sn_resolution: Option<ZInt>,
         is_qos: bool,
+        is_compression: bool,
         cookie: ZSlice,
         attachment: Option<Attachment>,
     ) -> TransportMessage {
@@ -2069,6 +2208,7 @@ impl TransportMessage {
                 pid,
                 sn_resolution,
                 is_qos,
+                is_compression,
                 cookie,
             }),
             attachment,
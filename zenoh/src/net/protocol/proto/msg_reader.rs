@@ -206,6 +206,7 @@ impl ZBuf {
             SEQ_NUM_RES
         };
         let is_qos = imsg::has_option(options, tmsg::init_options::QOS);
+        let is_compression = imsg::has_option(options, tmsg::init_options::COMPRESSION);
 
         Some(TransportBody::InitSyn(InitSyn {
             version,
@@ -213,6 +214,7 @@ impl ZBuf {
             pid,
             sn_resolution,
             is_qos,
+            is_compression,
         }))
     }
 
@@ -230,6 +232,7 @@ impl ZBuf {
             None
         };
         let is_qos = imsg::has_option(options, tmsg::init_options::QOS);
+        let is_compression = imsg::has_option(options, tmsg::init_options::COMPRESSION);
         let cookie = self.read_zslice_array()?;
 
         Some(TransportBody::InitAck(InitAck {
@@ -237,6 +240,7 @@ impl ZBuf {
             pid,
             sn_resolution,
             is_qos,
+            is_compression,
             cookie,
         }))
     }
@@ -402,8 +406,13 @@ impl ZBuf {
                 id: self.read_peerid()?,
             })
         };
+        let is_error = replier.is_some() && imsg::has_flag(header, zmsg::flag::E);
 
-        Some(ReplyContext { qid, replier })
+        Some(ReplyContext {
+            qid,
+            replier,
+            is_error,
+        })
     }
 
     pub fn read_zenoh_message(&mut self, reliability: Reliability) -> Option<ZenohMessage> {
@@ -662,6 +671,28 @@ impl ZBuf {
                 let key = self.read_reskey(imsg::has_flag(header, zmsg::flag::K))?;
                 Some(Declaration::ForgetQueryable(ForgetQueryable { key }))
             }
+            LIVELINESS_TOKEN => {
+                let key = self.read_reskey(imsg::has_flag(header, zmsg::flag::K))?;
+                Some(Declaration::LivelinessToken(LivelinessToken { key }))
+            }
+            FORGET_LIVELINESS_TOKEN => {
+                let key = self.read_reskey(imsg::has_flag(header, zmsg::flag::K))?;
+                Some(Declaration::ForgetLivelinessToken(ForgetLivelinessToken {
+                    key,
+                }))
+            }
+            LIVELINESS_SUBSCRIBER => {
+                let key = self.read_reskey(imsg::has_flag(header, zmsg::flag::K))?;
+                Some(Declaration::LivelinessSubscriber(LivelinessSubscriber {
+                    key,
+                }))
+            }
+            FORGET_LIVELINESS_SUBSCRIBER => {
+                let key = self.read_reskey(imsg::has_flag(header, zmsg::flag::K))?;
+                Some(Declaration::ForgetLivelinessSubscriber(
+                    ForgetLivelinessSubscriber { key },
+                ))
+            }
             unknown => {
                 log::trace!("Invalid ID for Declaration: {}", unknown);
                 None
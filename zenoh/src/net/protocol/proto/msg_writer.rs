@@ -431,6 +431,16 @@ impl WBuf {
             Declaration::ForgetQueryable(fq) => {
                 self.write(fq.header()) && self.write_reskey(&fq.key)
             }
+            Declaration::LivelinessToken(t) => self.write(t.header()) && self.write_reskey(&t.key),
+            Declaration::ForgetLivelinessToken(ft) => {
+                self.write(ft.header()) && self.write_reskey(&ft.key)
+            }
+            Declaration::LivelinessSubscriber(ls) => {
+                self.write(ls.header()) && self.write_reskey(&ls.key)
+            }
+            Declaration::ForgetLivelinessSubscriber(fls) => {
+                self.write(fls.header()) && self.write_reskey(&fls.key)
+            }
         }
     }
 
@@ -0,0 +1,405 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ADLINK zenoh team, <zenoh@adlink-labs.tech>
+//
+use super::protocol::core::{rname, PeerId};
+use async_std::fs;
+use std::fmt;
+use std::net::IpAddr;
+use std::str::FromStr;
+use zenoh_util::core::{ZError, ZErrorKind, ZResult};
+use zenoh_util::properties::config::*;
+use zenoh_util::{zerror, zerror2};
+
+const ALL: &str = "*";
+const DEFAULT: &str = "default";
+const SUBJECT_USER: &str = "user:";
+const SUBJECT_PEER: &str = "peer:";
+const SUBJECT_INTERFACE: &str = "iface:";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AclPermission {
+    Allow,
+    Deny,
+}
+
+impl FromStr for AclPermission {
+    type Err = ZError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "allow" => Ok(AclPermission::Allow),
+            "deny" => Ok(AclPermission::Deny),
+            _ => {
+                let e = format!("Invalid ACL permission: {}", s);
+                zerror!(ZErrorKind::Other { descr: e })
+            }
+        }
+    }
+}
+
+impl fmt::Display for AclPermission {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AclPermission::Allow => write!(f, "allow"),
+            AclPermission::Deny => write!(f, "deny"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AclAction {
+    /// Declare a publisher or route data
+    Publish,
+    /// Declare a subscriber
+    Subscribe,
+    /// Route a query
+    Query,
+    /// Declare a queryable
+    Queryable,
+    /// Declare a liveliness token
+    Liveliness,
+    /// Write into the admin space of the router, never allowed by default
+    Admin,
+}
+
+impl AclAction {
+    const ALL: [AclAction; 6] = [
+        AclAction::Publish,
+        AclAction::Subscribe,
+        AclAction::Query,
+        AclAction::Queryable,
+        AclAction::Liveliness,
+        AclAction::Admin,
+    ];
+}
+
+impl FromStr for AclAction {
+    type Err = ZError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "publish" => Ok(AclAction::Publish),
+            "subscribe" => Ok(AclAction::Subscribe),
+            "query" => Ok(AclAction::Query),
+            "queryable" => Ok(AclAction::Queryable),
+            "liveliness" => Ok(AclAction::Liveliness),
+            "admin" => Ok(AclAction::Admin),
+            _ => {
+                let e = format!("Invalid ACL action: {}", s);
+                zerror!(ZErrorKind::Other { descr: e })
+            }
+        }
+    }
+}
+
+impl fmt::Display for AclAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AclAction::Publish => write!(f, "publish"),
+            AclAction::Subscribe => write!(f, "subscribe"),
+            AclAction::Query => write!(f, "query"),
+            AclAction::Queryable => write!(f, "queryable"),
+            AclAction::Liveliness => write!(f, "liveliness"),
+            AclAction::Admin => write!(f, "admin"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AclSubject {
+    /// Any remote peer
+    Any,
+    /// The peers authenticated as the given user
+    User(String),
+    /// The peer with the given PeerId
+    Peer(String),
+    /// The peers connected through the given network interface
+    Interface(String),
+}
+
+impl FromStr for AclSubject {
+    type Err = ZError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == ALL {
+            Ok(AclSubject::Any)
+        } else if let Some(user) = s.strip_prefix(SUBJECT_USER) {
+            Ok(AclSubject::User(user.to_string()))
+        } else if let Some(pid) = s.strip_prefix(SUBJECT_PEER) {
+            Ok(AclSubject::Peer(pid.replace('-', "").to_lowercase()))
+        } else if let Some(iface) = s.strip_prefix(SUBJECT_INTERFACE) {
+            Ok(AclSubject::Interface(iface.to_string()))
+        } else {
+            let e = format!("Invalid ACL subject: {}", s);
+            zerror!(ZErrorKind::Other { descr: e })
+        }
+    }
+}
+
+impl fmt::Display for AclSubject {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AclSubject::Any => write!(f, "{}", ALL),
+            AclSubject::User(user) => write!(f, "{}{}", SUBJECT_USER, user),
+            AclSubject::Peer(pid) => write!(f, "{}{}", SUBJECT_PEER, pid),
+            AclSubject::Interface(iface) => write!(f, "{}{}", SUBJECT_INTERFACE, iface),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AclRule {
+    pub permission: AclPermission,
+    pub actions: Vec<AclAction>,
+    pub subject: AclSubject,
+    pub key_expr: String,
+}
+
+impl AclRule {
+    // An allow rule only applies to the keys it fully includes while a deny rule
+    // applies to any key intersecting with it. This way a subscriber or a queryable
+    // on a wildcard key is never allowed on a set of keys partially denied.
+    fn applies(&self, action: AclAction, key: &str) -> bool {
+        self.actions.contains(&action)
+            && match self.permission {
+                AclPermission::Allow => rname::include(&self.key_expr, key),
+                AclPermission::Deny => rname::intersect(&self.key_expr, key),
+            }
+    }
+}
+
+impl FromStr for AclRule {
+    type Err = ZError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<&str> = s.split_whitespace().collect();
+        if fields.len() != 4 {
+            let e = format!("Invalid ACL rule: {}", s);
+            return zerror!(ZErrorKind::Other { descr: e });
+        }
+        let permission = fields[0].parse()?;
+        let actions = if fields[1] == ALL {
+            AclAction::ALL.to_vec()
+        } else {
+            fields[1]
+                .split(',')
+                .map(|a| a.parse())
+                .collect::<ZResult<Vec<AclAction>>>()?
+        };
+        let subject = fields[2].parse()?;
+        let key_expr = fields[3].to_string();
+        Ok(AclRule {
+            permission,
+            actions,
+            subject,
+            key_expr,
+        })
+    }
+}
+
+impl fmt::Display for AclRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let actions: Vec<String> = self.actions.iter().map(|a| a.to_string()).collect();
+        write!(
+            f,
+            "{} {} {} {}",
+            self.permission,
+            actions.join(","),
+            self.subject,
+            self.key_expr
+        )
+    }
+}
+
+/// The access control rules of the router.
+///
+/// The rules are evaluated in order and the first rule applying to the subject,
+/// the action and the key expression decides the permission. The default permission
+/// is used when no rule applies.
+#[derive(Clone, Debug)]
+pub struct AccessControl {
+    default: AclPermission,
+    rules: Vec<AclRule>,
+}
+
+impl AccessControl {
+    pub fn new(default: AclPermission, rules: Vec<AclRule>) -> AccessControl {
+        AccessControl { default, rules }
+    }
+
+    /// Parse a list of rules, one per line. Empty lines and lines starting with `#`
+    /// are ignored. A `default <allow|deny>` line sets the default permission,
+    /// which is `allow` otherwise.
+    ///
+    /// ```text
+    /// default deny
+    /// # <allow|deny> <actions|*> <subject|*> <key expression>
+    /// allow publish,subscribe user:alice /demo/alice/**
+    /// allow liveliness        user:alice /demo/alice/alive
+    /// allow subscribe         iface:lo   /demo/**
+    /// deny  query             peer:a1b2  /**
+    /// allow publish,admin     user:ops   /@/router/**
+    /// ```
+    pub fn parse(content: &str) -> ZResult<AccessControl> {
+        let mut default = AclPermission::Allow;
+        let mut rules = vec![];
+        for line in content.lines().map(|l| l.trim()) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            match line.strip_prefix(DEFAULT) {
+                Some(permission) => default = permission.trim().parse()?,
+                None => rules.push(line.parse()?),
+            }
+        }
+        Ok(AccessControl::new(default, rules))
+    }
+
+    pub async fn from_config(config: &ConfigProperties) -> ZResult<Option<AccessControl>> {
+        match config.get(&ZN_ACL_RULES_KEY) {
+            Some(path) => {
+                let content = fs::read_to_string(path).await.map_err(|e| {
+                    zerror2!(ZErrorKind::Other {
+                        descr: format!("Invalid ACL rules file: {}", e)
+                    })
+                })?;
+                let acl = AccessControl::parse(&content)?;
+                log::debug!("Access control enabled with {} rules", acl.rules.len());
+                Ok(Some(acl))
+            }
+            None => Ok(None),
+        }
+    }
+
+    #[inline]
+    pub fn default_permission(&self) -> AclPermission {
+        self.default
+    }
+
+    #[inline]
+    pub fn rules(&self) -> &[AclRule] {
+        &self.rules
+    }
+
+    /// Select the rules applying to a remote peer.
+    ///
+    /// # Arguments
+    /// * `pid` - The [`PeerId`][PeerId] of the remote peer
+    ///
+    /// * `user` - The user the remote peer has been authenticated as, if any
+    ///
+    /// * `addresses` - The local addresses of the links to the remote peer
+    ///
+    pub(crate) fn face_acl(
+        &self,
+        pid: &PeerId,
+        user: Option<&str>,
+        addresses: &[IpAddr],
+    ) -> FaceAcl {
+        let pid = pid.to_string().to_lowercase();
+        let rules = self
+            .rules
+            .iter()
+            .filter(|rule| match &rule.subject {
+                AclSubject::Any => true,
+                AclSubject::User(u) => user == Some(u.as_str()),
+                AclSubject::Peer(p) => *p == pid,
+                AclSubject::Interface(iface) => {
+                    zenoh_util::net::get_unicast_addresses_of_interface(iface)
+                        .map(|ips| addresses.iter().any(|addr| ips.contains(addr)))
+                        .unwrap_or(false)
+                }
+            })
+            .cloned()
+            .collect();
+        FaceAcl {
+            default: self.default,
+            rules,
+        }
+    }
+}
+
+/// The access control rules applying to a [`Face`](super::face::Face).
+#[derive(Debug)]
+pub(crate) struct FaceAcl {
+    default: AclPermission,
+    rules: Vec<AclRule>,
+}
+
+impl FaceAcl {
+    pub(crate) fn is_allowed(&self, action: AclAction, key: &str) -> bool {
+        let default = match action {
+            AclAction::Admin => AclPermission::Deny,
+            _ => self.default,
+        };
+        self.rules
+            .iter()
+            .find(|rule| rule.applies(action, key))
+            .map_or(default, |rule| rule.permission)
+            == AclPermission::Allow
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn acl_rules() {
+        let acl = AccessControl::parse(
+            r#"
+            default deny
+            allow publish,subscribe user:alice /demo/alice/**
+            deny  *                 *          /demo/**/secret
+            allow subscribe         *          /demo/**
+            "#,
+        )
+        .unwrap();
+        assert_eq!(acl.default_permission(), AclPermission::Deny);
+        assert_eq!(acl.rules().len(), 3);
+
+        let pid = PeerId::new(1, [1u8; PeerId::MAX_SIZE]);
+        let alice = acl.face_acl(&pid, Some("alice"), &[]);
+        assert!(alice.is_allowed(AclAction::Publish, "/demo/alice/a"));
+        assert!(alice.is_allowed(AclAction::Subscribe, "/demo/alice/**"));
+        assert!(!alice.is_allowed(AclAction::Publish, "/demo/bob/a"));
+        assert!(alice.is_allowed(AclAction::Subscribe, "/demo/bob/a"));
+        assert!(!alice.is_allowed(AclAction::Query, "/demo/alice/a"));
+        assert!(!alice.is_allowed(AclAction::Liveliness, "/demo/alice/a"));
+
+        let bob = acl.face_acl(&pid, Some("bob"), &[]);
+        assert!(!bob.is_allowed(AclAction::Publish, "/demo/alice/a"));
+        assert!(bob.is_allowed(AclAction::Subscribe, "/demo/alice/a"));
+        // The subscription intersects with a denied key expression
+        assert!(!bob.is_allowed(AclAction::Subscribe, "/demo/**"));
+        assert!(!bob.is_allowed(AclAction::Subscribe, "/demo/bob/secret"));
+
+        // Writing into the admin space requires an explicit rule
+        let acl = AccessControl::parse("allow admin user:ops /@/router/**").unwrap();
+        let ops = acl.face_acl(&pid, Some("ops"), &[]);
+        assert!(ops.is_allowed(AclAction::Admin, "/@/router/a1b2/config/peers"));
+        assert!(ops.is_allowed(AclAction::Publish, "/@/router/a1b2/config/peers"));
+        let alice = acl.face_acl(&pid, Some("alice"), &[]);
+        assert!(!alice.is_allowed(AclAction::Admin, "/@/router/a1b2/config/peers"));
+        assert!(alice.is_allowed(AclAction::Publish, "/@/router/a1b2/config/peers"));
+
+        let acl = AccessControl::parse("allow liveliness * /demo/*/alive").unwrap();
+        assert!(acl
+            .face_acl(&pid, None, &[])
+            .is_allowed(AclAction::Liveliness, "/demo/a/alive"));
+
+        assert!(AccessControl::parse("allow publish user:alice").is_err());
+        assert!(AccessControl::parse("allow write * /**").is_err());
+        assert!(AccessControl::parse("permit * * /**").is_err());
+    }
+}
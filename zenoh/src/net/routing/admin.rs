@@ -0,0 +1,149 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ADLINK zenoh team, <zenoh@adlink-labs.tech>
+//
+use super::face::FaceState;
+use super::network::Network;
+use super::protocol::core::{whatami, PeerId, SubMode};
+use super::pubsub::compute_data_route;
+use super::resource::{Resource, Route};
+use super::router::Tables;
+use async_std::sync::Arc;
+use petgraph::graph::NodeIndex;
+use serde_json::{json, Value};
+
+fn face_to_json(face: &FaceState) -> Value {
+    json!({
+        "face": face.id,
+        "peer": face.pid.to_string(),
+        "whatami": whatami::to_string(face.whatami),
+    })
+}
+
+fn pids_to_json<'a>(pids: impl Iterator<Item = &'a PeerId>) -> Value {
+    json!(pids.map(|pid| pid.to_string()).collect::<Vec<String>>())
+}
+
+fn resource_to_json(res: &Resource) -> Value {
+    let mut subscribers = vec![];
+    let mut queryables = vec![];
+    for ctx in res.session_ctxs.values() {
+        if let Some(sub_info) = &ctx.subs {
+            let mut sub = face_to_json(&ctx.face);
+            sub["mode"] = json!(match sub_info.mode {
+                SubMode::Push => "push",
+                SubMode::Pull => "pull",
+            });
+            subscribers.push(sub);
+        }
+        if let Some(kind) = ctx.qabl {
+            let mut qabl = face_to_json(&ctx.face);
+            qabl["kind"] = json!(kind);
+            queryables.push(qabl);
+        }
+    }
+    let mut json = json!({
+        "name": res.name(),
+        "subscribers": subscribers,
+        "queryables": queryables,
+    });
+    if let Some(ctx) = res.context.as_ref() {
+        json["router_subs"] = pids_to_json(ctx.router_subs.iter());
+        json["peer_subs"] = pids_to_json(ctx.peer_subs.iter());
+        json["router_qabls"] = pids_to_json(ctx.router_qabls.keys());
+        json["peer_qabls"] = pids_to_json(ctx.peer_qabls.keys());
+    }
+    json
+}
+
+fn resources_to_json(from: &Arc<Resource>, resources: &mut Vec<Value>) {
+    for child in from.childs.values() {
+        if child.context.is_some() || !child.session_ctxs.is_empty() {
+            resources.push(resource_to_json(child));
+        }
+        resources_to_json(child, resources);
+    }
+}
+
+/// The resources declared in the routing tables along with their subscribers
+/// and queryables.
+pub fn resources(tables: &Tables) -> Value {
+    let mut resources = vec![];
+    resources_to_json(&tables.root_res, &mut resources);
+    json!(resources)
+}
+
+/// The number of resources matching a key expression on which a local
+/// session declared a queryable.
+pub fn local_queryables_count(tables: &Tables, key_expr: &str) -> usize {
+    Resource::get_matches(tables, key_expr)
+        .iter()
+        .filter_map(|res| res.upgrade())
+        .filter(|res| res.session_ctxs.values().any(|ctx| ctx.qabl.is_some()))
+        .count()
+}
+
+fn route_to_json(route: &Route) -> Value {
+    let faces: Vec<Value> = route
+        .values()
+        .map(|(face, reskey, routing_context)| {
+            let mut json = face_to_json(face);
+            json["key"] = json!(reskey.to_string());
+            if let Some(rc) = routing_context {
+                json["tree_id"] = json!(rc.tree_id);
+            }
+            json
+        })
+        .collect();
+    json!(faces)
+}
+
+fn tree_routes_to_json(
+    tables: &Tables,
+    net: &Network,
+    key_expr: &str,
+    source_type: whatami::Type,
+) -> Value {
+    let routes: Vec<Value> = net
+        .graph
+        .node_indices()
+        .map(|idx: NodeIndex| {
+            let route = compute_data_route(
+                tables,
+                &tables.root_res,
+                key_expr,
+                Some(idx.index()),
+                source_type,
+            );
+            json!({
+                "tree": net.graph[idx].pid.to_string(),
+                "route": route_to_json(&route),
+            })
+        })
+        .collect();
+    json!(routes)
+}
+
+/// The data routes computed for a key expression, one per tree of the
+/// routers and peers networks.
+pub fn data_routes(tables: &Tables, key_expr: &str) -> Value {
+    let mut json = json!({ "key_expr": key_expr });
+    if let Some(net) = tables.routers_net.as_ref() {
+        json["routers_data_routes"] = tree_routes_to_json(tables, net, key_expr, whatami::ROUTER);
+    }
+    if let Some(net) = tables.peers_net.as_ref() {
+        json["peers_data_routes"] = tree_routes_to_json(tables, net, key_expr, whatami::PEER);
+    }
+    let route = compute_data_route(tables, &tables.root_res, key_expr, None, whatami::CLIENT);
+    json["client_data_route"] = route_to_json(&route);
+    json
+}
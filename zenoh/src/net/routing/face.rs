@@ -11,18 +11,20 @@
 // Contributors:
 //   ADLINK zenoh team, <zenoh@adlink-labs.tech>
 //
+use super::acl::{AclAction, FaceAcl};
 use super::protocol::core::{
-    whatami, Channel, CongestionControl, PeerId, QueryConsolidation, QueryTarget, ResKey, SubInfo,
-    WhatAmI, ZInt,
+    rname, whatami, Channel, CongestionControl, PeerId, QueryConsolidation, QueryTarget, ResKey,
+    SubInfo, WhatAmI, ZInt,
 };
 use super::protocol::io::ZBuf;
-use super::protocol::proto::{DataInfo, RoutingContext};
+use super::protocol::proto::{Attachment, DataInfo, RoutingContext};
 use super::router::*;
-use super::transport::Primitives;
+use super::transport::{Primitives, TransportMulticast};
 use async_std::sync::Arc;
 use std::collections::{HashMap, HashSet};
 use std::fmt;
 use std::sync::RwLock;
+use std::time::Duration;
 
 pub struct FaceState {
     pub(super) id: usize,
@@ -32,12 +34,19 @@ pub struct FaceState {
     pub(super) link_id: usize,
     pub(super) local_mappings: HashMap<ZInt, Arc<Resource>>,
     pub(super) remote_mappings: HashMap<ZInt, Arc<Resource>>,
+    pub(super) remote_pubs: HashSet<Arc<Resource>>,
     pub(super) local_subs: HashSet<Arc<Resource>>,
     pub(super) remote_subs: HashSet<Arc<Resource>>,
     pub(super) local_qabls: HashMap<Arc<Resource>, ZInt>,
     pub(super) remote_qabls: HashSet<Arc<Resource>>,
+    pub(super) local_tokens: HashSet<Arc<Resource>>,
+    pub(super) remote_tokens: HashSet<Arc<Resource>>,
+    pub(super) local_token_subs: HashSet<String>,
+    pub(super) remote_token_subs: HashSet<String>,
     pub(super) next_qid: ZInt,
     pub(super) pending_queries: HashMap<ZInt, Arc<Query>>,
+    pub(super) mcast_group: Option<TransportMulticast>,
+    pub(super) acl: Option<FaceAcl>,
 }
 
 impl FaceState {
@@ -47,6 +56,8 @@ impl FaceState {
         whatami: WhatAmI,
         primitives: Arc<dyn Primitives + Send + Sync>,
         link_id: usize,
+        mcast_group: Option<TransportMulticast>,
+        acl: Option<FaceAcl>,
     ) -> Arc<FaceState> {
         Arc::new(FaceState {
             id,
@@ -56,12 +67,19 @@ impl FaceState {
             link_id,
             local_mappings: HashMap::new(),
             remote_mappings: HashMap::new(),
+            remote_pubs: HashSet::new(),
             local_subs: HashSet::new(),
             remote_subs: HashSet::new(),
             local_qabls: HashMap::new(),
             remote_qabls: HashSet::new(),
+            local_tokens: HashSet::new(),
+            remote_tokens: HashSet::new(),
+            local_token_subs: HashSet::new(),
+            remote_token_subs: HashSet::new(),
             next_qid: 0,
             pending_queries: HashMap::new(),
+            mcast_group,
+            acl,
         })
     }
 
@@ -74,6 +92,54 @@ impl FaceState {
         }
     }
 
+    // Check the access control rules of the face, if any, for the given action on the
+    // given resource
+    pub(super) fn is_allowed(&self, action: AclAction, prefix: &Resource, suffix: &str) -> bool {
+        match self.acl.as_ref() {
+            Some(acl) => {
+                let key = [&prefix.name(), suffix].concat();
+                let allowed = acl.is_allowed(action, &key);
+                if !allowed {
+                    log::warn!("{} denied {} on {}", self, action, key);
+                }
+                allowed
+            }
+            None => true,
+        }
+    }
+
+    // Writes into the config of the router alter its state. They need to be explicitly
+    // allowed by the access control rules, including for the local sessions (e.g. the
+    // one of the REST plugin) which are subject to the rules of the router's own pid
+    pub(super) fn is_admin_allowed(
+        &self,
+        tables: &Tables,
+        prefix: &Resource,
+        suffix: &str,
+    ) -> bool {
+        if !is_admin_key(prefix, suffix) {
+            return true;
+        }
+        let key = [&prefix.name(), suffix].concat();
+        if !rname::intersect(&key, &format!("/@/router/{}/config/**", tables.pid)) {
+            return true;
+        }
+        let allowed = if self.pid == tables.pid {
+            tables.acl.as_ref().is_some_and(|acl| {
+                acl.face_acl(&tables.pid, None, &[])
+                    .is_allowed(AclAction::Admin, &key)
+            })
+        } else {
+            self.acl
+                .as_ref()
+                .is_some_and(|acl| acl.is_allowed(AclAction::Admin, &key))
+        };
+        if !allowed {
+            log::warn!("{} denied {} on {}", self, AclAction::Admin, key);
+        }
+        allowed
+    }
+
     pub(super) fn get_next_local_id(&self) -> ZInt {
         let mut id = 1;
         while self.local_mappings.get(&id).is_some() || self.remote_mappings.get(&id).is_some() {
@@ -83,6 +149,22 @@ impl FaceState {
     }
 }
 
+// Check if a key expression targets the admin space without building its full name
+fn is_admin_key(prefix: &Resource, suffix: &str) -> bool {
+    let mut top = prefix;
+    while let Some(parent) = top.parent.as_ref() {
+        if parent.parent.is_none() {
+            break;
+        }
+        top = parent;
+    }
+    if top.parent.is_none() {
+        suffix.starts_with(rname::ADMIN_PREFIX)
+    } else {
+        top.suffix == rname::ADMIN_PREFIX[..rname::ADMIN_PREFIX.len() - 1]
+    }
+}
+
 impl fmt::Display for FaceState {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "Face{{{}, {}}}", self.id, self.pid)
@@ -95,14 +177,74 @@ pub struct Face {
     pub(crate) state: Arc<FaceState>,
 }
 
+impl Face {
+    fn is_allowed(&self, tables: &Tables, action: AclAction, reskey: &ResKey) -> bool {
+        if self.state.acl.is_none() {
+            return true;
+        }
+        let (prefixid, suffix) = reskey.into();
+        match tables.get_mapping(&self.state, &prefixid) {
+            Some(prefix) => self.state.is_allowed(action, prefix, suffix),
+            // The key cannot be resolved so it cannot be checked against the rules
+            None => {
+                log::warn!(
+                    "{} denied {} on unknown resource id {}",
+                    self.state,
+                    action,
+                    prefixid
+                );
+                false
+            }
+        }
+    }
+
+    /// Routes a query of a local session whose pending state is dropped after `timeout`.
+    /// The queries received from remote faces carry no timeout and the router default
+    /// applies to them.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn send_query_with_timeout(
+        &self,
+        reskey: &ResKey,
+        predicate: &str,
+        qid: ZInt,
+        target: QueryTarget,
+        consolidation: QueryConsolidation,
+        attachment: Option<Attachment>,
+        timeout: Duration,
+    ) {
+        let (prefixid, suffix) = reskey.into();
+        route_query(
+            &self.tables,
+            &self.state,
+            prefixid,
+            suffix,
+            predicate,
+            qid,
+            target,
+            consolidation,
+            None,
+            attachment,
+            timeout,
+        );
+    }
+}
+
 impl Primitives for Face {
     fn decl_resource(&self, rid: ZInt, reskey: &ResKey) {
+        // Each remote peer of a multicast group would declare its own resource ids
+        if self.state.mcast_group.is_some() {
+            log::warn!("{} ignored resource declaration {}", self.state, rid);
+            return;
+        }
         let (prefixid, suffix) = reskey.into();
         let mut tables = zwrite!(self.tables);
         declare_resource(&mut tables, &mut self.state.clone(), rid, prefixid, suffix);
     }
 
     fn forget_resource(&self, rid: ZInt) {
+        if self.state.mcast_group.is_some() {
+            return;
+        }
         let mut tables = zwrite!(self.tables);
         undeclare_resource(&mut tables, &mut self.state.clone(), rid);
     }
@@ -115,6 +257,9 @@ impl Primitives for Face {
     ) {
         let (prefixid, suffix) = reskey.into();
         let mut tables = zwrite!(self.tables);
+        if !self.is_allowed(&tables, AclAction::Subscribe, reskey) {
+            return;
+        }
         match (tables.whatami, self.state.whatami) {
             (whatami::ROUTER, whatami::ROUTER) => match routing_context {
                 Some(routing_context) => {
@@ -268,13 +413,33 @@ impl Primitives for Face {
         }
     }
 
-    fn decl_publisher(&self, _reskey: &ResKey, _routing_context: Option<RoutingContext>) {}
+    fn decl_publisher(&self, reskey: &ResKey, _routing_context: Option<RoutingContext>) {
+        let (prefixid, suffix) = reskey.into();
+        let mut tables = zwrite!(self.tables);
+        if !self.is_allowed(&tables, AclAction::Publish, reskey) {
+            return;
+        }
+        declare_client_publication(&mut tables, &mut self.state.clone(), prefixid, suffix);
+    }
 
-    fn forget_publisher(&self, _reskey: &ResKey, _routing_context: Option<RoutingContext>) {}
+    fn forget_publisher(&self, reskey: &ResKey, _routing_context: Option<RoutingContext>) {
+        let (prefixid, suffix) = reskey.into();
+        let mut tables = zwrite!(self.tables);
+        forget_client_publication(&mut tables, &mut self.state.clone(), prefixid, suffix);
+    }
 
     fn decl_queryable(&self, reskey: &ResKey, kind: ZInt, routing_context: Option<RoutingContext>) {
         let (prefixid, suffix) = reskey.into();
         let mut tables = zwrite!(self.tables);
+        if !self.is_allowed(&tables, AclAction::Queryable, reskey) {
+            return;
+        }
+        // Queries are not routed over multicast groups where each remote peer would
+        // send its own final reply
+        if self.state.mcast_group.is_some() {
+            log::debug!("{} ignored queryable declaration", self.state);
+            return;
+        }
         match (tables.whatami, self.state.whatami) {
             (whatami::ROUTER, whatami::ROUTER) => match routing_context {
                 Some(routing_context) => {
@@ -428,6 +593,171 @@ impl Primitives for Face {
         }
     }
 
+    fn decl_liveliness_token(&self, reskey: &ResKey, routing_context: Option<RoutingContext>) {
+        let (prefixid, suffix) = reskey.into();
+        let mut tables = zwrite!(self.tables);
+        if !self.is_allowed(&tables, AclAction::Liveliness, reskey) {
+            return;
+        }
+        match (tables.whatami, self.state.whatami) {
+            (whatami::ROUTER, whatami::ROUTER) => match routing_context {
+                Some(routing_context) => {
+                    let router = match tables
+                        .routers_net
+                        .as_ref()
+                        .unwrap()
+                        .get_link(self.state.link_id)
+                        .get_pid(&routing_context.tree_id)
+                    {
+                        Some(router) => *router,
+                        None => {
+                            log::error!(
+                                "Received router liveliness token with unknown routing context id {}",
+                                routing_context.tree_id
+                            );
+                            return;
+                        }
+                    };
+
+                    declare_router_token(
+                        &mut tables,
+                        &mut self.state.clone(),
+                        prefixid,
+                        suffix,
+                        router,
+                    )
+                }
+
+                None => {
+                    log::error!("Received router liveliness token with no routing context");
+                }
+            },
+            (whatami::ROUTER, whatami::PEER)
+            | (whatami::PEER, whatami::ROUTER)
+            | (whatami::PEER, whatami::PEER) => match routing_context {
+                Some(routing_context) => {
+                    let peer = match tables
+                        .peers_net
+                        .as_ref()
+                        .unwrap()
+                        .get_link(self.state.link_id)
+                        .get_pid(&routing_context.tree_id)
+                    {
+                        Some(peer) => *peer,
+                        None => {
+                            log::error!(
+                                "Received peer liveliness token with unknown routing context id {}",
+                                routing_context.tree_id
+                            );
+                            return;
+                        }
+                    };
+
+                    declare_peer_token(&mut tables, &mut self.state.clone(), prefixid, suffix, peer)
+                }
+
+                None => {
+                    log::error!("Received peer liveliness token with no routing context");
+                }
+            },
+            _ => declare_client_token(&mut tables, &mut self.state.clone(), prefixid, suffix),
+        }
+    }
+
+    fn forget_liveliness_token(&self, reskey: &ResKey, routing_context: Option<RoutingContext>) {
+        let (prefixid, suffix) = reskey.into();
+        let mut tables = zwrite!(self.tables);
+        match (tables.whatami, self.state.whatami) {
+            (whatami::ROUTER, whatami::ROUTER) => match routing_context {
+                Some(routing_context) => {
+                    let router = match tables
+                        .routers_net
+                        .as_ref()
+                        .unwrap()
+                        .get_link(self.state.link_id)
+                        .get_pid(&routing_context.tree_id)
+                    {
+                        Some(router) => *router,
+                        None => {
+                            log::error!(
+                                "Received router forget liveliness token with unknown routing context id {}",
+                                routing_context.tree_id
+                            );
+                            return;
+                        }
+                    };
+
+                    forget_router_token(
+                        &mut tables,
+                        &mut self.state.clone(),
+                        prefixid,
+                        suffix,
+                        &router,
+                    )
+                }
+
+                None => {
+                    log::error!("Received router forget liveliness token with no routing context");
+                }
+            },
+            (whatami::ROUTER, whatami::PEER)
+            | (whatami::PEER, whatami::ROUTER)
+            | (whatami::PEER, whatami::PEER) => match routing_context {
+                Some(routing_context) => {
+                    let peer = match tables
+                        .peers_net
+                        .as_ref()
+                        .unwrap()
+                        .get_link(self.state.link_id)
+                        .get_pid(&routing_context.tree_id)
+                    {
+                        Some(peer) => *peer,
+                        None => {
+                            log::error!(
+                                "Received peer forget liveliness token with unknown routing context id {}",
+                                routing_context.tree_id
+                            );
+                            return;
+                        }
+                    };
+
+                    forget_peer_token(
+                        &mut tables,
+                        &mut self.state.clone(),
+                        prefixid,
+                        suffix,
+                        &peer,
+                    )
+                }
+
+                None => {
+                    log::error!("Received peer forget liveliness token with no routing context");
+                }
+            },
+            _ => forget_client_token(&mut tables, &mut self.state.clone(), prefixid, suffix),
+        }
+    }
+
+    fn decl_liveliness_subscriber(
+        &self,
+        reskey: &ResKey,
+        _routing_context: Option<RoutingContext>,
+    ) {
+        let (prefixid, suffix) = reskey.into();
+        let mut tables = zwrite!(self.tables);
+        declare_client_token_subscription(&mut tables, &mut self.state.clone(), prefixid, suffix);
+    }
+
+    fn forget_liveliness_subscriber(
+        &self,
+        reskey: &ResKey,
+        _routing_context: Option<RoutingContext>,
+    ) {
+        let (prefixid, suffix) = reskey.into();
+        let mut tables = zwrite!(self.tables);
+        forget_client_token_subscription(&mut tables, &mut self.state.clone(), prefixid, suffix);
+    }
+
     fn send_data(
         &self,
         reskey: &ResKey,
@@ -436,6 +766,7 @@ impl Primitives for Face {
         congestion_control: CongestionControl,
         data_info: Option<DataInfo>,
         routing_context: Option<RoutingContext>,
+        attachment: Option<Attachment>,
     ) {
         let (prefixid, suffix) = reskey.into();
         full_reentrant_route_data(
@@ -448,6 +779,7 @@ impl Primitives for Face {
             data_info,
             payload,
             routing_context,
+            attachment,
         );
     }
 
@@ -459,11 +791,12 @@ impl Primitives for Face {
         target: QueryTarget,
         consolidation: QueryConsolidation,
         routing_context: Option<RoutingContext>,
+        attachment: Option<Attachment>,
     ) {
         let (prefixid, suffix) = reskey.into();
-        let mut tables = zwrite!(self.tables);
+        let timeout = zread!(self.tables).queries_default_timeout;
         route_query(
-            &mut tables,
+            &self.tables,
             &self.state,
             prefixid,
             suffix,
@@ -472,6 +805,8 @@ impl Primitives for Face {
             target,
             consolidation,
             routing_context,
+            attachment,
+            timeout,
         );
     }
 
@@ -480,9 +815,11 @@ impl Primitives for Face {
         qid: ZInt,
         replier_kind: ZInt,
         replier_id: PeerId,
+        is_error: bool,
         reskey: ResKey,
         info: Option<DataInfo>,
         payload: ZBuf,
+        attachment: Option<Attachment>,
     ) {
         let mut tables = zwrite!(self.tables);
         route_send_reply_data(
@@ -491,9 +828,11 @@ impl Primitives for Face {
             qid,
             replier_kind,
             replier_id,
+            is_error,
             reskey,
             info,
             payload,
+            attachment,
         );
     }
 
@@ -522,6 +861,8 @@ impl Primitives for Face {
         );
     }
 
+    fn send_matching_status(&self, _reskey: &ResKey, _matching: bool) {}
+
     fn send_close(&self) {
         zwrite!(self.tables).close_face(&Arc::downgrade(&self.state));
     }
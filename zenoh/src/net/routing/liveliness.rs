@@ -0,0 +1,787 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ADLINK zenoh team, <zenoh@adlink-labs.tech>
+//
+use async_std::sync::Arc;
+use petgraph::graph::NodeIndex;
+use std::collections::{HashMap, HashSet};
+use zenoh_util::sync::get_mut_unchecked;
+
+use super::protocol::core::{queryable, rname, whatami, PeerId, ResKey, ZInt};
+use super::protocol::io::ZBuf;
+use super::protocol::proto::RoutingContext;
+
+use super::face::FaceState;
+use super::resource::{
+    propagate_sourced, send_sourced_to_net_childs, Resource, Route, SessionContext,
+};
+use super::router::Tables;
+
+// Liveliness tokens follow the same propagation scheme as subscriptions:
+// routers and peers exchange them along the link-state trees (sourced tokens).
+// Clients declare liveliness subscribers and are only told about the tokens
+// matching them, while a client runtime declares all its tokens and the
+// liveliness subscribers of its sessions to the routers or peers it is connected to.
+
+/// The prefix of the queries answered by the routers and peers with the liveliness tokens
+/// alive matching the rest of the queried resource name
+/// (e.g. `/@/liveliness/group/*` for the tokens matching `/group/*`).
+pub const LIVELINESS_QUERY_PREFIX: &str = "/@/liveliness";
+
+#[inline]
+fn remote_router_tokens(tables: &Tables, res: &Arc<Resource>) -> bool {
+    res.context.is_some()
+        && res
+            .context()
+            .router_tokens
+            .iter()
+            .any(|peer| peer != &tables.pid)
+}
+
+#[inline]
+fn remote_peer_tokens(tables: &Tables, res: &Arc<Resource>) -> bool {
+    res.context.is_some()
+        && res
+            .context()
+            .peer_tokens
+            .iter()
+            .any(|peer| peer != &tables.pid)
+}
+
+#[inline]
+fn client_tokens(res: &Arc<Resource>) -> Vec<Arc<FaceState>> {
+    res.session_ctxs
+        .values()
+        .filter_map(|ctx| {
+            if ctx.token {
+                Some(ctx.face.clone())
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Returns true if the token on `res` is held by someone else than `face`.
+#[inline]
+fn token_alive_for(tables: &Tables, res: &Arc<Resource>, face: &Arc<FaceState>) -> bool {
+    remote_router_tokens(tables, res)
+        || remote_peer_tokens(tables, res)
+        || res
+            .session_ctxs
+            .values()
+            .any(|ctx| ctx.token && ctx.face.id != face.id)
+}
+
+#[inline]
+fn is_simple_token_dst(tables: &Tables, face: &Arc<FaceState>) -> bool {
+    tables.whatami == whatami::CLIENT || face.whatami == whatami::CLIENT
+}
+
+/// Returns true if `face` wants to be told about the token on `res`: the routers and peers
+/// a client runtime is connected to want all its tokens, the clients only the ones
+/// matching their liveliness subscribers.
+#[inline]
+fn wants_token(face: &Arc<FaceState>, res: &Arc<Resource>) -> bool {
+    face.whatami != whatami::CLIENT
+        || face
+            .remote_token_subs
+            .iter()
+            .any(|expr| rname::intersect(expr, &res.name()))
+}
+
+/// Returns all the tokens known by the tables.
+fn all_tokens(tables: &Tables) -> Vec<Arc<Resource>> {
+    let mut tokens = tables
+        .router_tokens
+        .iter()
+        .chain(tables.peer_tokens.iter())
+        .cloned()
+        .collect::<Vec<Arc<Resource>>>();
+    for face in tables.faces.values() {
+        tokens.extend(face.remote_tokens.iter().cloned());
+    }
+    tokens
+}
+
+fn propagate_simple_token(tables: &mut Tables, res: &Arc<Resource>) {
+    let dst_faces = tables
+        .faces
+        .values()
+        .filter(|face| {
+            is_simple_token_dst(tables, face)
+                && !face.local_tokens.contains(res)
+                && wants_token(face, res)
+                && token_alive_for(tables, res, face)
+        })
+        .cloned()
+        .collect::<Vec<Arc<FaceState>>>();
+    for mut dst_face in dst_faces {
+        get_mut_unchecked(&mut dst_face)
+            .local_tokens
+            .insert(res.clone());
+        let reskey = Resource::decl_key(res, &mut dst_face);
+        dst_face.primitives.decl_liveliness_token(&reskey, None);
+    }
+}
+
+fn propagate_forget_simple_token(tables: &mut Tables, res: &Arc<Resource>) {
+    let dst_faces = tables
+        .faces
+        .values()
+        .filter(|face| face.local_tokens.contains(res) && !token_alive_for(tables, res, face))
+        .cloned()
+        .collect::<Vec<Arc<FaceState>>>();
+    for mut dst_face in dst_faces {
+        let reskey = Resource::get_best_key(res, "", dst_face.id);
+        dst_face.primitives.forget_liveliness_token(&reskey, None);
+
+        get_mut_unchecked(&mut dst_face).local_tokens.remove(res);
+    }
+}
+
+fn propagate_sourced_token(
+    tables: &Tables,
+    res: &Arc<Resource>,
+    src_face: Option<&Arc<FaceState>>,
+    source: &PeerId,
+    net_type: whatami::Type,
+) {
+    propagate_sourced(
+        tables,
+        res,
+        src_face,
+        source,
+        net_type,
+        "token",
+        &|face, reskey, routing_context| {
+            log::debug!("Send liveliness token {} on {}", res.name(), face);
+            face.primitives
+                .decl_liveliness_token(reskey, routing_context);
+        },
+    );
+}
+
+fn register_router_token(
+    tables: &mut Tables,
+    face: &mut Arc<FaceState>,
+    res: &mut Arc<Resource>,
+    router: PeerId,
+) {
+    if !res.context().router_tokens.contains(&router) {
+        // Register router liveliness token
+        {
+            log::debug!(
+                "Register router liveliness token {} (router: {})",
+                res.name(),
+                router
+            );
+            get_mut_unchecked(res)
+                .context_mut()
+                .router_tokens
+                .insert(router);
+            tables.router_tokens.insert(res.clone());
+        }
+
+        // Propagate liveliness token to routers
+        propagate_sourced_token(tables, res, Some(face), &router, whatami::ROUTER);
+
+        // Propagate liveliness token to peers
+        if face.whatami != whatami::PEER {
+            register_peer_token(tables, face, res, tables.pid)
+        }
+    }
+}
+
+pub fn declare_router_token(
+    tables: &mut Tables,
+    face: &mut Arc<FaceState>,
+    prefixid: ZInt,
+    suffix: &str,
+    router: PeerId,
+) {
+    match tables.get_mapping(face, &prefixid).cloned() {
+        Some(mut prefix) => {
+            let mut res = Resource::make_resource(tables, &mut prefix, suffix);
+            Resource::match_resource(tables, &mut res);
+            register_router_token(tables, face, &mut res, router);
+
+            // Propagate liveliness token to clients
+            propagate_simple_token(tables, &res);
+        }
+        None => log::error!(
+            "Declare router liveliness token for unknown rid {}!",
+            prefixid
+        ),
+    }
+}
+
+fn register_peer_token(
+    tables: &mut Tables,
+    face: &mut Arc<FaceState>,
+    res: &mut Arc<Resource>,
+    peer: PeerId,
+) {
+    if !res.context().peer_tokens.contains(&peer) {
+        // Register peer liveliness token
+        {
+            log::debug!(
+                "Register peer liveliness token {} (peer: {})",
+                res.name(),
+                peer
+            );
+            get_mut_unchecked(res)
+                .context_mut()
+                .peer_tokens
+                .insert(peer);
+            tables.peer_tokens.insert(res.clone());
+        }
+
+        // Propagate liveliness token to peers
+        propagate_sourced_token(tables, res, Some(face), &peer, whatami::PEER);
+    }
+}
+
+pub fn declare_peer_token(
+    tables: &mut Tables,
+    face: &mut Arc<FaceState>,
+    prefixid: ZInt,
+    suffix: &str,
+    peer: PeerId,
+) {
+    match tables.get_mapping(face, &prefixid).cloned() {
+        Some(mut prefix) => {
+            let mut res = Resource::make_resource(tables, &mut prefix, suffix);
+            Resource::match_resource(tables, &mut res);
+            register_peer_token(tables, face, &mut res, peer);
+
+            if tables.whatami == whatami::ROUTER {
+                register_router_token(tables, face, &mut res, tables.pid);
+            }
+
+            // Propagate liveliness token to clients
+            propagate_simple_token(tables, &res);
+        }
+        None => log::error!(
+            "Declare peer liveliness token for unknown rid {}!",
+            prefixid
+        ),
+    }
+}
+
+fn register_client_token(_tables: &mut Tables, face: &mut Arc<FaceState>, res: &mut Arc<Resource>) {
+    // Register liveliness token
+    {
+        let res = get_mut_unchecked(res);
+        log::debug!("Register liveliness token {} for {}", res.name(), face);
+        match res.session_ctxs.get_mut(&face.id) {
+            Some(ctx) => get_mut_unchecked(ctx).token = true,
+            None => {
+                res.session_ctxs.insert(
+                    face.id,
+                    Arc::new(SessionContext {
+                        face: face.clone(),
+                        local_rid: None,
+                        remote_rid: None,
+                        subs: None,
+                        qabl: None,
+                        token: true,
+                        pub_matching: None,
+                        last_values: HashMap::new(),
+                    }),
+                );
+            }
+        }
+    }
+    get_mut_unchecked(face).remote_tokens.insert(res.clone());
+}
+
+pub fn declare_client_token(
+    tables: &mut Tables,
+    face: &mut Arc<FaceState>,
+    prefixid: ZInt,
+    suffix: &str,
+) {
+    match tables.get_mapping(face, &prefixid).cloned() {
+        Some(mut prefix) => {
+            let mut res = Resource::make_resource(tables, &mut prefix, suffix);
+            Resource::match_resource(tables, &mut res);
+
+            register_client_token(tables, face, &mut res);
+            match tables.whatami {
+                whatami::ROUTER => {
+                    register_router_token(tables, face, &mut res, tables.pid);
+                }
+                whatami::PEER => {
+                    register_peer_token(tables, face, &mut res, tables.pid);
+                }
+                _ => (),
+            }
+
+            // Propagate liveliness token to clients
+            propagate_simple_token(tables, &res);
+        }
+        None => log::error!("Declare liveliness token for unknown rid {}!", prefixid),
+    }
+}
+
+fn propagate_forget_sourced_token(
+    tables: &Tables,
+    res: &Arc<Resource>,
+    src_face: Option<&Arc<FaceState>>,
+    source: &PeerId,
+    net_type: whatami::Type,
+) {
+    propagate_sourced(
+        tables,
+        res,
+        src_face,
+        source,
+        net_type,
+        "forget token",
+        &|face, reskey, routing_context| {
+            log::debug!("Send forget liveliness token {} on {}", res.name(), face);
+            face.primitives
+                .forget_liveliness_token(reskey, routing_context);
+        },
+    );
+}
+
+fn unregister_router_token(tables: &mut Tables, res: &mut Arc<Resource>, router: &PeerId) {
+    log::debug!(
+        "Unregister router liveliness token {} (router: {})",
+        res.name(),
+        router
+    );
+    get_mut_unchecked(res)
+        .context_mut()
+        .router_tokens
+        .retain(|token| token != router);
+
+    if res.context().router_tokens.is_empty() {
+        tables
+            .router_tokens
+            .retain(|token| !Arc::ptr_eq(token, res));
+
+        undeclare_peer_token(tables, None, res, &tables.pid.clone());
+    }
+}
+
+fn undeclare_router_token(
+    tables: &mut Tables,
+    face: Option<&Arc<FaceState>>,
+    res: &mut Arc<Resource>,
+    router: &PeerId,
+) {
+    if res.context().router_tokens.contains(router) {
+        unregister_router_token(tables, res, router);
+        propagate_forget_sourced_token(tables, res, face, router, whatami::ROUTER);
+    }
+}
+
+pub fn forget_router_token(
+    tables: &mut Tables,
+    face: &mut Arc<FaceState>,
+    prefixid: ZInt,
+    suffix: &str,
+    router: &PeerId,
+) {
+    match tables.get_mapping(face, &prefixid) {
+        Some(prefix) => match Resource::get_resource(prefix, suffix) {
+            Some(mut res) => {
+                undeclare_router_token(tables, Some(face), &mut res, router);
+
+                propagate_forget_simple_token(tables, &res);
+                Resource::clean(&mut res)
+            }
+            None => log::error!("Undeclare unknown router liveliness token!"),
+        },
+        None => log::error!("Undeclare router liveliness token with unknown prefix!"),
+    }
+}
+
+fn unregister_peer_token(tables: &mut Tables, res: &mut Arc<Resource>, peer: &PeerId) {
+    log::debug!(
+        "Unregister peer liveliness token {} (peer: {})",
+        res.name(),
+        peer
+    );
+    get_mut_unchecked(res)
+        .context_mut()
+        .peer_tokens
+        .retain(|token| token != peer);
+
+    if res.context().peer_tokens.is_empty() {
+        tables.peer_tokens.retain(|token| !Arc::ptr_eq(token, res));
+    }
+}
+
+fn undeclare_peer_token(
+    tables: &mut Tables,
+    face: Option<&Arc<FaceState>>,
+    res: &mut Arc<Resource>,
+    peer: &PeerId,
+) {
+    if res.context().peer_tokens.contains(peer) {
+        unregister_peer_token(tables, res, peer);
+        propagate_forget_sourced_token(tables, res, face, peer, whatami::PEER);
+    }
+}
+
+pub fn forget_peer_token(
+    tables: &mut Tables,
+    face: &mut Arc<FaceState>,
+    prefixid: ZInt,
+    suffix: &str,
+    peer: &PeerId,
+) {
+    match tables.get_mapping(face, &prefixid) {
+        Some(prefix) => match Resource::get_resource(prefix, suffix) {
+            Some(mut res) => {
+                undeclare_peer_token(tables, Some(face), &mut res, peer);
+
+                if tables.whatami == whatami::ROUTER {
+                    let client_tokens = res.session_ctxs.values().any(|ctx| ctx.token);
+                    let peer_tokens = remote_peer_tokens(tables, &res);
+                    if !client_tokens && !peer_tokens {
+                        undeclare_router_token(tables, None, &mut res, &tables.pid.clone());
+                    }
+                }
+
+                propagate_forget_simple_token(tables, &res);
+                Resource::clean(&mut res)
+            }
+            None => log::error!("Undeclare unknown peer liveliness token!"),
+        },
+        None => log::error!("Undeclare peer liveliness token with unknown prefix!"),
+    }
+}
+
+pub(crate) fn undeclare_client_token(
+    tables: &mut Tables,
+    face: &mut Arc<FaceState>,
+    res: &mut Arc<Resource>,
+) {
+    log::debug!(
+        "Unregister client liveliness token {} for {}",
+        res.name(),
+        face
+    );
+    if let Some(ctx) = get_mut_unchecked(res).session_ctxs.get_mut(&face.id) {
+        get_mut_unchecked(ctx).token = false;
+    }
+    get_mut_unchecked(face).remote_tokens.remove(res);
+
+    let client_tokens = client_tokens(res);
+    match tables.whatami {
+        whatami::ROUTER if client_tokens.is_empty() && !remote_peer_tokens(tables, res) => {
+            undeclare_router_token(tables, None, res, &tables.pid.clone());
+        }
+        whatami::PEER if client_tokens.is_empty() => {
+            undeclare_peer_token(tables, None, res, &tables.pid.clone());
+        }
+        _ => (),
+    }
+
+    propagate_forget_simple_token(tables, res);
+    Resource::clean(res)
+}
+
+pub fn forget_client_token(
+    tables: &mut Tables,
+    face: &mut Arc<FaceState>,
+    prefixid: ZInt,
+    suffix: &str,
+) {
+    match tables.get_mapping(face, &prefixid) {
+        Some(prefix) => match Resource::get_resource(prefix, suffix) {
+            Some(mut res) => {
+                undeclare_client_token(tables, face, &mut res);
+            }
+            None => log::error!("Undeclare unknown liveliness token!"),
+        },
+        None => log::error!("Undeclare liveliness token with unknown prefix!"),
+    }
+}
+
+// A client runtime declares the liveliness subscribers of its sessions
+// to the routers and peers it is connected to
+fn propagate_token_subscription(tables: &mut Tables, expr: &str) {
+    let dst_faces = tables
+        .faces
+        .values()
+        .filter(|face| face.whatami != whatami::CLIENT && !face.local_token_subs.contains(expr))
+        .cloned()
+        .collect::<Vec<Arc<FaceState>>>();
+    for mut dst_face in dst_faces {
+        log::debug!("Send liveliness subscriber {} on {}", expr, dst_face);
+        get_mut_unchecked(&mut dst_face)
+            .local_token_subs
+            .insert(expr.to_string());
+        dst_face
+            .primitives
+            .decl_liveliness_subscriber(&expr.into(), None);
+    }
+}
+
+fn propagate_forget_token_subscription(tables: &mut Tables, expr: &str) {
+    let dst_faces = tables
+        .faces
+        .values()
+        .filter(|face| face.local_token_subs.contains(expr))
+        .cloned()
+        .collect::<Vec<Arc<FaceState>>>();
+    for mut dst_face in dst_faces {
+        log::debug!("Send forget liveliness subscriber {} on {}", expr, dst_face);
+        dst_face
+            .primitives
+            .forget_liveliness_subscriber(&expr.into(), None);
+        get_mut_unchecked(&mut dst_face)
+            .local_token_subs
+            .remove(expr);
+    }
+}
+
+pub fn declare_client_token_subscription(
+    tables: &mut Tables,
+    face: &mut Arc<FaceState>,
+    prefixid: ZInt,
+    suffix: &str,
+) {
+    match tables.get_mapping(face, &prefixid) {
+        Some(prefix) => {
+            let expr = [&prefix.name(), suffix].concat();
+            log::debug!("Register liveliness subscriber {} for {}", expr, face);
+            get_mut_unchecked(face)
+                .remote_token_subs
+                .insert(expr.clone());
+            if tables.whatami == whatami::CLIENT {
+                propagate_token_subscription(tables, &expr);
+            }
+
+            // Declare the matching tokens already alive to the face
+            for token in all_tokens(tables) {
+                if rname::intersect(&expr, &token.name()) {
+                    propagate_simple_token(tables, &token);
+                }
+            }
+        }
+        None => log::error!(
+            "Declare liveliness subscriber for unknown rid {}!",
+            prefixid
+        ),
+    }
+}
+
+pub(crate) fn undeclare_client_token_subscription(
+    tables: &mut Tables,
+    face: &mut Arc<FaceState>,
+    expr: &str,
+) {
+    log::debug!("Unregister liveliness subscriber {} for {}", expr, face);
+    get_mut_unchecked(face).remote_token_subs.remove(expr);
+    if tables.whatami == whatami::CLIENT
+        && !tables
+            .faces
+            .values()
+            .any(|face| face.remote_token_subs.contains(expr))
+    {
+        propagate_forget_token_subscription(tables, expr);
+    }
+
+    // Forget the tokens the face is no longer interested in
+    let tokens = face
+        .local_tokens
+        .iter()
+        .filter(|res| !wants_token(face, res))
+        .cloned()
+        .collect::<Vec<Arc<Resource>>>();
+    for res in tokens {
+        let reskey = Resource::get_best_key(&res, "", face.id);
+        face.primitives.forget_liveliness_token(&reskey, None);
+
+        get_mut_unchecked(face).local_tokens.remove(&res);
+    }
+}
+
+pub fn forget_client_token_subscription(
+    tables: &mut Tables,
+    face: &mut Arc<FaceState>,
+    prefixid: ZInt,
+    suffix: &str,
+) {
+    match tables.get_mapping(face, &prefixid) {
+        Some(prefix) => {
+            let expr = [&prefix.name(), suffix].concat();
+            undeclare_client_token_subscription(tables, face, &expr);
+        }
+        None => log::error!("Undeclare liveliness subscriber with unknown prefix!"),
+    }
+}
+
+pub(crate) fn liveliness_new_face(tables: &mut Tables, face: &mut Arc<FaceState>) {
+    if tables.whatami == whatami::CLIENT && face.whatami != whatami::CLIENT {
+        let exprs = tables
+            .faces
+            .values()
+            .flat_map(|face| face.remote_token_subs.iter().cloned())
+            .collect::<HashSet<String>>();
+        for expr in exprs {
+            propagate_token_subscription(tables, &expr);
+        }
+    }
+    if is_simple_token_dst(tables, face) {
+        for token in all_tokens(tables) {
+            propagate_simple_token(tables, &token);
+        }
+    }
+}
+
+// Routers and peers know all the tokens alive: they answer the liveliness queries
+// themselves, while a client runtime forwards them to the routers and peers it is
+// connected to.
+pub(crate) fn route_liveliness_query(
+    tables: &Tables,
+    face: &Arc<FaceState>,
+    qid: ZInt,
+    expr: &str,
+) -> Option<Route> {
+    if tables.whatami == whatami::CLIENT {
+        let mut route = Route::new();
+        for dst_face in tables.faces.values() {
+            if dst_face.whatami != whatami::CLIENT && dst_face.id != face.id {
+                let reskey = [LIVELINESS_QUERY_PREFIX, expr].concat().into();
+                route.insert(dst_face.id, (dst_face.clone(), reskey, None));
+            }
+        }
+        return Some(route);
+    }
+
+    let mut names = all_tokens(tables)
+        .iter()
+        .map(|res| res.name())
+        .filter(|name| rname::intersect(expr, name))
+        .collect::<Vec<String>>();
+    names.sort();
+    names.dedup();
+    for name in names {
+        log::trace!("Reply to liveliness query {}:{} with {}", face, qid, name);
+        face.primitives.clone().send_reply_data(
+            qid,
+            queryable::EVAL,
+            tables.pid,
+            false,
+            ResKey::RName(name),
+            None,
+            ZBuf::new(),
+            None,
+        );
+    }
+    log::debug!("Send final reply {}:{} (liveliness query)", face, qid);
+    face.primitives.clone().send_reply_final(qid);
+    None
+}
+
+// A peer joining a multicast group missed the tokens previously declared on it
+pub(crate) fn liveliness_new_mcast_peer(face: &Arc<FaceState>) {
+    for token in &face.local_tokens {
+        face.primitives
+            .decl_liveliness_token(&token.name().into(), None);
+    }
+}
+
+pub(crate) fn liveliness_remove_node(tables: &mut Tables, node: &PeerId, net_type: whatami::Type) {
+    match net_type {
+        whatami::ROUTER => {
+            for mut res in tables
+                .router_tokens
+                .iter()
+                .filter(|res| res.context().router_tokens.contains(node))
+                .cloned()
+                .collect::<Vec<Arc<Resource>>>()
+            {
+                unregister_router_token(tables, &mut res, node);
+
+                propagate_forget_simple_token(tables, &res);
+                Resource::clean(&mut res)
+            }
+        }
+        whatami::PEER => {
+            for mut res in tables
+                .peer_tokens
+                .iter()
+                .filter(|res| res.context().peer_tokens.contains(node))
+                .cloned()
+                .collect::<Vec<Arc<Resource>>>()
+            {
+                unregister_peer_token(tables, &mut res, node);
+
+                if tables.whatami == whatami::ROUTER {
+                    let client_tokens = res.session_ctxs.values().any(|ctx| ctx.token);
+                    let peer_tokens = remote_peer_tokens(tables, &res);
+                    if !client_tokens && !peer_tokens {
+                        undeclare_router_token(tables, None, &mut res, &tables.pid.clone());
+                    }
+                }
+
+                propagate_forget_simple_token(tables, &res);
+                Resource::clean(&mut res)
+            }
+        }
+        _ => (),
+    }
+}
+
+#[allow(clippy::mutable_key_type)]
+pub(crate) fn liveliness_tree_change(
+    tables: &mut Tables,
+    new_childs: &[Vec<NodeIndex>],
+    net_type: whatami::Type,
+) {
+    // propagate tokens to new childs
+    for (tree_sid, tree_childs) in new_childs.iter().enumerate() {
+        if !tree_childs.is_empty() {
+            let net = tables.get_net(net_type).unwrap();
+            let tree_idx = NodeIndex::new(tree_sid);
+            if net.graph.contains_node(tree_idx) {
+                let tree_id = net.graph[tree_idx].pid;
+
+                let tokens_res = match net_type {
+                    whatami::ROUTER => &tables.router_tokens,
+                    _ => &tables.peer_tokens,
+                };
+
+                for res in tokens_res {
+                    let tokens = match net_type {
+                        whatami::ROUTER => &res.context().router_tokens,
+                        _ => &res.context().peer_tokens,
+                    };
+                    if tokens.contains(&tree_id) {
+                        send_sourced_to_net_childs(
+                            tables,
+                            net,
+                            tree_childs,
+                            res,
+                            None,
+                            Some(RoutingContext::new(tree_sid as ZInt)),
+                            &|face, reskey, routing_context| {
+                                log::debug!("Send liveliness token {} on {}", res.name(), face);
+                                face.primitives
+                                    .decl_liveliness_token(reskey, routing_context);
+                            },
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
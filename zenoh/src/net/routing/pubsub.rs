@@ -23,43 +23,17 @@ use super::protocol::core::{
     whatami, Channel, CongestionControl, PeerId, Priority, Reliability, SubInfo, SubMode, ZInt,
 };
 use super::protocol::io::ZBuf;
-use super::protocol::proto::{DataInfo, RoutingContext};
+use super::protocol::proto::{Attachment, DataInfo, RoutingContext};
 
+use super::acl::AclAction;
 use super::face::FaceState;
 use super::network::Network;
-use super::resource::{elect_router, PullCaches, Resource, Route, SessionContext};
+use super::resource::{
+    elect_router, propagate_sourced, send_sourced_to_net_childs, PullCaches, Resource, Route,
+    SessionContext,
+};
 use super::router::Tables;
 
-#[inline]
-fn send_sourced_subscription_to_net_childs(
-    tables: &Tables,
-    net: &Network,
-    childs: &[NodeIndex],
-    res: &Arc<Resource>,
-    src_face: Option<&Arc<FaceState>>,
-    sub_info: &SubInfo,
-    routing_context: Option<RoutingContext>,
-) {
-    for child in childs {
-        if net.graph.contains_node(*child) {
-            match tables.get_face(&net.graph[*child].pid).cloned() {
-                Some(mut someface) => {
-                    if src_face.is_none() || someface.id != src_face.unwrap().id {
-                        let reskey = Resource::decl_key(res, &mut someface);
-
-                        log::debug!("Send subscription {} on {}", res.name(), someface);
-
-                        someface
-                            .primitives
-                            .decl_subscriber(&reskey, sub_info, routing_context);
-                    }
-                }
-                None => log::trace!("Unable to find face for pid {}", net.graph[*child].pid),
-            }
-        }
-    }
-}
-
 fn propagate_simple_subscription(
     tables: &mut Tables,
     res: &Arc<Resource>,
@@ -90,34 +64,19 @@ fn propagate_sourced_subscription(
     source: &PeerId,
     net_type: whatami::Type,
 ) {
-    let net = tables.get_net(net_type).unwrap();
-    match net.get_idx(source) {
-        Some(tree_sid) => {
-            if net.trees.len() > tree_sid.index() {
-                send_sourced_subscription_to_net_childs(
-                    tables,
-                    net,
-                    &net.trees[tree_sid.index()].childs,
-                    res,
-                    src_face,
-                    sub_info,
-                    Some(RoutingContext::new(tree_sid.index() as ZInt)),
-                );
-            } else {
-                log::trace!(
-                    "Propagating sub {}: tree for node {} sid:{} not yet ready",
-                    res.name(),
-                    tree_sid.index(),
-                    source
-                );
-            }
-        }
-        None => log::error!(
-            "Error propagating sub {}: cannot get index of {}!",
-            res.name(),
-            source
-        ),
-    }
+    propagate_sourced(
+        tables,
+        res,
+        src_face,
+        source,
+        net_type,
+        "sub",
+        &|face, reskey, routing_context| {
+            log::debug!("Send subscription {} on {}", res.name(), face);
+            face.primitives
+                .decl_subscriber(reskey, sub_info, routing_context);
+        },
+    );
 }
 
 fn register_router_subscription(
@@ -214,6 +173,7 @@ pub fn declare_peer_subscription(
                 propa_sub_info.mode = SubMode::Push;
                 register_router_subscription(tables, face, &mut res, &propa_sub_info, tables.pid);
             }
+            propagate_mcast_subscription(tables, &res);
 
             compute_matches_data_routes(tables, &mut res);
         }
@@ -251,6 +211,8 @@ fn register_client_subscription(
                         remote_rid: None,
                         subs: Some(sub_info.clone()),
                         qabl: None,
+                        token: false,
+                        pub_matching: None,
                         last_values: HashMap::new(),
                     }),
                 );
@@ -294,6 +256,7 @@ pub fn declare_client_subscription(
                     propagate_simple_subscription(tables, &res, sub_info, face);
                 }
             }
+            propagate_mcast_subscription(tables, &res);
 
             compute_matches_data_routes(tables, &mut res);
         }
@@ -301,6 +264,73 @@ pub fn declare_client_subscription(
     }
 }
 
+pub fn declare_client_publication(
+    tables: &mut Tables,
+    face: &mut Arc<FaceState>,
+    prefixid: ZInt,
+    suffix: &str,
+) {
+    match tables.get_mapping(face, &prefixid).cloned() {
+        Some(mut prefix) => {
+            let mut res = Resource::make_resource(tables, &mut prefix, suffix);
+            Resource::match_resource(tables, &mut res);
+
+            log::debug!("Register publication {} for {}", res.name(), face);
+            {
+                let res = get_mut_unchecked(&mut res);
+                match res.session_ctxs.get_mut(&face.id) {
+                    Some(ctx) => {
+                        if ctx.pub_matching.is_none() {
+                            get_mut_unchecked(ctx).pub_matching = Some(false);
+                        }
+                    }
+                    None => {
+                        res.session_ctxs.insert(
+                            face.id,
+                            Arc::new(SessionContext {
+                                face: face.clone(),
+                                local_rid: None,
+                                remote_rid: None,
+                                subs: None,
+                                qabl: None,
+                                token: false,
+                                pub_matching: Some(false),
+                                last_values: HashMap::new(),
+                            }),
+                        );
+                    }
+                }
+            }
+            get_mut_unchecked(face).remote_pubs.insert(res.clone());
+
+            compute_matching_statuses(tables, &mut res);
+        }
+        None => log::error!("Declare publication for unknown rid {}!", prefixid),
+    }
+}
+
+pub fn forget_client_publication(
+    tables: &mut Tables,
+    face: &mut Arc<FaceState>,
+    prefixid: ZInt,
+    suffix: &str,
+) {
+    match tables.get_mapping(face, &prefixid) {
+        Some(prefix) => match Resource::get_resource(prefix, suffix) {
+            Some(mut res) => {
+                log::debug!("Unregister publication {} for {}", res.name(), face);
+                if let Some(ctx) = get_mut_unchecked(&mut res).session_ctxs.get_mut(&face.id) {
+                    get_mut_unchecked(ctx).pub_matching = None;
+                }
+                get_mut_unchecked(face).remote_pubs.remove(&res);
+                Resource::clean(&mut res)
+            }
+            None => log::error!("Undeclare unknown publication!"),
+        },
+        None => log::error!("Undeclare publication with unknown prefix!"),
+    }
+}
+
 #[inline]
 fn remote_router_subs(tables: &Tables, res: &Arc<Resource>) -> bool {
     res.context.is_some()
@@ -335,30 +365,34 @@ fn client_subs(res: &Arc<Resource>) -> Vec<Arc<FaceState>> {
         .collect()
 }
 
-#[inline]
-fn send_forget_sourced_subscription_to_net_childs(
-    tables: &Tables,
-    net: &Network,
-    childs: &[NodeIndex],
-    res: &Arc<Resource>,
-    src_face: Option<&Arc<FaceState>>,
-    routing_context: Option<RoutingContext>,
-) {
-    for child in childs {
-        if net.graph.contains_node(*child) {
-            match tables.get_face(&net.graph[*child].pid).cloned() {
-                Some(mut someface) => {
-                    if src_face.is_none() || someface.id != src_face.unwrap().id {
-                        let reskey = Resource::decl_key(res, &mut someface);
-
-                        log::debug!("Send forget subscription {} on {}", res.name(), someface);
-
-                        someface
-                            .primitives
-                            .forget_subscriber(&reskey, routing_context);
-                    }
-                }
-                None => log::trace!("Unable to find face for pid {}", net.graph[*child].pid),
+// In peer mode the subscriptions are propagated through the peers network. They are also
+// declared to the multicast groups whose peers are not part of it, a group being only
+// told about the subscriptions of the other faces.
+fn propagate_mcast_subscription(tables: &mut Tables, res: &Arc<Resource>) {
+    if tables.whatami != whatami::PEER {
+        return;
+    }
+    let sub_info = SubInfo {
+        reliability: Reliability::Reliable, // @TODO
+        mode: SubMode::Push,
+        period: None,
+    };
+    let peer_subs = remote_peer_subs(tables, res);
+    for face in tables.faces.values_mut() {
+        if face.mcast_group.is_some() {
+            let alive = peer_subs
+                || res
+                    .session_ctxs
+                    .values()
+                    .any(|ctx| ctx.subs.is_some() && ctx.face.id != face.id);
+            if alive && !face.local_subs.contains(res) {
+                get_mut_unchecked(face).local_subs.insert(res.clone());
+                let reskey = Resource::decl_key(res, face);
+                face.primitives.decl_subscriber(&reskey, &sub_info, None);
+            } else if !alive && face.local_subs.contains(res) {
+                let reskey = Resource::get_best_key(res, "", face.id);
+                face.primitives.forget_subscriber(&reskey, None);
+                get_mut_unchecked(face).local_subs.remove(res);
             }
         }
     }
@@ -382,33 +416,18 @@ fn propagate_forget_sourced_subscription(
     source: &PeerId,
     net_type: whatami::Type,
 ) {
-    let net = tables.get_net(net_type).unwrap();
-    match net.get_idx(source) {
-        Some(tree_sid) => {
-            if net.trees.len() > tree_sid.index() {
-                send_forget_sourced_subscription_to_net_childs(
-                    tables,
-                    net,
-                    &net.trees[tree_sid.index()].childs,
-                    res,
-                    src_face,
-                    Some(RoutingContext::new(tree_sid.index() as ZInt)),
-                );
-            } else {
-                log::trace!(
-                    "Propagating forget sub {}: tree for node {} sid:{} not yet ready",
-                    res.name(),
-                    tree_sid.index(),
-                    source
-                );
-            }
-        }
-        None => log::error!(
-            "Error propagating forget sub {}: cannot get index of {}!",
-            res.name(),
-            source
-        ),
-    }
+    propagate_sourced(
+        tables,
+        res,
+        src_face,
+        source,
+        net_type,
+        "forget sub",
+        &|face, reskey, routing_context| {
+            log::debug!("Send forget subscription {} on {}", res.name(), face);
+            face.primitives.forget_subscriber(reskey, routing_context);
+        },
+    );
 }
 
 fn unregister_router_subscription(tables: &mut Tables, res: &mut Arc<Resource>, router: &PeerId) {
@@ -510,6 +529,7 @@ pub fn forget_peer_subscription(
                         undeclare_router_subscription(tables, None, &mut res, &tables.pid.clone());
                     }
                 }
+                propagate_mcast_subscription(tables, &res);
 
                 compute_matches_data_routes(tables, &mut res);
                 Resource::clean(&mut res)
@@ -560,6 +580,7 @@ pub(crate) fn undeclare_client_subscription(
             get_mut_unchecked(face).local_subs.remove(res);
         }
     }
+    propagate_mcast_subscription(tables, res);
 
     compute_matches_data_routes(tables, res);
     Resource::clean(res)
@@ -582,6 +603,19 @@ pub fn forget_client_subscription(
     }
 }
 
+// A peer joining a multicast group missed the subscriptions previously declared on it
+pub(crate) fn pubsub_new_mcast_peer(face: &Arc<FaceState>) {
+    let sub_info = SubInfo {
+        reliability: Reliability::Reliable, // @TODO
+        mode: SubMode::Push,
+        period: None,
+    };
+    for sub in &face.local_subs {
+        face.primitives
+            .decl_subscriber(&sub.name().into(), &sub_info, None);
+    }
+}
+
 pub(crate) fn pubsub_new_face(tables: &mut Tables, face: &mut Arc<FaceState>) {
     let sub_info = SubInfo {
         reliability: Reliability::Reliable, // @TODO
@@ -595,6 +629,16 @@ pub(crate) fn pubsub_new_face(tables: &mut Tables, face: &mut Arc<FaceState>) {
             face.primitives.decl_subscriber(&reskey, &sub_info, None);
         }
     }
+    if face.mcast_group.is_some() {
+        for sub in tables
+            .peer_subs
+            .iter()
+            .cloned()
+            .collect::<Vec<Arc<Resource>>>()
+        {
+            propagate_mcast_subscription(tables, &sub);
+        }
+    }
     if tables.whatami == whatami::CLIENT {
         for face in tables
             .faces
@@ -642,6 +686,7 @@ pub(crate) fn pubsub_remove_node(tables: &mut Tables, node: &PeerId, net_type: w
                         undeclare_router_subscription(tables, None, &mut res, &tables.pid.clone());
                     }
                 }
+                propagate_mcast_subscription(tables, &res);
 
                 compute_matches_data_routes(tables, &mut res);
                 Resource::clean(&mut res)
@@ -681,14 +726,21 @@ pub(crate) fn pubsub_tree_change(
                                 mode: SubMode::Push,
                                 period: None,
                             };
-                            send_sourced_subscription_to_net_childs(
+                            send_sourced_to_net_childs(
                                 tables,
                                 net,
                                 tree_childs,
                                 res,
                                 None,
-                                &sub_info,
                                 Some(RoutingContext::new(tree_sid as ZInt)),
+                                &|face, reskey, routing_context| {
+                                    log::debug!("Send subscription {} on {}", res.name(), face);
+                                    face.primitives.decl_subscriber(
+                                        reskey,
+                                        &sub_info,
+                                        routing_context,
+                                    );
+                                },
                             );
                         }
                     }
@@ -699,6 +751,7 @@ pub(crate) fn pubsub_tree_change(
 
     // recompute routes
     compute_data_routes_from(tables, &mut tables.root_res.clone());
+    compute_matching_statuses_from(tables, &mut tables.root_res.clone());
 }
 
 #[inline]
@@ -741,7 +794,7 @@ fn insert_faces_for_subs(
     }
 }
 
-fn compute_data_route(
+pub(super) fn compute_data_route(
     tables: &Tables,
     prefix: &Arc<Resource>,
     suffix: &str,
@@ -920,6 +973,14 @@ fn compute_data_routes_from(tables: &mut Tables, res: &mut Arc<Resource>) {
     }
 }
 
+fn compute_matching_statuses_from(tables: &mut Tables, res: &mut Arc<Resource>) {
+    compute_matching_statuses(tables, res);
+    let res = get_mut_unchecked(res);
+    for child in res.childs.values_mut() {
+        compute_matching_statuses_from(tables, child);
+    }
+}
+
 pub(crate) fn compute_matches_data_routes(tables: &mut Tables, res: &mut Arc<Resource>) {
     if res.context.is_some() {
         compute_data_routes(tables, res);
@@ -930,6 +991,42 @@ pub(crate) fn compute_matches_data_routes(tables: &mut Tables, res: &mut Arc<Res
                 compute_data_routes(tables, &mut match_.upgrade().unwrap());
             }
         }
+
+        for match_ in &res.context().matches {
+            compute_matching_statuses(tables, &mut match_.upgrade().unwrap());
+        }
+    }
+}
+
+/// Updates the matching status of the publications declared on the given resource
+/// and notifies the publishing faces of the changes.
+///
+/// A publication is matching when data published on its resource would be routed
+/// to at least one face other than the publishing one.
+fn compute_matching_statuses(tables: &mut Tables, res: &mut Arc<Resource>) {
+    let publications = res
+        .session_ctxs
+        .values()
+        .filter(|ctx| ctx.pub_matching.is_some())
+        .cloned()
+        .collect::<Vec<Arc<SessionContext>>>();
+    if publications.is_empty() {
+        return;
+    }
+    let route = compute_data_route(tables, res, "", None, whatami::CLIENT);
+    for mut ctx in publications {
+        let matching = route.values().any(|(face, _, _)| face.id != ctx.face.id);
+        if ctx.pub_matching != Some(matching) {
+            get_mut_unchecked(&mut ctx).pub_matching = Some(matching);
+            let reskey = Resource::get_best_key(res, "", ctx.face.id);
+            log::debug!(
+                "Matching status of {} for {}: {}",
+                res.name(),
+                ctx.face,
+                matching
+            );
+            ctx.face.primitives.send_matching_status(&reskey, matching);
+        }
     }
 }
 
@@ -1072,9 +1169,9 @@ fn get_matching_pulls(
 }
 
 macro_rules! send_to_first {
-    ($route:expr, $srcface:expr, $payload:expr, $channel:expr, $cong_ctrl:expr, $data_info:expr) => {
+    ($route:expr, $srcface:expr, $payload:expr, $channel:expr, $cong_ctrl:expr, $data_info:expr, $attachment:expr) => {
         let (outface, reskey, context) = $route.values().next().unwrap();
-        if $srcface.id != outface.id {
+        if is_forwarded_to($srcface, outface) {
             outface
                 .primitives
                 .send_data(
@@ -1084,15 +1181,16 @@ macro_rules! send_to_first {
                     $cong_ctrl,
                     $data_info,
                     *context,
+                    $attachment,
                 )
         }
     }
 }
 
 macro_rules! send_to_all {
-    ($route:expr, $srcface:expr, $payload:expr, $channel:expr, $cong_ctrl:expr, $data_info:expr) => {
+    ($route:expr, $srcface:expr, $payload:expr, $channel:expr, $cong_ctrl:expr, $data_info:expr, $attachment:expr) => {
         for (outface, reskey, context) in $route.values() {
-            if $srcface.id != outface.id {
+            if is_forwarded_to($srcface, outface) {
                 outface
                     .primitives
                     .send_data(
@@ -1102,6 +1200,7 @@ macro_rules! send_to_all {
                         $cong_ctrl,
                         $data_info.clone(),
                         *context,
+                        $attachment.clone(),
                     )
             }
         }
@@ -1114,17 +1213,28 @@ macro_rules! cache_data {
         $prefix:expr,
         $suffix:expr,
         $payload:expr,
-        $info:expr
+        $info:expr,
+        $attachment:expr
     ) => {
         for context in $matching_pulls.iter() {
             get_mut_unchecked(&mut context.clone()).last_values.insert(
                 [&$prefix.name(), $suffix].concat(),
-                ($info.clone(), $payload.clone()),
+                ($info.clone(), $payload.clone(), $attachment.clone()),
             );
         }
     };
 }
 
+// Only the data published by the local sessions and the clients is pushed on the
+// multicast groups. The peers of a group may also be reachable through the link
+// state networks or through other groups and forwarding their data could loop.
+#[inline]
+fn is_forwarded_to(srcface: &FaceState, outface: &FaceState) -> bool {
+    srcface.id != outface.id
+        && (outface.mcast_group.is_none()
+            || (srcface.whatami == whatami::CLIENT && srcface.mcast_group.is_none()))
+}
+
 #[inline]
 #[allow(clippy::too_many_arguments)]
 pub fn route_data(
@@ -1137,10 +1247,16 @@ pub fn route_data(
     info: Option<DataInfo>,
     payload: ZBuf,
     routing_context: Option<RoutingContext>,
+    attachment: Option<Attachment>,
 ) {
     match tables.get_mapping(face, &rid).cloned() {
         Some(prefix) => {
             log::trace!("Route data for res {}{}", prefix.name(), suffix);
+            if !face.is_allowed(AclAction::Publish, &prefix, suffix)
+                || !face.is_admin_allowed(tables, &prefix, suffix)
+            {
+                return;
+            }
 
             let res = Resource::get_resource(&prefix, suffix);
             let route = get_data_route(tables, face, &res, &prefix, suffix, routing_context);
@@ -1148,16 +1264,38 @@ pub fn route_data(
 
             if !(route.is_empty() && matching_pulls.is_empty()) {
                 let data_info = treat_timestamp!(&tables.hlc, info);
-
                 if route.len() == 1 && matching_pulls.len() == 0 {
-                    send_to_first!(route, face, payload, channel, congestion_control, data_info);
+                    send_to_first!(
+                        route,
+                        face,
+                        payload,
+                        channel,
+                        congestion_control,
+                        data_info,
+                        attachment
+                    );
                 } else {
                     if !matching_pulls.is_empty() {
                         let lock = zlock!(tables.pull_caches_lock);
-                        cache_data!(matching_pulls, prefix, suffix, payload, data_info);
+                        cache_data!(
+                            matching_pulls,
+                            prefix,
+                            suffix,
+                            payload,
+                            data_info,
+                            attachment
+                        );
                         drop(lock);
                     }
-                    send_to_all!(route, face, payload, channel, congestion_control, data_info);
+                    send_to_all!(
+                        route,
+                        face,
+                        payload,
+                        channel,
+                        congestion_control,
+                        data_info,
+                        attachment
+                    );
                 }
             }
         }
@@ -1179,11 +1317,17 @@ pub fn full_reentrant_route_data(
     info: Option<DataInfo>,
     payload: ZBuf,
     routing_context: Option<RoutingContext>,
+    attachment: Option<Attachment>,
 ) {
     let tables = zread!(tables_ref);
     match tables.get_mapping(face, &rid).cloned() {
         Some(prefix) => {
             log::trace!("Route data for res {}{}", prefix.name(), suffix);
+            if !face.is_allowed(AclAction::Publish, &prefix, suffix)
+                || !face.is_admin_allowed(&tables, &prefix, suffix)
+            {
+                return;
+            }
 
             let res = Resource::get_resource(&prefix, suffix);
             let route = get_data_route(&tables, face, &res, &prefix, suffix, routing_context);
@@ -1191,18 +1335,40 @@ pub fn full_reentrant_route_data(
 
             if !(route.is_empty() && matching_pulls.is_empty()) {
                 let data_info = treat_timestamp!(&tables.hlc, info);
-
                 if route.len() == 1 && matching_pulls.len() == 0 {
                     drop(tables);
-                    send_to_first!(route, face, payload, channel, congestion_control, data_info);
+                    send_to_first!(
+                        route,
+                        face,
+                        payload,
+                        channel,
+                        congestion_control,
+                        data_info,
+                        attachment
+                    );
                 } else {
                     if !matching_pulls.is_empty() {
                         let lock = zlock!(tables.pull_caches_lock);
-                        cache_data!(matching_pulls, prefix, suffix, payload, data_info);
+                        cache_data!(
+                            matching_pulls,
+                            prefix,
+                            suffix,
+                            payload,
+                            data_info,
+                            attachment
+                        );
                         drop(lock);
                     }
                     drop(tables);
-                    send_to_all!(route, face, payload, channel, congestion_control, data_info);
+                    send_to_all!(
+                        route,
+                        face,
+                        payload,
+                        channel,
+                        congestion_control,
+                        data_info,
+                        attachment
+                    );
                 }
             }
         }
@@ -1229,7 +1395,7 @@ pub fn pull_data(
                     Some(mut ctx) => match &ctx.subs {
                         Some(subinfo) => {
                             let lock = zlock!(tables.pull_caches_lock);
-                            for (name, (info, data)) in &ctx.last_values {
+                            for (name, (info, data, attachment)) in &ctx.last_values {
                                 let reskey =
                                     Resource::get_best_key(&tables.root_res, name, face.id);
                                 face.primitives.send_data(
@@ -1242,6 +1408,7 @@ pub fn pull_data(
                                     CongestionControl::default(), // @TODO: Default value for the time being
                                     info.clone(),
                                     None,
+                                    attachment.clone(),
                                 );
                             }
                             get_mut_unchecked(&mut ctx).last_values.clear();
@@ -11,19 +11,24 @@
 // Contributors:
 //   ADLINK zenoh team, <zenoh@adlink-labs.tech>
 //
-use async_std::sync::Arc;
+use async_std::sync::{Arc, Weak};
+use async_std::task::{self, JoinHandle};
 use petgraph::graph::NodeIndex;
 use std::borrow::Cow;
 use std::collections::HashMap;
+use std::sync::{Mutex, RwLock};
+use std::time::Duration;
 use zenoh_util::sync::get_mut_unchecked;
 
 use super::protocol::core::{
     queryable, whatami, PeerId, QueryConsolidation, QueryTarget, ResKey, ZInt,
 };
 use super::protocol::io::ZBuf;
-use super::protocol::proto::{DataInfo, RoutingContext};
+use super::protocol::proto::{Attachment, DataInfo, RoutingContext};
 
+use super::acl::AclAction;
 use super::face::FaceState;
+use super::liveliness::{route_liveliness_query, LIVELINESS_QUERY_PREFIX};
 use super::network::Network;
 use super::resource::{elect_router, Resource, Route, SessionContext};
 use super::router::Tables;
@@ -31,6 +36,16 @@ use super::router::Tables;
 pub(crate) struct Query {
     src_face: Arc<FaceState>,
     src_qid: ZInt,
+    timeout: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl Query {
+    /// Cancels the timeout task of a query that got all its final replies.
+    fn cancel_timeout(&self) {
+        if let Some(timeout) = zlock!(self.timeout).take() {
+            task::spawn(timeout.cancel());
+        }
+    }
 }
 
 fn local_router_qabl_kind(tables: &Tables, res: &Arc<Resource>) -> ZInt {
@@ -140,6 +155,7 @@ fn propagate_simple_queryable(
         // let current_qabl = dst_face.local_qabls.get(res);
         if (src_face.is_none() || src_face.as_ref().unwrap().id != dst_face.id)
             // && (current_qabl.is_none() || *current_qabl.unwrap() != kind) // @TODO
+            && dst_face.mcast_group.is_none()
             && match tables.whatami {
                 whatami::ROUTER => dst_face.whatami == whatami::CLIENT,
                 whatami::PEER => dst_face.whatami == whatami::CLIENT,
@@ -338,6 +354,8 @@ fn register_client_queryable(
                         remote_rid: None,
                         subs: None,
                         qabl: Some(kind),
+                        token: false,
+                        pub_matching: None,
                         last_values: HashMap::new(),
                     }),
                 );
@@ -671,7 +689,10 @@ pub fn forget_client_queryable(
 }
 
 pub(crate) fn queries_new_face(tables: &mut Tables, face: &mut Arc<FaceState>) {
-    if face.whatami == whatami::CLIENT && tables.whatami != whatami::CLIENT {
+    if face.whatami == whatami::CLIENT
+        && tables.whatami != whatami::CLIENT
+        && face.mcast_group.is_none()
+    {
         for qabl in &tables.router_qabls {
             let kind = local_qabl_kind(tables.whatami, &tables.pid.clone(), qabl, face);
             get_mut_unchecked(face)
@@ -1011,7 +1032,7 @@ pub(crate) fn compute_matches_query_routes(tables: &mut Tables, res: &mut Arc<Re
 
 #[allow(clippy::too_many_arguments)]
 pub fn route_query(
-    tables: &mut Tables,
+    tables_ref: &Arc<RwLock<Tables>>,
     face: &Arc<FaceState>,
     rid: ZInt,
     suffix: &str,
@@ -1020,7 +1041,11 @@ pub fn route_query(
     target: QueryTarget,
     consolidation: QueryConsolidation,
     routing_context: Option<RoutingContext>,
+    attachment: Option<Attachment>,
+    timeout: Duration,
 ) {
+    let mut guard = zwrite!(tables_ref);
+    let tables = &mut *guard;
     match tables.get_mapping(face, &rid) {
         Some(prefix) => {
             log::debug!(
@@ -1030,37 +1055,69 @@ pub fn route_query(
                 prefix.name(),
                 suffix,
             );
+            if !face.is_allowed(AclAction::Query, prefix, suffix) {
+                log::debug!("Send final reply {}:{} (access denied)", face, qid);
+                face.primitives.clone().send_reply_final(qid);
+                return;
+            }
 
-            let route = match tables.whatami {
-                whatami::ROUTER => match face.whatami {
-                    whatami::ROUTER => {
-                        let routers_net = tables.routers_net.as_ref().unwrap();
-                        let local_context = routers_net
-                            .get_local_context(routing_context.map(|rc| rc.tree_id), face.link_id);
-                        (target.kind == queryable::ALL_KINDS)
-                            .then(|| Resource::get_resource(prefix, suffix))
-                            .flatten()
-                            .map(|res| res.routers_query_route(local_context))
-                            .flatten()
-                            .unwrap_or_else(|| {
-                                compute_query_route(
-                                    tables,
-                                    prefix,
-                                    suffix,
-                                    target.kind,
-                                    Some(local_context),
-                                    whatami::ROUTER,
-                                )
-                            })
-                    }
-                    whatami::PEER => {
-                        let peers_net = tables.peers_net.as_ref().unwrap();
-                        let local_context = peers_net
-                            .get_local_context(routing_context.map(|rc| rc.tree_id), face.link_id);
-                        (target.kind == queryable::ALL_KINDS)
+            let res_name = [&prefix.name(), suffix].concat();
+            let route = if let Some(expr) = res_name.strip_prefix(LIVELINESS_QUERY_PREFIX) {
+                match route_liveliness_query(tables, face, qid, expr) {
+                    Some(route) => Arc::new(route),
+                    None => return,
+                }
+            } else {
+                match tables.whatami {
+                    whatami::ROUTER => match face.whatami {
+                        whatami::ROUTER => {
+                            let routers_net = tables.routers_net.as_ref().unwrap();
+                            let local_context = routers_net.get_local_context(
+                                routing_context.map(|rc| rc.tree_id),
+                                face.link_id,
+                            );
+                            (target.kind == queryable::ALL_KINDS)
+                                .then(|| Resource::get_resource(prefix, suffix))
+                                .flatten()
+                                .map(|res| res.routers_query_route(local_context))
+                                .flatten()
+                                .unwrap_or_else(|| {
+                                    compute_query_route(
+                                        tables,
+                                        prefix,
+                                        suffix,
+                                        target.kind,
+                                        Some(local_context),
+                                        whatami::ROUTER,
+                                    )
+                                })
+                        }
+                        whatami::PEER => {
+                            let peers_net = tables.peers_net.as_ref().unwrap();
+                            let local_context = peers_net.get_local_context(
+                                routing_context.map(|rc| rc.tree_id),
+                                face.link_id,
+                            );
+                            (target.kind == queryable::ALL_KINDS)
+                                .then(|| Resource::get_resource(prefix, suffix))
+                                .flatten()
+                                .map(|res| res.peers_query_route(local_context))
+                                .flatten()
+                                .unwrap_or_else(|| {
+                                    compute_query_route(
+                                        tables,
+                                        prefix,
+                                        suffix,
+                                        target.kind,
+                                        Some(local_context),
+                                        whatami::PEER,
+                                    )
+                                })
+                        }
+                        _ => (target.kind == queryable::ALL_KINDS)
                             .then(|| Resource::get_resource(prefix, suffix))
                             .flatten()
-                            .map(|res| res.peers_query_route(local_context))
+                            .map(|res| res.routers_query_route(0))
                             .flatten()
                             .unwrap_or_else(|| {
                                 compute_query_route(
@@ -1068,36 +1125,38 @@ pub fn route_query(
                                     prefix,
                                     suffix,
                                     target.kind,
-                                    Some(local_context),
-                                    whatami::PEER,
+                                    None,
+                                    whatami::CLIENT,
                                 )
-                            })
-                    }
-                    _ => (target.kind == queryable::ALL_KINDS)
-                        .then(|| Resource::get_resource(prefix, suffix))
-                        .flatten()
-                        .map(|res| res.routers_query_route(0))
-                        .flatten()
-                        .unwrap_or_else(|| {
-                            compute_query_route(
-                                tables,
-                                prefix,
-                                suffix,
-                                target.kind,
-                                None,
-                                whatami::CLIENT,
-                            )
-                        }),
-                },
-                whatami::PEER => match face.whatami {
-                    whatami::ROUTER | whatami::PEER => {
-                        let peers_net = tables.peers_net.as_ref().unwrap();
-                        let local_context = peers_net
-                            .get_local_context(routing_context.map(|rc| rc.tree_id), face.link_id);
-                        (target.kind == queryable::ALL_KINDS)
+                            }),
+                    },
+                    whatami::PEER => match face.whatami {
+                        whatami::ROUTER | whatami::PEER => {
+                            let peers_net = tables.peers_net.as_ref().unwrap();
+                            let local_context = peers_net.get_local_context(
+                                routing_context.map(|rc| rc.tree_id),
+                                face.link_id,
+                            );
+                            (target.kind == queryable::ALL_KINDS)
+                                .then(|| Resource::get_resource(prefix, suffix))
+                                .flatten()
+                                .map(|res| res.peers_query_route(local_context))
+                                .flatten()
+                                .unwrap_or_else(|| {
+                                    compute_query_route(
+                                        tables,
+                                        prefix,
+                                        suffix,
+                                        target.kind,
+                                        Some(local_context),
+                                        whatami::PEER,
+                                    )
+                                })
+                        }
+                        _ => (target.kind == queryable::ALL_KINDS)
                             .then(|| Resource::get_resource(prefix, suffix))
                             .flatten()
-                            .map(|res| res.peers_query_route(local_context))
+                            .map(|res| res.peers_query_route(0))
                             .flatten()
                             .unwrap_or_else(|| {
                                 compute_query_route(
@@ -1105,15 +1164,15 @@ pub fn route_query(
                                     prefix,
                                     suffix,
                                     target.kind,
-                                    Some(local_context),
-                                    whatami::PEER,
+                                    None,
+                                    whatami::CLIENT,
                                 )
-                            })
-                    }
+                            }),
+                    },
                     _ => (target.kind == queryable::ALL_KINDS)
                         .then(|| Resource::get_resource(prefix, suffix))
                         .flatten()
-                        .map(|res| res.peers_query_route(0))
+                        .map(|res| res.client_query_route())
                         .flatten()
                         .unwrap_or_else(|| {
                             compute_query_route(
@@ -1125,22 +1184,7 @@ pub fn route_query(
                                 whatami::CLIENT,
                             )
                         }),
-                },
-                _ => (target.kind == queryable::ALL_KINDS)
-                    .then(|| Resource::get_resource(prefix, suffix))
-                    .flatten()
-                    .map(|res| res.client_query_route())
-                    .flatten()
-                    .unwrap_or_else(|| {
-                        compute_query_route(
-                            tables,
-                            prefix,
-                            suffix,
-                            target.kind,
-                            None,
-                            whatami::CLIENT,
-                        )
-                    }),
+                }
             };
 
             if route.is_empty()
@@ -1152,7 +1196,9 @@ pub fn route_query(
                 let query = Arc::new(Query {
                     src_face: face.clone(),
                     src_qid: qid,
+                    timeout: Mutex::new(None),
                 });
+                let mut pending = vec![];
 
                 for (outface, reskey, context) in route.values() {
                     if face.id != outface.id {
@@ -1161,6 +1207,7 @@ pub fn route_query(
                         outface_mut.next_qid += 1;
                         let qid = outface_mut.next_qid;
                         outface_mut.pending_queries.insert(qid, query.clone());
+                        pending.push((Arc::downgrade(&outface), qid));
 
                         log::trace!("Propagate query {}:{} to {}", query.src_face, qid, outface);
 
@@ -1171,9 +1218,15 @@ pub fn route_query(
                             target.clone(),
                             consolidation.clone(),
                             *context,
+                            attachment.clone(),
                         )
                     }
                 }
+
+                if !pending.is_empty() {
+                    *zlock!(query.timeout) =
+                        Some(schedule_query_timeout(tables_ref, pending, timeout));
+                }
             }
         }
         None => {
@@ -1190,9 +1243,11 @@ pub(crate) fn route_send_reply_data(
     qid: ZInt,
     replier_kind: ZInt,
     replier_id: PeerId,
+    is_error: bool,
     reskey: ResKey,
     info: Option<DataInfo>,
     payload: ZBuf,
+    attachment: Option<Attachment>,
 ) {
     match face.pending_queries.get(&qid) {
         Some(query) => {
@@ -1200,9 +1255,11 @@ pub(crate) fn route_send_reply_data(
                 query.src_qid,
                 replier_kind,
                 replier_id,
+                is_error,
                 reskey,
                 info,
                 payload,
+                attachment,
             );
         }
         None => log::error!("Route reply for unknown query!"),
@@ -1225,6 +1282,7 @@ pub(crate) fn route_send_reply_final(_tables: &mut Tables, face: &mut Arc<FaceSt
                     .primitives
                     .clone()
                     .send_reply_final(query.src_qid);
+                query.cancel_timeout();
             }
             get_mut_unchecked(face).pending_queries.remove(&qid);
         }
@@ -1232,6 +1290,38 @@ pub(crate) fn route_send_reply_final(_tables: &mut Tables, face: &mut Arc<FaceSt
     }
 }
 
+fn schedule_query_timeout(
+    tables_ref: &Arc<RwLock<Tables>>,
+    pending: Vec<(Weak<FaceState>, ZInt)>,
+    timeout: Duration,
+) -> JoinHandle<()> {
+    let tables_ref = tables_ref.clone();
+    task::spawn(async move {
+        task::sleep(timeout).await;
+        let _tables = zwrite!(tables_ref);
+        for (face, qid) in pending {
+            if let Some(mut face) = face.upgrade() {
+                if let Some(query) = get_mut_unchecked(&mut face).pending_queries.remove(&qid) {
+                    log::debug!(
+                        "Timeout reply {}:{} from {}",
+                        query.src_face,
+                        query.src_qid,
+                        face
+                    );
+                    if Arc::strong_count(&query) == 1 {
+                        log::debug!("Propagate final reply {}:{}", query.src_face, query.src_qid);
+                        query
+                            .src_face
+                            .primitives
+                            .clone()
+                            .send_reply_final(query.src_qid);
+                    }
+                }
+            }
+        }
+    })
+}
+
 pub(crate) fn finalize_pending_queries(_tables: &mut Tables, face: &mut Arc<FaceState>) {
     for query in face.pending_queries.values() {
         log::debug!(
@@ -1247,6 +1337,7 @@ pub(crate) fn finalize_pending_queries(_tables: &mut Tables, face: &mut Arc<Face
                 .primitives
                 .clone()
                 .send_reply_final(query.src_qid);
+            query.cancel_timeout();
         }
     }
     get_mut_unchecked(face).pending_queries.clear();
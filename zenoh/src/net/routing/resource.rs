@@ -12,12 +12,14 @@
 //   ADLINK zenoh team, <zenoh@adlink-labs.tech>
 //
 use super::face::FaceState;
+use super::network::Network;
 use super::protocol::core::rname;
-use super::protocol::core::{PeerId, ResKey, SubInfo, ZInt};
+use super::protocol::core::{whatami, PeerId, ResKey, SubInfo, ZInt};
 use super::protocol::io::ZBuf;
-use super::protocol::proto::{DataInfo, RoutingContext};
+use super::protocol::proto::{Attachment, DataInfo, RoutingContext};
 use super::router::Tables;
 use async_std::sync::{Arc, Weak};
+use petgraph::graph::NodeIndex;
 use std::collections::hash_map::DefaultHasher;
 use std::collections::{HashMap, HashSet};
 use std::hash::{Hash, Hasher};
@@ -32,7 +34,10 @@ pub(super) struct SessionContext {
     pub(super) remote_rid: Option<ZInt>,
     pub(super) subs: Option<SubInfo>,
     pub(super) qabl: Option<ZInt>,
-    pub(super) last_values: HashMap<String, (Option<DataInfo>, ZBuf)>,
+    pub(super) token: bool,
+    // Some(matching status) if the face declared a publication on this resource
+    pub(super) pub_matching: Option<bool>,
+    pub(super) last_values: HashMap<String, (Option<DataInfo>, ZBuf, Option<Attachment>)>,
 }
 
 pub(super) struct ResourceContext {
@@ -40,6 +45,8 @@ pub(super) struct ResourceContext {
     pub(super) peer_subs: HashSet<PeerId>,
     pub(super) router_qabls: HashMap<PeerId, ZInt>,
     pub(super) peer_qabls: HashMap<PeerId, ZInt>,
+    pub(super) router_tokens: HashSet<PeerId>,
+    pub(super) peer_tokens: HashSet<PeerId>,
     pub(super) matches: Vec<Weak<Resource>>,
     pub(super) matching_pulls: Arc<PullCaches>,
     pub(super) routers_data_routes: Vec<Arc<Route>>,
@@ -57,6 +64,8 @@ impl ResourceContext {
             peer_subs: HashSet::new(),
             router_qabls: HashMap::new(),
             peer_qabls: HashMap::new(),
+            router_tokens: HashSet::new(),
+            peer_tokens: HashSet::new(),
             matches: Vec::new(),
             matching_pulls: Arc::new(Vec::new()),
             routers_data_routes: Vec::new(),
@@ -242,6 +251,14 @@ impl Resource {
         result
     }
 
+    /// The number of resources declared below the given one.
+    pub fn tree_size(from: &Arc<Resource>) -> usize {
+        from.childs
+            .values()
+            .map(|child| 1 + Resource::tree_size(child))
+            .sum()
+    }
+
     pub fn make_resource(
         tables: &mut Tables,
         from: &mut Arc<Resource>,
@@ -348,6 +365,10 @@ impl Resource {
 
     #[inline]
     pub fn decl_key(res: &Arc<Resource>, face: &mut Arc<FaceState>) -> ResKey {
+        // The remote peers of a multicast group would not agree on resource ids
+        if face.mcast_group.is_some() {
+            return res.name().into();
+        }
         let (nonwild_prefix, wildsuffix) = Resource::nonwild_prefix(res);
         match nonwild_prefix {
             Some(mut nonwild_prefix) => {
@@ -361,6 +382,8 @@ impl Resource {
                             remote_rid: None,
                             subs: None,
                             qabl: None,
+                            token: false,
+                            pub_matching: None,
                             last_values: HashMap::new(),
                         })
                     });
@@ -530,6 +553,8 @@ pub fn declare_resource(
                             remote_rid: Some(rid),
                             subs: None,
                             qabl: None,
+                            token: false,
+                            pub_matching: None,
                             last_values: HashMap::new(),
                         })
                     })
@@ -604,3 +629,77 @@ pub(super) fn elect_router<'a>(res_name: &str, routers: &'a [PeerId]) -> &'a Pee
             .0
     }
 }
+
+/// Sends a declaration of `res` to the faces of the given childs of a routing tree
+/// (but `src_face`), `send` being called with the key of `res` for each face.
+#[inline]
+pub(super) fn send_sourced_to_net_childs<F>(
+    tables: &Tables,
+    net: &Network,
+    childs: &[NodeIndex],
+    res: &Arc<Resource>,
+    src_face: Option<&Arc<FaceState>>,
+    routing_context: Option<RoutingContext>,
+    send: &F,
+) where
+    F: Fn(&Arc<FaceState>, &ResKey, Option<RoutingContext>),
+{
+    for child in childs {
+        if net.graph.contains_node(*child) {
+            match tables.get_face(&net.graph[*child].pid).cloned() {
+                Some(mut someface) => {
+                    if src_face.is_none() || someface.id != src_face.unwrap().id {
+                        let reskey = Resource::decl_key(res, &mut someface);
+                        send(&someface, &reskey, routing_context);
+                    }
+                }
+                None => log::trace!("Unable to find face for pid {}", net.graph[*child].pid),
+            }
+        }
+    }
+}
+
+/// Sends a declaration of `res` made by `source` along the routing tree of `source`
+/// in the `net_type` network. `what` names the declaration in the logs.
+pub(super) fn propagate_sourced<F>(
+    tables: &Tables,
+    res: &Arc<Resource>,
+    src_face: Option<&Arc<FaceState>>,
+    source: &PeerId,
+    net_type: whatami::Type,
+    what: &str,
+    send: &F,
+) where
+    F: Fn(&Arc<FaceState>, &ResKey, Option<RoutingContext>),
+{
+    let net = tables.get_net(net_type).unwrap();
+    match net.get_idx(source) {
+        Some(tree_sid) => {
+            if net.trees.len() > tree_sid.index() {
+                send_sourced_to_net_childs(
+                    tables,
+                    net,
+                    &net.trees[tree_sid.index()].childs,
+                    res,
+                    src_face,
+                    Some(RoutingContext::new(tree_sid.index() as ZInt)),
+                    send,
+                );
+            } else {
+                log::trace!(
+                    "Propagating {} {}: tree for node {} sid:{} not yet ready",
+                    what,
+                    res.name(),
+                    tree_sid.index(),
+                    source
+                );
+            }
+        }
+        None => log::error!(
+            "Error propagating {} {}: cannot get index of {}!",
+            what,
+            res.name(),
+            source
+        ),
+    }
+}
@@ -11,7 +11,9 @@
 // Contributors:
 //   ADLINK zenoh team, <zenoh@adlink-labs.tech>
 //
+use super::acl::{AccessControl, FaceAcl};
 use super::face::{Face, FaceState};
+pub use super::liveliness::*;
 use super::network::{shared_nodes, Network};
 use super::protocol::core::{whatami, PeerId, WhatAmI, ZInt};
 use super::protocol::proto::{ZenohBody, ZenohMessage};
@@ -19,13 +21,17 @@ pub use super::pubsub::*;
 pub use super::queries::*;
 pub use super::resource::*;
 use super::runtime::Runtime;
-use super::transport::{DeMux, Mux, Primitives, TransportPeerEventHandler, TransportUnicast};
+use super::transport::{
+    DeMux, Mux, Primitives, TransportMulticast, TransportPeerEventHandler, TransportUnicast,
+};
 use crate::net::link::Link;
 use async_std::sync::{Arc, Weak};
 use async_std::task::JoinHandle;
 use std::any::Any;
 use std::collections::{HashMap, HashSet};
+use std::net::IpAddr;
 use std::sync::{Mutex, RwLock};
+use std::time::Duration;
 use uhlc::HLC;
 use zenoh_util::core::ZResult;
 use zenoh_util::sync::get_mut_unchecked;
@@ -43,38 +49,51 @@ pub struct Tables {
     #[allow(dead_code)]
     pub(crate) hlc: Option<Arc<HLC>>,
     pub(crate) root_res: Arc<Resource>,
+    pub(crate) queries_default_timeout: Duration,
     pub(crate) faces: HashMap<usize, Arc<FaceState>>,
     pub(crate) pull_caches_lock: Mutex<()>,
     pub(crate) router_subs: HashSet<Arc<Resource>>,
     pub(crate) peer_subs: HashSet<Arc<Resource>>,
     pub(crate) router_qabls: HashSet<Arc<Resource>>,
     pub(crate) peer_qabls: HashSet<Arc<Resource>>,
+    pub(crate) router_tokens: HashSet<Arc<Resource>>,
+    pub(crate) peer_tokens: HashSet<Arc<Resource>>,
     pub(crate) routers_net: Option<Network>,
     pub(crate) peers_net: Option<Network>,
     pub(crate) shared_nodes: Vec<PeerId>,
     pub(crate) routers_trees_task: Option<JoinHandle<()>>,
     pub(crate) peers_trees_task: Option<JoinHandle<()>>,
+    pub(crate) acl: Option<AccessControl>,
 }
 
 impl Tables {
-    pub fn new(pid: PeerId, whatami: whatami::Type, hlc: Option<Arc<HLC>>) -> Self {
+    pub fn new(
+        pid: PeerId,
+        whatami: whatami::Type,
+        hlc: Option<Arc<HLC>>,
+        queries_default_timeout: Duration,
+    ) -> Self {
         Tables {
             pid,
             whatami,
             face_counter: 0,
             hlc,
             root_res: Resource::root(),
+            queries_default_timeout,
             faces: HashMap::new(),
             pull_caches_lock: Mutex::new(()),
             router_subs: HashSet::new(),
             peer_subs: HashSet::new(),
             router_qabls: HashSet::new(),
             peer_qabls: HashSet::new(),
+            router_tokens: HashSet::new(),
+            peer_tokens: HashSet::new(),
             routers_net: None,
             peers_net: None,
             shared_nodes: vec![],
             routers_trees_task: None,
             peers_trees_task: None,
+            acl: None,
         }
     }
 
@@ -120,18 +139,31 @@ impl Tables {
         whatami: WhatAmI,
         primitives: Arc<dyn Primitives + Send + Sync>,
         link_id: usize,
+        mcast_group: Option<TransportMulticast>,
+        acl: Option<FaceAcl>,
     ) -> Weak<FaceState> {
         let fid = self.face_counter;
         self.face_counter += 1;
         let mut newface = self
             .faces
             .entry(fid)
-            .or_insert_with(|| FaceState::new(fid, pid, whatami, primitives.clone(), link_id))
+            .or_insert_with(|| {
+                FaceState::new(
+                    fid,
+                    pid,
+                    whatami,
+                    primitives.clone(),
+                    link_id,
+                    mcast_group,
+                    acl,
+                )
+            })
             .clone();
         log::debug!("New {}", newface);
 
         pubsub_new_face(self, &mut newface);
         queries_new_face(self, &mut newface);
+        liveliness_new_face(self, &mut newface);
 
         Arc::downgrade(&newface)
     }
@@ -142,7 +174,7 @@ impl Tables {
         whatami: WhatAmI,
         primitives: Arc<dyn Primitives + Send + Sync>,
     ) -> Weak<FaceState> {
-        self.open_net_face(pid, whatami, primitives, 0)
+        self.open_net_face(pid, whatami, primitives, 0, None, None)
     }
 
     pub fn close_face(&mut self, face: &Weak<FaceState>) {
@@ -163,6 +195,10 @@ impl Tables {
                     Resource::clean(&mut res);
                 }
                 face.local_mappings.clear();
+                for mut res in face.remote_pubs.drain() {
+                    get_mut_unchecked(&mut res).session_ctxs.remove(&face.id);
+                    Resource::clean(&mut res);
+                }
                 for mut res in face.remote_subs.drain() {
                     get_mut_unchecked(&mut res).session_ctxs.remove(&face.id);
                     undeclare_client_subscription(self, &mut face_clone, &mut res);
@@ -173,6 +209,16 @@ impl Tables {
                     undeclare_client_queryable(self, &mut face_clone, &mut res);
                     Resource::clean(&mut res);
                 }
+                face.local_tokens.clear();
+                for mut res in face.remote_tokens.drain() {
+                    get_mut_unchecked(&mut res).session_ctxs.remove(&face.id);
+                    undeclare_client_token(self, &mut face_clone, &mut res);
+                    Resource::clean(&mut res);
+                }
+                face.local_token_subs.clear();
+                for expr in face.remote_token_subs.drain().collect::<Vec<String>>() {
+                    undeclare_client_token_subscription(self, &mut face_clone, &expr);
+                }
                 self.faces.remove(&face.id);
             }
             None => log::error!("Face already closed!"),
@@ -221,6 +267,7 @@ impl Tables {
                 log::trace!("Compute routes");
                 pubsub_tree_change(&mut tables, &new_childs, net_type);
                 queries_tree_change(&mut tables, &new_childs, net_type);
+                liveliness_tree_change(&mut tables, &new_childs, net_type);
 
                 log::trace!("Computations completed");
                 match net_type {
@@ -242,10 +289,20 @@ pub struct Router {
 }
 
 impl Router {
-    pub fn new(pid: PeerId, whatami: whatami::Type, hlc: Option<Arc<HLC>>) -> Self {
+    pub fn new(
+        pid: PeerId,
+        whatami: whatami::Type,
+        hlc: Option<Arc<HLC>>,
+        queries_default_timeout: Duration,
+    ) -> Self {
         Router {
             whatami,
-            tables: Arc::new(RwLock::new(Tables::new(pid, whatami, hlc))),
+            tables: Arc::new(RwLock::new(Tables::new(
+                pid,
+                whatami,
+                hlc,
+                queries_default_timeout,
+            ))),
         }
     }
 
@@ -322,6 +379,18 @@ impl Router {
             );
         }
 
+        let pid = transport.get_pid()?;
+        let acl = tables.acl.as_ref().map(|acl| {
+            let user = transport.get_user().ok().flatten();
+            let addresses: Vec<IpAddr> = transport
+                .get_links()
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|link| link.src.address.get_ip())
+                .collect();
+            acl.face_acl(&pid, user.as_deref(), &addresses)
+        });
+
         let handler = Arc::new(LinkStateInterceptor::new(
             transport.clone(),
             self.tables.clone(),
@@ -329,10 +398,12 @@ impl Router {
                 tables: self.tables.clone(),
                 state: tables
                     .open_net_face(
-                        transport.get_pid().unwrap(),
+                        pid,
                         whatami,
                         Arc::new(Mux::new(transport)),
                         link_id,
+                        None,
+                        acl,
                     )
                     .upgrade()
                     .unwrap(),
@@ -352,6 +423,73 @@ impl Router {
         }
         Ok(handler)
     }
+
+    // The remote peers of a multicast group are not part of the link state networks.
+    // They are all reached through a single face bound to the multicast transport that
+    // declares and receives declarations like a client face.
+    pub fn new_transport_multicast(&self, transport: TransportMulticast) -> ZResult<Face> {
+        let mut tables = zwrite!(self.tables);
+        // The face stands for a group of peers and is given its own PeerId
+        let pid = PeerId::rand();
+        let acl = tables.acl.as_ref().map(|acl| {
+            let addresses: Vec<IpAddr> = transport
+                .get_link()
+                .ok()
+                .and_then(|link| link.src.address.get_ip())
+                .into_iter()
+                .collect();
+            acl.face_acl(&pid, None, &addresses)
+        });
+        let face = Face {
+            tables: self.tables.clone(),
+            state: tables
+                .open_net_face(
+                    pid,
+                    whatami::CLIENT,
+                    Arc::new(Mux::new(transport.clone())),
+                    0,
+                    Some(transport),
+                    acl,
+                )
+                .upgrade()
+                .unwrap(),
+        };
+        Ok(face)
+    }
+
+    pub fn new_peer_multicast(&self, face: Face) -> ZResult<Arc<McastPeerInterceptor>> {
+        let tables = zread!(self.tables);
+        pubsub_new_mcast_peer(&face.state);
+        liveliness_new_mcast_peer(&face.state);
+        drop(tables);
+        Ok(Arc::new(McastPeerInterceptor {
+            demux: DeMux::new(face),
+        }))
+    }
+}
+
+// The messages of the remote peers of a multicast group are all handled by the face
+// of the group, which is closed with the multicast transport and not when a peer leaves
+pub struct McastPeerInterceptor {
+    demux: DeMux<Face>,
+}
+
+impl TransportPeerEventHandler for McastPeerInterceptor {
+    fn handle_message(&self, msg: ZenohMessage) -> ZResult<()> {
+        self.demux.handle_message(msg)
+    }
+
+    fn new_link(&self, _link: Link) {}
+
+    fn del_link(&self, _link: Link) {}
+
+    fn closing(&self) {}
+
+    fn closed(&self) {}
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
 }
 
 pub struct LinkStateInterceptor {
@@ -389,6 +527,7 @@ impl TransportPeerEventHandler for LinkStateInterceptor {
                             .link_states(list.link_states, pid)
                         {
                             pubsub_remove_node(&mut tables, &removed_node.pid, whatami::ROUTER);
+                            liveliness_remove_node(&mut tables, &removed_node.pid, whatami::ROUTER);
                             queries_remove_node(&mut tables, &removed_node.pid, whatami::ROUTER);
                         }
 
@@ -409,6 +548,7 @@ impl TransportPeerEventHandler for LinkStateInterceptor {
                             .link_states(list.link_states, pid)
                         {
                             pubsub_remove_node(&mut tables, &removed_node.pid, whatami::PEER);
+                            liveliness_remove_node(&mut tables, &removed_node.pid, whatami::PEER);
                             queries_remove_node(&mut tables, &removed_node.pid, whatami::PEER);
                         }
 
@@ -449,6 +589,7 @@ impl TransportPeerEventHandler for LinkStateInterceptor {
                             tables.routers_net.as_mut().unwrap().remove_link(&pid)
                         {
                             pubsub_remove_node(&mut tables, &removed_node.pid, whatami::ROUTER);
+                            liveliness_remove_node(&mut tables, &removed_node.pid, whatami::ROUTER);
                             queries_remove_node(&mut tables, &removed_node.pid, whatami::ROUTER);
                         }
 
@@ -466,6 +607,7 @@ impl TransportPeerEventHandler for LinkStateInterceptor {
                             tables.peers_net.as_mut().unwrap().remove_link(&pid)
                         {
                             pubsub_remove_node(&mut tables, &removed_node.pid, whatami::PEER);
+                            liveliness_remove_node(&mut tables, &removed_node.pid, whatami::PEER);
                             queries_remove_node(&mut tables, &removed_node.pid, whatami::PEER);
                         }
 
@@ -10,15 +10,18 @@
 //
 // Contributors:
 //   ADLINK zenoh team, <zenoh@adlink-labs.tech>
+use super::link::EndPoint;
 use super::protocol::{
     core::{
-        queryable::EVAL, rname, Channel, CongestionControl, PeerId, QueryConsolidation,
+        queryable::EVAL, rname, whatami, Channel, CongestionControl, PeerId, QueryConsolidation,
         QueryTarget, ResKey, SubInfo, ZInt,
     },
     io::ZBuf,
-    proto::{encoding, DataInfo, RoutingContext},
+    proto::{data_kind, encoding, Attachment, DataInfo, RoutingContext},
 };
+use super::routing;
 use super::routing::face::Face;
+use super::routing::resource::Resource;
 use super::transport::Primitives;
 use super::Runtime;
 use async_std::sync::Arc;
@@ -28,7 +31,15 @@ use futures::future::{BoxFuture, FutureExt};
 use log::{error, trace};
 use serde_json::json;
 use std::collections::HashMap;
+use std::fmt::Write;
 use std::sync::Mutex;
+use zenoh_util::core::{ZError, ZErrorKind, ZResult};
+use zenoh_util::{zerror, zerror2};
+const CONFIG_PATH: &str = "/config";
+const ROUTES_PATH: &str = "/routing/routes";
+// The storages plugin answers on the admin path of each of its storages
+const STORAGES_PATH: &str = "/plugin/storages/backend/*/storage/*";
+
 type PluginsHandles = zenoh_plugin_trait::loading::PluginsHandles<
     super::plugins::Requirements,
     super::plugins::StartArgs,
@@ -36,23 +47,33 @@ type PluginsHandles = zenoh_plugin_trait::loading::PluginsHandles<
 
 pub struct AdminContext {
     runtime: Runtime,
-    plugins_mgr: PluginsHandles,
+    plugins_mgr: Arc<PluginsHandles>,
     pid_str: String,
     version: String,
+    // The log level configured when starting, restored when the log_level entry is deleted
+    log_level: log::LevelFilter,
 }
 
 type Handler = Box<dyn Fn(&AdminContext) -> BoxFuture<'_, (ZBuf, ZInt)> + Send + Sync>;
+// A handler answering for a set of paths given the queried key expression,
+// returning the path of each entry along with its value
+type CollectionHandler = Box<
+    dyn for<'a> Fn(&'a AdminContext, &'a str) -> BoxFuture<'a, Vec<(String, ZBuf, ZInt)>>
+        + Send
+        + Sync,
+>;
 
 pub struct AdminSpace {
     pid: PeerId,
     primitives: Mutex<Option<Arc<Face>>>,
     mappings: Mutex<HashMap<ZInt, String>>,
     handlers: HashMap<String, Arc<Handler>>,
+    collections: HashMap<String, Arc<CollectionHandler>>,
     context: Arc<AdminContext>,
 }
 
 impl AdminSpace {
-    pub async fn start(runtime: &Runtime, plugins_mgr: PluginsHandles, version: String) {
+    pub async fn start(runtime: &Runtime, plugins_mgr: Arc<PluginsHandles>, version: String) {
         let pid_str = runtime.get_pid_str();
         let root_path = format!("/@/router/{}", pid_str);
 
@@ -69,17 +90,48 @@ impl AdminSpace {
             [&root_path, "/linkstate/peers"].concat(),
             Arc::new(Box::new(|context| linkstate_peers_data(context).boxed())),
         );
+        handlers.insert(
+            [&root_path, "/acl"].concat(),
+            Arc::new(Box::new(|context| acl_data(context).boxed())),
+        );
+        handlers.insert(
+            [&root_path, "/routing/resources"].concat(),
+            Arc::new(Box::new(|context| routing_resources_data(context).boxed())),
+        );
+        handlers.insert(
+            [&root_path, "/peers"].concat(),
+            Arc::new(Box::new(|context| peers_data(context).boxed())),
+        );
+        handlers.insert(
+            [&root_path, "/metrics"].concat(),
+            Arc::new(Box::new(|context| metrics_data(context).boxed())),
+        );
+
+        let mut collections: HashMap<String, Arc<CollectionHandler>> = HashMap::new();
+        collections.insert(
+            [&root_path, "/session/*"].concat(),
+            Arc::new(Box::new(|context, _| sessions_data(context).boxed())),
+        );
+        collections.insert(
+            [&root_path, ROUTES_PATH, "/**"].concat(),
+            Arc::new(Box::new(|context, name| {
+                routing_routes_data(context, name).boxed()
+            })),
+        );
+
         let context = Arc::new(AdminContext {
             runtime: runtime.clone(),
             plugins_mgr,
             pid_str,
             version,
+            log_level: log::max_level(),
         });
         let admin = Arc::new(AdminSpace {
             pid: runtime.pid,
             primitives: Mutex::new(None),
             mappings: Mutex::new(HashMap::new()),
             handlers,
+            collections,
             context,
         });
 
@@ -87,6 +139,11 @@ impl AdminSpace {
         zlock!(admin.primitives).replace(primitives.clone());
 
         primitives.decl_queryable(&[&root_path, "/**"].concat().into(), EVAL, None);
+        primitives.decl_subscriber(
+            &[&root_path, CONFIG_PATH, "/**"].concat().into(),
+            &SubInfo::default(),
+            None,
+        );
     }
 
     pub fn reskey_to_string(&self, key: &ResKey) -> Option<String> {
@@ -149,6 +206,30 @@ impl Primitives for AdminSpace {
         trace!("recv Forget Queryable {:?}", _reskey);
     }
 
+    fn decl_liveliness_token(&self, _reskey: &ResKey, _routing_context: Option<RoutingContext>) {
+        trace!("recv Liveliness Token {:?}", _reskey);
+    }
+
+    fn forget_liveliness_token(&self, _reskey: &ResKey, _routing_context: Option<RoutingContext>) {
+        trace!("recv Forget Liveliness Token {:?}", _reskey);
+    }
+
+    fn decl_liveliness_subscriber(
+        &self,
+        _reskey: &ResKey,
+        _routing_context: Option<RoutingContext>,
+    ) {
+        trace!("recv Liveliness Subscriber {:?}", _reskey);
+    }
+
+    fn forget_liveliness_subscriber(
+        &self,
+        _reskey: &ResKey,
+        _routing_context: Option<RoutingContext>,
+    ) {
+        trace!("recv Forget Liveliness Subscriber {:?}", _reskey);
+    }
+
     fn send_data(
         &self,
         reskey: &ResKey,
@@ -157,6 +238,7 @@ impl Primitives for AdminSpace {
         congestion_control: CongestionControl,
         data_info: Option<DataInfo>,
         _routing_context: Option<RoutingContext>,
+        _attachment: Option<Attachment>,
     ) {
         trace!(
             "recv Data {:?} {:?} {:?} {:?} {:?}",
@@ -166,6 +248,41 @@ impl Primitives for AdminSpace {
             congestion_control,
             data_info,
         );
+        let name = match self.reskey_to_string(reskey) {
+            Some(name) => name,
+            None => {
+                error!("Unknown ResKey!!");
+                return;
+            }
+        };
+        let config_path = format!("/@/router/{}{}/", self.context.pid_str, CONFIG_PATH);
+        if let Some(key) = name.strip_prefix(&config_path) {
+            let key = key.to_string();
+            let kind = data_info
+                .and_then(|info| info.kind)
+                .unwrap_or(data_kind::DEFAULT);
+            let value = String::from_utf8_lossy(&payload.contiguous())
+                .trim()
+                .to_string();
+            let context = self.context.clone();
+            // router is not re-entrant
+            task::spawn(async move {
+                match config_write(&context, &key, kind, &value).await {
+                    Ok(()) => log::info!(
+                        "Admin space {} {}: {}",
+                        data_kind::to_string(kind),
+                        name,
+                        value
+                    ),
+                    Err(e) => log::warn!(
+                        "Admin space {} {} failed: {}",
+                        data_kind::to_string(kind),
+                        name,
+                        e
+                    ),
+                }
+            });
+        }
     }
 
     fn send_query(
@@ -176,6 +293,7 @@ impl Primitives for AdminSpace {
         target: QueryTarget,
         _consolidation: QueryConsolidation,
         _routing_context: Option<RoutingContext>,
+        _attachment: Option<Attachment>,
     ) {
         trace!(
             "recv Query {:?} {:?} {:?} {:?}",
@@ -189,21 +307,43 @@ impl Primitives for AdminSpace {
         let primitives = zlock!(self.primitives).as_ref().unwrap().clone();
 
         let mut matching_handlers = vec![];
-        match self.reskey_to_string(reskey) {
+        let mut matching_collections = vec![];
+        let name = match self.reskey_to_string(reskey) {
             Some(name) => {
                 for (path, handler) in &self.handlers {
                     if rname::intersect(&name, path) {
                         matching_handlers.push((path.clone(), handler.clone()));
                     }
                 }
+                for (path, handler) in &self.collections {
+                    if rname::intersect(&name, path) {
+                        matching_collections.push(handler.clone());
+                    }
+                }
+                name
+            }
+            None => {
+                error!("Unknown ResKey!!");
+                String::new()
             }
-            None => error!("Unknown ResKey!!"),
         };
 
         // router is not re-entrant
         task::spawn(async move {
+            let mut replies = vec![];
             for (path, handler) in matching_handlers {
                 let (payload, encoding) = handler(&context).await;
+                replies.push((path, payload, encoding));
+            }
+            for handler in matching_collections {
+                for (path, payload, encoding) in handler(&context, &name).await {
+                    if rname::intersect(&name, &path) {
+                        replies.push((path, payload, encoding));
+                    }
+                }
+            }
+
+            for (path, payload, encoding) in replies {
                 let mut data_info = DataInfo::new();
                 data_info.encoding = Some(encoding);
 
@@ -211,9 +351,11 @@ impl Primitives for AdminSpace {
                     qid,
                     EVAL,
                     pid,
+                    false,
                     ResKey::RName(path),
                     Some(data_info),
                     payload,
+                    None,
                 );
             }
 
@@ -226,15 +368,18 @@ impl Primitives for AdminSpace {
         qid: ZInt,
         replier_kind: ZInt,
         replier_id: PeerId,
+        is_error: bool,
         reskey: ResKey,
         info: Option<DataInfo>,
         payload: ZBuf,
+        _attachment: Option<Attachment>,
     ) {
         trace!(
-            "recv ReplyData {:?} {:?} {:?} {:?} {:?} {:?}",
+            "recv ReplyData {:?} {:?} {:?} {:?} {:?} {:?} {:?}",
             qid,
             replier_kind,
             replier_id,
+            is_error,
             reskey,
             info,
             payload
@@ -261,11 +406,66 @@ impl Primitives for AdminSpace {
         );
     }
 
+    fn send_matching_status(&self, _reskey: &ResKey, _matching: bool) {
+        trace!("recv MatchingStatus {:?} {}", _reskey, _matching);
+    }
+
     fn send_close(&self) {
         trace!("recv Close");
     }
 }
 
+// Apply a write on the config of the router. The acl entry is not writable.
+async fn config_write(context: &AdminContext, key: &str, kind: ZInt, value: &str) -> ZResult<()> {
+    match key {
+        "listeners" => {
+            let endpoint: EndPoint = value.parse()?;
+            if endpoint.locator.address.is_multicast() {
+                return zerror!(ZErrorKind::InvalidLocator {
+                    descr: format!("Multicast listener {} not supported", endpoint)
+                });
+            }
+            let manager = context.runtime.manager();
+            if kind == data_kind::DELETE {
+                manager.del_listener(&endpoint).await
+            } else {
+                manager.add_listener(endpoint).await.map(|_| ())
+            }
+        }
+        "peers" => {
+            let endpoint: EndPoint = value.parse()?;
+            if kind == data_kind::DELETE {
+                context.runtime.disconnect_peer(&endpoint).await
+            } else {
+                if !context.runtime.claim_peer_connector(&endpoint) {
+                    log::debug!("Already connecting to configured peer {}", endpoint);
+                    return Ok(());
+                }
+                let runtime = context.runtime.clone();
+                task::spawn(async move { runtime.peer_connector(endpoint).await });
+                Ok(())
+            }
+        }
+        "log_level" => {
+            // The log level can not go beyond the one enabled when starting zenohd
+            let level = if kind == data_kind::DELETE {
+                context.log_level
+            } else {
+                value.parse().map_err(|_| {
+                    zerror2!(ZErrorKind::Other {
+                        descr: format!("Invalid log level: {}", value)
+                    })
+                })?
+            };
+            log::set_max_level(level);
+            Ok(())
+        }
+        _ => zerror!(ZErrorKind::Other {
+            descr: format!("Unknown or read-only config entry: {}", key)
+        }),
+    }
+}
+
 pub async fn router_data(context: &AdminContext) -> (ZBuf, ZInt) {
     let transport_mgr = context.runtime.manager().clone();
 
@@ -312,6 +512,98 @@ pub async fn router_data(context: &AdminContext) -> (ZBuf, ZInt) {
     (ZBuf::from(json.to_string().as_bytes()), encoding::APP_JSON)
 }
 
+pub async fn sessions_data(context: &AdminContext) -> Vec<(String, ZBuf, ZInt)> {
+    let mut sessions = vec![];
+    for transport in context.runtime.manager().get_transports() {
+        let peer = match transport.get_peer() {
+            Ok(peer) => peer,
+            Err(_) => continue,
+        };
+        let links: Vec<serde_json::Value> = peer
+            .links
+            .iter()
+            .map(|link| {
+                json!({
+                    "src": link.src.to_string(),
+                    "dst": link.dst.to_string(),
+                    "mtu": link.mtu,
+                    "is_reliable": link.is_reliable,
+                    "is_streamed": link.is_streamed,
+                })
+            })
+            .collect();
+        let json = json!({
+            "peer": peer.pid.to_string(),
+            "whatami": whatami::to_string(peer.whatami),
+            "links": links,
+            "is_qos": peer.is_qos,
+            "is_shm": peer.is_shm,
+            "lease": transport.get_lease().ok().flatten().map(|l| l.as_millis() as u64),
+            "sn_resolution": transport.get_sn_resolution().ok(),
+            "user": transport.get_user().ok().flatten(),
+        });
+        #[cfg(feature = "stats")]
+        let mut json = json;
+        #[cfg(feature = "stats")]
+        if let Ok(stats) = transport.get_stats() {
+            json["stats"] = json!({
+                "tx_msgs": stats.tx_msgs(),
+                "tx_bytes": stats.tx_bytes(),
+                "rx_msgs": stats.rx_msgs(),
+                "rx_bytes": stats.rx_bytes(),
+            });
+        }
+        log::trace!("AdminSpace sessions_data: {:?}", json);
+        let path = format!("/@/router/{}/session/{}", context.pid_str, peer.pid);
+        sessions.push((
+            path,
+            ZBuf::from(json.to_string().as_bytes()),
+            encoding::APP_JSON,
+        ));
+    }
+    sessions
+}
+
+pub async fn routing_resources_data(context: &AdminContext) -> (ZBuf, ZInt) {
+    let tables = zread!(context.runtime.router.tables);
+    let json = routing::admin::resources(&tables);
+    log::trace!("AdminSpace routing_resources_data: {:?}", json);
+    (ZBuf::from(json.to_string().as_bytes()), encoding::APP_JSON)
+}
+
+// The routes are computed for each resource matching the key expression following
+// the routes path, or for the key expression itself if it has no wildcard
+pub async fn routing_routes_data(context: &AdminContext, name: &str) -> Vec<(String, ZBuf, ZInt)> {
+    let routes_path = format!("/@/router/{}{}", context.pid_str, ROUTES_PATH);
+    match name.strip_prefix(&routes_path) {
+        Some(key_expr) if key_expr.starts_with('/') => {
+            let tables = zread!(context.runtime.router.tables);
+            let mut keys: Vec<String> = Resource::get_matches(&tables, key_expr)
+                .iter()
+                .filter_map(|res| res.upgrade())
+                .map(|res| res.name())
+                .collect();
+            if !key_expr.contains('*') {
+                keys.push(key_expr.to_string());
+            }
+            keys.sort();
+            keys.dedup();
+            keys.into_iter()
+                .map(|key| {
+                    let json = routing::admin::data_routes(&tables, &key);
+                    log::trace!("AdminSpace routing_routes_data: {:?}", json);
+                    (
+                        [&routes_path, key.as_str()].concat(),
+                        ZBuf::from(json.to_string().as_bytes()),
+                        encoding::APP_JSON,
+                    )
+                })
+                .collect()
+        }
+        _ => vec![],
+    }
+}
+
 pub async fn linkstate_routers_data(context: &AdminContext) -> (ZBuf, ZInt) {
     let tables = zread!(context.runtime.router.tables);
 
@@ -322,6 +614,238 @@ pub async fn linkstate_routers_data(context: &AdminContext) -> (ZBuf, ZInt) {
     res
 }
 
+pub async fn acl_data(context: &AdminContext) -> (ZBuf, ZInt) {
+    let tables = zread!(context.runtime.router.tables);
+
+    let json = match tables.acl.as_ref() {
+        Some(acl) => {
+            let rules: Vec<serde_json::Value> = acl
+                .rules()
+                .iter()
+                .map(|rule| {
+                    json!({
+                        "permission": rule.permission.to_string(),
+                        "actions": rule.actions.iter().map(|a| a.to_string()).collect::<Vec<String>>(),
+                        "subject": rule.subject.to_string(),
+                        "key_expr": rule.key_expr,
+                    })
+                })
+                .collect();
+            json!({
+                "enabled": true,
+                "default": acl.default_permission().to_string(),
+                "rules": rules,
+            })
+        }
+        None => json!({ "enabled": false }),
+    };
+    log::trace!("AdminSpace acl_data: {:?}", json);
+    (ZBuf::from(json.to_string().as_bytes()), encoding::APP_JSON)
+}
+
+pub async fn peers_data(context: &AdminContext) -> (ZBuf, ZInt) {
+    let peers: Vec<serde_json::Value> = zlock!(context.runtime.peer_connectors)
+        .values()
+        .map(|connector| {
+            json!({
+                "endpoint": connector.endpoint.to_string(),
+                "state": connector.state.to_string(),
+                "attempts": connector.attempts,
+            })
+        })
+        .collect();
+    let json = serde_json::Value::Array(peers);
+    log::trace!("AdminSpace peers_data: {:?}", json);
+    (ZBuf::from(json.to_string().as_bytes()), encoding::APP_JSON)
+}
+
+// Write a metric family in the Prometheus text exposition format.
+// Each sample is a list of labels along with the sample value.
+fn write_metric(
+    out: &mut String,
+    name: &str,
+    kind: &str,
+    help: &str,
+    samples: &[(Vec<(&str, String)>, usize)],
+) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+    for (labels, value) in samples.iter() {
+        if labels.is_empty() {
+            let _ = writeln!(out, "{} {}", name, value);
+        } else {
+            let labels: Vec<String> = labels
+                .iter()
+                .map(|(k, v)| format!("{}=\"{}\"", k, v))
+                .collect();
+            let _ = writeln!(out, "{}{{{}}} {}", name, labels.join(","), value);
+        }
+    }
+}
+
+pub async fn metrics_data(context: &AdminContext) -> (ZBuf, ZInt) {
+    let mut out = String::new();
+
+    // transports metrics
+    let transports = context.runtime.manager().get_transports();
+    write_metric(
+        &mut out,
+        "zenoh_transports",
+        "gauge",
+        "Number of open unicast transports.",
+        &[(vec![], transports.len())],
+    );
+
+    let mut queues = vec![];
+    #[cfg(feature = "stats")]
+    let mut stats = vec![];
+    for transport in transports.iter() {
+        let peer = match transport.get_pid() {
+            Ok(pid) => pid.to_string(),
+            Err(_) => continue,
+        };
+        if let Ok(depths) = transport.get_queue_depths() {
+            for (priority, depth) in depths.into_iter().enumerate() {
+                let labels = vec![("peer", peer.clone()), ("priority", priority.to_string())];
+                queues.push((labels, depth));
+            }
+        }
+        #[cfg(feature = "stats")]
+        if let Ok(s) = transport.get_stats() {
+            stats.push((vec![("peer", peer)], s));
+        }
+    }
+    #[cfg(feature = "stats")]
+    {
+        type Counter = fn(&super::transport::TransportStatsUnicast) -> usize;
+        let counters: [(&str, &str, Counter); 4] = [
+            (
+                "zenoh_transport_tx_messages_total",
+                "Number of messages sent on the transport.",
+                |s| s.tx_msgs(),
+            ),
+            (
+                "zenoh_transport_tx_bytes_total",
+                "Number of bytes sent on the transport.",
+                |s| s.tx_bytes(),
+            ),
+            (
+                "zenoh_transport_rx_messages_total",
+                "Number of messages received on the transport.",
+                |s| s.rx_msgs(),
+            ),
+            (
+                "zenoh_transport_rx_bytes_total",
+                "Number of bytes received on the transport.",
+                |s| s.rx_bytes(),
+            ),
+        ];
+        for (name, help, counter) in counters.iter() {
+            let samples: Vec<_> = stats
+                .iter()
+                .map(|(labels, s)| (labels.clone(), counter(s)))
+                .collect();
+            write_metric(&mut out, name, "counter", help, &samples);
+        }
+    }
+    write_metric(
+        &mut out,
+        "zenoh_transport_queue_batches",
+        "gauge",
+        "Number of batches waiting to be transmitted in the transport pipeline.",
+        &queues,
+    );
+
+    // routing tables metrics
+    {
+        let tables = zread!(context.runtime.router.tables);
+        write_metric(
+            &mut out,
+            "zenoh_routing_faces",
+            "gauge",
+            "Number of faces in the routing tables.",
+            &[(vec![], tables.faces.len())],
+        );
+        write_metric(
+            &mut out,
+            "zenoh_routing_resources",
+            "gauge",
+            "Number of resources in the routing tables.",
+            &[(vec![], Resource::tree_size(&tables.root_res))],
+        );
+        write_metric(
+            &mut out,
+            "zenoh_routing_subscriptions",
+            "gauge",
+            "Number of subscriptions in the routing tables.",
+            &[
+                (
+                    vec![("scope", "router".to_string())],
+                    tables.router_subs.len(),
+                ),
+                (vec![("scope", "peer".to_string())], tables.peer_subs.len()),
+            ],
+        );
+        write_metric(
+            &mut out,
+            "zenoh_routing_queryables",
+            "gauge",
+            "Number of queryables in the routing tables.",
+            &[
+                (
+                    vec![("scope", "router".to_string())],
+                    tables.router_qabls.len(),
+                ),
+                (vec![("scope", "peer".to_string())], tables.peer_qabls.len()),
+            ],
+        );
+        let mut nodes = vec![];
+        if let Some(net) = tables.routers_net.as_ref() {
+            nodes.push((
+                vec![("network", "routers".to_string())],
+                net.graph.node_count(),
+            ));
+        }
+        if let Some(net) = tables.peers_net.as_ref() {
+            nodes.push((
+                vec![("network", "peers".to_string())],
+                net.graph.node_count(),
+            ));
+        }
+        write_metric(
+            &mut out,
+            "zenoh_routing_nodes",
+            "gauge",
+            "Number of nodes in the link state networks.",
+            &nodes,
+        );
+    }
+
+    // plugins metrics
+    write_metric(
+        &mut out,
+        "zenoh_plugins",
+        "gauge",
+        "Number of loaded plugins.",
+        &[(vec![], context.plugins_mgr.plugins().len())],
+    );
+    let storages_path = format!("/@/router/{}{}", context.pid_str, STORAGES_PATH);
+    let storages = {
+        let tables = zread!(context.runtime.router.tables);
+        routing::admin::local_queryables_count(&tables, &storages_path)
+    };
+    write_metric(
+        &mut out,
+        "zenoh_storages",
+        "gauge",
+        "Number of storages.",
+        &[(vec![], storages)],
+    );
+
+    log::trace!("AdminSpace metrics_data: {:?}", out);
+    (ZBuf::from(out.as_bytes()), encoding::TEXT_PLAIN)
+}
+
 pub async fn linkstate_peers_data(context: &AdminContext) -> (ZBuf, ZInt) {
     (
         ZBuf::from(
@@ -340,3 +864,274 @@ pub async fn linkstate_peers_data(context: &AdminContext) -> (ZBuf, ZInt) {
         encoding::TEXT_PLAIN,
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::net::config;
+    use crate::net::plugins::PluginsManager;
+    use crate::net::Session;
+    use async_std::prelude::FutureExt as _;
+    use std::time::Duration;
+    use zenoh_util::LibLoader;
+
+    const TIMEOUT: Duration = Duration::from_secs(60);
+
+    async fn admin_context(listener: &str) -> AdminContext {
+        let mut config = config::peer();
+        config.insert(config::ZN_LISTENER_KEY, listener.to_string());
+        config.insert(config::ZN_MULTICAST_SCOUTING_KEY, "false".to_string());
+        let runtime = Runtime::new(0, config, None).await.unwrap();
+        AdminContext {
+            pid_str: runtime.get_pid_str(),
+            plugins_mgr: plugins_mgr(&runtime),
+            runtime,
+            version: "test".to_string(),
+            log_level: log::max_level(),
+        }
+    }
+
+    fn plugins_mgr(runtime: &Runtime) -> Arc<PluginsHandles> {
+        let (starter, _) = PluginsManager::builder()
+            .into_dynamic(LibLoader::new::<&str>(&[], false))
+            .get_requirements();
+        let (plugins_mgr, _) = starter.start(&(runtime.clone(), clap::ArgMatches::default()));
+        Arc::new(plugins_mgr)
+    }
+
+    #[test]
+    fn write_metric_format() {
+        let mut out = String::new();
+        write_metric(
+            &mut out,
+            "zenoh_test",
+            "counter",
+            "A test metric.",
+            &[
+                (vec![], 1),
+                (
+                    vec![("peer", "A".to_string()), ("priority", "0".to_string())],
+                    2,
+                ),
+            ],
+        );
+        write_metric(&mut out, "zenoh_empty", "gauge", "No samples.", &[]);
+        assert_eq!(
+            out,
+            "# HELP zenoh_test A test metric.\n\
+             # TYPE zenoh_test counter\n\
+             zenoh_test 1\n\
+             zenoh_test{peer=\"A\",priority=\"0\"} 2\n\
+             # HELP zenoh_empty No samples.\n\
+             # TYPE zenoh_empty gauge\n"
+        );
+    }
+
+    #[test]
+    fn metrics() {
+        task::block_on(async {
+            let context = admin_context("inproc/zenoh-test-admin-metrics").await;
+            let session = Session::init(context.runtime.clone(), true, vec![], vec![]).await;
+            let storage = format!(
+                "/@/router/{}/plugin/storages/backend/memory/storage/test",
+                context.pid_str
+            );
+            let storage = session
+                .declare_queryable(&storage.into(), EVAL)
+                .await
+                .unwrap();
+            let other = session
+                .declare_queryable(&"/test/admin/metrics".into(), EVAL)
+                .await
+                .unwrap();
+
+            let (data, encoding) = metrics_data(&context).await;
+            assert_eq!(encoding, encoding::TEXT_PLAIN);
+            let data = String::from_utf8(data.to_vec()).unwrap();
+            let lines: Vec<&str> = data.lines().collect();
+            for line in [
+                "# TYPE zenoh_transports gauge",
+                "zenoh_transports 0",
+                "# TYPE zenoh_routing_faces gauge",
+                "# TYPE zenoh_routing_queryables gauge",
+                "zenoh_routing_nodes{network=\"peers\"} 1",
+                "zenoh_plugins 0",
+                "# HELP zenoh_storages Number of storages.",
+                "zenoh_storages 1",
+            ]
+            .iter()
+            {
+                assert!(lines.contains(line), "{:?} not in {:?}", line, lines);
+            }
+
+            other.undeclare().await.unwrap();
+            storage.undeclare().await.unwrap();
+            session.close().await.unwrap();
+            context.runtime.close().await.unwrap();
+        });
+    }
+
+    #[test]
+    fn sessions() {
+        task::block_on(async {
+            let locator = "inproc/zenoh-test-admin-sessions";
+            let context = admin_context(locator).await;
+            let mut config = config::peer();
+            config.insert(config::ZN_PEER_KEY, locator.to_string());
+            config.insert(config::ZN_MULTICAST_SCOUTING_KEY, "false".to_string());
+            let peer = crate::net::open(config).await.unwrap();
+            let peer_id = peer.id().await;
+
+            let sessions = async {
+                loop {
+                    let sessions = sessions_data(&context).await;
+                    if !sessions.is_empty() {
+                        break sessions;
+                    }
+                    task::sleep(Duration::from_millis(100)).await;
+                }
+            }
+            .timeout(TIMEOUT)
+            .await
+            .unwrap();
+            assert_eq!(sessions.len(), 1);
+            let (path, data, encoding) = &sessions[0];
+            assert_eq!(
+                path,
+                &format!("/@/router/{}/session/{}", context.pid_str, peer_id)
+            );
+            assert_eq!(*encoding, encoding::APP_JSON);
+            let json: serde_json::Value = serde_json::from_slice(&data.to_vec()).unwrap();
+            assert_eq!(json["peer"], json!(peer_id));
+            assert_eq!(json["whatami"], json!("Peer"));
+            assert_eq!(json["links"].as_array().unwrap().len(), 1);
+            assert_eq!(json["links"][0]["src"], json!(locator));
+            assert!(json["lease"].is_u64());
+            #[cfg(feature = "stats")]
+            assert!(json["stats"]["rx_msgs"].is_u64());
+
+            peer.close().await.unwrap();
+            context.runtime.close().await.unwrap();
+        });
+    }
+
+    #[test]
+    fn routing() {
+        task::block_on(async {
+            let context = admin_context("inproc/zenoh-test-admin-routing").await;
+            let session = Session::init(context.runtime.clone(), true, vec![], vec![]).await;
+            let sub_info = SubInfo::default();
+            let sub_a = session
+                .declare_subscriber(&"/test/routes/a".into(), &sub_info)
+                .await
+                .unwrap();
+            let sub_b = session
+                .declare_subscriber(&"/test/routes/b".into(), &sub_info)
+                .await
+                .unwrap();
+            let routes_path = format!("/@/router/{}{}", context.pid_str, ROUTES_PATH);
+
+            // The resources are listed along with their subscribers
+            let (data, encoding) = routing_resources_data(&context).await;
+            assert_eq!(encoding, encoding::APP_JSON);
+            let json: serde_json::Value = serde_json::from_slice(&data.to_vec()).unwrap();
+            let resource = json
+                .as_array()
+                .unwrap()
+                .iter()
+                .find(|res| res["name"] == "/test/routes/a")
+                .unwrap();
+            assert_eq!(resource["subscribers"].as_array().unwrap().len(), 1);
+
+            // A wildcard gets one reply per matching resource, keyed by the resource
+            let replies =
+                routing_routes_data(&context, &[&routes_path, "/test/routes/*"].concat()).await;
+            let keys: Vec<&str> = replies.iter().map(|(key, _, _)| key.as_str()).collect();
+            assert_eq!(
+                keys,
+                vec![
+                    [&routes_path, "/test/routes/a"].concat(),
+                    [&routes_path, "/test/routes/b"].concat(),
+                ]
+            );
+            for (key, data, encoding) in replies.iter() {
+                assert_eq!(*encoding, encoding::APP_JSON);
+                let json: serde_json::Value = serde_json::from_slice(&data.to_vec()).unwrap();
+                assert_eq!(json["key_expr"], json!(key.strip_prefix(&routes_path)));
+                assert_eq!(json["peers_data_routes"].as_array().unwrap().len(), 1);
+            }
+
+            // A key without wildcard gets a reply even if no resource is declared on it
+            let replies =
+                routing_routes_data(&context, &[&routes_path, "/test/routes/c"].concat()).await;
+            assert_eq!(replies.len(), 1);
+            assert_eq!(replies[0].0, [&routes_path, "/test/routes/c"].concat());
+
+            let replies = routing_routes_data(&context, &routes_path).await;
+            assert!(replies.is_empty());
+
+            sub_b.undeclare().await.unwrap();
+            sub_a.undeclare().await.unwrap();
+            session.close().await.unwrap();
+            context.runtime.close().await.unwrap();
+        });
+    }
+
+    #[test]
+    fn config_write_acl() {
+        task::block_on(async {
+            let pid = "a1b2c3";
+            let rules = std::env::temp_dir().join("zenoh-test-admin-acl-rules.txt");
+            std::fs::write(
+                &rules,
+                format!("allow admin peer:{} /@/router/*/config/listeners", pid),
+            )
+            .unwrap();
+            let mut config = config::peer();
+            config.insert(
+                config::ZN_LISTENER_KEY,
+                "inproc/zenoh-test-admin-acl".to_string(),
+            );
+            config.insert(config::ZN_MULTICAST_SCOUTING_KEY, "false".to_string());
+            config.insert(
+                config::ZN_ACL_RULES_KEY,
+                rules.to_str().unwrap().to_string(),
+            );
+            let runtime = Runtime::new(0, config, Some(pid)).await.unwrap();
+            AdminSpace::start(&runtime, plugins_mgr(&runtime), "test".to_string()).await;
+            let config_path = format!("/@/router/{}{}", runtime.get_pid_str(), CONFIG_PATH);
+
+            // A local session, like the one of the REST plugin, is subject to the rules of
+            // the router's own pid: it may add a listener but not re-point the router
+            let session = Session::init(runtime.clone(), true, vec![], vec![]).await;
+            session
+                .write(
+                    &[&config_path, "/peers"].concat().into(),
+                    "inproc/zenoh-test-admin-acl-peer".as_bytes().into(),
+                )
+                .await
+                .unwrap();
+            let listener: EndPoint = "inproc/zenoh-test-admin-acl-listener".parse().unwrap();
+            session
+                .write(
+                    &[&config_path, "/listeners"].concat().into(),
+                    listener.to_string().as_bytes().into(),
+                )
+                .await
+                .unwrap();
+            async {
+                while !runtime.manager().get_listeners().contains(&listener) {
+                    task::sleep(Duration::from_millis(100)).await;
+                }
+            }
+            .timeout(TIMEOUT)
+            .await
+            .unwrap();
+            assert!(zlock!(runtime.peer_connectors).is_empty());
+
+            session.close().await.unwrap();
+            runtime.close().await.unwrap();
+            let _ = std::fs::remove_file(rules);
+        });
+    }
+}
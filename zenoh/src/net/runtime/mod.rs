@@ -15,27 +15,34 @@ mod adminspace;
 pub mod orchestrator;
 
 use super::link;
-use super::link::{Link, Locator};
+use super::link::{EndPoint, Link, Locator};
 use super::plugins;
 use super::protocol;
 use super::protocol::core::{whatami, PeerId, WhatAmI};
 use super::protocol::proto::{ZenohBody, ZenohMessage};
 use super::routing;
+use super::routing::acl::AccessControl;
+use super::routing::face::Face;
 use super::routing::pubsub::full_reentrant_route_data;
 use super::routing::router::{LinkStateInterceptor, Router};
 use super::transport;
 use super::transport::{
-    TransportEventHandler, TransportManager, TransportManagerConfig, TransportMulticast,
-    TransportMulticastEventHandler, TransportPeer, TransportPeerEventHandler, TransportUnicast,
+    Primitives, TransportEventHandler, TransportManager, TransportManagerConfig,
+    TransportMulticast, TransportMulticastEventHandler, TransportPeer, TransportPeerEventHandler,
+    TransportUnicast,
 };
 pub use adminspace::AdminSpace;
 use async_std::sync::Arc;
+use orchestrator::PeerConnector;
 use std::any::Any;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
 use uhlc::HLC;
 use zenoh_util::core::{ZError, ZErrorKind, ZResult};
 use zenoh_util::properties::config::*;
-use zenoh_util::sync::get_mut_unchecked;
-use zenoh_util::{zerror, zerror2};
+use zenoh_util::sync::{get_mut_unchecked, RetryPolicy};
+use zenoh_util::{zerror, zerror2, zparse};
 
 pub struct RuntimeState {
     pub pid: PeerId,
@@ -44,6 +51,9 @@ pub struct RuntimeState {
     pub config: ConfigProperties,
     pub manager: TransportManager,
     pub hlc: Option<Arc<HLC>>,
+    pub retry_policy: RetryPolicy,
+    pub(crate) peer_connectors: Mutex<HashMap<Locator, PeerConnector>>,
+    closed: AtomicBool,
 }
 
 #[derive(Clone)]
@@ -98,7 +108,19 @@ impl Runtime {
             None
         };
 
-        let router = Arc::new(Router::new(pid, whatami, hlc.clone()));
+        let queries_default_timeout = std::time::Duration::from_millis(zparse!(
+            config.get_or(&ZN_QUERY_TIMEOUT_KEY, ZN_QUERY_TIMEOUT_DEFAULT)
+        )?);
+
+        let retry_policy = RetryPolicy::from_config(&config)?;
+
+        let router = Arc::new(Router::new(
+            pid,
+            whatami,
+            hlc.clone(),
+            queries_default_timeout,
+        ));
+        zwrite!(router.tables).acl = AccessControl::from_config(&config).await?;
 
         let handler = Arc::new(RuntimeTransportEventHandler {
             runtime: std::sync::RwLock::new(None),
@@ -120,6 +142,9 @@ impl Runtime {
                 config: config.clone(),
                 manager: transport_manager,
                 hlc,
+                retry_policy,
+                peer_connectors: Mutex::new(HashMap::new()),
+                closed: AtomicBool::new(false),
             }),
         };
         *handler.runtime.write().unwrap() = Some(runtime.clone());
@@ -160,12 +185,22 @@ impl Runtime {
 
     pub async fn close(&self) -> ZResult<()> {
         log::trace!("Runtime::close())");
+        // Closing the transports below must not trigger reconnections
+        self.closed.store(true, Ordering::Release);
         for session in &mut self.manager().get_transports() {
             session.close().await?;
         }
+        for transport in self.manager().get_transports_multicast() {
+            transport.close().await?;
+        }
         Ok(())
     }
 
+    #[inline]
+    pub(crate) fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Acquire)
+    }
+
     pub fn get_pid_str(&self) -> String {
         self.pid.to_string()
     }
@@ -188,7 +223,7 @@ impl TransportEventHandler for RuntimeTransportEventHandler {
         match zread!(self.runtime).as_ref() {
             Some(runtime) => Ok(Arc::new(RuntimeSession {
                 runtime: runtime.clone(),
-                locator: std::sync::RwLock::new(None),
+                endpoint: std::sync::RwLock::new(None),
                 sub_event_handler: runtime.router.new_transport_unicast(transport).unwrap(),
             })),
             None => zerror!(ZErrorKind::Other {
@@ -199,16 +234,49 @@ impl TransportEventHandler for RuntimeTransportEventHandler {
 
     fn new_multicast(
         &self,
-        _transport: TransportMulticast,
+        transport: TransportMulticast,
     ) -> ZResult<Arc<dyn TransportMulticastEventHandler>> {
-        // @TODO
-        unimplemented!();
+        match zread!(self.runtime).as_ref() {
+            Some(runtime) => {
+                let face = runtime.router.new_transport_multicast(transport)?;
+                Ok(Arc::new(RuntimeMulticastGroup {
+                    runtime: runtime.clone(),
+                    face,
+                }))
+            }
+            None => zerror!(ZErrorKind::Other {
+                descr: "Runtime not yet ready!".to_string()
+            }),
+        }
+    }
+}
+
+pub(super) struct RuntimeMulticastGroup {
+    runtime: Runtime,
+    face: Face,
+}
+
+impl TransportMulticastEventHandler for RuntimeMulticastGroup {
+    fn new_peer(&self, peer: TransportPeer) -> ZResult<Arc<dyn TransportPeerEventHandler>> {
+        log::debug!("New peer {} on multicast group {}", peer.pid, self.face);
+        let interceptor = self.runtime.router.new_peer_multicast(self.face.clone())?;
+        Ok(interceptor)
+    }
+
+    fn closing(&self) {
+        self.face.send_close();
+    }
+
+    fn closed(&self) {}
+
+    fn as_any(&self) -> &dyn Any {
+        self
     }
 }
 
 pub(super) struct RuntimeSession {
     pub(super) runtime: Runtime,
-    pub(super) locator: std::sync::RwLock<Option<Locator>>,
+    pub(super) endpoint: std::sync::RwLock<Option<EndPoint>>,
     pub(super) sub_event_handler: Arc<LinkStateInterceptor>,
 }
 
@@ -229,6 +297,7 @@ impl TransportPeerEventHandler for RuntimeSession {
                     data.data_info,
                     data.payload,
                     msg.routing_context,
+                    msg.attachment,
                 );
                 return Ok(());
             } else {
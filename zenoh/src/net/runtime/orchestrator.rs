@@ -20,6 +20,7 @@ use super::{Runtime, RuntimeSession};
 use async_std::net::UdpSocket;
 use futures::prelude::*;
 use socket2::{Domain, Socket, Type};
+use std::fmt;
 use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 use std::time::Duration;
 use zenoh_util::core::{ZError, ZErrorKind, ZResult};
@@ -31,9 +32,6 @@ const SEND_BUF_INITIAL_SIZE: usize = 8;
 const SCOUT_INITIAL_PERIOD: u64 = 1000; //ms
 const SCOUT_MAX_PERIOD: u64 = 8000; //ms
 const SCOUT_PERIOD_INCREASE_FACTOR: u64 = 2;
-const CONNECTION_RETRY_INITIAL_PERIOD: u64 = 1000; //ms
-const CONNECTION_RETRY_MAX_PERIOD: u64 = 4000; //ms
-const CONNECTION_RETRY_PERIOD_INCREASE_FACTOR: u64 = 2;
 const ROUTER_DEFAULT_LISTENER: &str = "tcp/0.0.0.0:7447";
 const PEER_DEFAULT_LISTENER: &str = "tcp/0.0.0.0:0";
 
@@ -42,6 +40,32 @@ pub enum Loop {
     Break,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum PeerConnectorState {
+    Connecting,
+    Connected,
+    Failed,
+}
+
+impl fmt::Display for PeerConnectorState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PeerConnectorState::Connecting => write!(f, "connecting"),
+            PeerConnectorState::Connected => write!(f, "connected"),
+            PeerConnectorState::Failed => write!(f, "failed"),
+        }
+    }
+}
+
+// The connection status of a configured peer, as exposed in the admin space.
+// The attempts are the failed ones since the last successful connection.
+#[derive(Clone, Debug)]
+pub(crate) struct PeerConnector {
+    pub(crate) endpoint: EndPoint,
+    pub(crate) state: PeerConnectorState,
+    pub(crate) attempts: usize,
+}
+
 impl Runtime {
     pub async fn start(&mut self) -> ZResult<()> {
         match self.whatami {
@@ -66,7 +90,7 @@ impl Runtime {
                 "" => None,
                 s => Some(s.parse().unwrap()),
             })
-            .collect::<Vec<Locator>>();
+            .collect::<Vec<EndPoint>>();
         let scouting = config
             .get_or(&ZN_MULTICAST_SCOUTING_KEY, ZN_MULTICAST_SCOUTING_DEFAULT)
             .to_lowercase()
@@ -116,14 +140,10 @@ impl Runtime {
                 }
             }
             _ => {
-                for locator in &peers {
-                    let endpoint = EndPoint {
-                        locator: locator.clone(),
-                        config: None,
-                    };
-                    match self.manager().open_transport(endpoint).await {
+                for endpoint in &peers {
+                    match self.manager().open_transport(endpoint.clone()).await {
                         Ok(_) => return Ok(()),
-                        Err(err) => log::warn!("Unable to connect to {}! {}", locator, err),
+                        Err(err) => log::warn!("Unable to connect to {}! {}", endpoint, err),
                     }
                 }
                 log::error!("Unable to connect to any of {:?}! ", peers);
@@ -151,7 +171,7 @@ impl Runtime {
                 "" => None,
                 s => Some(s.parse().unwrap()),
             })
-            .collect::<Vec<Locator>>();
+            .collect::<Vec<EndPoint>>();
         let scouting = config
             .get_or(&ZN_MULTICAST_SCOUTING_KEY, ZN_MULTICAST_SCOUTING_DEFAULT)
             .to_lowercase()
@@ -231,7 +251,7 @@ impl Runtime {
                 "" => None,
                 s => Some(s.parse().unwrap()),
             })
-            .collect::<Vec<Locator>>();
+            .collect::<Vec<EndPoint>>();
         let scouting = config
             .get_or(&ZN_MULTICAST_SCOUTING_KEY, ZN_MULTICAST_SCOUTING_DEFAULT)
             .to_lowercase()
@@ -295,6 +315,21 @@ impl Runtime {
                 locator: listener.clone(),
                 config: None,
             };
+            if listener.address.is_multicast() {
+                match self.manager().open_transport_multicast(endpoint).await {
+                    Ok(_) => log::debug!("Multicast group {} joined", listener),
+                    Err(err) => {
+                        log::error!("Unable to join multicast group {} : {}", listener, err);
+                        return zerror!(
+                            ZErrorKind::IoError {
+                                descr: "".to_string()
+                            },
+                            err
+                        );
+                    }
+                }
+                continue;
+            }
             match self.manager().add_listener(endpoint).await {
                 Ok(listener) => log::debug!("Listener {} added", listener),
                 Err(err) => {
@@ -481,16 +516,21 @@ impl Runtime {
         Ok(std::net::UdpSocket::from(socket).into())
     }
 
-    async fn peer_connector(&self, peer: Locator) {
-        let mut delay = CONNECTION_RETRY_INITIAL_PERIOD;
-        loop {
+    pub(super) async fn peer_connector(&self, peer: EndPoint) {
+        let mut retry = self.retry_policy.retry();
+        zlock!(self.peer_connectors).insert(
+            peer.locator.clone(),
+            PeerConnector {
+                endpoint: peer.clone(),
+                state: PeerConnectorState::Connecting,
+                attempts: 0,
+            },
+        );
+        while !self.is_closed() {
             log::trace!("Trying to connect to configured peer {}", peer);
-            let endpoint = EndPoint {
-                locator: peer.clone(),
-                config: None,
-            };
-            if let Ok(transport) = self.manager().open_transport(endpoint).await {
+            if let Ok(transport) = self.manager().open_transport(peer.clone()).await {
                 log::debug!("Successfully connected to configured peer {}", peer);
+                self.set_peer_connector(&peer, PeerConnectorState::Connected, 0);
                 if let Some(orch_transport) = transport
                     .get_callback()
                     .unwrap()
@@ -498,23 +538,100 @@ impl Runtime {
                     .as_any()
                     .downcast_ref::<super::RuntimeSession>()
                 {
-                    *zwrite!(orch_transport.locator) = Some(peer);
+                    *zwrite!(orch_transport.endpoint) = Some(peer);
                 }
                 break;
             }
+            let delay = match retry.next_delay() {
+                Some(delay) => delay,
+                None => {
+                    log::warn!(
+                        "Unable to connect to configured peer {} after {} attempts. Giving up.",
+                        peer,
+                        retry.attempts()
+                    );
+                    self.set_peer_connector(&peer, PeerConnectorState::Failed, retry.attempts());
+                    break;
+                }
+            };
             log::debug!(
                 "Unable to connect to configured peer {}. Retry in {} ms.",
                 peer,
-                delay
+                delay.as_millis()
             );
-            async_std::task::sleep(Duration::from_millis(delay)).await;
-            delay *= CONNECTION_RETRY_PERIOD_INCREASE_FACTOR;
-            if delay > CONNECTION_RETRY_MAX_PERIOD {
-                delay = CONNECTION_RETRY_MAX_PERIOD;
+            self.set_peer_connector(&peer, PeerConnectorState::Connecting, retry.attempts());
+            async_std::task::sleep(delay).await;
+            // Stop retrying if the peer has been removed in the meantime
+            if !zlock!(self.peer_connectors).contains_key(&peer.locator) {
+                log::debug!("Stop connecting to removed peer {}", peer);
+                break;
+            }
+        }
+    }
+
+    // Register a configured peer, unless a connector is already running for its locator.
+    // A connector that gave up is replaced.
+    pub(super) fn claim_peer_connector(&self, peer: &EndPoint) -> bool {
+        let mut connectors = zlock!(self.peer_connectors);
+        match connectors.get(&peer.locator) {
+            Some(connector) if connector.state != PeerConnectorState::Failed => false,
+            _ => {
+                connectors.insert(
+                    peer.locator.clone(),
+                    PeerConnector {
+                        endpoint: peer.clone(),
+                        state: PeerConnectorState::Connecting,
+                        attempts: 0,
+                    },
+                );
+                true
             }
         }
     }
 
+    // Update the status of a configured peer, unless it has been removed
+    fn set_peer_connector(&self, peer: &EndPoint, state: PeerConnectorState, attempts: usize) {
+        if let Some(connector) = zlock!(self.peer_connectors).get_mut(&peer.locator) {
+            connector.state = state;
+            connector.attempts = attempts;
+        }
+    }
+
+    // Close the transports opened towards a configured peer, without trying to reconnect
+    pub(super) async fn disconnect_peer(&self, peer: &EndPoint) -> ZResult<()> {
+        // Removing the peer also stops a pending peer_connector
+        let mut found = zlock!(self.peer_connectors).remove(&peer.locator).is_some();
+        for transport in self.manager().get_transports() {
+            let is_peer = match transport.get_callback()? {
+                Some(callback) => match callback.as_any().downcast_ref::<RuntimeSession>() {
+                    Some(session) => {
+                        let mut endpoint = zwrite!(session.endpoint);
+                        if endpoint.as_ref().map(|e| &e.locator) == Some(&peer.locator) {
+                            *endpoint = None;
+                            true
+                        } else {
+                            false
+                        }
+                    }
+                    None => false,
+                },
+                None => false,
+            };
+            if is_peer {
+                log::debug!("Disconnecting from configured peer {}", peer);
+                transport.close().await?;
+                found = true;
+            }
+        }
+        if found {
+            Ok(())
+        } else {
+            zerror!(ZErrorKind::Other {
+                descr: format!("Not connected to configured peer {}", peer)
+            })
+        }
+    }
+
     pub async fn scout<Fut, F>(
         sockets: &[UdpSocket],
         what: WhatAmI,
@@ -755,25 +872,34 @@ impl Runtime {
     }
 
     pub(super) fn closing_session(session: &RuntimeSession) {
+        if session.runtime.is_closed() {
+            return;
+        }
         match session.runtime.whatami {
             whatami::CLIENT => {
                 let runtime = session.runtime.clone();
                 async_std::task::spawn(async move {
-                    let mut delay = CONNECTION_RETRY_INITIAL_PERIOD;
-                    while runtime.start_client().await.is_err() {
-                        async_std::task::sleep(std::time::Duration::from_millis(delay)).await;
-                        delay *= CONNECTION_RETRY_PERIOD_INCREASE_FACTOR;
-                        if delay > CONNECTION_RETRY_MAX_PERIOD {
-                            delay = CONNECTION_RETRY_MAX_PERIOD;
+                    // Retry forever unless a maximum number of attempts is configured
+                    let mut retry = runtime.retry_policy.retry();
+                    while !runtime.is_closed() && runtime.start_client().await.is_err() {
+                        match retry.next_delay() {
+                            Some(delay) => async_std::task::sleep(delay).await,
+                            None => {
+                                log::error!(
+                                    "Unable to reconnect after {} attempts. Giving up.",
+                                    retry.attempts()
+                                );
+                                break;
+                            }
                         }
                     }
                 });
             }
             _ => {
-                if let Some(locator) = &*zread!(session.locator) {
-                    let locator = locator.clone();
+                if let Some(endpoint) = &*zread!(session.endpoint) {
+                    let endpoint = endpoint.clone();
                     let runtime = session.runtime.clone();
-                    async_std::task::spawn(async move { runtime.peer_connector(locator).await });
+                    async_std::task::spawn(async move { runtime.peer_connector(endpoint).await });
                 }
             }
         }
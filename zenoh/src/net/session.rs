@@ -13,6 +13,7 @@
 //
 use super::info::*;
 use super::routing::face::Face;
+use super::routing::liveliness::LIVELINESS_QUERY_PREFIX;
 use super::*;
 use async_std::sync::Arc;
 use async_std::task;
@@ -24,12 +25,12 @@ use protocol::{
         ResourceId, ZInt,
     },
     io::ZBuf,
-    proto::RoutingContext,
+    proto::{Attachment, RoutingContext},
 };
 use runtime::Runtime;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::RwLock;
 use std::time::Duration;
 use transport::Primitives;
@@ -46,6 +47,23 @@ zconfigurable! {
     static ref API_OPEN_SESSION_DELAY: u64 = 500;
 }
 
+const ATTACHMENT_WBUF_SIZE: usize = 64;
+
+fn attachment_from_properties(properties: &[Property]) -> Attachment {
+    let mut wbuf = WBuf::new(ATTACHMENT_WBUF_SIZE, false);
+    wbuf.write_properties(properties);
+    Attachment::new(wbuf.into())
+}
+
+fn properties_from_attachment(attachment: Option<Attachment>) -> Option<Vec<Property>> {
+    let mut attachment = attachment?;
+    let properties = attachment.buffer.read_properties();
+    if properties.is_none() {
+        warn!("Error while decoding attachment properties: attachment dropped");
+    }
+    properties
+}
+
 pub(crate) struct SessionState {
     primitives: Option<Arc<Face>>, // @TODO replace with MaybeUninit ??
     rid_counter: AtomicUsize,      // @TODO: manage rollover and uniqueness
@@ -54,10 +72,15 @@ pub(crate) struct SessionState {
     local_resources: HashMap<ResourceId, Resource>,
     remote_resources: HashMap<ResourceId, Resource>,
     publishers: HashMap<Id, Arc<PublisherState>>,
+    matching_publications: HashSet<String>,
     subscribers: HashMap<Id, Arc<SubscriberState>>,
     local_subscribers: HashMap<Id, Arc<SubscriberState>>,
     queryables: HashMap<Id, Arc<QueryableState>>,
+    liveliness_tokens: HashMap<Id, Arc<LivelinessTokenState>>,
+    remote_liveliness_tokens: HashSet<String>,
+    liveliness_subscribers: HashMap<Id, Arc<SubscriberState>>,
     queries: HashMap<ZInt, QueryState>,
+    queries_default_timeout: Duration,
     local_routing: bool,
     join_subscriptions: Vec<String>,
     join_publications: Vec<String>,
@@ -68,6 +91,7 @@ impl SessionState {
         local_routing: bool,
         join_subscriptions: Vec<String>,
         join_publications: Vec<String>,
+        queries_default_timeout: Duration,
     ) -> SessionState {
         SessionState {
             primitives: None,
@@ -77,10 +101,15 @@ impl SessionState {
             local_resources: HashMap::new(),
             remote_resources: HashMap::new(),
             publishers: HashMap::new(),
+            matching_publications: HashSet::new(),
             subscribers: HashMap::new(),
             local_subscribers: HashMap::new(),
             queryables: HashMap::new(),
+            liveliness_tokens: HashMap::new(),
+            remote_liveliness_tokens: HashSet::new(),
+            liveliness_subscribers: HashMap::new(),
             queries: HashMap::new(),
+            queries_default_timeout,
             local_routing,
             join_subscriptions,
             join_publications,
@@ -154,6 +183,52 @@ impl SessionState {
             self.remotekey_to_resname(reskey)
         }
     }
+
+    /// Returns the resource name used to declare a publication on `resname` to the routing layer.
+    fn publication_resname<'a>(&'a self, resname: &'a str) -> &'a str {
+        self.join_publications
+            .iter()
+            .find(|s| rname::include(s, resname))
+            .map(|s| s.as_str())
+            .unwrap_or(resname)
+    }
+
+    fn is_publication_matching(&self, resname: &str) -> bool {
+        self.matching_publications
+            .contains(self.publication_resname(resname))
+            || (self.local_routing
+                && self
+                    .subscribers
+                    .values()
+                    .any(|sub| rname::intersect(&sub.resname, resname)))
+            || self
+                .local_subscribers
+                .values()
+                .any(|sub| rname::intersect(&sub.resname, resname))
+    }
+
+    fn update_matching_statuses(&self) {
+        for pub_state in self.publishers.values() {
+            pub_state.set_matching(self.is_publication_matching(&pub_state.resname));
+        }
+    }
+
+    #[inline]
+    fn is_liveliness_token_alive(&self, resname: &str) -> bool {
+        self.remote_liveliness_tokens.contains(resname)
+            || self
+                .liveliness_tokens
+                .values()
+                .any(|token| token.resname == resname)
+    }
+
+    fn liveliness_subscribers_for(&self, resname: &str) -> Vec<Arc<SubscriberState>> {
+        self.liveliness_subscribers
+            .values()
+            .filter(|sub| rname::intersect(&sub.resname, resname))
+            .cloned()
+            .collect()
+    }
 }
 
 impl fmt::Debug for SessionState {
@@ -250,10 +325,13 @@ impl Session {
         join_publications: Vec<String>,
     ) -> impl ZFuture<Output = Session> {
         let router = runtime.router.clone();
+        // The default timeout was parsed from the configuration by the Runtime
+        let queries_default_timeout = zread!(router.tables).queries_default_timeout;
         let state = Arc::new(RwLock::new(SessionState::new(
             local_routing,
             join_subscriptions,
             join_publications,
+            queries_default_timeout,
         )));
         let session = Session {
             runtime,
@@ -469,6 +547,9 @@ impl Session {
             let pub_state = Arc::new(PublisherState {
                 id,
                 reskey: resource.clone(),
+                resname: resname.clone(),
+                matching: AtomicBool::new(false),
+                matching_senders: RwLock::new(vec![]),
             });
             let declared_pub = match state
                 .join_publications
@@ -491,6 +572,7 @@ impl Session {
             };
 
             state.publishers.insert(id, pub_state.clone());
+            pub_state.set_matching(state.is_publication_matching(&resname));
 
             if let Some(res) = declared_pub {
                 let primitives = state.primitives.as_ref().unwrap().clone();
@@ -524,8 +606,10 @@ impl Session {
                         });
                         if !joined_pub {
                             let primitives = state.primitives.as_ref().unwrap().clone();
-                            let reskey = join_pub.clone().into();
+                            let join_pub = join_pub.clone();
+                            state.matching_publications.remove(&join_pub);
                             drop(state);
+                            let reskey = join_pub.into();
                             primitives.forget_publisher(&reskey, None);
                         }
                     }
@@ -536,6 +620,7 @@ impl Session {
                         });
                         if !twin_pub {
                             let primitives = state.primitives.as_ref().unwrap().clone();
+                            state.matching_publications.remove(&resname);
                             drop(state);
                             primitives.forget_publisher(&pub_state.reskey, None);
                         }
@@ -585,6 +670,7 @@ impl Session {
         };
 
         state.subscribers.insert(sub_state.id, sub_state.clone());
+        state.update_matching_statuses();
         for res in state.local_resources.values_mut() {
             if rname::matches(&sub_state.resname, &res.name) {
                 res.subscribers.push(sub_state.clone());
@@ -732,6 +818,7 @@ impl Session {
                     state
                         .local_subscribers
                         .insert(sub_state.id, sub_state.clone());
+                    state.update_matching_statuses();
                     for res in state.local_resources.values_mut() {
                         if rname::matches(&sub_state.resname, &res.name) {
                             res.local_subscribers.push(sub_state.clone());
@@ -757,6 +844,7 @@ impl Session {
         let mut state = zwrite!(self.state);
         zready(if let Some(sub_state) = state.subscribers.remove(&sid) {
             trace!("undeclare_subscriber({:?})", sub_state);
+            state.update_matching_statuses();
             for res in state.local_resources.values_mut() {
                 res.subscribers.retain(|sub| sub.id != sub_state.id);
             }
@@ -798,6 +886,7 @@ impl Session {
             })
         } else if let Some(sub_state) = state.local_subscribers.remove(&sid) {
             trace!("undeclare_subscriber({:?})", sub_state);
+            state.update_matching_statuses();
             for res in state.local_resources.values_mut() {
                 res.local_subscribers.retain(|sub| sub.id != sub_state.id);
             }
@@ -844,6 +933,7 @@ impl Session {
     ///         res_name: "/resource/name".to_string(),
     ///         payload: "value".as_bytes().into(),
     ///         data_info: None,
+    ///         attachment: None,
     ///     }).await;
     /// }
     /// # })
@@ -917,6 +1007,274 @@ impl Session {
         })
     }
 
+    /// Declare a [LivelinessToken](LivelinessToken) for the given resource key.
+    ///
+    /// The token is visible to all the [LivelinessSubscriber](LivelinessSubscriber)s with a matching
+    /// resource key until it is undeclared or the connection to this session is lost.
+    ///
+    /// # Arguments
+    ///
+    /// * `resource` - The resource key of the token
+    ///
+    /// # Examples
+    /// ```
+    /// # async_std::task::block_on(async {
+    /// use zenoh::net::*;
+    ///
+    /// let session = open(config::peer()).await.unwrap();
+    /// let token = session.declare_liveliness_token(&"/group/member1".into()).await.unwrap();
+    /// # })
+    /// ```
+    pub fn declare_liveliness_token(
+        &self,
+        reskey: &ResKey,
+    ) -> impl ZFuture<Output = ZResult<LivelinessToken<'_>>> {
+        trace!("declare_liveliness_token({:?})", reskey);
+        let mut state = zwrite!(self.state);
+        let resname = match state.localkey_to_resname(reskey) {
+            Ok(resname) => resname,
+            Err(e) => return zready(Err(e)),
+        };
+        let id = state.decl_id_counter.fetch_add(1, Ordering::SeqCst);
+        let token_state = Arc::new(LivelinessTokenState {
+            id,
+            reskey: reskey.clone(),
+            resname,
+        });
+        let declared = !state
+            .liveliness_tokens
+            .values()
+            .any(|token| token.resname == token_state.resname);
+        let appeared = !state.is_liveliness_token_alive(&token_state.resname);
+        state.liveliness_tokens.insert(id, token_state.clone());
+        let subscribers = if appeared {
+            state.liveliness_subscribers_for(&token_state.resname)
+        } else {
+            vec![]
+        };
+        let primitives = state.primitives.as_ref().unwrap().clone();
+        drop(state);
+
+        if declared {
+            primitives.decl_liveliness_token(&token_state.reskey, None);
+        }
+        Session::notify_liveliness(&subscribers, &token_state.resname, data_kind::PUT);
+
+        zready(Ok(LivelinessToken {
+            session: self,
+            state: token_state,
+            alive: true,
+        }))
+    }
+
+    pub(crate) fn undeclare_liveliness_token(
+        &self,
+        tid: usize,
+    ) -> impl ZFuture<Output = ZResult<()>> {
+        let mut state = zwrite!(self.state);
+        zready(match state.liveliness_tokens.remove(&tid) {
+            Some(token_state) => {
+                trace!("undeclare_liveliness_token({:?})", token_state);
+                // Note: there might be several tokens with the same resource name.
+                // Before calling forget_liveliness_token(reskey), check if this was the last one.
+                let forget = !state
+                    .liveliness_tokens
+                    .values()
+                    .any(|token| token.resname == token_state.resname);
+                let subscribers = if !state.is_liveliness_token_alive(&token_state.resname) {
+                    state.liveliness_subscribers_for(&token_state.resname)
+                } else {
+                    vec![]
+                };
+                let primitives = state.primitives.as_ref().unwrap().clone();
+                drop(state);
+
+                if forget {
+                    primitives.forget_liveliness_token(&token_state.reskey, None);
+                }
+                Session::notify_liveliness(&subscribers, &token_state.resname, data_kind::DELETE);
+                Ok(())
+            }
+            None => zerror!(ZErrorKind::Other {
+                descr: "Unable to find liveliness token".into()
+            }),
+        })
+    }
+
+    /// Declare a [LivelinessSubscriber](LivelinessSubscriber) for the given resource key.
+    ///
+    /// A PUT [Sample](Sample) is received for each matching liveliness token currently alive
+    /// and for each one that appears later on. A DELETE [Sample](Sample) is received each time
+    /// a matching token disappears.
+    ///
+    /// # Arguments
+    ///
+    /// * `resource` - The resource key of the liveliness tokens to subscribe to
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # async_std::task::block_on(async {
+    /// use zenoh::net::*;
+    /// use futures::prelude::*;
+    ///
+    /// let session = open(config::peer()).await.unwrap();
+    /// let mut subscriber = session.declare_liveliness_subscriber(&"/group/*".into()).await.unwrap();
+    /// while let Some(sample) = subscriber.receiver().next().await {
+    ///     match sample.data_info.and_then(|info| info.kind) {
+    ///         Some(data_kind::DELETE) => println!("{} left", sample.res_name),
+    ///         _ => println!("{} joined", sample.res_name),
+    ///     }
+    /// }
+    /// # })
+    /// ```
+    pub fn declare_liveliness_subscriber(
+        &self,
+        reskey: &ResKey,
+    ) -> impl ZFuture<Output = ZResult<LivelinessSubscriber<'_>>> {
+        trace!("declare_liveliness_subscriber({:?})", reskey);
+        let (sender, receiver) = bounded(*API_DATA_RECEPTION_CHANNEL_SIZE);
+        let mut state = zwrite!(self.state);
+        let resname = match state.localkey_to_resname(reskey) {
+            Ok(resname) => resname,
+            Err(e) => return zready(Err(e)),
+        };
+        let id = state.decl_id_counter.fetch_add(1, Ordering::SeqCst);
+        let sub_state = Arc::new(SubscriberState {
+            id,
+            reskey: reskey.clone(),
+            resname,
+            invoker: SubscriberInvoker::Sender(sender),
+        });
+        let declared = !state
+            .liveliness_subscribers
+            .values()
+            .any(|sub| sub.resname == sub_state.resname);
+        state
+            .liveliness_subscribers
+            .insert(sub_state.id, sub_state.clone());
+        let alive = Session::alive_liveliness_tokens(&state, &sub_state.resname);
+        let primitives = state.primitives.as_ref().unwrap().clone();
+        drop(state);
+
+        for resname in alive {
+            Session::notify_liveliness(std::slice::from_ref(&sub_state), &resname, data_kind::PUT);
+        }
+        // The remote tokens are only declared to this session once it subscribed to them
+        if declared {
+            primitives.decl_liveliness_subscriber(&sub_state.reskey, None);
+        }
+
+        zready(Ok(LivelinessSubscriber {
+            session: self,
+            state: sub_state,
+            alive: true,
+            receiver: SampleReceiver::new(receiver),
+        }))
+    }
+
+    pub(crate) fn undeclare_liveliness_subscriber(
+        &self,
+        sid: usize,
+    ) -> impl ZFuture<Output = ZResult<()>> {
+        let mut state = zwrite!(self.state);
+        zready(match state.liveliness_subscribers.remove(&sid) {
+            Some(sub_state) => {
+                trace!("undeclare_liveliness_subscriber({:?})", sub_state);
+                // Note: there might be several liveliness subscribers on the same resource name.
+                // Before calling forget_liveliness_subscriber(reskey), check if this was the last one.
+                if !state
+                    .liveliness_subscribers
+                    .values()
+                    .any(|sub| sub.resname == sub_state.resname)
+                {
+                    let primitives = state.primitives.as_ref().unwrap().clone();
+                    drop(state);
+                    primitives.forget_liveliness_subscriber(&sub_state.reskey, None);
+                }
+                Ok(())
+            }
+            None => zerror!(ZErrorKind::Other {
+                descr: "Unable to find liveliness subscriber".into()
+            }),
+        })
+    }
+
+    /// Get the resource names of the liveliness tokens currently alive that match
+    /// the given resource key.
+    ///
+    /// The tokens are looked for by the routers or peers this session is connected to,
+    /// or by this session's runtime if it is itself a router or a peer.
+    ///
+    /// # Arguments
+    ///
+    /// * `resource` - The resource key of the liveliness tokens to look for
+    ///
+    /// # Examples
+    /// ```
+    /// # async_std::task::block_on(async {
+    /// use zenoh::net::*;
+    ///
+    /// let session = open(config::peer()).await.unwrap();
+    /// for name in session.query_liveliness(&"/group/*".into()).await.unwrap() {
+    ///     println!("{} is alive", name);
+    /// }
+    /// # })
+    /// ```
+    pub fn query_liveliness(&self, reskey: &ResKey) -> impl ZFuture<Output = ZResult<Vec<String>>> {
+        trace!("query_liveliness({:?})", reskey);
+        let resname = match zread!(self.state).localkey_to_resname(reskey) {
+            Ok(resname) => resname,
+            Err(e) => return zpinbox(async move { Err(e) }),
+        };
+        let replies = self
+            .query(
+                &[LIVELINESS_QUERY_PREFIX, &resname].concat().into(),
+                "",
+                QueryTarget::default(),
+                QueryConsolidation::none(),
+            )
+            .wait();
+        zpinbox(async move {
+            let mut replies = replies?;
+            let mut alive = vec![];
+            while let Some(reply) = replies.next().await {
+                if let Reply::Ok(reply) = reply {
+                    alive.push(reply.data.res_name);
+                }
+            }
+            alive.sort();
+            alive.dedup();
+            Ok(alive)
+        })
+    }
+
+    fn alive_liveliness_tokens(state: &SessionState, resname: &str) -> Vec<String> {
+        let mut alive = state
+            .liveliness_tokens
+            .values()
+            .map(|token| token.resname.clone())
+            .chain(state.remote_liveliness_tokens.iter().cloned())
+            .filter(|name| rname::intersect(resname, name))
+            .collect::<Vec<String>>();
+        alive.sort();
+        alive.dedup();
+        alive
+    }
+
+    fn notify_liveliness(subscribers: &[Arc<SubscriberState>], resname: &str, kind: ZInt) {
+        for sub in subscribers {
+            let mut info = DataInfo::new();
+            info.kind = Some(kind);
+            Session::invoke_subscriber(
+                &sub.invoker,
+                resname.to_string(),
+                ZBuf::new(),
+                Some(info),
+                None,
+            );
+        }
+    }
+
     /// Write data.
     ///
     /// # Arguments
@@ -956,8 +1314,9 @@ impl Session {
             CongestionControl::default(), // @TODO: default for the time being
             data_info.clone(),
             None,
+            None,
         );
-        self.handle_data(true, resource, data_info, payload);
+        self.handle_data(true, resource, data_info, payload, None);
         zready(Ok(()))
     }
 
@@ -989,6 +1348,59 @@ impl Session {
         congestion_control: CongestionControl,
     ) -> impl ZFuture<Output = ZResult<()>> {
         trace!("write_ext({:?}, [...])", resource);
+        self.write_ext_inner(resource, payload, encoding, kind, congestion_control, None)
+    }
+
+    /// Write data with options and user properties attached.
+    ///
+    /// # Arguments
+    ///
+    /// * `resource` - The resource key to write
+    /// * `payload` - The value to write
+    /// * `encoding` - The encoding of the value
+    /// * `kind` - The kind of value
+    /// * `congestion_control` - The value for the congestion control
+    /// * `attachment` - The user properties carried along with the value
+    ///
+    /// # Examples
+    /// ```
+    /// # async_std::task::block_on(async {
+    /// use zenoh::net::*;
+    ///
+    /// let session = open(config::peer()).await.unwrap();
+    /// let attachment = vec![Property { key: 0x01, value: b"trace-1234".to_vec() }];
+    /// session.write_ext_with_attachment(&"/resource/name".into(), "value".as_bytes().into(), encoding::TEXT_PLAIN, data_kind::PUT, CongestionControl::Drop, attachment).await.unwrap();
+    /// # })
+    /// ```
+    pub fn write_ext_with_attachment(
+        &self,
+        resource: &ResKey,
+        payload: ZBuf,
+        encoding: ZInt,
+        kind: ZInt,
+        congestion_control: CongestionControl,
+        attachment: Vec<Property>,
+    ) -> impl ZFuture<Output = ZResult<()>> {
+        trace!("write_ext_with_attachment({:?}, [...])", resource);
+        self.write_ext_inner(
+            resource,
+            payload,
+            encoding,
+            kind,
+            congestion_control,
+            Some(attachment),
+        )
+    }
+
+    fn write_ext_inner(
+        &self,
+        resource: &ResKey,
+        payload: ZBuf,
+        encoding: ZInt,
+        kind: ZInt,
+        congestion_control: CongestionControl,
+        attachment: Option<Vec<Property>>,
+    ) -> impl ZFuture<Output = ZResult<()>> {
         let state = zread!(self.state);
         let primitives = state.primitives.as_ref().unwrap().clone();
         drop(state);
@@ -1009,8 +1421,9 @@ impl Session {
             congestion_control,
             data_info.clone(),
             None,
+            attachment.as_deref().map(attachment_from_properties),
         );
-        self.handle_data(true, resource, data_info, payload);
+        self.handle_data(true, resource, data_info, payload, attachment);
         zready(Ok(()))
     }
 
@@ -1020,6 +1433,7 @@ impl Session {
         res_name: String,
         payload: ZBuf,
         data_info: Option<DataInfo>,
+        attachment: Option<Vec<Property>>,
     ) {
         match invoker {
             SubscriberInvoker::Handler(handler) => {
@@ -1028,6 +1442,7 @@ impl Session {
                     res_name,
                     payload,
                     data_info,
+                    attachment,
                 });
             }
             SubscriberInvoker::Sender(sender) => {
@@ -1035,6 +1450,7 @@ impl Session {
                     res_name,
                     payload,
                     data_info,
+                    attachment,
                 }) {
                     error!("SubscriberInvoker error: {}", e);
                 }
@@ -1042,14 +1458,27 @@ impl Session {
         }
     }
 
-    fn handle_data(&self, local: bool, reskey: &ResKey, info: Option<DataInfo>, payload: ZBuf) {
+    fn handle_data(
+        &self,
+        local: bool,
+        reskey: &ResKey,
+        info: Option<DataInfo>,
+        payload: ZBuf,
+        attachment: Option<Vec<Property>>,
+    ) {
         let state = zread!(self.state);
         if let ResKey::RId(rid) = reskey {
             match state.get_res(rid, local) {
                 Some(res) => {
                     if !local && res.subscribers.len() == 1 {
                         let sub = res.subscribers.get(0).unwrap();
-                        Session::invoke_subscriber(&sub.invoker, res.name.clone(), payload, info);
+                        Session::invoke_subscriber(
+                            &sub.invoker,
+                            res.name.clone(),
+                            payload,
+                            info,
+                            attachment,
+                        );
                     } else {
                         if !local || state.local_routing {
                             for sub in &res.subscribers {
@@ -1058,6 +1487,7 @@ impl Session {
                                     res.name.clone(),
                                     payload.clone(),
                                     info.clone(),
+                                    attachment.clone(),
                                 );
                             }
                         }
@@ -1068,6 +1498,7 @@ impl Session {
                                     res.name.clone(),
                                     payload.clone(),
                                     info.clone(),
+                                    attachment.clone(),
                                 );
                             }
                         }
@@ -1088,6 +1519,7 @@ impl Session {
                                     resname.clone(),
                                     payload.clone(),
                                     info.clone(),
+                                    attachment.clone(),
                                 );
                             }
                         }
@@ -1100,6 +1532,7 @@ impl Session {
                                     resname.clone(),
                                     payload.clone(),
                                     info.clone(),
+                                    attachment.clone(),
                                 );
                             }
                         }
@@ -1123,6 +1556,9 @@ impl Session {
 
     /// Query data from the matching queryables in the system.
     ///
+    /// The query is finalized after the session default timeout (see
+    /// [`ZN_QUERY_TIMEOUT_KEY`](config::ZN_QUERY_TIMEOUT_KEY)) if some replies are still missing.
+    ///
     /// # Arguments
     ///
     /// * `resource` - The resource key to query
@@ -1144,7 +1580,7 @@ impl Session {
     ///     QueryConsolidation::default()
     /// ).await.unwrap();
     /// while let Some(reply) = replies.next().await {
-    ///     println!(">> Received {:?}", reply.data);
+    ///     println!(">> Received {:?}", reply);
     /// }
     /// # })
     /// ```
@@ -1154,19 +1590,140 @@ impl Session {
         predicate: &str,
         target: QueryTarget,
         consolidation: QueryConsolidation,
+    ) -> impl ZFuture<Output = ZResult<ReplyReceiver>> {
+        let timeout = zread!(self.state).queries_default_timeout;
+        self.query_with_timeout(resource, predicate, target, consolidation, timeout)
+    }
+
+    /// Query data from the matching queryables in the system with user properties attached.
+    ///
+    /// The query is finalized after the session default timeout (see
+    /// [`ZN_QUERY_TIMEOUT_KEY`](config::ZN_QUERY_TIMEOUT_KEY)) if some replies are still missing.
+    ///
+    /// # Arguments
+    ///
+    /// * `resource` - The resource key to query
+    /// * `predicate` - An indication to matching queryables about the queried data
+    /// * `target` - The kind of queryables that should be target of this query
+    /// * `consolidation` - The kind of consolidation that should be applied on replies
+    /// * `attachment` - The user properties carried along with the query
+    ///
+    /// # Examples
+    /// ```
+    /// # async_std::task::block_on(async {
+    /// use zenoh::net::*;
+    /// use futures::prelude::*;
+    ///
+    /// let session = open(config::peer()).await.unwrap();
+    /// let attachment = vec![Property { key: 0x01, value: b"trace-1234".to_vec() }];
+    /// let mut replies = session.query_with_attachment(
+    ///     &"/resource/name".into(),
+    ///     "predicate",
+    ///     QueryTarget::default(),
+    ///     QueryConsolidation::default(),
+    ///     attachment,
+    /// ).await.unwrap();
+    /// while let Some(reply) = replies.next().await {
+    ///     println!(">> Received {:?}", reply);
+    /// }
+    /// # })
+    /// ```
+    pub fn query_with_attachment(
+        &self,
+        resource: &ResKey,
+        predicate: &str,
+        target: QueryTarget,
+        consolidation: QueryConsolidation,
+        attachment: Vec<Property>,
+    ) -> impl ZFuture<Output = ZResult<ReplyReceiver>> {
+        let timeout = zread!(self.state).queries_default_timeout;
+        self.query_inner(
+            resource,
+            predicate,
+            target,
+            consolidation,
+            Some(attachment),
+            timeout,
+        )
+    }
+
+    /// Query data from the matching queryables in the system with a specific timeout.
+    ///
+    /// When the `timeout` expires, the [`ReplyReceiver`] is closed with the replies
+    /// received so far.
+    ///
+    /// # Arguments
+    ///
+    /// * `resource` - The resource key to query
+    /// * `predicate` - An indication to matching queryables about the queried data
+    /// * `target` - The kind of queryables that should be target of this query
+    /// * `consolidation` - The kind of consolidation that should be applied on replies
+    /// * `timeout` - The maximum time to wait for the final replies
+    ///
+    /// # Examples
+    /// ```
+    /// # async_std::task::block_on(async {
+    /// use zenoh::net::*;
+    /// use futures::prelude::*;
+    /// use std::time::Duration;
+    ///
+    /// let session = open(config::peer()).await.unwrap();
+    /// let mut replies = session.query_with_timeout(
+    ///     &"/resource/name".into(),
+    ///     "predicate",
+    ///     QueryTarget::default(),
+    ///     QueryConsolidation::default(),
+    ///     Duration::from_secs(1),
+    /// ).await.unwrap();
+    /// while let Some(reply) = replies.next().await {
+    ///     println!(">> Received {:?}", reply);
+    /// }
+    /// # })
+    /// ```
+    pub fn query_with_timeout(
+        &self,
+        resource: &ResKey,
+        predicate: &str,
+        target: QueryTarget,
+        consolidation: QueryConsolidation,
+        timeout: Duration,
+    ) -> impl ZFuture<Output = ZResult<ReplyReceiver>> {
+        self.query_inner(resource, predicate, target, consolidation, None, timeout)
+    }
+
+    fn query_inner(
+        &self,
+        resource: &ResKey,
+        predicate: &str,
+        target: QueryTarget,
+        consolidation: QueryConsolidation,
+        attachment: Option<Vec<Property>>,
+        timeout: Duration,
     ) -> impl ZFuture<Output = ZResult<ReplyReceiver>> {
         trace!(
-            "query({:?}, {:?}, {:?}, {:?})",
+            "query({:?}, {:?}, {:?}, {:?}, {:?})",
             resource,
             predicate,
             target,
-            consolidation
+            consolidation,
+            timeout
         );
         let mut state = zwrite!(self.state);
         let qid = state.qid_counter.fetch_add(1, Ordering::SeqCst);
         let (rep_sender, rep_receiver) = bounded(*API_REPLY_RECEPTION_CHANNEL_SIZE);
         let nb_final = if state.local_routing { 2 } else { 1 };
         trace!("Register query {} (nb_final = {})", qid, nb_final);
+        let c_state = self.state.clone();
+        let timeout_task = task::spawn(async move {
+            task::sleep(timeout).await;
+            let mut state = zwrite!(c_state);
+            if let Some(mut query) = state.queries.remove(&qid) {
+                trace!("Timeout on query {}! Close query.", qid);
+                // This is the timeout task itself, no need to cancel it
+                query.timeout = None;
+                query.finalize();
+            }
+        });
         state.queries.insert(
             qid,
             QueryState {
@@ -1178,27 +1735,39 @@ impl Session {
                     None
                 },
                 rep_sender,
+                timeout: Some(timeout_task),
             },
         );
 
         let primitives = state.primitives.as_ref().unwrap().clone();
         let local_routing = state.local_routing;
         drop(state);
-        primitives.send_query(
+
+        primitives.send_query_with_timeout(
             resource,
             predicate,
             qid,
             target.clone(),
             consolidation.clone(),
-            None,
+            attachment.as_deref().map(attachment_from_properties),
+            timeout,
         );
         if local_routing {
-            self.handle_query(true, resource, predicate, qid, target, consolidation);
+            self.handle_query(
+                true,
+                resource,
+                predicate,
+                qid,
+                target,
+                consolidation,
+                attachment,
+            );
         }
 
         zready(Ok(ReplyReceiver::new(rep_receiver)))
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn handle_query(
         &self,
         local: bool,
@@ -1207,6 +1776,7 @@ impl Session {
         qid: ZInt,
         target: QueryTarget,
         _consolidation: QueryConsolidation,
+        attachment: Option<Vec<Property>>,
     ) {
         let (primitives, resname, kinds_and_senders) = {
             let state = zread!(self.state);
@@ -1256,6 +1826,7 @@ impl Session {
             let _ = req_sender.send(Query {
                 res_name: resname.clone(),
                 predicate: predicate.clone(),
+                attachment: attachment.clone(),
                 replies_sender: RepliesSender {
                     kind,
                     sender: rep_sender.clone(),
@@ -1269,28 +1840,40 @@ impl Session {
         if local {
             let this = self.clone();
             task::spawn(async move {
-                while let Some((kind, sample)) = rep_receiver.stream().next().await {
+                while let Some((kind, reply)) = rep_receiver.stream().next().await {
+                    let (is_error, sample) = match reply {
+                        Ok(sample) => (false, sample),
+                        Err(value) => (true, value),
+                    };
                     this.send_reply_data(
                         qid,
                         kind,
                         pid,
+                        is_error,
                         ResKey::RName(sample.res_name),
                         sample.data_info,
                         sample.payload,
+                        sample.attachment.as_deref().map(attachment_from_properties),
                     );
                 }
                 this.send_reply_final(qid);
             });
         } else {
             task::spawn(async move {
-                while let Some((kind, sample)) = rep_receiver.stream().next().await {
+                while let Some((kind, reply)) = rep_receiver.stream().next().await {
+                    let (is_error, sample) = match reply {
+                        Ok(sample) => (false, sample),
+                        Err(value) => (true, value),
+                    };
                     primitives.send_reply_data(
                         qid,
                         kind,
                         pid,
+                        is_error,
                         ResKey::RName(sample.res_name),
                         sample.data_info,
                         sample.payload,
+                        sample.attachment.as_deref().map(attachment_from_properties),
                     );
                 }
                 primitives.send_reply_final(qid);
@@ -1361,6 +1944,60 @@ impl Primitives for Session {
         trace!("recv Forget Queryable {:?}", _reskey);
     }
 
+    fn decl_liveliness_token(&self, reskey: &ResKey, _routing_context: Option<RoutingContext>) {
+        trace!("recv Liveliness Token {:?}", reskey);
+        let mut state = zwrite!(self.state);
+        match state.remotekey_to_resname(reskey) {
+            Ok(resname) => {
+                let subscribers = if !state.is_liveliness_token_alive(&resname) {
+                    state.liveliness_subscribers_for(&resname)
+                } else {
+                    vec![]
+                };
+                state.remote_liveliness_tokens.insert(resname.clone());
+                drop(state);
+                Session::notify_liveliness(&subscribers, &resname, data_kind::PUT);
+            }
+            Err(_) => error!("Received Liveliness Token for unkown reskey: {}", reskey),
+        }
+    }
+
+    fn decl_liveliness_subscriber(
+        &self,
+        _reskey: &ResKey,
+        _routing_context: Option<RoutingContext>,
+    ) {
+        trace!("recv Liveliness Subscriber {:?}", _reskey);
+    }
+
+    fn forget_liveliness_subscriber(
+        &self,
+        _reskey: &ResKey,
+        _routing_context: Option<RoutingContext>,
+    ) {
+        trace!("recv Forget Liveliness Subscriber {:?}", _reskey);
+    }
+
+    fn forget_liveliness_token(&self, reskey: &ResKey, _routing_context: Option<RoutingContext>) {
+        trace!("recv Forget Liveliness Token {:?}", reskey);
+        let mut state = zwrite!(self.state);
+        match state.remotekey_to_resname(reskey) {
+            Ok(resname) => {
+                if state.remote_liveliness_tokens.remove(&resname)
+                    && !state.is_liveliness_token_alive(&resname)
+                {
+                    let subscribers = state.liveliness_subscribers_for(&resname);
+                    drop(state);
+                    Session::notify_liveliness(&subscribers, &resname, data_kind::DELETE);
+                }
+            }
+            Err(_) => error!(
+                "Received Forget Liveliness Token for unkown reskey: {}",
+                reskey
+            ),
+        }
+    }
+
     fn send_data(
         &self,
         reskey: &ResKey,
@@ -1369,6 +2006,7 @@ impl Primitives for Session {
         congestion_control: CongestionControl,
         info: Option<DataInfo>,
         _routing_context: Option<RoutingContext>,
+        attachment: Option<Attachment>,
     ) {
         trace!(
             "recv Data {:?} {:?} {:?} {:?} {:?}",
@@ -1378,7 +2016,13 @@ impl Primitives for Session {
             congestion_control,
             info,
         );
-        self.handle_data(false, reskey, info, payload)
+        self.handle_data(
+            false,
+            reskey,
+            info,
+            payload,
+            properties_from_attachment(attachment),
+        )
     }
 
     fn send_query(
@@ -1389,6 +2033,7 @@ impl Primitives for Session {
         target: QueryTarget,
         consolidation: QueryConsolidation,
         _routing_context: Option<RoutingContext>,
+        attachment: Option<Attachment>,
     ) {
         trace!(
             "recv Query {:?} {:?} {:?} {:?}",
@@ -1397,7 +2042,15 @@ impl Primitives for Session {
             target,
             consolidation
         );
-        self.handle_query(false, reskey, predicate, qid, target, consolidation)
+        self.handle_query(
+            false,
+            reskey,
+            predicate,
+            qid,
+            target,
+            consolidation,
+            properties_from_attachment(attachment),
+        )
     }
 
     fn send_reply_data(
@@ -1405,15 +2058,18 @@ impl Primitives for Session {
         qid: ZInt,
         replier_kind: ZInt,
         replier_id: PeerId,
+        is_error: bool,
         reskey: ResKey,
         data_info: Option<DataInfo>,
         payload: ZBuf,
+        attachment: Option<Attachment>,
     ) {
         trace!(
-            "recv ReplyData {:?} {:?} {:?} {:?} {:?} {:?}",
+            "recv ReplyData {:?} {:?} {:?} {:?} {:?} {:?} {:?}",
             qid,
             replier_kind,
             replier_id,
+            is_error,
             reskey,
             data_info,
             payload
@@ -1428,18 +2084,24 @@ impl Primitives for Session {
         };
         match state.queries.get_mut(&qid) {
             Some(query) => {
-                let new_reply = Reply {
+                let new_reply = ReplyData {
                     data: Sample {
                         res_name,
                         payload,
                         data_info,
+                        attachment: properties_from_attachment(attachment),
                     },
                     replier_kind,
                     replier_id,
                 };
+                if is_error {
+                    // Error replies are never consolidated
+                    let _ = query.rep_sender.send(Reply::Err(new_reply));
+                    return;
+                }
                 match query.reception_mode {
                     ConsolidationMode::None => {
-                        let _ = query.rep_sender.send(new_reply);
+                        let _ = query.rep_sender.send(Reply::Ok(new_reply));
                     }
                     ConsolidationMode::Lazy => {
                         match query
@@ -1455,7 +2117,7 @@ impl Primitives for Session {
                                         .as_mut()
                                         .unwrap()
                                         .insert(new_reply.data.res_name.clone(), new_reply.clone());
-                                    let _ = query.rep_sender.send(new_reply);
+                                    let _ = query.rep_sender.send(Reply::Ok(new_reply));
                                 }
                             }
                             None => {
@@ -1464,7 +2126,7 @@ impl Primitives for Session {
                                     .as_mut()
                                     .unwrap()
                                     .insert(new_reply.data.res_name.clone(), new_reply.clone());
-                                let _ = query.rep_sender.send(new_reply);
+                                let _ = query.rep_sender.send(Reply::Ok(new_reply));
                             }
                         }
                     }
@@ -1509,11 +2171,7 @@ impl Primitives for Session {
                 query.nb_final -= 1;
                 if query.nb_final == 0 {
                     let query = state.queries.remove(&qid).unwrap();
-                    if query.reception_mode == ConsolidationMode::Full {
-                        for (_, reply) in query.replies.unwrap().into_iter() {
-                            let _ = query.rep_sender.send(reply);
-                        }
-                    }
+                    query.finalize();
                     trace!("Close query {}", qid);
                 }
             }
@@ -1539,6 +2197,22 @@ impl Primitives for Session {
         );
     }
 
+    fn send_matching_status(&self, reskey: &ResKey, matching: bool) {
+        trace!("recv MatchingStatus {:?} {}", reskey, matching);
+        let mut state = zwrite!(self.state);
+        match state.remotekey_to_resname(reskey) {
+            Ok(resname) => {
+                if matching {
+                    state.matching_publications.insert(resname);
+                } else {
+                    state.matching_publications.remove(&resname);
+                }
+                state.update_matching_statuses();
+            }
+            Err(_) => error!("Received MatchingStatus for unkown reskey: {}", reskey),
+        }
+    }
+
     fn send_close(&self) {
         trace!("recv Close");
     }
@@ -0,0 +1,129 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ADLINK zenoh team, <zenoh@adlink-labs.tech>
+//
+use lz4_flex::block;
+use zenoh_util::core::{ZError, ZErrorKind, ZResult};
+use zenoh_util::zerror2;
+
+/// The number of bytes prepended to every batch sent on a link with compression enabled.
+pub(crate) const BATCH_HEADER_LEN: usize = 1;
+// The batch payload is sent as it is
+pub(crate) const BATCH_UNCOMPRESSED: u8 = 0x00;
+// The batch payload is an LZ4 block
+pub(crate) const BATCH_LZ4: u8 = 0x01;
+
+const LENGTH_BYTES: usize = 2;
+
+/// Compress a serialized batch into `buffer` for transmission on a link with compression enabled.
+///
+/// ```text
+///  7 6 5 4 3 2 1 0
+/// +-+-+-+-+-+-+-+-+
+/// ~    length     ~ if is_streamed (16 bits little-endian, including the header)
+/// +---------------+
+/// |    header     | BATCH_UNCOMPRESSED or BATCH_LZ4
+/// +---------------+
+/// ~    payload    ~
+/// +---------------+
+/// ```
+///
+/// The payload is sent uncompressed whenever compressing it would not save any byte.
+///
+/// # Arguments
+/// * `batch` - The serialized batch, including the 2 bytes length if `is_streamed`.
+///
+/// * `is_streamed` - The batch is meant to be used for a stream-based transport protocol.
+///
+/// * `buffer` - The buffer the bytes to be sent on the link are written into.
+///
+pub(crate) fn compress_batch(batch: &[u8], is_streamed: bool, buffer: &mut Vec<u8>) {
+    let start = if is_streamed { LENGTH_BYTES } else { 0 };
+    let payload = &batch[start..];
+    let offset = start + BATCH_HEADER_LEN;
+
+    buffer.clear();
+    buffer.resize(offset + block::get_maximum_output_size(payload.len()), 0);
+    let len = match block::compress_into(payload, &mut buffer[offset..]) {
+        Ok(n) if n < payload.len() => {
+            buffer[start] = BATCH_LZ4;
+            n
+        }
+        _ => {
+            buffer[start] = BATCH_UNCOMPRESSED;
+            buffer[offset..offset + payload.len()].copy_from_slice(payload);
+            payload.len()
+        }
+    };
+    buffer.truncate(offset + len);
+
+    if is_streamed {
+        let length = (BATCH_HEADER_LEN + len) as u16;
+        buffer[..LENGTH_BYTES].copy_from_slice(&length.to_le_bytes());
+    }
+}
+
+/// Decompress an LZ4 batch payload into `buffer`, returning the number of decompressed bytes.
+pub(crate) fn decompress_batch(payload: &[u8], buffer: &mut [u8]) -> ZResult<usize> {
+    block::decompress_into(payload, buffer).map_err(|e| {
+        let e = format!("Batch decompression error: {}", e);
+        zerror2!(ZErrorKind::InvalidMessage { descr: e })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compression_batch() {
+        let compressible =
+            br#"{"temperature": 21.5, "humidity": 40.2, "temperature": 21.5}"#.repeat(8);
+        let incompressible: Vec<u8> = (0..64u8).collect();
+
+        for payload in [&compressible[..], &incompressible[..]].iter() {
+            for is_streamed in [false, true].iter() {
+                let mut batch = vec![];
+                if *is_streamed {
+                    batch.extend_from_slice(&(payload.len() as u16).to_le_bytes());
+                }
+                batch.extend_from_slice(payload);
+
+                let mut buffer = vec![];
+                compress_batch(&batch, *is_streamed, &mut buffer);
+
+                let start = if *is_streamed {
+                    let length = u16::from_le_bytes([buffer[0], buffer[1]]) as usize;
+                    assert_eq!(length, buffer.len() - LENGTH_BYTES);
+                    LENGTH_BYTES
+                } else {
+                    0
+                };
+
+                let mut output = vec![0u8; payload.len()];
+                let n = match buffer[start] {
+                    BATCH_LZ4 => {
+                        assert!(buffer.len() < batch.len());
+                        decompress_batch(&buffer[start + BATCH_HEADER_LEN..], &mut output).unwrap()
+                    }
+                    BATCH_UNCOMPRESSED => {
+                        let raw = &buffer[start + BATCH_HEADER_LEN..];
+                        output[..raw.len()].copy_from_slice(raw);
+                        raw.len()
+                    }
+                    h => panic!("Unknown batch header: {}", h),
+                };
+                assert_eq!(&output[..n], *payload);
+            }
+        }
+    }
+}
@@ -12,6 +12,8 @@
 //   ADLINK zenoh team, <zenoh@adlink-labs.tech>
 //
 pub(crate) mod batch;
+#[cfg(feature = "transport_compression")]
+pub(crate) mod compression;
 pub(crate) mod conduit;
 pub(crate) mod defragmentation;
 pub(crate) mod pipeline;
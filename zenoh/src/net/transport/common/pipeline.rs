@@ -574,6 +574,14 @@ impl TransmissionPipeline {
         self.cond_canrefill[queue].notify_one();
     }
 
+    /// The number of batches waiting to be transmitted in each priority queue.
+    pub(crate) fn queue_depths(&self) -> Vec<usize> {
+        zlock!(self.stage_out)
+            .iter()
+            .map(|s| s.inner.len())
+            .collect()
+    }
+
     pub(crate) fn disable(&self) {
         // Mark the pipeline as no longer active
         self.active.store(false, Ordering::Release);
@@ -53,6 +53,9 @@ pub(super) struct TransportLinkMulticast {
     pipeline: Option<Arc<TransmissionPipeline>>,
     // The signals to stop TX/RX tasks
     handle_tx: Option<Arc<JoinHandle<()>>>,
+    // The signal to send a Join before the next batch
+    join_now: Arc<AtomicBool>,
+    signal_join: Signal,
     active_rx: Arc<AtomicBool>,
     signal_rx: Signal,
     handle_rx: Option<Arc<JoinHandle<()>>>,
@@ -68,6 +71,8 @@ impl TransportLinkMulticast {
             inner: link,
             pipeline: None,
             handle_tx: None,
+            join_now: Arc::new(AtomicBool::new(false)),
+            signal_join: Signal::new(),
             active_rx: Arc::new(AtomicBool::new(false)),
             signal_rx: Signal::new(),
             handle_rx: None,
@@ -111,12 +116,16 @@ impl TransportLinkMulticast {
             // Spawn the TX task
             let c_link = self.inner.clone();
             let c_transport = self.transport.clone();
+            let c_join_now = self.join_now.clone();
+            let c_signal = self.signal_join.clone();
             let handle = task::spawn(async move {
                 let res = tx_task(
                     pipeline,
                     c_link.clone(),
                     config,
                     initial_sns,
+                    c_join_now,
+                    c_signal,
                     #[cfg(feature = "stats")]
                     c_transport.stats.clone(),
                 )
@@ -132,6 +141,12 @@ impl TransportLinkMulticast {
         }
     }
 
+    // A new peer drops the messages sent on the group until it receives a Join
+    pub(super) fn send_join(&self) {
+        self.join_now.store(true, Ordering::Release);
+        self.signal_join.trigger();
+    }
+
     pub(super) fn stop_tx(&mut self) {
         if let Some(pipeline) = self.pipeline.take() {
             pipeline.disable();
@@ -203,6 +218,8 @@ async fn tx_task(
     link: LinkMulticast,
     config: TransportLinkMulticastConfig,
     mut next_sns: Vec<ConduitSn>,
+    join_now: Arc<AtomicBool>,
+    signal_join: Signal,
     #[cfg(feature = "stats")] stats: TransportMulticastStatsInner,
 ) -> ZResult<()> {
     enum Action {
@@ -222,20 +239,27 @@ async fn tx_task(
         }
     }
 
-    async fn join(last_join: Instant, join_interval: Duration) -> Action {
+    async fn join(
+        last_join: Instant,
+        join_interval: Duration,
+        join_now: &AtomicBool,
+        signal_join: &Signal,
+    ) -> Action {
         let now = Instant::now();
         let target = last_join + join_interval;
-        if now < target {
+        if now < target && !join_now.load(Ordering::Acquire) {
             let left = target - now;
-            task::sleep(left).await;
+            let _ = signal_join.wait().timeout(left).await;
         }
+        join_now.store(false, Ordering::Release);
         Action::Join
     }
 
     let mut last_join = Instant::now() - config.join_interval;
     loop {
-        match pull(&pipeline, config.keep_alive)
-            .race(join(last_join, config.join_interval))
+        // The Join is polled first to be sent before the batches that follow a new peer
+        match join(last_join, config.join_interval, &join_now, &signal_join)
+            .race(pull(&pipeline, config.keep_alive))
             .await
         {
             Action::Pull((batch, priority)) => {
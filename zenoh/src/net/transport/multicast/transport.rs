@@ -405,6 +405,11 @@ impl TransportMulticastInner {
             links: vec![link],
         };
 
+        // Let the new peer know about this one before anything else is sent to it
+        if let Some(link) = zread!(self.link).as_ref() {
+            link.send_join();
+        }
+
         let handler = match zread!(self.callback).as_ref() {
             Some(cb) => cb.new_peer(peer.clone())?,
             None => return Ok(()),
@@ -66,6 +66,22 @@ impl<P: 'static + Primitives> TransportPeerEventHandler for DeMux<P> {
                             self.primitives
                                 .forget_queryable(&q.key, msg.routing_context);
                         }
+                        Declaration::LivelinessToken(t) => {
+                            self.primitives
+                                .decl_liveliness_token(&t.key, msg.routing_context);
+                        }
+                        Declaration::ForgetLivelinessToken(ft) => {
+                            self.primitives
+                                .forget_liveliness_token(&ft.key, msg.routing_context);
+                        }
+                        Declaration::LivelinessSubscriber(ls) => {
+                            self.primitives
+                                .decl_liveliness_subscriber(&ls.key, msg.routing_context);
+                        }
+                        Declaration::ForgetLivelinessSubscriber(fls) => {
+                            self.primitives
+                                .forget_liveliness_subscriber(&fls.key, msg.routing_context);
+                        }
                     }
                 }
             }
@@ -85,6 +101,7 @@ impl<P: 'static + Primitives> TransportPeerEventHandler for DeMux<P> {
                         congestion_control,
                         data_info,
                         msg.routing_context,
+                        msg.attachment,
                     );
                 }
                 Some(rep) => match rep.replier {
@@ -93,9 +110,11 @@ impl<P: 'static + Primitives> TransportPeerEventHandler for DeMux<P> {
                             rep.qid,
                             replier.kind,
                             replier.id,
+                            rep.is_error,
                             key,
                             data_info,
                             payload,
+                            msg.attachment,
                         );
                     }
                     None => {
@@ -129,6 +148,7 @@ impl<P: 'static + Primitives> TransportPeerEventHandler for DeMux<P> {
                     target.unwrap_or_default(),
                     consolidation,
                     msg.routing_context,
+                    msg.attachment,
                 );
             }
 
@@ -19,7 +19,7 @@ use super::protocol::core::{
     Channel, CongestionControl, PeerId, QueryConsolidation, QueryTarget, ResKey, SubInfo, ZInt,
 };
 use super::protocol::io::ZBuf;
-use super::protocol::proto::{DataInfo, RoutingContext};
+use super::protocol::proto::{Attachment, DataInfo, RoutingContext};
 pub use demux::*;
 pub use mux::*;
 
@@ -41,6 +41,17 @@ pub trait Primitives: Send + Sync {
     fn decl_queryable(&self, reskey: &ResKey, kind: ZInt, routing_context: Option<RoutingContext>);
     fn forget_queryable(&self, reskey: &ResKey, routing_context: Option<RoutingContext>);
 
+    fn decl_liveliness_token(&self, reskey: &ResKey, routing_context: Option<RoutingContext>);
+    fn forget_liveliness_token(&self, reskey: &ResKey, routing_context: Option<RoutingContext>);
+
+    fn decl_liveliness_subscriber(&self, reskey: &ResKey, routing_context: Option<RoutingContext>);
+    fn forget_liveliness_subscriber(
+        &self,
+        reskey: &ResKey,
+        routing_context: Option<RoutingContext>,
+    );
+
+    #[allow(clippy::too_many_arguments)]
     fn send_data(
         &self,
         reskey: &ResKey,
@@ -49,8 +60,10 @@ pub trait Primitives: Send + Sync {
         cogestion_control: CongestionControl,
         data_info: Option<DataInfo>,
         routing_context: Option<RoutingContext>,
+        attachment: Option<Attachment>,
     );
 
+    #[allow(clippy::too_many_arguments)]
     fn send_query(
         &self,
         reskey: &ResKey,
@@ -59,22 +72,28 @@ pub trait Primitives: Send + Sync {
         target: QueryTarget,
         consolidation: QueryConsolidation,
         routing_context: Option<RoutingContext>,
+        attachment: Option<Attachment>,
     );
 
+    #[allow(clippy::too_many_arguments)]
     fn send_reply_data(
         &self,
         qid: ZInt,
         replier_kind: ZInt,
         replier_id: PeerId,
+        is_error: bool,
         reskey: ResKey,
         info: Option<DataInfo>,
         payload: ZBuf,
+        attachment: Option<Attachment>,
     );
 
     fn send_reply_final(&self, qid: ZInt);
 
     fn send_pull(&self, is_final: bool, reskey: &ResKey, pull_id: ZInt, max_samples: &Option<ZInt>);
 
+    fn send_matching_status(&self, reskey: &ResKey, matching: bool);
+
     fn send_close(&self);
 }
 
@@ -112,6 +131,22 @@ impl Primitives for DummyPrimitives {
     }
     fn forget_queryable(&self, _reskey: &ResKey, _routing_context: Option<RoutingContext>) {}
 
+    fn decl_liveliness_token(&self, _reskey: &ResKey, _routing_context: Option<RoutingContext>) {}
+    fn forget_liveliness_token(&self, _reskey: &ResKey, _routing_context: Option<RoutingContext>) {}
+
+    fn decl_liveliness_subscriber(
+        &self,
+        _reskey: &ResKey,
+        _routing_context: Option<RoutingContext>,
+    ) {
+    }
+    fn forget_liveliness_subscriber(
+        &self,
+        _reskey: &ResKey,
+        _routing_context: Option<RoutingContext>,
+    ) {
+    }
+
     fn send_data(
         &self,
         _reskey: &ResKey,
@@ -120,6 +155,7 @@ impl Primitives for DummyPrimitives {
         _cogestion_control: CongestionControl,
         _info: Option<DataInfo>,
         _routing_context: Option<RoutingContext>,
+        _attachment: Option<Attachment>,
     ) {
     }
     fn send_query(
@@ -130,6 +166,7 @@ impl Primitives for DummyPrimitives {
         _target: QueryTarget,
         _consolidation: QueryConsolidation,
         _routing_context: Option<RoutingContext>,
+        _attachment: Option<Attachment>,
     ) {
     }
     fn send_reply_data(
@@ -137,9 +174,11 @@ impl Primitives for DummyPrimitives {
         _qid: ZInt,
         _replier_kind: ZInt,
         _replier_id: PeerId,
+        _is_error: bool,
         _reskey: ResKey,
         _info: Option<DataInfo>,
         _payload: ZBuf,
+        _attachment: Option<Attachment>,
     ) {
     }
     fn send_reply_final(&self, _qid: ZInt) {}
@@ -152,5 +191,7 @@ impl Primitives for DummyPrimitives {
     ) {
     }
 
+    fn send_matching_status(&self, _reskey: &ResKey, _matching: bool) {}
+
     fn send_close(&self) {}
 }
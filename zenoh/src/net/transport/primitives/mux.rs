@@ -11,29 +11,50 @@
 // Contributors:
 //   ADLINK zenoh team, <zenoh@adlink-labs.tech>
 //
-use super::super::TransportUnicast;
+use super::super::{TransportMulticast, TransportUnicast};
 use super::protocol::core::{
     Channel, CongestionControl, PeerId, QueryConsolidation, QueryTarget, ResKey, SubInfo, ZInt,
 };
 use super::protocol::io::ZBuf;
 use super::protocol::proto::{
-    zmsg, DataInfo, Declaration, ForgetPublisher, ForgetQueryable, ForgetResource,
-    ForgetSubscriber, Publisher, Queryable, ReplierInfo, ReplyContext, Resource, RoutingContext,
+    zmsg, Attachment, DataInfo, Declaration, ForgetLivelinessSubscriber, ForgetLivelinessToken,
+    ForgetPublisher, ForgetQueryable, ForgetResource, ForgetSubscriber, LivelinessSubscriber,
+    LivelinessToken, Publisher, Queryable, ReplierInfo, ReplyContext, Resource, RoutingContext,
     Subscriber, ZenohMessage,
 };
 use super::Primitives;
+use zenoh_util::core::ZResult;
 
-pub struct Mux {
-    handler: TransportUnicast,
+/// A transport on which a [`Mux`] sends the messages of the primitives.
+pub trait MuxTransport: Send + Sync {
+    fn handle_message(&self, message: ZenohMessage) -> ZResult<()>;
 }
 
-impl Mux {
-    pub(crate) fn new(handler: TransportUnicast) -> Mux {
+impl MuxTransport for TransportUnicast {
+    #[inline(always)]
+    fn handle_message(&self, message: ZenohMessage) -> ZResult<()> {
+        TransportUnicast::handle_message(self, message)
+    }
+}
+
+impl MuxTransport for TransportMulticast {
+    #[inline(always)]
+    fn handle_message(&self, message: ZenohMessage) -> ZResult<()> {
+        TransportMulticast::handle_message(self, message)
+    }
+}
+
+pub struct Mux<T: MuxTransport> {
+    handler: T,
+}
+
+impl<T: MuxTransport> Mux<T> {
+    pub(crate) fn new(handler: T) -> Mux<T> {
         Mux { handler }
     }
 }
 
-impl Primitives for Mux {
+impl<T: MuxTransport> Primitives for Mux<T> {
     fn decl_resource(&self, rid: ZInt, reskey: &ResKey) {
         let d = Declaration::Resource(Resource {
             rid,
@@ -120,6 +141,50 @@ impl Primitives for Mux {
                 .handle_message(ZenohMessage::make_declare(decls, routing_context, None));
     }
 
+    fn decl_liveliness_token(&self, reskey: &ResKey, routing_context: Option<RoutingContext>) {
+        let d = Declaration::LivelinessToken(LivelinessToken {
+            key: reskey.clone(),
+        });
+        let decls = vec![d];
+        let _ =
+            self.handler
+                .handle_message(ZenohMessage::make_declare(decls, routing_context, None));
+    }
+
+    fn forget_liveliness_token(&self, reskey: &ResKey, routing_context: Option<RoutingContext>) {
+        let d = Declaration::ForgetLivelinessToken(ForgetLivelinessToken {
+            key: reskey.clone(),
+        });
+        let decls = vec![d];
+        let _ =
+            self.handler
+                .handle_message(ZenohMessage::make_declare(decls, routing_context, None));
+    }
+
+    fn decl_liveliness_subscriber(&self, reskey: &ResKey, routing_context: Option<RoutingContext>) {
+        let d = Declaration::LivelinessSubscriber(LivelinessSubscriber {
+            key: reskey.clone(),
+        });
+        let decls = vec![d];
+        let _ =
+            self.handler
+                .handle_message(ZenohMessage::make_declare(decls, routing_context, None));
+    }
+
+    fn forget_liveliness_subscriber(
+        &self,
+        reskey: &ResKey,
+        routing_context: Option<RoutingContext>,
+    ) {
+        let d = Declaration::ForgetLivelinessSubscriber(ForgetLivelinessSubscriber {
+            key: reskey.clone(),
+        });
+        let decls = vec![d];
+        let _ =
+            self.handler
+                .handle_message(ZenohMessage::make_declare(decls, routing_context, None));
+    }
+
     fn send_data(
         &self,
         reskey: &ResKey,
@@ -128,6 +193,7 @@ impl Primitives for Mux {
         cogestion_control: CongestionControl,
         data_info: Option<DataInfo>,
         routing_context: Option<RoutingContext>,
+        attachment: Option<Attachment>,
     ) {
         let _ = self.handler.handle_message(ZenohMessage::make_data(
             reskey.clone(),
@@ -137,7 +203,7 @@ impl Primitives for Mux {
             data_info,
             routing_context,
             None,
-            None,
+            attachment,
         ));
     }
 
@@ -149,6 +215,7 @@ impl Primitives for Mux {
         target: QueryTarget,
         consolidation: QueryConsolidation,
         routing_context: Option<RoutingContext>,
+        attachment: Option<Attachment>,
     ) {
         let target_opt = if target == QueryTarget::default() {
             None
@@ -162,7 +229,7 @@ impl Primitives for Mux {
             target_opt,
             consolidation,
             routing_context,
-            None,
+            attachment,
         ));
     }
 
@@ -171,9 +238,11 @@ impl Primitives for Mux {
         qid: ZInt,
         replier_kind: ZInt,
         replier_id: PeerId,
+        is_error: bool,
         reskey: ResKey,
         data_info: Option<DataInfo>,
         payload: ZBuf,
+        attachment: Option<Attachment>,
     ) {
         let _ = self.handler.handle_message(ZenohMessage::make_data(
             reskey,
@@ -182,14 +251,15 @@ impl Primitives for Mux {
             zmsg::default_congestion_control::REPLY,
             data_info,
             None,
-            Some(ReplyContext::new(
+            Some(ReplyContext {
                 qid,
-                Some(ReplierInfo {
+                replier: Some(ReplierInfo {
                     kind: replier_kind,
                     id: replier_id,
                 }),
-            )),
-            None,
+                is_error,
+            }),
+            attachment,
         ));
     }
 
@@ -218,6 +288,11 @@ impl Primitives for Mux {
         ));
     }
 
+    fn send_matching_status(&self, _reskey: &ResKey, _matching: bool) {
+        // Matching statuses are computed by the routing tables of each node
+        // and are never sent on the network.
+    }
+
     fn send_close(&self) {
         // self.handler.closing().await;
     }
@@ -11,6 +11,7 @@
 // Contributors:
 //   ADLINK zenoh team, <zenoh@adlink-labs.tech>
 //
+mod pubkey;
 #[cfg(feature = "zero-copy")]
 mod shm;
 mod userpassword;
@@ -22,6 +23,7 @@ use super::protocol::io::{WBuf, ZBuf};
 use crate::net::link::{Link, Locator};
 use async_std::sync::Arc;
 use async_trait::async_trait;
+pub use pubkey::*;
 #[cfg(feature = "zero-copy")]
 pub use shm::*;
 use std::collections::HashSet;
@@ -121,6 +123,7 @@ pub enum PeerAuthenticatorId {
     Reserved = 0,
     UserPassword = 1,
     Shm = 2,
+    PublicKey = 3,
 }
 
 #[derive(Clone)]
@@ -137,6 +140,11 @@ impl PeerAuthenticator {
             pas.insert(pa.into());
         }
 
+        let mut res = PubKeyAuthenticator::from_config(config).await?;
+        if let Some(pa) = res.take() {
+            pas.insert(pa.into());
+        }
+
         #[cfg(feature = "zero-copy")]
         {
             let mut res = SharedMemoryAuthenticator::from_config(config).await?;
@@ -188,19 +196,24 @@ impl fmt::Display for AuthenticatedPeerLink {
 // Authenticated peer transport
 pub struct AuthenticatedPeerTransport {
     pub is_shm: bool,
+    pub user: Option<String>,
 }
 
 impl AuthenticatedPeerTransport {
     pub fn merge(self, other: Self) -> Self {
         Self {
             is_shm: self.is_shm || other.is_shm,
+            user: self.user.or(other.user),
         }
     }
 }
 
 impl Default for AuthenticatedPeerTransport {
     fn default() -> Self {
-        Self { is_shm: false }
+        Self {
+            is_shm: false,
+            user: None,
+        }
     }
 }
 
@@ -254,6 +267,8 @@ pub trait PeerAuthenticatorTrait: Send + Sync {
     ///
     /// * `peer_id`         - The [`PeerId`][PeerId] of the sender of the InitSyn message
     ///
+    /// * `local_id`        - The [`PeerId`][PeerId] of the receiver of the InitSyn message
+    ///
     /// * `sn_resolution`   - The sn_resolution negotiated by the sender of the InitSyn message
     ///
     /// * `properties`      - The optional [`Property`][Property] included in the InitSyn message
@@ -262,6 +277,7 @@ pub trait PeerAuthenticatorTrait: Send + Sync {
         &self,
         link: &AuthenticatedPeerLink,
         peer_id: &PeerId,
+        local_id: &PeerId,
         sn_resolution: ZInt,
         properties: &[Property],
     ) -> ZResult<PeerAuthenticatorOutput>;
@@ -359,6 +375,7 @@ impl PeerAuthenticatorTrait for DummyPeerAuthenticator {
         &self,
         _link: &AuthenticatedPeerLink,
         _peer_id: &PeerId,
+        _local_id: &PeerId,
         _sn_resolution: ZInt,
         _properties: &[Property],
     ) -> ZResult<PeerAuthenticatorOutput> {
@@ -0,0 +1,653 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ADLINK zenoh team, <zenoh@adlink-labs.tech>
+//
+use super::{
+    AuthenticatedPeerLink, PeerAuthenticator, PeerAuthenticatorId, PeerAuthenticatorOutput,
+    PeerAuthenticatorTrait,
+};
+use super::{Locator, PeerId, Property, WBuf, ZBuf, ZInt};
+use async_std::fs;
+use async_std::sync::{Arc, Mutex, RwLock};
+use async_trait::async_trait;
+use rand::{Rng, SeedableRng};
+use ring::signature::{Ed25519KeyPair, KeyPair, UnparsedPublicKey, ED25519};
+use std::collections::{HashMap, HashSet};
+use zenoh_util::core::{ZError, ZErrorKind, ZResult};
+use zenoh_util::crypto::PseudoRng;
+use zenoh_util::properties::config::*;
+use zenoh_util::{zasynclock, zasyncread, zasyncwrite};
+
+const WBUF_SIZE: usize = 64;
+const PUBKEY_VERSION: ZInt = 0;
+const NONCE_SIZE: usize = 32;
+const PUBLIC_KEY_SIZE: usize = 32;
+// The DER prefix of an Ed25519 SubjectPublicKeyInfo, followed by the 32 bytes of the raw key
+const SPKI_PREFIX: [u8; 12] = [
+    0x30, 0x2a, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x03, 0x21, 0x00,
+];
+// The contexts of the signatures, so that a signature can not be replayed in another step
+const INIT_ACK_CONTEXT: &[u8] = b"zenoh-pubkey-init-ack";
+const OPEN_SYN_CONTEXT: &[u8] = b"zenoh-pubkey-open-syn";
+
+/*************************************/
+/*             InitSyn               */
+/*************************************/
+///  7 6 5 4 3 2 1 0
+/// +-+-+-+-+-+-+-+-+
+/// |0 0 0|  ATTCH  |
+/// +-+-+-+---------+
+/// ~    version    ~
+/// +---------------+
+/// ~  public key   ~
+/// +---------------+
+/// ~     nonce     ~ -- The challenge to be signed by the responder
+/// +---------------+
+struct InitSynProperty {
+    version: ZInt,
+    public_key: Vec<u8>,
+    nonce: Vec<u8>,
+}
+
+impl WBuf {
+    fn write_init_syn_property_pubkey(&mut self, init_syn_property: &InitSynProperty) -> bool {
+        zcheck!(self.write_zint(init_syn_property.version));
+        zcheck!(self.write_bytes_array(&init_syn_property.public_key));
+        self.write_bytes_array(&init_syn_property.nonce)
+    }
+}
+
+impl ZBuf {
+    fn read_init_syn_property_pubkey(&mut self) -> Option<InitSynProperty> {
+        let version = self.read_zint()?;
+        let public_key = self.read_bytes_array()?;
+        let nonce = self.read_bytes_array()?;
+        Some(InitSynProperty {
+            version,
+            public_key,
+            nonce,
+        })
+    }
+}
+
+/*************************************/
+/*             InitAck               */
+/*************************************/
+///  7 6 5 4 3 2 1 0
+/// +-+-+-+-+-+-+-+-+
+/// |0 0 0|  ATTCH  |
+/// +-+-+-+---------+
+/// ~  public key   ~
+/// +---------------+
+/// ~     nonce     ~ -- The challenge to be signed by the initiator
+/// +---------------+
+/// ~   signature   ~ -- The signature of both the initiator and responder challenges
+/// +---------------+    and of both the initiator and responder PeerIds
+struct InitAckProperty {
+    public_key: Vec<u8>,
+    nonce: Vec<u8>,
+    signature: Vec<u8>,
+}
+
+impl WBuf {
+    fn write_init_ack_property_pubkey(&mut self, init_ack_property: &InitAckProperty) -> bool {
+        zcheck!(self.write_bytes_array(&init_ack_property.public_key));
+        zcheck!(self.write_bytes_array(&init_ack_property.nonce));
+        self.write_bytes_array(&init_ack_property.signature)
+    }
+}
+
+impl ZBuf {
+    fn read_init_ack_property_pubkey(&mut self) -> Option<InitAckProperty> {
+        let public_key = self.read_bytes_array()?;
+        let nonce = self.read_bytes_array()?;
+        let signature = self.read_bytes_array()?;
+        Some(InitAckProperty {
+            public_key,
+            nonce,
+            signature,
+        })
+    }
+}
+
+/*************************************/
+/*             OpenSyn               */
+/*************************************/
+///  7 6 5 4 3 2 1 0
+/// +-+-+-+-+-+-+-+-+
+/// |0 0 0|  ATTCH  |
+/// +-+-+-+---------+
+/// ~   signature   ~ -- The signature of the responder challenge and of the initiator PeerId
+/// +---------------+
+struct OpenSynProperty {
+    signature: Vec<u8>,
+}
+
+impl WBuf {
+    fn write_open_syn_property_pubkey(&mut self, open_syn_property: &OpenSynProperty) -> bool {
+        self.write_bytes_array(&open_syn_property.signature)
+    }
+}
+
+impl ZBuf {
+    fn read_open_syn_property_pubkey(&mut self) -> Option<OpenSynProperty> {
+        let signature = self.read_bytes_array()?;
+        Some(OpenSynProperty { signature })
+    }
+}
+
+/*************************************/
+/*              Utils                */
+/*************************************/
+// The message to be signed: the context of the step, the challenge and the data bound to it
+fn challenge_message(context: &[u8], nonce: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut msg = Vec::with_capacity(context.len() + nonce.len() + data.len());
+    msg.extend_from_slice(context);
+    msg.extend_from_slice(nonce);
+    msg.extend_from_slice(data);
+    msg
+}
+
+// The message signed by the responder in the InitAck: both challenges and both PeerIds,
+// so that the PeerId of the responder can not be replaced by a relay
+fn init_ack_message(
+    initiator_nonce: &[u8],
+    responder_nonce: &[u8],
+    initiator_id: &PeerId,
+    responder_id: &PeerId,
+) -> Vec<u8> {
+    let mut data = responder_nonce.to_vec();
+    for id in [initiator_id, responder_id].iter() {
+        data.push(id.size() as u8);
+        data.extend_from_slice(id.as_slice());
+    }
+    challenge_message(INIT_ACK_CONTEXT, initiator_nonce, &data)
+}
+
+fn verify(public_key: &[u8], msg: &[u8], signature: &[u8]) -> bool {
+    UnparsedPublicKey::new(&ED25519, public_key)
+        .verify(msg, signature)
+        .is_ok()
+}
+
+// Decode the base64 body of a PEM document, or return the content as it is if not PEM encoded
+fn pem_to_der(content: &[u8]) -> ZResult<Vec<u8>> {
+    let text = match std::str::from_utf8(content) {
+        Ok(text) if text.trim_start().starts_with("-----BEGIN") => text,
+        _ => return Ok(content.to_vec()),
+    };
+    let body: String = text
+        .lines()
+        .map(|l| l.trim())
+        .filter(|l| !l.is_empty() && !l.starts_with("-----"))
+        .collect();
+    base64::decode(body).map_err(|e| {
+        let e = format!("Invalid PEM document: {}", e);
+        zerror2!(ZErrorKind::Other { descr: e })
+    })
+}
+
+/// Parse a list of Ed25519 public keys, one per line. Each key is the base64 encoding of
+/// either the 32 bytes of the raw key or of its DER SubjectPublicKeyInfo, as found in the
+/// body of a PEM public key. PEM boundaries, empty lines and lines starting with `#` are ignored.
+pub fn parse_public_keys(content: &str) -> ZResult<HashSet<Vec<u8>>> {
+    let mut keys = HashSet::new();
+    for line in content.lines().map(|l| l.trim()) {
+        if line.is_empty() || line.starts_with('#') || line.starts_with("-----") {
+            continue;
+        }
+        let der = base64::decode(line).map_err(|e| {
+            let e = format!("Invalid public key {}: {}", line, e);
+            zerror2!(ZErrorKind::Other { descr: e })
+        })?;
+        let key = if der.len() == PUBLIC_KEY_SIZE {
+            der
+        } else if der.len() == SPKI_PREFIX.len() + PUBLIC_KEY_SIZE && der.starts_with(&SPKI_PREFIX)
+        {
+            der[SPKI_PREFIX.len()..].to_vec()
+        } else {
+            let e = format!("Invalid Ed25519 public key: {}", line);
+            return zerror!(ZErrorKind::Other { descr: e });
+        };
+        keys.insert(key);
+    }
+    Ok(keys)
+}
+
+/*************************************/
+/*          Authenticator            */
+/*************************************/
+type LinkKey = (Locator, Locator);
+
+struct Authenticated {
+    public_key: Vec<u8>,
+    links: HashSet<LinkKey>,
+}
+
+// The challenge sent in an InitAck to an initiator
+struct Challenge {
+    peer_id: PeerId,
+    public_key: Vec<u8>,
+    nonce: Vec<u8>,
+}
+
+pub struct PubKeyAuthenticator {
+    keypair: Ed25519KeyPair,
+    trusted: RwLock<HashSet<Vec<u8>>>,
+    // The local PeerId and the nonce sent in the InitSyn messages
+    init_syn_nonces: Mutex<HashMap<LinkKey, (PeerId, Vec<u8>)>>,
+    // The challenges sent in the InitAck messages
+    init_ack_nonces: Mutex<HashMap<LinkKey, Challenge>>,
+    authenticated: Mutex<HashMap<PeerId, Authenticated>>,
+    prng: Mutex<PseudoRng>,
+}
+
+impl PubKeyAuthenticator {
+    /// Create a new [`PubKeyAuthenticator`][PubKeyAuthenticator].
+    ///
+    /// # Arguments
+    /// * `pkcs8`   - The DER encoded PKCS#8 document of the Ed25519 private key of this peer
+    ///
+    /// * `trusted` - The raw Ed25519 public keys of the peers allowed to open a transport
+    ///
+    pub fn new(pkcs8: &[u8], trusted: HashSet<Vec<u8>>) -> ZResult<PubKeyAuthenticator> {
+        let keypair = Ed25519KeyPair::from_pkcs8_maybe_unchecked(pkcs8).map_err(|e| {
+            let e = format!("Invalid Ed25519 private key: {}", e);
+            zerror2!(ZErrorKind::Other { descr: e })
+        })?;
+        Ok(PubKeyAuthenticator {
+            keypair,
+            trusted: RwLock::new(trusted),
+            init_syn_nonces: Mutex::new(HashMap::new()),
+            init_ack_nonces: Mutex::new(HashMap::new()),
+            authenticated: Mutex::new(HashMap::new()),
+            prng: Mutex::new(PseudoRng::from_entropy()),
+        })
+    }
+
+    /// The raw Ed25519 public key of this peer.
+    pub fn public_key(&self) -> Vec<u8> {
+        self.keypair.public_key().as_ref().to_vec()
+    }
+
+    pub async fn add_trusted_key(&self, public_key: Vec<u8>) -> ZResult<()> {
+        let mut guard = zasyncwrite!(self.trusted);
+        guard.insert(public_key);
+        Ok(())
+    }
+
+    pub async fn del_trusted_key(&self, public_key: &[u8]) -> ZResult<()> {
+        let mut guard = zasyncwrite!(self.trusted);
+        guard.remove(public_key);
+        Ok(())
+    }
+
+    pub async fn from_config(config: &ConfigProperties) -> ZResult<Option<PubKeyAuthenticator>> {
+        let path = match config.get(&ZN_AUTH_PRIVATE_KEY_KEY) {
+            Some(path) => path,
+            None => return Ok(None),
+        };
+        let content = fs::read(path).await.map_err(|e| {
+            zerror2!(ZErrorKind::Other {
+                descr: format!("Invalid private key file: {}", e)
+            })
+        })?;
+        let pkcs8 = pem_to_der(&content)?;
+
+        let mut trusted = HashSet::new();
+        if let Some(path) = config.get(&ZN_AUTH_TRUSTED_KEYS_KEY) {
+            let content = fs::read_to_string(path).await.map_err(|e| {
+                zerror2!(ZErrorKind::Other {
+                    descr: format!("Invalid trusted keys file: {}", e)
+                })
+            })?;
+            trusted = parse_public_keys(&content)?;
+        }
+
+        log::debug!(
+            "Public key authentication is enabled with {} trusted keys",
+            trusted.len()
+        );
+        Ok(Some(PubKeyAuthenticator::new(&pkcs8, trusted)?))
+    }
+
+    async fn is_trusted(&self, public_key: &[u8]) -> bool {
+        zasyncread!(self.trusted).contains(public_key)
+    }
+
+    async fn make_nonce(&self) -> Vec<u8> {
+        let mut nonce = vec![0u8; NONCE_SIZE];
+        zasynclock!(self.prng).fill(&mut nonce[..]);
+        nonce
+    }
+
+    // Bind the public key to the PeerId: all the links of a same PeerId must be
+    // authenticated with the same key
+    async fn bind(
+        &self,
+        link: &AuthenticatedPeerLink,
+        peer_id: PeerId,
+        public_key: Vec<u8>,
+    ) -> ZResult<()> {
+        let mut guard = zasynclock!(self.authenticated);
+        match guard.get_mut(&peer_id) {
+            Some(auth) => {
+                if auth.public_key != public_key {
+                    return zerror!(ZErrorKind::InvalidMessage {
+                        descr: format!(
+                            "Peer {} is already authenticated with another public key on link: {}",
+                            peer_id, link
+                        ),
+                    });
+                }
+                auth.links.insert((link.src.clone(), link.dst.clone()));
+            }
+            None => {
+                let mut links = HashSet::new();
+                links.insert((link.src.clone(), link.dst.clone()));
+                let auth = Authenticated { public_key, links };
+                guard.insert(peer_id, auth);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl PeerAuthenticatorTrait for PubKeyAuthenticator {
+    fn id(&self) -> PeerAuthenticatorId {
+        PeerAuthenticatorId::PublicKey
+    }
+
+    async fn get_init_syn_properties(
+        &self,
+        link: &AuthenticatedPeerLink,
+        peer_id: &PeerId,
+    ) -> ZResult<PeerAuthenticatorOutput> {
+        let nonce = self.make_nonce().await;
+        let init_syn_property = InitSynProperty {
+            version: PUBKEY_VERSION,
+            public_key: self.public_key(),
+            nonce: nonce.clone(),
+        };
+        let mut wbuf = WBuf::new(WBUF_SIZE, false);
+        wbuf.write_init_syn_property_pubkey(&init_syn_property);
+        let zbuf: ZBuf = wbuf.into();
+
+        // Keep the nonce to verify the signature of the responder
+        zasynclock!(self.init_syn_nonces)
+            .insert((link.src.clone(), link.dst.clone()), (*peer_id, nonce));
+
+        let mut res = PeerAuthenticatorOutput::default();
+        res.properties.push(Property {
+            key: PeerAuthenticatorId::PublicKey as ZInt,
+            value: zbuf.to_vec(),
+        });
+        Ok(res)
+    }
+
+    async fn handle_init_syn(
+        &self,
+        link: &AuthenticatedPeerLink,
+        peer_id: &PeerId,
+        local_id: &PeerId,
+        _sn_resolution: ZInt,
+        properties: &[Property],
+    ) -> ZResult<PeerAuthenticatorOutput> {
+        let res = properties
+            .iter()
+            .find(|p| p.key == PeerAuthenticatorId::PublicKey as ZInt);
+        let mut zbuf: ZBuf = match res {
+            Some(p) => p.value.clone().into(),
+            None => {
+                return zerror!(ZErrorKind::InvalidMessage {
+                    descr: format!("Received InitSyn with no attachment on link: {}", link),
+                });
+            }
+        };
+        let init_syn_property = match zbuf.read_init_syn_property_pubkey() {
+            Some(isp) => isp,
+            None => {
+                return zerror!(ZErrorKind::InvalidMessage {
+                    descr: format!("Received InitSyn with invalid attachment on link: {}", link),
+                });
+            }
+        };
+
+        if init_syn_property.version > PUBKEY_VERSION {
+            return zerror!(ZErrorKind::InvalidMessage {
+                descr: format!("Rejected InitSyn with invalid attachment on link: {}", link),
+            });
+        }
+
+        if !self.is_trusted(&init_syn_property.public_key).await {
+            return zerror!(ZErrorKind::InvalidMessage {
+                descr: format!(
+                    "Rejected InitSyn with untrusted public key on link: {}",
+                    link
+                ),
+            });
+        }
+
+        // Create a new challenge for the initiator and answer the one received
+        let nonce = self.make_nonce().await;
+        let msg = init_ack_message(&init_syn_property.nonce, &nonce, peer_id, local_id);
+        let init_ack_property = InitAckProperty {
+            public_key: self.public_key(),
+            nonce: nonce.clone(),
+            signature: self.keypair.sign(&msg).as_ref().to_vec(),
+        };
+
+        // Insert the nonce in the set of sent nonces
+        zasynclock!(self.init_ack_nonces).insert(
+            (link.src.clone(), link.dst.clone()),
+            Challenge {
+                peer_id: *peer_id,
+                public_key: init_syn_property.public_key,
+                nonce,
+            },
+        );
+
+        let mut wbuf = WBuf::new(WBUF_SIZE, false);
+        wbuf.write_init_ack_property_pubkey(&init_ack_property);
+        let zbuf: ZBuf = wbuf.into();
+        let mut res = PeerAuthenticatorOutput::default();
+        res.properties.push(Property {
+            key: PeerAuthenticatorId::PublicKey as ZInt,
+            value: zbuf.to_vec(),
+        });
+        Ok(res)
+    }
+
+    async fn handle_init_ack(
+        &self,
+        link: &AuthenticatedPeerLink,
+        peer_id: &PeerId,
+        _sn_resolution: ZInt,
+        properties: &[Property],
+    ) -> ZResult<PeerAuthenticatorOutput> {
+        let key = (link.src.clone(), link.dst.clone());
+        let (local_id, nonce) = match zasynclock!(self.init_syn_nonces).remove(&key) {
+            Some(tuple) => tuple,
+            None => {
+                return zerror!(ZErrorKind::InvalidMessage {
+                    descr: format!(
+                        "Received InitAck but no nonce has been associated to link: {}",
+                        link
+                    ),
+                });
+            }
+        };
+
+        let res = properties
+            .iter()
+            .find(|p| p.key == PeerAuthenticatorId::PublicKey as ZInt);
+        let mut zbuf: ZBuf = match res {
+            Some(p) => p.value.clone().into(),
+            None => {
+                return zerror!(ZErrorKind::InvalidMessage {
+                    descr: format!("Received InitAck with no attachment on link: {}", link),
+                });
+            }
+        };
+        let init_ack_property = match zbuf.read_init_ack_property_pubkey() {
+            Some(iap) => iap,
+            None => {
+                return zerror!(ZErrorKind::InvalidMessage {
+                    descr: format!("Received InitAck with invalid attachment on link: {}", link),
+                });
+            }
+        };
+
+        if !self.is_trusted(&init_ack_property.public_key).await {
+            return zerror!(ZErrorKind::InvalidMessage {
+                descr: format!(
+                    "Rejected InitAck with untrusted public key on link: {}",
+                    link
+                ),
+            });
+        }
+
+        // Verify that the responder owns the private key of the public key it claims,
+        // and that the PeerIds are the ones the responder signed for
+        let msg = init_ack_message(&nonce, &init_ack_property.nonce, &local_id, peer_id);
+        if !verify(
+            &init_ack_property.public_key,
+            &msg,
+            &init_ack_property.signature,
+        ) {
+            return zerror!(ZErrorKind::InvalidMessage {
+                descr: format!("Rejected InitAck with invalid signature on link: {}", link),
+            });
+        }
+        self.bind(link, *peer_id, init_ack_property.public_key)
+            .await?;
+
+        // Answer the challenge of the responder, binding the local PeerId to the public key
+        let msg = challenge_message(
+            OPEN_SYN_CONTEXT,
+            &init_ack_property.nonce,
+            local_id.as_slice(),
+        );
+        let open_syn_property = OpenSynProperty {
+            signature: self.keypair.sign(&msg).as_ref().to_vec(),
+        };
+        let mut wbuf = WBuf::new(WBUF_SIZE, false);
+        wbuf.write_open_syn_property_pubkey(&open_syn_property);
+        let zbuf: ZBuf = wbuf.into();
+        let mut res = PeerAuthenticatorOutput::default();
+        res.properties.push(Property {
+            key: PeerAuthenticatorId::PublicKey as ZInt,
+            value: zbuf.to_vec(),
+        });
+        Ok(res)
+    }
+
+    async fn handle_open_syn(
+        &self,
+        link: &AuthenticatedPeerLink,
+        properties: &[Property],
+    ) -> ZResult<PeerAuthenticatorOutput> {
+        let challenge =
+            match zasynclock!(self.init_ack_nonces).remove(&(link.src.clone(), link.dst.clone())) {
+                Some(challenge) => challenge,
+                None => {
+                    return zerror!(ZErrorKind::InvalidMessage {
+                        descr: format!(
+                            "Received OpenSyn but no nonce has been associated to link: {}",
+                            link
+                        ),
+                    });
+                }
+            };
+
+        let res = properties
+            .iter()
+            .find(|p| p.key == PeerAuthenticatorId::PublicKey as ZInt);
+        let mut zbuf: ZBuf = match res {
+            Some(p) => p.value.clone().into(),
+            None => {
+                return zerror!(ZErrorKind::InvalidMessage {
+                    descr: format!("Received OpenSyn with no attachment on link: {}", link),
+                });
+            }
+        };
+        let open_syn_property = match zbuf.read_open_syn_property_pubkey() {
+            Some(osp) => osp,
+            None => {
+                return zerror!(ZErrorKind::InvalidMessage {
+                    descr: format!("Received OpenSyn with invalid attachment on link: {}", link),
+                });
+            }
+        };
+
+        // Verify that the initiator owns the private key and the PeerId it claims
+        let msg = challenge_message(
+            OPEN_SYN_CONTEXT,
+            &challenge.nonce,
+            challenge.peer_id.as_slice(),
+        );
+        if !verify(&challenge.public_key, &msg, &open_syn_property.signature) {
+            return zerror!(ZErrorKind::InvalidMessage {
+                descr: format!("Rejected OpenSyn with invalid signature on link: {}", link),
+            });
+        }
+        self.bind(link, challenge.peer_id, challenge.public_key)
+            .await?;
+
+        Ok(PeerAuthenticatorOutput::default())
+    }
+
+    async fn handle_open_ack(
+        &self,
+        _link: &AuthenticatedPeerLink,
+        _properties: &[Property],
+    ) -> ZResult<PeerAuthenticatorOutput> {
+        Ok(PeerAuthenticatorOutput::default())
+    }
+
+    async fn handle_link_err(&self, link: &AuthenticatedPeerLink) {
+        let key = (link.src.clone(), link.dst.clone());
+        zasynclock!(self.init_syn_nonces).remove(&key);
+        zasynclock!(self.init_ack_nonces).remove(&key);
+
+        // Need to check if it authenticated and remove it if this is the last link
+        let mut guard = zasynclock!(self.authenticated);
+        let mut to_del: Option<PeerId> = None;
+        for (peer_id, auth) in guard.iter_mut() {
+            auth.links.remove(&key);
+            if auth.links.is_empty() {
+                to_del = Some(*peer_id);
+                break;
+            }
+        }
+        if let Some(peer_id) = to_del.take() {
+            guard.remove(&peer_id);
+        }
+    }
+
+    async fn handle_close(&self, peer_id: &PeerId) {
+        zasynclock!(self.authenticated).remove(peer_id);
+    }
+}
+
+impl From<Arc<PubKeyAuthenticator>> for PeerAuthenticator {
+    fn from(v: Arc<PubKeyAuthenticator>) -> PeerAuthenticator {
+        PeerAuthenticator(v)
+    }
+}
+
+impl From<PubKeyAuthenticator> for PeerAuthenticator {
+    fn from(v: PubKeyAuthenticator) -> PeerAuthenticator {
+        Self::from(Arc::new(v))
+    }
+}
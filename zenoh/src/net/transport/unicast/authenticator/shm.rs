@@ -229,6 +229,7 @@ impl PeerAuthenticatorTrait for SharedMemoryAuthenticator {
         &self,
         link: &AuthenticatedPeerLink,
         peer_id: &PeerId,
+        _local_id: &PeerId,
         _sn_resolution: ZInt,
         properties: &[Property],
     ) -> ZResult<PeerAuthenticatorOutput> {
@@ -254,6 +254,7 @@ impl PeerAuthenticatorTrait for UserPasswordAuthenticator {
         &self,
         link: &AuthenticatedPeerLink,
         peer_id: &PeerId,
+        _local_id: &PeerId,
         sn_resolution: ZInt,
         properties: &[Property],
     ) -> ZResult<PeerAuthenticatorOutput> {
@@ -413,6 +414,8 @@ impl PeerAuthenticatorTrait for UserPasswordAuthenticator {
             });
         }
 
+        let user = String::from_utf8_lossy(&open_syn_property.user).to_string();
+
         // Check PID validity
         let mut guard = zasynclock!(self.authenticated);
         match guard.get_mut(&peer_id) {
@@ -438,7 +441,9 @@ impl PeerAuthenticatorTrait for UserPasswordAuthenticator {
             }
         }
 
-        Ok(PeerAuthenticatorOutput::default())
+        let mut res = PeerAuthenticatorOutput::default();
+        res.transport.user = Some(user);
+        Ok(res)
     }
 
     async fn handle_open_ack(
@@ -65,6 +65,7 @@ struct Cookie {
     pid: PeerId,
     sn_resolution: ZInt,
     is_qos: bool,
+    is_compression: bool,
     nonce: ZInt,
 }
 
@@ -74,6 +75,7 @@ impl WBuf {
         zcheck!(self.write_peerid(&cookie.pid));
         zcheck!(self.write_zint(cookie.sn_resolution));
         zcheck!(self.write(if cookie.is_qos { 1 } else { 0 }));
+        zcheck!(self.write(if cookie.is_compression { 1 } else { 0 }));
         zcheck!(self.write_zint(cookie.nonce));
         true
     }
@@ -85,6 +87,7 @@ impl ZBuf {
         let pid = self.read_peerid()?;
         let sn_resolution = self.read_zint()?;
         let is_qos = self.read()? == 1;
+        let is_compression = self.read()? == 1;
         let nonce = self.read_zint()?;
 
         Some(Cookie {
@@ -92,6 +95,7 @@ impl ZBuf {
             pid,
             sn_resolution,
             is_qos,
+            is_compression,
             nonce,
         })
     }
@@ -125,12 +129,14 @@ async fn close_link(
 /*************************************/
 struct OpenInitSynOutput {
     sn_resolution: ZInt,
+    is_compression: bool,
     auth_transport: AuthenticatedPeerTransport,
 }
 async fn open_send_init_syn(
     manager: &TransportManager,
     link: &LinkUnicast,
     auth_link: &AuthenticatedPeerLink,
+    is_compression: bool,
 ) -> IResult<OpenInitSynOutput> {
     let mut auth = PeerAuthenticatorOutput::default();
     for pa in manager.config.unicast.peer_authenticator.iter() {
@@ -148,6 +154,7 @@ async fn open_send_init_syn(
         manager.config.pid,
         manager.config.sn_resolution,
         manager.config.unicast.is_qos,
+        is_compression,
         attachment_from_config(&auth.properties).ok(),
     );
     let _ = link
@@ -157,6 +164,7 @@ async fn open_send_init_syn(
 
     let output = OpenInitSynOutput {
         sn_resolution: manager.config.sn_resolution,
+        is_compression,
         auth_transport: auth.transport,
     };
     Ok(output)
@@ -167,6 +175,7 @@ struct OpenInitAckOutput {
     whatami: WhatAmI,
     sn_resolution: ZInt,
     is_qos: bool,
+    is_compression: bool,
     initial_sn_tx: ZInt,
     cookie: ZSlice,
     open_syn_attachment: Option<Attachment>,
@@ -292,6 +301,8 @@ async fn open_recv_init_ack(
         whatami: init_ack.whatami,
         sn_resolution,
         is_qos: init_ack.is_qos,
+        // Compression is used only if requested and accepted by the remote peer
+        is_compression: input.is_compression && init_ack.is_compression,
         initial_sn_tx,
         cookie: init_ack.cookie,
         open_syn_attachment: attachment_from_config(&auth.properties).ok(),
@@ -306,6 +317,7 @@ struct OpenOpenSynOutput {
     sn_resolution: ZInt,
     initial_sn_tx: ZInt,
     is_qos: bool,
+    is_compression: bool,
     auth_transport: AuthenticatedPeerTransport,
 }
 async fn open_send_open_syn(
@@ -333,6 +345,7 @@ async fn open_send_open_syn(
         sn_resolution: input.sn_resolution,
         initial_sn_tx: input.initial_sn_tx,
         is_qos: input.is_qos,
+        is_compression: input.is_compression,
         auth_transport: input.auth_transport,
     };
     Ok(output)
@@ -343,6 +356,7 @@ struct OpenAckOutput {
     whatami: WhatAmI,
     sn_resolution: ZInt,
     is_qos: bool,
+    is_compression: bool,
     initial_sn_tx: ZInt,
     initial_sn_rx: ZInt,
     lease: Duration,
@@ -407,6 +421,7 @@ async fn open_recv_open_ack(
         whatami: input.whatami,
         sn_resolution: input.sn_resolution,
         is_qos: input.is_qos,
+        is_compression: input.is_compression,
         initial_sn_tx: input.initial_sn_tx,
         initial_sn_rx,
         lease,
@@ -419,8 +434,9 @@ async fn open_stages(
     manager: &TransportManager,
     link: &LinkUnicast,
     auth_link: &AuthenticatedPeerLink,
+    is_compression: bool,
 ) -> IResult<OpenAckOutput> {
-    let output = open_send_init_syn(manager, link, auth_link).await?;
+    let output = open_send_init_syn(manager, link, auth_link, is_compression).await?;
     let output = open_recv_init_ack(manager, link, auth_link, output).await?;
     let output = open_send_open_syn(manager, link, auth_link, output).await?;
     open_recv_open_ack(manager, link, auth_link, output).await
@@ -429,6 +445,7 @@ async fn open_stages(
 pub(crate) async fn open_link(
     manager: &TransportManager,
     link: &LinkUnicast,
+    is_compression: bool,
 ) -> ZResult<TransportUnicast> {
     let auth_link = AuthenticatedPeerLink {
         src: link.get_src(),
@@ -436,7 +453,7 @@ pub(crate) async fn open_link(
         peer_id: None,
     };
 
-    let res = open_stages(manager, link, &auth_link).await;
+    let res = open_stages(manager, link, &auth_link, is_compression).await;
     let info = match res {
         Ok(v) => v,
         Err((e, reason)) => {
@@ -453,6 +470,7 @@ pub(crate) async fn open_link(
         initial_sn_rx: info.initial_sn_rx,
         is_shm: info.auth_transport.is_shm,
         is_qos: info.is_qos,
+        user: info.auth_transport.user.clone(),
     };
     let res = manager.init_transport_unicast(config);
     let transport = match res {
@@ -477,7 +495,7 @@ pub(crate) async fn open_link(
         //       target interval. For simplicity, we compute the keep_alive interval as 1/4 of the
         //       transport lease.
         let keep_alive = manager.config.unicast.keep_alive.min(info.lease / 4);
-        let _ = t.add_link(link.clone())?;
+        t.add_link(link.clone(), info.is_compression)?;
 
         // Start the TX loop
         let _ = t.start_tx(link, keep_alive, manager.config.batch_size)?;
@@ -532,6 +550,7 @@ struct AcceptInitSynOutput {
     pid: PeerId,
     sn_resolution: ZInt,
     is_qos: bool,
+    is_compression: bool,
     init_ack_attachment: Option<Attachment>,
     auth_transport: AuthenticatedPeerTransport,
 }
@@ -596,6 +615,20 @@ async fn accept_recv_init_syn(
         ));
     }
 
+    // Check if the PeerId matches the one authenticated at link level
+    if let Some(pid) = auth_link.peer_id.as_ref() {
+        if pid != &init_syn.pid {
+            let e = format!(
+                "Rejecting InitSyn on {} because of PeerId mismatch: expected {}, received {}",
+                link, pid, init_syn.pid
+            );
+            return Err((
+                zerror2!(ZErrorKind::InvalidMessage { descr: e }),
+                Some(tmsg::close_reason::INVALID),
+            ));
+        }
+    }
+
     // Validate the InitSyn with the peer authenticators
     let init_syn_properties: Vec<Property> = match msg.attachment.take() {
         Some(att) => {
@@ -609,6 +642,7 @@ async fn accept_recv_init_syn(
             .handle_init_syn(
                 auth_link,
                 &init_syn.pid,
+                &manager.config.pid,
                 init_syn.sn_resolution,
                 &init_syn_properties,
             )
@@ -617,11 +651,18 @@ async fn accept_recv_init_syn(
         auth = auth.merge(ps);
     }
 
+    // Compression is enabled only if both sides request it
+    #[cfg(feature = "transport_compression")]
+    let is_compression = init_syn.is_compression && manager.config.unicast.is_compression;
+    #[cfg(not(feature = "transport_compression"))]
+    let is_compression = false;
+
     let output = AcceptInitSynOutput {
         whatami: init_syn.whatami,
         pid: init_syn.pid,
         sn_resolution: init_syn.sn_resolution,
         is_qos: init_syn.is_qos,
+        is_compression,
         init_ack_attachment: attachment_from_config(&auth.properties).ok(),
         auth_transport: auth.transport,
     };
@@ -648,6 +689,7 @@ async fn accept_send_init_ack(
         pid: input.pid,
         sn_resolution: agreed_sn_resolution,
         is_qos: input.is_qos,
+        is_compression: input.is_compression,
         nonce: zasynclock!(manager.prng).gen_range(0..agreed_sn_resolution),
     };
     wbuf.write_cookie(&cookie);
@@ -678,6 +720,7 @@ async fn accept_send_init_ack(
         apid,
         sn_resolution,
         input.is_qos,
+        input.is_compression,
         cookie,
         input.init_ack_attachment,
     );
@@ -891,6 +934,7 @@ async fn accept_init_transport(
         initial_sn_rx: input.initial_sn,
         is_shm: input.auth_transport.is_shm,
         is_qos: input.cookie.is_qos,
+        user: input.auth_transport.user.clone(),
     };
     let transport = manager
         .init_transport_unicast(config)
@@ -899,7 +943,7 @@ async fn accept_init_transport(
     // Retrieve the transport's transport
     let t = transport.get_transport().map_err(|e| (e, None))?;
     let _ = t
-        .add_link(link.clone())
+        .add_link(link.clone(), input.cookie.is_compression)
         .map_err(|e| (e, Some(tmsg::close_reason::GENERIC)))?;
 
     log::debug!(
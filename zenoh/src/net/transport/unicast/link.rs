@@ -11,6 +11,8 @@
 // Contributors:
 //   ADLINK zenoh team, <zenoh@adlink-labs.tech>
 //
+#[cfg(feature = "transport_compression")]
+use super::common::compression::{self, BATCH_HEADER_LEN, BATCH_LZ4};
 use super::common::{conduit::TransportConduitTx, pipeline::TransmissionPipeline};
 use super::protocol::core::Priority;
 use super::protocol::io::{ZBuf, ZSlice};
@@ -25,7 +27,7 @@ use async_std::task::JoinHandle;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
-use zenoh_util::collections::RecyclingObjectPool;
+use zenoh_util::collections::{RecyclingObject, RecyclingObjectPool};
 use zenoh_util::core::{ZError, ZErrorKind, ZResult};
 use zenoh_util::sync::Signal;
 use zenoh_util::zerror;
@@ -36,8 +38,12 @@ pub(super) struct TransportLinkUnicast {
     pub(super) inner: LinkUnicast,
     // The transport this link is associated to
     transport: TransportUnicastInner,
+    // The batches sent and received on this link are compressed
+    is_compression: bool,
     // The transmission pipeline
     pipeline: Option<Arc<TransmissionPipeline>>,
+    // The lease announced by the remote peer on this link
+    lease: Option<Duration>,
     // The signals to stop TX/RX tasks
     handle_tx: Option<Arc<JoinHandle<()>>>,
     active_rx: Arc<AtomicBool>,
@@ -46,11 +52,17 @@ pub(super) struct TransportLinkUnicast {
 }
 
 impl TransportLinkUnicast {
-    pub(super) fn new(transport: TransportUnicastInner, link: LinkUnicast) -> TransportLinkUnicast {
+    pub(super) fn new(
+        transport: TransportUnicastInner,
+        link: LinkUnicast,
+        is_compression: bool,
+    ) -> TransportLinkUnicast {
         TransportLinkUnicast {
             transport,
             inner: link,
+            is_compression,
             pipeline: None,
+            lease: None,
             handle_tx: None,
             active_rx: Arc::new(AtomicBool::new(false)),
             signal_rx: Signal::new(),
@@ -70,6 +82,11 @@ impl TransportLinkUnicast {
         self.pipeline.clone()
     }
 
+    #[inline]
+    pub(super) fn get_lease(&self) -> Option<Duration> {
+        self.lease
+    }
+
     pub(super) fn start_tx(
         &mut self,
         keep_alive: Duration,
@@ -77,9 +94,16 @@ impl TransportLinkUnicast {
         conduit_tx: Arc<[TransportConduitTx]>,
     ) {
         if self.handle_tx.is_none() {
+            #[allow(unused_mut)] // Used when transport_compression feature is enabled
+            let mut batch_size = batch_size.min(self.inner.get_mtu());
+            #[cfg(feature = "transport_compression")]
+            if self.is_compression {
+                // Reserve the room for the compression header
+                batch_size -= BATCH_HEADER_LEN as u16;
+            }
             // The pipeline
             let pipeline = Arc::new(TransmissionPipeline::new(
-                batch_size.min(self.inner.get_mtu()),
+                batch_size,
                 self.inner.is_streamed(),
                 conduit_tx,
             ));
@@ -88,11 +112,13 @@ impl TransportLinkUnicast {
             // Spawn the TX task
             let c_link = self.inner.clone();
             let c_transport = self.transport.clone();
+            let c_is_compression = self.is_compression;
             let handle = task::spawn(async move {
                 let res = tx_task(
                     pipeline,
                     c_link.clone(),
                     keep_alive,
+                    c_is_compression,
                     #[cfg(feature = "stats")]
                     c_transport.stats.clone(),
                 )
@@ -116,6 +142,7 @@ impl TransportLinkUnicast {
 
     pub(super) fn start_rx(&mut self, lease: Duration) {
         if self.handle_rx.is_none() {
+            self.lease = Some(lease);
             self.active_rx.store(true, Ordering::Release);
             // Spawn the RX task
             let c_link = self.inner.clone();
@@ -123,6 +150,7 @@ impl TransportLinkUnicast {
             let c_signal = self.signal_rx.clone();
             let c_active = self.active_rx.clone();
             let c_rx_buff_size = self.transport.manager.config.link_rx_buff_size;
+            let c_is_compression = self.is_compression;
 
             let handle = task::spawn(async move {
                 // Start the consume task
@@ -133,6 +161,7 @@ impl TransportLinkUnicast {
                     c_signal.clone(),
                     c_active.clone(),
                     c_rx_buff_size,
+                    c_is_compression,
                 )
                 .await;
                 c_active.store(false, Ordering::Release);
@@ -175,18 +204,30 @@ impl TransportLinkUnicast {
 /*************************************/
 /*              TASKS                */
 /*************************************/
+#[allow(unused_variables)] // Used when transport_compression feature is enabled
 async fn tx_task(
     pipeline: Arc<TransmissionPipeline>,
     link: LinkUnicast,
     keep_alive: Duration,
+    is_compression: bool,
     #[cfg(feature = "stats")] stats: TransportUnicastStatsInner,
 ) -> ZResult<()> {
+    // The buffer to compress the batches onto
+    #[cfg(feature = "transport_compression")]
+    let mut buffer: Vec<u8> = vec![];
     loop {
         match pipeline.pull().timeout(keep_alive).await {
             Ok(res) => match res {
                 Some((batch, priority)) => {
-                    // Send the buffer on the link
                     let bytes = batch.as_bytes();
+                    #[cfg(feature = "transport_compression")]
+                    let bytes = if is_compression {
+                        compression::compress_batch(bytes, link.is_streamed(), &mut buffer);
+                        &buffer[..]
+                    } else {
+                        bytes
+                    };
+                    // Send the buffer on the link
                     let _ = link.write_all(bytes).await?;
 
                     #[cfg(feature = "stats")]
@@ -212,8 +253,16 @@ async fn tx_task(
     // Drain the transmission pipeline and write remaining bytes on the wire
     let mut batches = pipeline.drain();
     for (b, _) in batches.drain(..) {
+        let bytes = b.as_bytes();
+        #[cfg(feature = "transport_compression")]
+        let bytes = if is_compression {
+            compression::compress_batch(bytes, link.is_streamed(), &mut buffer);
+            &buffer[..]
+        } else {
+            bytes
+        };
         let _ = link
-            .write_all(b.as_bytes())
+            .write_all(bytes)
             .timeout(keep_alive)
             .await
             .map_err(|_| {
@@ -238,6 +287,7 @@ async fn rx_task_stream(
     signal: Signal,
     active: Arc<AtomicBool>,
     rx_buff_size: usize,
+    is_compression: bool,
 ) -> ZResult<()> {
     enum Action {
         Read(usize),
@@ -282,7 +332,7 @@ async fn rx_task_stream(
             })??;
         match action {
             Action::Read(n) => {
-                zbuf.add_zslice(ZSlice::new(buffer.into(), 0, n));
+                zbuf.add_zslice(batch_zslice(buffer, n, is_compression, &pool)?);
 
                 #[cfg(feature = "stats")]
                 transport.stats.inc_rx_bytes(2 + n); // Account for the batch len encoding (16 bits)
@@ -315,6 +365,7 @@ async fn rx_task_dgram(
     signal: Signal,
     active: Arc<AtomicBool>,
     rx_buff_size: usize,
+    is_compression: bool,
 ) -> ZResult<()> {
     enum Action {
         Read(usize),
@@ -364,7 +415,7 @@ async fn rx_task_dgram(
                 transport.stats.inc_rx_bytes(n);
 
                 // Add the received bytes to the ZBuf for deserialization
-                zbuf.add_zslice(ZSlice::new(buffer.into(), 0, n));
+                zbuf.add_zslice(batch_zslice(buffer, n, is_compression, &pool)?);
 
                 // Deserialize all the messages from the current ZBuf
                 while zbuf.can_read() {
@@ -395,10 +446,54 @@ async fn rx_task(
     signal: Signal,
     active: Arc<AtomicBool>,
     rx_buff_size: usize,
+    is_compression: bool,
 ) -> ZResult<()> {
     if link.is_streamed() {
-        rx_task_stream(link, transport, lease, signal, active, rx_buff_size).await
+        rx_task_stream(
+            link,
+            transport,
+            lease,
+            signal,
+            active,
+            rx_buff_size,
+            is_compression,
+        )
+        .await
     } else {
-        rx_task_dgram(link, transport, lease, signal, active, rx_buff_size).await
+        rx_task_dgram(
+            link,
+            transport,
+            lease,
+            signal,
+            active,
+            rx_buff_size,
+            is_compression,
+        )
+        .await
+    }
+}
+
+// Build the ZSlice holding the serialized messages of a batch of n bytes read from the link,
+// eventually decompressing it onto a new buffer from the pool
+#[allow(unused_variables)] // Used when transport_compression feature is enabled
+fn batch_zslice<F: Fn() -> Box<[u8]>>(
+    buffer: RecyclingObject<Box<[u8]>>,
+    n: usize,
+    is_compression: bool,
+    pool: &RecyclingObjectPool<Box<[u8]>, F>,
+) -> ZResult<ZSlice> {
+    #[cfg(feature = "transport_compression")]
+    if is_compression {
+        if n < BATCH_HEADER_LEN {
+            let e = "Missing batch compression header".to_string();
+            return zerror!(ZErrorKind::InvalidMessage { descr: e });
+        }
+        if buffer[0] == BATCH_LZ4 {
+            let mut output = pool.try_take().unwrap_or_else(|| pool.alloc());
+            let len = compression::decompress_batch(&buffer[BATCH_HEADER_LEN..n], &mut output)?;
+            return Ok(ZSlice::new(output.into(), 0, len));
+        }
+        return Ok(ZSlice::new(buffer.into(), BATCH_HEADER_LEN, n));
     }
+    Ok(ZSlice::new(buffer.into(), 0, n))
 }
@@ -38,6 +38,8 @@ pub struct TransportManagerConfigUnicast {
     pub is_qos: bool,
     #[cfg(feature = "zero-copy")]
     pub is_shm: bool,
+    #[cfg(feature = "transport_compression")]
+    pub is_compression: bool,
     pub peer_authenticator: HashSet<PeerAuthenticator>,
     pub link_authenticator: HashSet<LinkAuthenticator>,
 }
@@ -64,6 +66,8 @@ pub struct TransportManagerConfigBuilderUnicast {
     pub(super) is_qos: bool,
     #[cfg(feature = "zero-copy")]
     pub(super) is_shm: bool,
+    #[cfg(feature = "transport_compression")]
+    pub(super) is_compression: bool,
     pub(super) peer_authenticator: HashSet<PeerAuthenticator>,
     pub(super) link_authenticator: HashSet<LinkAuthenticator>,
 }
@@ -80,6 +84,8 @@ impl Default for TransportManagerConfigBuilderUnicast {
             is_qos: zparse!(ZN_QOS_DEFAULT).unwrap(),
             #[cfg(feature = "zero-copy")]
             is_shm: zparse!(ZN_SHM_DEFAULT).unwrap(),
+            #[cfg(feature = "transport_compression")]
+            is_compression: zparse!(ZN_COMPRESSION_DEFAULT).unwrap(),
             peer_authenticator: HashSet::new(),
             link_authenticator: HashSet::new(),
         }
@@ -138,6 +144,12 @@ impl TransportManagerConfigBuilderUnicast {
         self
     }
 
+    #[cfg(feature = "transport_compression")]
+    pub fn compression(mut self, is_compression: bool) -> Self {
+        self.is_compression = is_compression;
+        self
+    }
+
     pub async fn from_config(
         mut self,
         properties: &ConfigProperties,
@@ -167,6 +179,10 @@ impl TransportManagerConfigBuilderUnicast {
         if let Some(v) = properties.get(&ZN_SHM_KEY) {
             self = self.shm(zparse!(v)?);
         }
+        #[cfg(feature = "transport_compression")]
+        if let Some(v) = properties.get(&ZN_COMPRESSION_KEY) {
+            self = self.compression(zparse!(v)?);
+        }
 
         self = self.peer_authenticator(PeerAuthenticator::from_config(properties).await?);
         self = self.link_authenticator(LinkAuthenticator::from_config(properties).await?);
@@ -198,6 +214,8 @@ impl TransportManagerConfigBuilderUnicast {
             is_qos: self.is_qos,
             #[cfg(feature = "zero-copy")]
             is_shm: self.is_shm,
+            #[cfg(feature = "transport_compression")]
+            is_compression: self.is_compression,
         }
     }
 }
@@ -379,6 +397,7 @@ impl TransportManager {
             initial_sn_rx: config.initial_sn_rx,
             is_shm: config.is_shm,
             is_qos: config.is_qos,
+            user: config.user,
         };
         let a_st = Arc::new(TransportUnicastInner::new(stc));
 
@@ -435,10 +454,25 @@ impl TransportManager {
             endpoint.config = Some(Arc::new(config));
         };
 
+        // The compression may be requested or disabled per endpoint
+        #[cfg(feature = "transport_compression")]
+        let is_compression = match endpoint
+            .config
+            .as_ref()
+            .and_then(|c| c.get(ZN_COMPRESSION_STR))
+        {
+            Some(v) => zparse!(v)?,
+            None => self.config.unicast.is_compression,
+        };
+        #[cfg(not(feature = "transport_compression"))]
+        let is_compression = false;
+
         // Create a new link associated by calling the Link Manager
-        let link = manager.new_link(endpoint).await?;
+        let link = manager.new_link(endpoint.clone()).await?;
+        // Inject the network faults configured on the endpoint, if any
+        let link = impairment::impair(link, &endpoint)?;
         // Open the link
-        super::establishment::open_link(self, &link).await
+        super::establishment::open_link(self, &link, is_compression).await
     }
 
     pub fn get_transport_unicast(&self, peer: &PeerId) -> Option<TransportUnicast> {
@@ -487,7 +521,8 @@ impl TransportManager {
         guard.insert(link.clone(), None);
         drop(guard);
 
-        let mut peer_id: Option<PeerId> = None;
+        // Links may already have authenticated the remote PeerId (e.g. TLS client certificates)
+        let mut peer_id: Option<PeerId> = link.get_peer_id();
         let peer_link = Link::from(&link);
         for la in self.config.unicast.link_authenticator.iter() {
             let res = la.handle_new_link(&peer_link).await;
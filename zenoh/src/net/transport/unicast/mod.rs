@@ -28,6 +28,7 @@ use crate::net::link::Link;
 pub use manager::*;
 use std::fmt;
 use std::sync::{Arc, Weak};
+use std::time::Duration;
 use transport::TransportUnicastInner;
 use zenoh_util::core::{ZError, ZErrorKind, ZResult};
 use zenoh_util::zerror2;
@@ -44,7 +45,30 @@ pub struct TransportStatsUnicast {
     rx_bytes: usize,
 }
 
-#[derive(Clone, Copy)]
+#[cfg(feature = "stats")]
+impl TransportStatsUnicast {
+    #[inline]
+    pub fn tx_msgs(&self) -> usize {
+        self.tx_msgs
+    }
+
+    #[inline]
+    pub fn tx_bytes(&self) -> usize {
+        self.tx_bytes
+    }
+
+    #[inline]
+    pub fn rx_msgs(&self) -> usize {
+        self.rx_msgs
+    }
+
+    #[inline]
+    pub fn rx_bytes(&self) -> usize {
+        self.rx_bytes
+    }
+}
+
+#[derive(Clone)]
 pub(crate) struct TransportConfigUnicast {
     pub(crate) peer: PeerId,
     pub(crate) whatami: WhatAmI,
@@ -53,6 +77,7 @@ pub(crate) struct TransportConfigUnicast {
     pub(crate) initial_sn_rx: ZInt,
     pub(crate) is_shm: bool,
     pub(crate) is_qos: bool,
+    pub(crate) user: Option<String>,
 }
 
 /// [`TransportUnicast`] is the transport handler returned
@@ -100,6 +125,13 @@ impl TransportUnicast {
         Ok(transport.is_qos())
     }
 
+    /// The name of the user the remote peer has been authenticated as, if any.
+    #[inline(always)]
+    pub fn get_user(&self) -> ZResult<Option<String>> {
+        let transport = self.get_transport()?;
+        Ok(transport.get_user())
+    }
+
     #[inline(always)]
     pub fn get_callback(&self) -> ZResult<Option<Arc<dyn TransportPeerEventHandler>>> {
         let transport = self.get_transport()?;
@@ -132,6 +164,21 @@ impl TransportUnicast {
             .collect())
     }
 
+    /// The lease announced by the remote peer, the shortest one if it differs among the links.
+    #[inline(always)]
+    pub fn get_lease(&self) -> ZResult<Option<Duration>> {
+        let transport = self.get_transport()?;
+        Ok(transport.get_lease())
+    }
+
+    /// The number of batches waiting to be transmitted in each priority queue,
+    /// summed over all the links of the transport.
+    #[inline(always)]
+    pub fn get_queue_depths(&self) -> ZResult<Vec<usize>> {
+        let transport = self.get_transport()?;
+        Ok(transport.get_queue_depths())
+    }
+
     #[inline(always)]
     pub fn schedule(&self, message: ZenohMessage) -> ZResult<()> {
         let transport = self.get_transport()?;
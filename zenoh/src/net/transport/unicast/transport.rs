@@ -131,6 +131,8 @@ pub(crate) struct TransportUnicastInner {
     pub(super) alive: AsyncArc<AsyncMutex<bool>>,
     // The transport can do shm
     pub(super) is_shm: bool,
+    // The user the peer has been authenticated as
+    pub(super) user: Option<String>,
     // Transport statistics
     #[cfg(feature = "stats")]
     pub(super) stats: TransportUnicastStatsInner,
@@ -145,6 +147,7 @@ pub(crate) struct TransportUnicastConfig {
     pub(crate) initial_sn_rx: ZInt,
     pub(crate) is_shm: bool,
     pub(crate) is_qos: bool,
+    pub(crate) user: Option<String>,
 }
 
 impl TransportUnicastInner {
@@ -205,6 +208,7 @@ impl TransportUnicastInner {
             callback: Arc::new(RwLock::new(None)),
             alive: AsyncArc::new(AsyncMutex::new(true)),
             is_shm: config.is_shm,
+            user: config.user,
             #[cfg(feature = "stats")]
             stats: TransportUnicastStatsInner::default(),
         }
@@ -261,7 +265,7 @@ impl TransportUnicastInner {
     /*************************************/
     /*               LINK                */
     /*************************************/
-    pub(super) fn add_link(&self, link: LinkUnicast) -> ZResult<()> {
+    pub(super) fn add_link(&self, link: LinkUnicast, is_compression: bool) -> ZResult<()> {
         let mut guard = zwrite!(self.links);
         if guard.len() >= self.manager.config.unicast.max_links {
             return zerror!(ZErrorKind::InvalidLink {
@@ -279,7 +283,7 @@ impl TransportUnicastInner {
         }
 
         // Create a channel link from a link
-        let link = TransportLinkUnicast::new(self.clone(), link);
+        let link = TransportLinkUnicast::new(self.clone(), link, is_compression);
 
         // Add the link to the channel
         let mut links = Vec::with_capacity(guard.len() + 1);
@@ -417,6 +421,10 @@ impl TransportUnicastInner {
         self.is_shm
     }
 
+    pub(crate) fn get_user(&self) -> Option<String> {
+        self.user.clone()
+    }
+
     pub(crate) fn is_qos(&self) -> bool {
         self.conduit_tx.len() > 1
     }
@@ -509,4 +517,21 @@ impl TransportUnicastInner {
             .map(|l| l.get_link().clone())
             .collect()
     }
+
+    pub(crate) fn get_lease(&self) -> Option<Duration> {
+        zread!(self.links)
+            .iter()
+            .filter_map(|l| l.get_lease())
+            .min()
+    }
+
+    pub(crate) fn get_queue_depths(&self) -> Vec<usize> {
+        let mut depths = vec![0; self.conduit_tx.len()];
+        for pipeline in zread!(self.links).iter().filter_map(|l| l.get_pipeline()) {
+            for (d, q) in depths.iter_mut().zip(pipeline.queue_depths()) {
+                *d += q;
+            }
+        }
+        depths
+    }
 }
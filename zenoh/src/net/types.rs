@@ -14,13 +14,20 @@
 use crate::net::Session;
 use crate::utils::new_reception_timestamp;
 use async_std::sync::Arc;
+use async_std::task::{self, JoinHandle};
 use flume::*;
 use std::collections::HashMap;
 use std::fmt;
 use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::RwLock;
 use std::task::{Context, Poll};
 use uhlc::Timestamp;
+use zenoh_util::zconfigurable;
+
+zconfigurable! {
+    static ref API_MATCHING_STATUS_CHANNEL_SIZE: usize = 16;
+}
 
 /// A read-only bytes buffer.
 pub use super::protocol::io::{ZBuf, ZSlice};
@@ -48,6 +55,9 @@ pub use super::protocol::core::PeerId;
 /// A time period.
 pub use super::protocol::core::Period;
 
+/// A user-defined key/value property that can be attached to samples and queries.
+pub use super::protocol::core::Property;
+
 /// The [Queryable](Queryable)s that should be target of a [query](Session::query).
 pub use super::protocol::core::Target;
 
@@ -98,7 +108,7 @@ pub use zenoh_util::sync::ZReady;
 /// ```
 /// # use zenoh::net::protocol::io::ZBuf;
 /// # use zenoh::net::protocol::proto::DataInfo;
-/// # let sample = zenoh::net::Sample { res_name: "".to_string(), payload: ZBuf::new(), data_info: None };
+/// # let sample = zenoh::net::Sample { res_name: "".to_string(), payload: ZBuf::new(), data_info: None, attachment: None };
 /// if let Some(info) = sample.data_info {
 ///     match info.timestamp {
 ///         Some(ts) => println!("Sample's timestamp: {}", ts),
@@ -130,6 +140,7 @@ pub struct Sample {
     pub res_name: String,
     pub payload: ZBuf,
     pub data_info: Option<DataInfo>,
+    pub attachment: Option<Vec<Property>>,
 }
 
 impl Sample {
@@ -161,9 +172,11 @@ impl Sample {
 pub type DataHandler = dyn FnMut(Sample) + Send + Sync + 'static;
 
 /// Structs received b y a [Queryable](Queryable).
+#[derive(Clone)]
 pub struct Query {
     pub res_name: String,
     pub predicate: String,
+    pub attachment: Option<Vec<Property>>,
     pub replies_sender: RepliesSender,
 }
 
@@ -174,6 +187,7 @@ impl Query {
     }
 
     #[inline(always)]
+    #[allow(clippy::result_large_err)] // The unsent sample is given back to the caller
     pub fn try_reply(&self, msg: Sample) -> Result<(), TrySendError<Sample>> {
         self.replies_sender.try_send(msg)
     }
@@ -182,6 +196,17 @@ impl Query {
     pub async fn reply_async(&'_ self, msg: Sample) {
         self.replies_sender.send_async(msg).await
     }
+
+    /// Replies to this query with an error. The querier receives it as a [Reply::Err](Reply::Err).
+    #[inline(always)]
+    pub fn reply_err(&'_ self, value: Sample) {
+        self.replies_sender.send_err(value)
+    }
+
+    #[inline(always)]
+    pub async fn reply_err_async(&'_ self, value: Sample) {
+        self.replies_sender.send_err_async(value).await
+    }
 }
 
 impl fmt::Debug for Query {
@@ -194,20 +219,45 @@ impl fmt::Debug for Query {
     }
 }
 
-/// Structs returned by a [query](Session::query).
+/// The [Sample](Sample) sent by a queryable in reply to a [query](Session::query).
 #[derive(Clone, Debug)]
-pub struct Reply {
+pub struct ReplyData {
     pub data: Sample,
     pub replier_kind: ZInt,
     pub replier_id: PeerId,
 }
 
+/// Structs returned by a [query](Session::query).
 #[derive(Clone, Debug)]
+pub enum Reply {
+    /// A value sent with [reply](Query::reply).
+    Ok(ReplyData),
+    /// An error sent with [reply_err](Query::reply_err).
+    Err(ReplyData),
+}
+
+#[derive(Debug)]
 pub(crate) struct QueryState {
     pub(crate) nb_final: usize,
     pub(crate) reception_mode: ConsolidationMode,
-    pub(crate) replies: Option<HashMap<String, Reply>>,
+    pub(crate) replies: Option<HashMap<String, ReplyData>>,
     pub(crate) rep_sender: Sender<Reply>,
+    pub(crate) timeout: Option<JoinHandle<()>>,
+}
+
+impl QueryState {
+    /// Sends the consolidated replies if any, closes the reply channel
+    /// and cancels the timeout task if still pending.
+    pub(crate) fn finalize(self) {
+        if let Some(timeout) = self.timeout {
+            task::spawn(timeout.cancel());
+        }
+        if self.reception_mode == ConsolidationMode::Full {
+            for (_, reply) in self.replies.unwrap().into_iter() {
+                let _ = self.rep_sender.send(Reply::Ok(reply));
+            }
+        }
+    }
 }
 
 pub(crate) type Id = usize;
@@ -216,6 +266,42 @@ pub(crate) type Id = usize;
 pub(crate) struct PublisherState {
     pub(crate) id: Id,
     pub(crate) reskey: ResKey,
+    pub(crate) resname: String,
+    pub(crate) matching: AtomicBool,
+    pub(crate) matching_senders: RwLock<Vec<Sender<MatchingStatus>>>,
+}
+
+impl PublisherState {
+    /// Updates the matching status and notifies the listeners if it changed.
+    pub(crate) fn set_matching(&self, matching: bool) {
+        if self.matching.swap(matching, Ordering::SeqCst) != matching {
+            let status = MatchingStatus { matching };
+            zwrite!(self.matching_senders).retain(|sender| match sender.try_send(status) {
+                Ok(()) => true,
+                Err(TrySendError::Full(_)) => {
+                    log::warn!(
+                        "Dropping matching status notification for {}: receiver full",
+                        self.resname
+                    );
+                    true
+                }
+                Err(TrySendError::Disconnected(_)) => false,
+            });
+        }
+    }
+}
+
+/// The matching status of a [Publisher](Publisher).
+///
+/// A [Publisher](Publisher) is matching when at least one subscriber would receive the data it publishes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MatchingStatus {
+    pub matching: bool,
+}
+
+zreceiver! {
+    #[derive(Clone)]
+    pub struct MatchingStatusReceiver : Receiver<MatchingStatus> {}
 }
 
 /// A publisher.
@@ -228,6 +314,50 @@ pub struct Publisher<'a> {
 }
 
 impl Publisher<'_> {
+    /// Returns the current [MatchingStatus](MatchingStatus) of this [Publisher](Publisher).
+    ///
+    /// # Examples
+    /// ```
+    /// # async_std::task::block_on(async {
+    /// use zenoh::net::*;
+    ///
+    /// let session = open(config::peer()).await.unwrap();
+    /// let publisher = session.declare_publisher(&"/resource/name".into()).await.unwrap();
+    /// if publisher.matching_status().matching {
+    ///     session.write(&"/resource/name".into(), "value".as_bytes().into()).await.unwrap();
+    /// }
+    /// # })
+    /// ```
+    #[inline]
+    pub fn matching_status(&self) -> MatchingStatus {
+        MatchingStatus {
+            matching: self.state.matching.load(Ordering::SeqCst),
+        }
+    }
+
+    /// Returns a [MatchingStatusReceiver](MatchingStatusReceiver) that receives the changes of the
+    /// [MatchingStatus](MatchingStatus) of this [Publisher](Publisher).
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # async_std::task::block_on(async {
+    /// use zenoh::net::*;
+    /// use futures::prelude::*;
+    ///
+    /// let session = open(config::peer()).await.unwrap();
+    /// let publisher = session.declare_publisher(&"/resource/name".into()).await.unwrap();
+    /// let mut statuses = publisher.matching_status_receiver();
+    /// while let Some(status) = statuses.next().await {
+    ///     println!("Publisher matching: {}", status.matching);
+    /// }
+    /// # })
+    /// ```
+    pub fn matching_status_receiver(&self) -> MatchingStatusReceiver {
+        let (sender, receiver) = bounded(*API_MATCHING_STATUS_CHANNEL_SIZE);
+        zwrite!(self.state.matching_senders).push(sender);
+        MatchingStatusReceiver::new(receiver)
+    }
+
     /// Undeclare a [Publisher](Publisher) previously declared with [declare_publisher](Session::declare_publisher).
     ///
     /// Publishers are automatically undeclared when dropped, but you may want to use this function to handle errors or
@@ -447,6 +577,116 @@ impl fmt::Debug for CallbackSubscriber<'_> {
     }
 }
 
+#[derive(Debug)]
+pub(crate) struct LivelinessTokenState {
+    pub(crate) id: Id,
+    pub(crate) reskey: ResKey,
+    pub(crate) resname: String,
+}
+
+/// A liveliness token.
+///
+/// A liveliness token signals the presence of the application that declared it to all
+/// [LivelinessSubscriber](LivelinessSubscriber)s with a matching resource key. The token is
+/// withdrawn when undeclared, when dropped, or when the connection to the declaring session is lost.
+pub struct LivelinessToken<'a> {
+    pub(crate) session: &'a Session,
+    pub(crate) state: Arc<LivelinessTokenState>,
+    pub(crate) alive: bool,
+}
+
+impl LivelinessToken<'_> {
+    /// Undeclare a [LivelinessToken](LivelinessToken) previously declared with
+    /// [declare_liveliness_token](Session::declare_liveliness_token).
+    ///
+    /// # Examples
+    /// ```
+    /// # async_std::task::block_on(async {
+    /// use zenoh::net::*;
+    ///
+    /// let session = open(config::peer()).await.unwrap();
+    /// let token = session.declare_liveliness_token(&"/group/member1".into()).await.unwrap();
+    /// token.undeclare().await.unwrap();
+    /// # })
+    /// ```
+    #[inline]
+    pub fn undeclare(mut self) -> impl ZFuture<Output = ZResult<()>> {
+        self.alive = false;
+        self.session.undeclare_liveliness_token(self.state.id)
+    }
+}
+
+impl Drop for LivelinessToken<'_> {
+    fn drop(&mut self) {
+        if self.alive {
+            let _ = self
+                .session
+                .undeclare_liveliness_token(self.state.id)
+                .wait();
+        }
+    }
+}
+
+impl fmt::Debug for LivelinessToken<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.state.fmt(f)
+    }
+}
+
+/// A subscriber to the liveliness tokens matching a resource key.
+///
+/// A [Sample](Sample) with a PUT kind is received each time a matching token appears,
+/// and a [Sample](Sample) with a DELETE kind each time a matching token disappears.
+/// LivelinessSubscribers are automatically undeclared when dropped.
+pub struct LivelinessSubscriber<'a> {
+    pub(crate) session: &'a Session,
+    pub(crate) state: Arc<SubscriberState>,
+    pub(crate) alive: bool,
+    pub(crate) receiver: SampleReceiver,
+}
+
+impl LivelinessSubscriber<'_> {
+    pub fn receiver(&mut self) -> &mut SampleReceiver {
+        &mut self.receiver
+    }
+
+    /// Undeclare a [LivelinessSubscriber](LivelinessSubscriber) previously declared with
+    /// [declare_liveliness_subscriber](Session::declare_liveliness_subscriber).
+    ///
+    /// # Examples
+    /// ```
+    /// # async_std::task::block_on(async {
+    /// use zenoh::net::*;
+    ///
+    /// let session = open(config::peer()).await.unwrap();
+    /// let subscriber = session.declare_liveliness_subscriber(&"/group/*".into()).await.unwrap();
+    /// subscriber.undeclare().await.unwrap();
+    /// # })
+    /// ```
+    #[inline]
+    pub fn undeclare(mut self) -> impl ZFuture<Output = ZResult<()>> {
+        self.alive = false;
+        self.session.undeclare_liveliness_subscriber(self.state.id)
+    }
+}
+
+impl Drop for LivelinessSubscriber<'_> {
+    fn drop(&mut self) {
+        if self.alive {
+            let _ = self
+                .session
+                .undeclare_liveliness_subscriber(self.state.id)
+                .wait();
+        }
+    }
+}
+
+impl fmt::Debug for LivelinessSubscriber<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.state.fmt(f)
+    }
+}
+
 zreceiver! {
     #[derive(Clone)]
     pub struct ReplyReceiver : Receiver<Reply> {}
@@ -526,23 +766,33 @@ impl fmt::Debug for Queryable<'_> {
 #[derive(Clone)]
 pub struct RepliesSender {
     pub(crate) kind: ZInt,
-    pub(crate) sender: Sender<(ZInt, Sample)>,
+    pub(crate) sender: Sender<(ZInt, Result<Sample, Sample>)>,
 }
 
 impl RepliesSender {
     #[inline(always)]
     pub fn send(&'_ self, msg: Sample) {
-        if let Err(e) = self.sender.send((self.kind, msg)) {
+        if let Err(e) = self.sender.send((self.kind, Ok(msg))) {
             log::error!("Error sending reply: {}", e);
         }
     }
 
     #[inline(always)]
+    #[allow(clippy::result_large_err)] // The unsent sample is given back to the caller
     pub fn try_send(&self, msg: Sample) -> Result<(), TrySendError<Sample>> {
-        match self.sender.try_send((self.kind, msg)) {
+        match self.sender.try_send((self.kind, Ok(msg))) {
             Ok(()) => Ok(()),
-            Err(TrySendError::Full(sample)) => Err(TrySendError::Full(sample.1)),
-            Err(TrySendError::Disconnected(sample)) => Err(TrySendError::Disconnected(sample.1)),
+            Err(TrySendError::Full((_, sample))) => Err(TrySendError::Full(sample.unwrap())),
+            Err(TrySendError::Disconnected((_, sample))) => {
+                Err(TrySendError::Disconnected(sample.unwrap()))
+            }
+        }
+    }
+
+    #[inline(always)]
+    pub fn send_err(&'_ self, value: Sample) {
+        if let Err(e) = self.sender.send((self.kind, Err(value))) {
+            log::error!("Error sending error reply: {}", e);
         }
     }
 
@@ -568,11 +818,18 @@ impl RepliesSender {
 
     #[inline(always)]
     pub async fn send_async(&self, msg: Sample) {
-        if let Err(e) = self.sender.send_async((self.kind, msg)).await {
+        if let Err(e) = self.sender.send_async((self.kind, Ok(msg))).await {
             log::error!("Error sending reply: {}", e);
         }
     }
 
+    #[inline(always)]
+    pub async fn send_err_async(&self, value: Sample) {
+        if let Err(e) = self.sender.send_async((self.kind, Err(value))).await {
+            log::error!("Error sending error reply: {}", e);
+        }
+    }
+
     // @TODO
     // #[inline(always)]
     // pub fn sink(&self) -> flume::r#async::SendSink<'_, Sample> {
@@ -214,27 +214,77 @@ impl Workspace<'_> {
     /// ```
     pub fn get(&self, selector: &Selector) -> impl ZFuture<Output = ZResult<DataReceiver>> {
         debug!("get on {}", selector);
-        zready_try!({
-            let reskey = self.pathexpr_to_reskey(&selector.path_expr)?;
-            let decode_value = !selector.properties.contains_key("raw");
-            let consolidation = if selector.has_time_range() {
-                QueryConsolidation::none()
-            } else {
-                QueryConsolidation::default()
-            };
+        zready(self.get_ext(selector, None))
+    }
 
-            self.session()
+    /// Get a selection of [`Path`]/[`Value`] from zenoh, waiting at most `timeout` for the replies.  
+    /// When the `timeout` expires, the returned [`async_std::stream::Stream`] ends with the [`Data`]
+    /// received so far.
+    /// Note that the [`Selector`] can be absolute or relative to this Workspace.
+    ///
+    /// # Examples
+    /// ```
+    /// # async_std::task::block_on(async {
+    /// use zenoh::*;
+    /// use std::convert::TryInto;
+    /// use std::time::Duration;
+    /// use futures::prelude::*;
+    ///
+    /// let zenoh = Zenoh::new(net::config::default()).await.unwrap();
+    /// let workspace = zenoh.workspace(None).await.unwrap();
+    /// let mut data_stream = workspace.get_with_timeout(
+    ///     &"/demo/example/**".try_into().unwrap(),
+    ///     Duration::from_secs(1),
+    /// ).await.unwrap();
+    /// while let Some(data) = data_stream.next().await {
+    ///     println!(">> {} : {:?} at {}",
+    ///         data.path, data.value, data.timestamp
+    ///     )
+    /// }
+    /// # })
+    /// ```
+    pub fn get_with_timeout(
+        &self,
+        selector: &Selector,
+        timeout: Duration,
+    ) -> impl ZFuture<Output = ZResult<DataReceiver>> {
+        debug!("get on {} with timeout {:?}", selector, timeout);
+        zready(self.get_ext(selector, Some(timeout)))
+    }
+
+    fn get_ext(&self, selector: &Selector, timeout: Option<Duration>) -> ZResult<DataReceiver> {
+        let reskey = self.pathexpr_to_reskey(&selector.path_expr)?;
+        let decode_value = !selector.properties.contains_key("raw");
+        let consolidation = if selector.has_time_range() {
+            QueryConsolidation::none()
+        } else {
+            QueryConsolidation::default()
+        };
+
+        let receiver = match timeout {
+            Some(timeout) => self
+                .session()
+                .query_with_timeout(
+                    &reskey,
+                    &selector.predicate,
+                    QueryTarget::default(),
+                    consolidation,
+                    timeout,
+                )
+                .wait()?,
+            None => self
+                .session()
                 .query(
                     &reskey,
                     &selector.predicate,
                     QueryTarget::default(),
                     consolidation,
                 )
-                .wait()
-                .map(|receiver| DataReceiver {
-                    receiver,
-                    decode_value,
-                })
+                .wait()?,
+        };
+        Ok(DataReceiver {
+            receiver,
+            decode_value,
         })
     }
 
@@ -439,6 +489,18 @@ ztranscoder! {
 
 impl DataReceiver {
     fn transcode(&self, reply: Reply) -> ZResult<Data> {
+        let reply = match reply {
+            Reply::Ok(reply) => reply,
+            Reply::Err(err) => {
+                return zerror!(ZErrorKind::Other {
+                    descr: format!(
+                        "Error reply for {}: {}",
+                        err.data.res_name,
+                        String::from_utf8_lossy(&err.data.payload.contiguous())
+                    )
+                })
+            }
+        };
         let path: Path = reply.data.res_name.try_into().unwrap();
         let (encoding, timestamp) = if let Some(info) = reply.data.data_info {
             (
@@ -570,6 +632,7 @@ impl Change {
             res_name: self.path.to_string(),
             payload,
             data_info: Some(info),
+            attachment: None,
         }
     }
 }
@@ -608,6 +671,7 @@ fn path_value_to_sample(path: Path, value: Value) -> Sample {
         res_name: path.to_string(),
         payload,
         data_info: Some(info),
+        attachment: None,
     }
 }
 
@@ -27,6 +27,8 @@ use zenoh_util::properties::Properties;
 use zenoh_util::zasync_executor_init;
 
 const SLEEP: Duration = Duration::from_millis(100);
+#[cfg(feature = "transport_ws")]
+const TIMEOUT: Duration = Duration::from_secs(60);
 const RUNS: usize = 10;
 
 // Transport Handler
@@ -243,6 +245,197 @@ fn endpoint_unix() {
     let _ = std::fs::remove_file("zenoh-test-unix-socket-1.sock.lock");
 }
 
+#[cfg(all(feature = "transport_unixsock-dgram", target_family = "unix"))]
+#[test]
+fn endpoint_unix_dgram() {
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+
+    // Remove the files if they still exists
+    let _ = std::fs::remove_file("zenoh-test-unix-dgram-0.sock");
+    let _ = std::fs::remove_file("zenoh-test-unix-dgram-1.sock");
+    // Define the locators
+    let endpoints: Vec<EndPoint> = vec![
+        "unixsock-dgram/zenoh-test-unix-dgram-0.sock"
+            .parse()
+            .unwrap(),
+        "unixsock-dgram/zenoh-test-unix-dgram-1.sock#mtu=4096"
+            .parse()
+            .unwrap(),
+    ];
+    task::block_on(run(&endpoints));
+    let _ = std::fs::remove_file("zenoh-test-unix-dgram-0.sock");
+    let _ = std::fs::remove_file("zenoh-test-unix-dgram-1.sock");
+    let _ = std::fs::remove_file("zenoh-test-unix-dgram-0.sock.lock");
+    let _ = std::fs::remove_file("zenoh-test-unix-dgram-1.sock.lock");
+}
+
+#[cfg(all(
+    feature = "transport_unixsock-stream",
+    feature = "transport_unixsock-dgram",
+    target_os = "linux"
+))]
+#[test]
+fn endpoint_unix_abstract() {
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+
+    // Define the locators in the abstract namespace
+    let endpoints: Vec<EndPoint> = vec![
+        "unixsock-stream/@zenoh-test-unix-socket-abstract-0"
+            .parse()
+            .unwrap(),
+        "unixsock-dgram/@zenoh-test-unix-dgram-abstract-0"
+            .parse()
+            .unwrap(),
+    ];
+    task::block_on(run(&endpoints));
+    // No socket nor lock file is created in the abstract namespace
+    assert!(!std::path::Path::new("@zenoh-test-unix-socket-abstract-0").exists());
+    assert!(!std::path::Path::new("@zenoh-test-unix-socket-abstract-0.lock").exists());
+}
+
+#[cfg(feature = "transport_ws")]
+#[test]
+fn endpoint_ws() {
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+
+    // Define the locators
+    let endpoints: Vec<EndPoint> = vec![
+        "ws/127.0.0.1:9454".parse().unwrap(),
+        "ws/[::1]:9454".parse().unwrap(),
+        "ws/localhost:9455".parse().unwrap(),
+    ];
+    task::block_on(run(&endpoints));
+}
+
+#[cfg(feature = "transport_ws")]
+#[test]
+fn endpoint_ws_stalled_handshake() {
+    use async_std::io::ReadExt;
+    use async_std::net::TcpStream;
+    use async_std::prelude::FutureExt;
+    use zenoh::net::transport::TransportManagerConfigUnicast;
+
+    task::block_on(async {
+        zasync_executor_init!();
+
+        let open_timeout = Duration::from_millis(1_000);
+        let endpoint: EndPoint = "ws/127.0.0.1:9456".parse().unwrap();
+        let manager = |id: u8| {
+            let unicast = TransportManagerConfigUnicast::builder()
+                .open_timeout(open_timeout)
+                .build();
+            let config = TransportManagerConfig::builder()
+                .whatami(whatami::PEER)
+                .pid(PeerId::new(1, [id; PeerId::MAX_SIZE]))
+                .unicast(unicast)
+                .build(Arc::new(SH));
+            TransportManager::new(config)
+        };
+        let router = manager(0);
+        router.add_listener(endpoint.clone()).await.unwrap();
+        task::sleep(SLEEP).await;
+
+        // A client never performing the WebSocket handshake does not block the listener
+        let mut stalled = TcpStream::connect("127.0.0.1:9456").await.unwrap();
+        let client = manager(1);
+        let transport = client
+            .open_transport(endpoint.clone())
+            .timeout(open_timeout)
+            .await
+            .unwrap()
+            .unwrap();
+
+        // The stalled connection is closed once the handshake times out
+        let mut buf = [0u8; 8];
+        let read = stalled
+            .read(&mut buf)
+            .timeout(4 * open_timeout)
+            .await
+            .unwrap();
+        assert!(matches!(read, Ok(0) | Err(_)));
+
+        transport.close().await.unwrap();
+        router.del_listener(&endpoint).await.unwrap();
+    });
+}
+
+#[cfg(feature = "transport_ws")]
+#[test]
+fn endpoint_ws_host_header() {
+    use async_std::io::ReadExt;
+    use async_std::net::{TcpListener, ToSocketAddrs};
+    use async_std::prelude::FutureExt;
+
+    task::block_on(async {
+        zasync_executor_init!();
+
+        // Listen on the address the DNS name resolves to
+        let addr = "localhost:9457"
+            .to_socket_addrs()
+            .await
+            .unwrap()
+            .next()
+            .unwrap();
+        let listener = TcpListener::bind(addr).await.unwrap();
+
+        let endpoint: EndPoint = "ws/localhost:9457".parse().unwrap();
+        let client = TransportManager::new(
+            TransportManagerConfig::builder()
+                .whatami(whatami::PEER)
+                .pid(PeerId::new(1, [0u8; PeerId::MAX_SIZE]))
+                .build(Arc::new(SH)),
+        );
+        let open = task::spawn(async move { client.open_transport(endpoint).await });
+
+        // The handshake request carries the DNS name of the locator
+        let (mut stream, _) = listener.accept().timeout(TIMEOUT).await.unwrap().unwrap();
+        let mut request = vec![];
+        let mut buf = [0u8; 256];
+        while !request.ends_with(b"\r\n\r\n") {
+            let n = stream
+                .read(&mut buf)
+                .timeout(TIMEOUT)
+                .await
+                .unwrap()
+                .unwrap();
+            assert!(n > 0);
+            request.extend_from_slice(&buf[..n]);
+        }
+        let request = String::from_utf8(request).unwrap();
+        assert!(
+            request
+                .lines()
+                .any(|l| l.eq_ignore_ascii_case("host: localhost:9457")),
+            "{}",
+            request
+        );
+
+        drop(stream);
+        assert!(open.await.is_err());
+    });
+}
+
+#[cfg(feature = "transport_inproc")]
+#[test]
+fn endpoint_inproc() {
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+
+    // Define the locators
+    let endpoints: Vec<EndPoint> = vec![
+        "inproc/zenoh-test-inproc-0".parse().unwrap(),
+        "inproc/zenoh-test-inproc-1#mtu=1024".parse().unwrap(),
+    ];
+    task::block_on(run(&endpoints));
+}
+
 #[cfg(all(feature = "transport_tcp", feature = "transport_udp"))]
 #[test]
 fn endpoint_tcp_udp() {
@@ -73,15 +73,19 @@ fn gen_routing_context() -> RoutingContext {
 
 fn gen_reply_context(is_final: bool) -> ReplyContext {
     let qid = gen!(ZInt);
-    let replier = if !is_final {
-        Some(ReplierInfo {
+    if !is_final {
+        let replier = ReplierInfo {
             kind: thread_rng().gen_range(0..4),
             id: gen_pid(),
-        })
+        };
+        if gen_bool!() {
+            ReplyContext::new_error(qid, replier)
+        } else {
+            ReplyContext::new(qid, Some(replier))
+        }
     } else {
-        None
-    };
-    ReplyContext::new(qid, replier)
+        ReplyContext::new(qid, None)
+    }
 }
 
 fn gen_attachment() -> Attachment {
@@ -156,6 +160,10 @@ fn gen_declarations() -> Vec<Declaration> {
             kind: queryable::EVAL,
         }),
         Declaration::ForgetQueryable(ForgetQueryable { key: gen_key() }),
+        Declaration::LivelinessToken(LivelinessToken { key: gen_key() }),
+        Declaration::ForgetLivelinessToken(ForgetLivelinessToken { key: gen_key() }),
+        Declaration::LivelinessSubscriber(LivelinessSubscriber { key: gen_key() }),
+        Declaration::ForgetLivelinessSubscriber(ForgetLivelinessSubscriber { key: gen_key() }),
     ]
 }
 
@@ -323,6 +331,7 @@ fn codec_init() {
                             gen_pid(),
                             *s,
                             *q,
+                            gen_bool!(),
                             a.clone(),
                         );
                         test_write_read_transport_message(msg);
@@ -341,6 +350,7 @@ fn codec_init() {
                             gen_pid(),
                             *s,
                             *q,
+                            gen_bool!(),
                             gen_buffer(64).into(),
                             a.clone(),
                         );
@@ -0,0 +1,900 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ADLINK zenoh team, <zenoh@adlink-labs.tech>
+//
+use async_std::prelude::*;
+use async_std::task;
+use std::time::Duration;
+use zenoh::net::queryable::EVAL;
+use zenoh::net::*;
+use zenoh_util::zasync_executor_init;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const QUERY_TIMEOUT: Duration = Duration::from_secs(1);
+
+fn local_config() -> ConfigProperties {
+    let mut config = config::peer();
+    config.insert(config::ZN_MULTICAST_SCOUTING_KEY, "false".to_string());
+    config
+}
+
+#[test]
+fn session_query_timeout() {
+    task::block_on(async {
+        zasync_executor_init!();
+
+        let session = open(local_config()).await.unwrap();
+        let resource: ResKey = "/test/session/query_timeout".into();
+        let mut queryable = session.declare_queryable(&resource, EVAL).await.unwrap();
+
+        let mut replies = session
+            .query_with_timeout(
+                &resource,
+                "",
+                QueryTarget::default(),
+                QueryConsolidation::default(),
+                QUERY_TIMEOUT,
+            )
+            .await
+            .unwrap();
+
+        // Keep the query alive without replying: only the timeout can close the receiver
+        let query = queryable.receiver().next().timeout(TIMEOUT).await.unwrap();
+        assert!(query.is_some());
+
+        let reply = replies.next().timeout(TIMEOUT).await.unwrap();
+        assert!(reply.is_none());
+
+        drop(query);
+        queryable.undeclare().await.unwrap();
+        session.close().await.unwrap();
+    });
+}
+
+fn is_delete(sample: &Sample) -> bool {
+    sample.data_info.as_ref().and_then(|info| info.kind) == Some(data_kind::DELETE)
+}
+
+#[test]
+fn session_liveliness() {
+    task::block_on(async {
+        zasync_executor_init!();
+
+        let locator = "inproc/zenoh-test-session-liveliness".to_string();
+        let mut config = local_config();
+        config.insert(config::ZN_LISTENER_KEY, locator.clone());
+        let peer1 = open(config).await.unwrap();
+
+        let mut config = local_config();
+        config.insert(config::ZN_PEER_KEY, locator.clone());
+        let peer2 = open(config).await.unwrap();
+
+        let mut config = config::client(Some(locator));
+        config.insert(config::ZN_MULTICAST_SCOUTING_KEY, "false".to_string());
+        let client = open(config).await.unwrap();
+
+        let mut subscriber = peer2
+            .declare_liveliness_subscriber(&"/test/liveliness/*".into())
+            .await
+            .unwrap();
+
+        // A token declared by a peer is seen by the other peer
+        let token1 = peer1
+            .declare_liveliness_token(&"/test/liveliness/peer1".into())
+            .await
+            .unwrap();
+        let sample = subscriber.receiver().next().timeout(TIMEOUT).await.unwrap();
+        let sample = sample.unwrap();
+        assert_eq!(sample.res_name, "/test/liveliness/peer1");
+        assert!(!is_delete(&sample));
+
+        // A token declared by a client is propagated through the peer it is connected to
+        let token2 = client
+            .declare_liveliness_token(&"/test/liveliness/client".into())
+            .await
+            .unwrap();
+        let sample = subscriber.receiver().next().timeout(TIMEOUT).await.unwrap();
+        let sample = sample.unwrap();
+        assert_eq!(sample.res_name, "/test/liveliness/client");
+        assert!(!is_delete(&sample));
+
+        // The liveliness queries are answered by the peers, for their clients as well
+        let expected = vec![
+            "/test/liveliness/client".to_string(),
+            "/test/liveliness/peer1".to_string(),
+        ];
+        let alive = peer2
+            .query_liveliness(&"/test/liveliness/*".into())
+            .await
+            .unwrap();
+        assert_eq!(alive, expected);
+        let alive = client
+            .query_liveliness(&"/test/liveliness/*".into())
+            .await
+            .unwrap();
+        assert_eq!(alive, expected);
+
+        // A client is told about the tokens alive once it subscribed to them
+        let mut client_subscriber = client
+            .declare_liveliness_subscriber(&"/test/liveliness/*".into())
+            .await
+            .unwrap();
+        let mut names = vec![];
+        for _ in 0..2 {
+            let sample = client_subscriber
+                .receiver()
+                .next()
+                .timeout(TIMEOUT)
+                .await
+                .unwrap();
+            let sample = sample.unwrap();
+            assert!(!is_delete(&sample));
+            names.push(sample.res_name);
+        }
+        names.sort();
+        assert_eq!(names, expected);
+
+        // Undeclaring a token withdraws it
+        token1.undeclare().await.unwrap();
+        let sample = subscriber.receiver().next().timeout(TIMEOUT).await.unwrap();
+        let sample = sample.unwrap();
+        assert_eq!(sample.res_name, "/test/liveliness/peer1");
+        assert!(is_delete(&sample));
+        let sample = client_subscriber
+            .receiver()
+            .next()
+            .timeout(TIMEOUT)
+            .await
+            .unwrap();
+        let sample = sample.unwrap();
+        assert_eq!(sample.res_name, "/test/liveliness/peer1");
+        assert!(is_delete(&sample));
+        client_subscriber.undeclare().await.unwrap();
+
+        // Closing the session owning a token withdraws it
+        // (the token is leaked so that it is not undeclared beforehand)
+        std::mem::forget(token2);
+        client.close().await.unwrap();
+        let sample = subscriber.receiver().next().timeout(TIMEOUT).await.unwrap();
+        let sample = sample.unwrap();
+        assert_eq!(sample.res_name, "/test/liveliness/client");
+        assert!(is_delete(&sample));
+
+        let alive = peer2
+            .query_liveliness(&"/test/liveliness/*".into())
+            .await
+            .unwrap();
+        assert!(alive.is_empty(), "{:?}", alive);
+
+        subscriber.undeclare().await.unwrap();
+        peer2.close().await.unwrap();
+        peer1.close().await.unwrap();
+    });
+}
+
+#[test]
+fn session_matching_status() {
+    task::block_on(async {
+        zasync_executor_init!();
+
+        let locator = "tcp/127.0.0.1:17452".to_string();
+        let mut config = local_config();
+        config.insert(config::ZN_MODE_KEY, "router".to_string());
+        config.insert(config::ZN_LISTENER_KEY, locator.clone());
+        let router = open(config).await.unwrap();
+
+        let mut config = local_config();
+        config.insert(config::ZN_PEER_KEY, locator.clone());
+        let peer = open(config).await.unwrap();
+
+        let mut config = config::client(Some(locator));
+        config.insert(config::ZN_MULTICAST_SCOUTING_KEY, "false".to_string());
+        let client = open(config).await.unwrap();
+
+        let sub_info = SubInfo {
+            reliability: Reliability::Reliable,
+            mode: SubMode::Push,
+            period: None,
+        };
+
+        // Client mode publisher matched by a peer subscriber
+        let resource: ResKey = "/test/matching/client".into();
+        let publisher = client.declare_publisher(&resource).await.unwrap();
+        let mut statuses = publisher.matching_status_receiver();
+        assert!(!publisher.matching_status().matching);
+
+        let subscriber = peer.declare_subscriber(&resource, &sub_info).await.unwrap();
+        let status = statuses.next().timeout(TIMEOUT).await.unwrap().unwrap();
+        assert!(status.matching);
+        assert!(publisher.matching_status().matching);
+
+        subscriber.undeclare().await.unwrap();
+        let status = statuses.next().timeout(TIMEOUT).await.unwrap().unwrap();
+        assert!(!status.matching);
+        assert!(!publisher.matching_status().matching);
+        publisher.undeclare().await.unwrap();
+
+        // Peer mode publisher matched by a client subscriber
+        let resource: ResKey = "/test/matching/peer".into();
+        let publisher = peer.declare_publisher(&resource).await.unwrap();
+        let mut statuses = publisher.matching_status_receiver();
+        assert!(!publisher.matching_status().matching);
+
+        let subscriber = client
+            .declare_subscriber(&"/test/matching/*".into(), &sub_info)
+            .await
+            .unwrap();
+        let status = statuses.next().timeout(TIMEOUT).await.unwrap().unwrap();
+        assert!(status.matching);
+
+        subscriber.undeclare().await.unwrap();
+        let status = statuses.next().timeout(TIMEOUT).await.unwrap().unwrap();
+        assert!(!status.matching);
+        publisher.undeclare().await.unwrap();
+
+        client.close().await.unwrap();
+        peer.close().await.unwrap();
+        router.close().await.unwrap();
+    });
+}
+
+#[test]
+fn session_attachments() {
+    task::block_on(async {
+        zasync_executor_init!();
+
+        let locator = "tcp/127.0.0.1:17453".to_string();
+        let mut config = local_config();
+        config.insert(config::ZN_MODE_KEY, "router".to_string());
+        config.insert(config::ZN_LISTENER_KEY, locator.clone());
+        let router = open(config).await.unwrap();
+
+        let mut config = config::client(Some(locator.clone()));
+        config.insert(config::ZN_MULTICAST_SCOUTING_KEY, "false".to_string());
+        let client1 = open(config).await.unwrap();
+
+        let mut config = config::client(Some(locator));
+        config.insert(config::ZN_MULTICAST_SCOUTING_KEY, "false".to_string());
+        let client2 = open(config).await.unwrap();
+
+        let resource: ResKey = "/test/attachments".into();
+        let sub_info = SubInfo {
+            reliability: Reliability::Reliable,
+            mode: SubMode::Push,
+            period: None,
+        };
+        let mut subscriber = client1
+            .declare_subscriber(&resource, &sub_info)
+            .await
+            .unwrap();
+        let mut queryable = client1.declare_queryable(&resource, EVAL).await.unwrap();
+        task::sleep(QUERY_TIMEOUT).await;
+
+        // Samples
+        let attachment = vec![
+            Property {
+                key: 0x01,
+                value: b"trace-1234".to_vec(),
+            },
+            Property {
+                key: 0x02,
+                value: b"v2".to_vec(),
+            },
+        ];
+        client2
+            .write_ext_with_attachment(
+                &resource,
+                "value".as_bytes().into(),
+                encoding::TEXT_PLAIN,
+                data_kind::PUT,
+                CongestionControl::Block,
+                attachment.clone(),
+            )
+            .await
+            .unwrap();
+        let sample = subscriber
+            .receiver()
+            .next()
+            .timeout(TIMEOUT)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(sample.attachment, Some(attachment.clone()));
+
+        client2
+            .write(&resource, "value".as_bytes().into())
+            .await
+            .unwrap();
+        let sample = subscriber
+            .receiver()
+            .next()
+            .timeout(TIMEOUT)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(sample.attachment.is_none());
+
+        // Queries and replies
+        let reply_attachment = vec![Property {
+            key: 0x03,
+            value: b"schema-1".to_vec(),
+        }];
+        let mut replies = client2
+            .query_with_attachment(
+                &resource,
+                "",
+                QueryTarget::default(),
+                QueryConsolidation::default(),
+                attachment.clone(),
+            )
+            .await
+            .unwrap();
+        let query = queryable
+            .receiver()
+            .next()
+            .timeout(TIMEOUT)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(query.attachment, Some(attachment));
+        query.reply(Sample {
+            res_name: "/test/attachments".to_string(),
+            payload: "reply".as_bytes().into(),
+            data_info: None,
+            attachment: Some(reply_attachment.clone()),
+        });
+        drop(query);
+        match replies.next().timeout(TIMEOUT).await.unwrap().unwrap() {
+            Reply::Ok(reply) => assert_eq!(reply.data.attachment, Some(reply_attachment)),
+            Reply::Err(_) => panic!("Unexpected error reply"),
+        }
+
+        subscriber.undeclare().await.unwrap();
+        queryable.undeclare().await.unwrap();
+        client2.close().await.unwrap();
+        client1.close().await.unwrap();
+        router.close().await.unwrap();
+    });
+}
+
+#[test]
+fn session_error_replies() {
+    task::block_on(async {
+        zasync_executor_init!();
+
+        let locator = "tcp/127.0.0.1:17454".to_string();
+        let mut config = local_config();
+        config.insert(config::ZN_MODE_KEY, "router".to_string());
+        config.insert(config::ZN_LISTENER_KEY, locator.clone());
+        let router = open(config).await.unwrap();
+
+        let mut config = config::client(Some(locator.clone()));
+        config.insert(config::ZN_MULTICAST_SCOUTING_KEY, "false".to_string());
+        let client1 = open(config).await.unwrap();
+
+        let mut config = config::client(Some(locator));
+        config.insert(config::ZN_MULTICAST_SCOUTING_KEY, "false".to_string());
+        let client2 = open(config).await.unwrap();
+
+        let resource: ResKey = "/test/error_replies".into();
+        let mut queryable = client1.declare_queryable(&resource, EVAL).await.unwrap();
+        task::sleep(QUERY_TIMEOUT).await;
+
+        for consolidation in [QueryConsolidation::none(), QueryConsolidation::default()] {
+            let mut replies = client2
+                .query(&resource, "", QueryTarget::default(), consolidation)
+                .await
+                .unwrap();
+            let query = queryable
+                .receiver()
+                .next()
+                .timeout(TIMEOUT)
+                .await
+                .unwrap()
+                .unwrap();
+            query.reply(Sample {
+                res_name: "/test/error_replies".to_string(),
+                payload: "value".as_bytes().into(),
+                data_info: None,
+                attachment: None,
+            });
+            query.reply_err(Sample {
+                res_name: "/test/error_replies".to_string(),
+                payload: "failure".as_bytes().into(),
+                data_info: None,
+                attachment: None,
+            });
+            drop(query);
+
+            let mut oks = vec![];
+            let mut errs = vec![];
+            while let Some(reply) = replies.next().timeout(TIMEOUT).await.unwrap() {
+                match reply {
+                    Reply::Ok(reply) => oks.push(reply.data.payload.to_vec()),
+                    Reply::Err(err) => errs.push(err.data.payload.to_vec()),
+                }
+            }
+            assert_eq!(oks, vec![b"value".to_vec()]);
+            assert_eq!(errs, vec![b"failure".to_vec()]);
+        }
+
+        queryable.undeclare().await.unwrap();
+        client2.close().await.unwrap();
+        client1.close().await.unwrap();
+        router.close().await.unwrap();
+    });
+}
+
+#[test]
+fn session_compression() {
+    task::block_on(async {
+        zasync_executor_init!();
+
+        let locator = "tcp/127.0.0.1:17455".to_string();
+        let mut config = local_config();
+        config.insert(config::ZN_MODE_KEY, "router".to_string());
+        config.insert(config::ZN_LISTENER_KEY, locator.clone());
+        // Compression is only enabled on the transports where both sides request it
+        config.insert(config::ZN_COMPRESSION_KEY, "true".to_string());
+        let router = open(config).await.unwrap();
+
+        // Compression requested with the config key
+        let mut config = config::client(Some(locator.clone()));
+        config.insert(config::ZN_MULTICAST_SCOUTING_KEY, "false".to_string());
+        config.insert(config::ZN_COMPRESSION_KEY, "true".to_string());
+        let client1 = open(config).await.unwrap();
+
+        // Compression requested with the endpoint property
+        let mut config = config::client(Some(format!("{}#compression=true", locator)));
+        config.insert(config::ZN_MULTICAST_SCOUTING_KEY, "false".to_string());
+        let client2 = open(config).await.unwrap();
+
+        // No compression
+        let mut config = config::client(Some(locator));
+        config.insert(config::ZN_MULTICAST_SCOUTING_KEY, "false".to_string());
+        let client3 = open(config).await.unwrap();
+
+        let resource: ResKey = "/test/compression".into();
+        let sub_info = SubInfo {
+            reliability: Reliability::Reliable,
+            mode: SubMode::Push,
+            period: None,
+        };
+        let mut subscriber1 = client1
+            .declare_subscriber(&resource, &sub_info)
+            .await
+            .unwrap();
+        let mut subscriber3 = client3
+            .declare_subscriber(&resource, &sub_info)
+            .await
+            .unwrap();
+        task::sleep(QUERY_TIMEOUT).await;
+
+        // A small and a fragmented compressible payload, and an incompressible one
+        let small = br#"{"temperature": 21.5, "humidity": 40.2}"#.to_vec();
+        let large = small.repeat(4_096);
+        let random: Vec<u8> = (0..4_096).map(|_| rand::random::<u8>()).collect();
+        for payload in [small, large, random].iter() {
+            client2
+                .write(&resource, payload.clone().into())
+                .await
+                .unwrap();
+            for subscriber in [&mut subscriber1, &mut subscriber3].iter_mut() {
+                let sample = subscriber
+                    .receiver()
+                    .next()
+                    .timeout(TIMEOUT)
+                    .await
+                    .unwrap()
+                    .unwrap();
+                assert_eq!(&sample.payload.to_vec(), payload);
+            }
+        }
+
+        subscriber1.undeclare().await.unwrap();
+        subscriber3.undeclare().await.unwrap();
+        client3.close().await.unwrap();
+        client2.close().await.unwrap();
+        client1.close().await.unwrap();
+        router.close().await.unwrap();
+    });
+}
+
+#[test]
+fn session_multicast() {
+    task::block_on(async {
+        zasync_executor_init!();
+
+        // Two peers only linked through a multicast group,
+        // a client being connected to the first one
+        let group = "udp/224.0.0.224:17458".to_string();
+        let locator = "tcp/127.0.0.1:17458".to_string();
+        let mut config = local_config();
+        config.insert(config::ZN_LISTENER_KEY, format!("{},{}", group, locator));
+        let peer1 = open(config).await.unwrap();
+
+        let mut config = local_config();
+        config.insert(config::ZN_LISTENER_KEY, group);
+        let peer2 = open(config).await.unwrap();
+
+        let mut config = config::client(Some(locator));
+        config.insert(config::ZN_MULTICAST_SCOUTING_KEY, "false".to_string());
+        let client = open(config).await.unwrap();
+
+        let sub_info = SubInfo {
+            reliability: Reliability::Reliable,
+            mode: SubMode::Push,
+            period: None,
+        };
+        let mut subscriber2 = peer2
+            .declare_subscriber(&"/test/multicast/**".into(), &sub_info)
+            .await
+            .unwrap();
+        let mut client_subscriber = client
+            .declare_subscriber(&"/test/multicast/**".into(), &sub_info)
+            .await
+            .unwrap();
+        task::sleep(QUERY_TIMEOUT).await;
+
+        // The subscriptions are declared on the group
+        peer1
+            .write(&"/test/multicast/peer1".into(), "value".as_bytes().into())
+            .await
+            .unwrap();
+        let sample = subscriber2
+            .receiver()
+            .next()
+            .timeout(TIMEOUT)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(sample.res_name, "/test/multicast/peer1");
+        let sample = client_subscriber
+            .receiver()
+            .next()
+            .timeout(TIMEOUT)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(sample.res_name, "/test/multicast/peer1");
+
+        // The data received from the group is routed to the client
+        peer2
+            .write(&"/test/multicast/peer2".into(), "value".as_bytes().into())
+            .await
+            .unwrap();
+        let sample = client_subscriber
+            .receiver()
+            .next()
+            .timeout(TIMEOUT)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(sample.res_name, "/test/multicast/peer2");
+
+        // Undeclared subscriptions are withdrawn from the group
+        subscriber2.undeclare().await.unwrap();
+        client_subscriber.undeclare().await.unwrap();
+        let mut subscriber1 = peer1
+            .declare_subscriber(&"/test/multicast/**".into(), &sub_info)
+            .await
+            .unwrap();
+        task::sleep(QUERY_TIMEOUT).await;
+        for key in ["/test/multicast/a", "/test/multicast/b"].iter() {
+            peer2
+                .write(&(*key).into(), key.as_bytes().into())
+                .await
+                .unwrap();
+        }
+        for key in ["/test/multicast/a", "/test/multicast/b"].iter() {
+            let sample = subscriber1
+                .receiver()
+                .next()
+                .timeout(TIMEOUT)
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(sample.res_name, *key);
+        }
+
+        subscriber1.undeclare().await.unwrap();
+        client.close().await.unwrap();
+        peer2.close().await.unwrap();
+        peer1.close().await.unwrap();
+    });
+}
+
+#[test]
+fn session_acl() {
+    task::block_on(async {
+        zasync_executor_init!();
+
+        let dir = std::env::temp_dir();
+        let dictionary = dir.join("zenoh-test-session-acl-dictionary.txt");
+        std::fs::write(&dictionary, "alice=alicepwd\nbob=bobpwd\n").unwrap();
+        let rules = dir.join("zenoh-test-session-acl-rules.txt");
+        std::fs::write(
+            &rules,
+            r#"
+            default deny
+            allow publish,queryable,liveliness user:alice /test/acl/alice/**
+            allow subscribe         user:bob   /test/acl/**
+            allow query             user:bob   /test/acl/alice/**
+            allow publish,admin     user:alice /@/router/**
+            allow publish           user:bob   /@/router/**
+            "#,
+        )
+        .unwrap();
+
+        let locator = "tcp/127.0.0.1:17456".to_string();
+        let mut config = local_config();
+        config.insert(config::ZN_MODE_KEY, "router".to_string());
+        config.insert(config::ZN_LISTENER_KEY, locator.clone());
+        config.insert(
+            config::ZN_USER_PASSWORD_DICTIONARY_KEY,
+            dictionary.to_str().unwrap().to_string(),
+        );
+        config.insert(
+            config::ZN_ACL_RULES_KEY,
+            rules.to_str().unwrap().to_string(),
+        );
+        let router = open(config).await.unwrap();
+
+        let client = |user: &str, password: &str| {
+            let mut config = config::client(Some(locator.clone()));
+            config.insert(config::ZN_MULTICAST_SCOUTING_KEY, "false".to_string());
+            config.insert(config::ZN_USER_KEY, user.to_string());
+            config.insert(config::ZN_PASSWORD_KEY, password.to_string());
+            open(config)
+        };
+        let alice = client("alice", "alicepwd").await.unwrap();
+        let bob = client("bob", "bobpwd").await.unwrap();
+
+        let sub_info = SubInfo {
+            reliability: Reliability::Reliable,
+            mode: SubMode::Push,
+            period: None,
+        };
+        let mut subscriber = bob
+            .declare_subscriber(&"/test/acl/**".into(), &sub_info)
+            .await
+            .unwrap();
+        let mut queryable = alice
+            .declare_queryable(&"/test/acl/alice/**".into(), EVAL)
+            .await
+            .unwrap();
+        task::sleep(QUERY_TIMEOUT).await;
+
+        // Alice is not allowed to publish outside of her own keys
+        for key in ["/test/acl/bob/a", "/test/acl/alice/a"].iter() {
+            alice
+                .write(&(*key).into(), key.as_bytes().into())
+                .await
+                .unwrap();
+        }
+        let sample = subscriber
+            .receiver()
+            .next()
+            .timeout(TIMEOUT)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(sample.res_name, "/test/acl/alice/a");
+
+        // Bob is only allowed to query Alice's keys
+        let mut replies = bob
+            .query(
+                &"/test/acl/**".into(),
+                "",
+                QueryTarget::default(),
+                QueryConsolidation::none(),
+            )
+            .await
+            .unwrap();
+        assert!(replies.next().timeout(TIMEOUT).await.unwrap().is_none());
+
+        let mut replies = bob
+            .query(
+                &"/test/acl/alice/q".into(),
+                "",
+                QueryTarget::default(),
+                QueryConsolidation::none(),
+            )
+            .await
+            .unwrap();
+        let query = queryable
+            .receiver()
+            .next()
+            .timeout(TIMEOUT)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(query.res_name, "/test/acl/alice/q");
+        query.reply(Sample {
+            res_name: "/test/acl/alice/q".to_string(),
+            payload: "value".as_bytes().into(),
+            data_info: None,
+            attachment: None,
+        });
+        drop(query);
+        let mut count = 0;
+        while let Some(reply) = replies.next().timeout(TIMEOUT).await.unwrap() {
+            assert!(matches!(reply, Reply::Ok(_)));
+            count += 1;
+        }
+        assert_eq!(count, 1);
+
+        // Alice is not allowed to declare liveliness tokens outside of her own keys
+        let mut liveliness_subscriber = bob
+            .declare_liveliness_subscriber(&"/test/acl/**".into())
+            .await
+            .unwrap();
+        let bob_token = alice
+            .declare_liveliness_token(&"/test/acl/bob/alive".into())
+            .await
+            .unwrap();
+        let alice_token = alice
+            .declare_liveliness_token(&"/test/acl/alice/alive".into())
+            .await
+            .unwrap();
+        let sample = liveliness_subscriber
+            .receiver()
+            .next()
+            .timeout(TIMEOUT)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(sample.res_name, "/test/acl/alice/alive");
+
+        // Only Alice is allowed to write into the config of the router
+        let admin_key = format!("/@/router/{}/config/peers", router.id().await);
+        let mut admin_subscriber = router
+            .declare_subscriber(&admin_key.as_str().into(), &sub_info)
+            .await
+            .unwrap();
+        task::sleep(QUERY_TIMEOUT).await;
+        for session in [&bob, &alice].iter() {
+            session
+                .write(
+                    &admin_key.as_str().into(),
+                    session.id().await.into_bytes().into(),
+                )
+                .await
+                .unwrap();
+        }
+        let sample = admin_subscriber
+            .receiver()
+            .next()
+            .timeout(TIMEOUT)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            sample.payload.contiguous().as_slice(),
+            alice.id().await.as_bytes()
+        );
+        assert!(admin_subscriber
+            .receiver()
+            .next()
+            .timeout(QUERY_TIMEOUT)
+            .await
+            .is_err());
+
+        admin_subscriber.undeclare().await.unwrap();
+        alice_token.undeclare().await.unwrap();
+        bob_token.undeclare().await.unwrap();
+        liveliness_subscriber.undeclare().await.unwrap();
+        queryable.undeclare().await.unwrap();
+        subscriber.undeclare().await.unwrap();
+        bob.close().await.unwrap();
+        alice.close().await.unwrap();
+        router.close().await.unwrap();
+        let _ = std::fs::remove_file(dictionary);
+        let _ = std::fs::remove_file(rules);
+    });
+}
+
+#[test]
+fn session_admin_write() {
+    task::block_on(async {
+        zasync_executor_init!();
+
+        let locator = "tcp/127.0.0.1:17457".to_string();
+        let mut config = local_config();
+        config.insert(config::ZN_MODE_KEY, "router".to_string());
+        config.insert(config::ZN_LISTENER_KEY, locator.clone());
+        let router = open(config).await.unwrap();
+
+        let mut config = config::client(Some(locator));
+        config.insert(config::ZN_MULTICAST_SCOUTING_KEY, "false".to_string());
+        let client = open(config).await.unwrap();
+
+        let sub_info = SubInfo {
+            reliability: Reliability::Reliable,
+            mode: SubMode::Push,
+            period: None,
+        };
+        let admin_key = format!("/@/router/{}/config/peers", router.id().await);
+        let storage_key = format!(
+            "/@/router/{}/plugin/storages/backend/memory/storage/test",
+            router.id().await
+        );
+        let mut admin_subscriber = router
+            .declare_subscriber(&admin_key.as_str().into(), &sub_info)
+            .await
+            .unwrap();
+        let mut subscriber = router
+            .declare_subscriber(&"/test/admin/**".into(), &sub_info)
+            .await
+            .unwrap();
+        let mut storage_subscriber = router
+            .declare_subscriber(&storage_key.as_str().into(), &sub_info)
+            .await
+            .unwrap();
+        task::sleep(QUERY_TIMEOUT).await;
+
+        // Remote peers are not allowed to write into the config of the router without
+        // access control rules
+        for key in [admin_key.as_str(), "/test/admin/a"].iter() {
+            client
+                .write(&(*key).into(), key.as_bytes().into())
+                .await
+                .unwrap();
+        }
+        let sample = subscriber
+            .receiver()
+            .next()
+            .timeout(TIMEOUT)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(sample.res_name, "/test/admin/a");
+        assert!(admin_subscriber
+            .receiver()
+            .next()
+            .timeout(QUERY_TIMEOUT)
+            .await
+            .is_err());
+
+        // Neither through a wildcard key expression
+        client
+            .write(&"/@/router/*/config/*".into(), "peers".as_bytes().into())
+            .await
+            .unwrap();
+        assert!(admin_subscriber
+            .receiver()
+            .next()
+            .timeout(QUERY_TIMEOUT)
+            .await
+            .is_err());
+
+        // The rest of the admin space, like the storages of the plugins, is writable
+        client
+            .write(&storage_key.as_str().into(), "value".as_bytes().into())
+            .await
+            .unwrap();
+        let sample = storage_subscriber
+            .receiver()
+            .next()
+            .timeout(TIMEOUT)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(sample.res_name, storage_key);
+
+        storage_subscriber.undeclare().await.unwrap();
+        admin_subscriber.undeclare().await.unwrap();
+        subscriber.undeclare().await.unwrap();
+        client.close().await.unwrap();
+        router.close().await.unwrap();
+    });
+}
@@ -13,14 +13,15 @@
 //
 use async_std::sync::Arc;
 use std::convert::TryInto;
+use std::time::Duration;
 use uhlc::HLC;
 use zenoh::net::protocol::core::rname::intersect;
 use zenoh::net::protocol::core::{
-    whatami, Channel, CongestionControl, PeerId, QueryConsolidation, QueryTarget, Reliability,
-    ResKey, SubInfo, SubMode, ZInt,
+    queryable, whatami, Channel, CongestionControl, PeerId, QueryConsolidation, QueryTarget,
+    Reliability, ResKey, SubInfo, SubMode, ZInt,
 };
 use zenoh::net::protocol::io::ZBuf;
-use zenoh::net::protocol::proto::{DataInfo, RoutingContext};
+use zenoh::net::protocol::proto::{Attachment, DataInfo, RoutingContext};
 use zenoh::net::routing::router::*;
 use zenoh::net::transport::{DummyPrimitives, Primitives};
 use zenoh_util::zlock;
@@ -31,6 +32,7 @@ fn base_test() {
         PeerId::new(0, [0; 16]),
         whatami::CLIENT,
         Some(Arc::new(HLC::default())),
+        Duration::from_millis(1000),
     );
     let primitives = Arc::new(DummyPrimitives::new());
     let face = tables.open_face(PeerId::new(0, [0; 16]), whatami::CLIENT, primitives);
@@ -126,6 +128,7 @@ fn match_test() {
         PeerId::new(0, [0; 16]),
         whatami::CLIENT,
         Some(Arc::new(HLC::default())),
+        Duration::from_millis(1000),
     );
     let primitives = Arc::new(DummyPrimitives::new());
     let face = tables.open_face(PeerId::new(0, [0; 16]), whatami::CLIENT, primitives);
@@ -161,6 +164,7 @@ fn clean_test() {
         PeerId::new(0, [0; 16]),
         whatami::CLIENT,
         Some(Arc::new(HLC::default())),
+        Duration::from_millis(1000),
     );
 
     let primitives = Arc::new(DummyPrimitives::new());
@@ -335,6 +339,8 @@ fn clean_test() {
 pub struct ClientPrimitives {
     data: std::sync::Mutex<Option<ResKey>>,
     mapping: std::sync::Mutex<std::collections::HashMap<ZInt, String>>,
+    query: std::sync::Mutex<Option<ZInt>>,
+    reply_final: std::sync::Mutex<Option<ZInt>>,
 }
 
 impl ClientPrimitives {
@@ -342,6 +348,8 @@ impl ClientPrimitives {
         ClientPrimitives {
             data: std::sync::Mutex::new(None),
             mapping: std::sync::Mutex::new(std::collections::HashMap::new()),
+            query: std::sync::Mutex::new(None),
+            reply_final: std::sync::Mutex::new(None),
         }
     }
 
@@ -413,6 +421,22 @@ impl Primitives for ClientPrimitives {
     }
     fn forget_queryable(&self, _reskey: &ResKey, _routing_context: Option<RoutingContext>) {}
 
+    fn decl_liveliness_token(&self, _reskey: &ResKey, _routing_context: Option<RoutingContext>) {}
+    fn forget_liveliness_token(&self, _reskey: &ResKey, _routing_context: Option<RoutingContext>) {}
+
+    fn decl_liveliness_subscriber(
+        &self,
+        _reskey: &ResKey,
+        _routing_context: Option<RoutingContext>,
+    ) {
+    }
+    fn forget_liveliness_subscriber(
+        &self,
+        _reskey: &ResKey,
+        _routing_context: Option<RoutingContext>,
+    ) {
+    }
+
     fn send_data(
         &self,
         reskey: &ResKey,
@@ -421,6 +445,7 @@ impl Primitives for ClientPrimitives {
         _congestion_control: CongestionControl,
         _info: Option<DataInfo>,
         _routing_context: Option<RoutingContext>,
+        _attachment: Option<Attachment>,
     ) {
         *zlock!(self.data) = Some(reskey.clone());
     }
@@ -429,11 +454,13 @@ impl Primitives for ClientPrimitives {
         &self,
         _reskey: &ResKey,
         _predicate: &str,
-        _qid: ZInt,
+        qid: ZInt,
         _target: QueryTarget,
         _consolidation: QueryConsolidation,
         _routing_context: Option<RoutingContext>,
+        _attachment: Option<Attachment>,
     ) {
+        *zlock!(self.query) = Some(qid);
     }
 
     fn send_reply_data(
@@ -441,12 +468,16 @@ impl Primitives for ClientPrimitives {
         _qid: ZInt,
         _replier_kind: ZInt,
         _replier_id: PeerId,
+        _is_error: bool,
         _reskey: ResKey,
         _info: Option<DataInfo>,
         _payload: ZBuf,
+        _attachment: Option<Attachment>,
     ) {
     }
-    fn send_reply_final(&self, _qid: ZInt) {}
+    fn send_reply_final(&self, qid: ZInt) {
+        *zlock!(self.reply_final) = Some(qid);
+    }
 
     fn send_pull(
         &self,
@@ -457,6 +488,8 @@ impl Primitives for ClientPrimitives {
     ) {
     }
 
+    fn send_matching_status(&self, _reskey: &ResKey, _matching: bool) {}
+
     fn send_close(&self) {}
 }
 
@@ -466,6 +499,7 @@ fn client_test() {
         PeerId::new(0, [0; 16]),
         whatami::CLIENT,
         Some(Arc::new(HLC::default())),
+        Duration::from_millis(1000),
     );
     let sub_info = SubInfo {
         reliability: Reliability::Reliable,
@@ -568,6 +602,7 @@ fn client_test() {
         None,
         ZBuf::new(),
         None,
+        None,
     );
 
     // functionnal check
@@ -595,6 +630,7 @@ fn client_test() {
         None,
         ZBuf::new(),
         None,
+        None,
     );
 
     // functionnal check
@@ -622,6 +658,7 @@ fn client_test() {
         None,
         ZBuf::new(),
         None,
+        None,
     );
 
     // functionnal check
@@ -649,6 +686,7 @@ fn client_test() {
         None,
         ZBuf::new(),
         None,
+        None,
     );
 
     // functionnal check
@@ -676,6 +714,7 @@ fn client_test() {
         None,
         ZBuf::new(),
         None,
+        None,
     );
 
     // functionnal check
@@ -690,3 +729,81 @@ fn client_test() {
     // mapping strategy check
     // assert_eq!(primitives2.get_last_key().unwrap(), ResKey::RIdWithSuffix(31, "/z2_pub1".to_string()));
 }
+
+#[test]
+fn query_timeout_test() {
+    let router = Router::new(
+        PeerId::new(0, [0; 16]),
+        whatami::CLIENT,
+        Some(Arc::new(HLC::default())),
+        Duration::from_secs(60),
+    );
+
+    let primitives0 = Arc::new(ClientPrimitives::new());
+    let face0 = router.new_primitives(primitives0.clone());
+    face0.decl_queryable(&"/test/query".into(), queryable::EVAL, None);
+
+    let primitives1 = Arc::new(ClientPrimitives::new());
+    let face1 = router.new_primitives(primitives1.clone());
+    let refs = Arc::strong_count(&router.tables);
+    face1.send_query(
+        &"/test/query".into(),
+        "",
+        7,
+        QueryTarget::default(),
+        QueryConsolidation::default(),
+        None,
+        None,
+    );
+    let qid = zlock!(primitives0.query).unwrap();
+    assert_eq!(*zlock!(primitives1.reply_final), None);
+
+    // The final reply finalizes the query and cancels its timeout task
+    face0.send_reply_final(qid);
+    assert_eq!(*zlock!(primitives1.reply_final), Some(7));
+    let mut tries = 0;
+    while Arc::strong_count(&router.tables) > refs {
+        tries += 1;
+        assert!(tries < 50, "The timeout task of the query is still pending");
+        std::thread::sleep(Duration::from_millis(100));
+    }
+}
+
+#[test]
+fn admin_test() {
+    let mut tables = Tables::new(
+        PeerId::new(0, [0; 16]),
+        whatami::CLIENT,
+        Some(Arc::new(HLC::default())),
+        Duration::from_millis(1000),
+    );
+    let primitives = Arc::new(DummyPrimitives::new());
+    let face = tables.open_face(PeerId::new(0, [0; 16]), whatami::CLIENT, primitives);
+    let sub_info = SubInfo {
+        reliability: Reliability::Reliable,
+        mode: SubMode::Push,
+        period: None,
+    };
+    declare_client_subscription(
+        &mut tables,
+        &mut face.upgrade().unwrap(),
+        0,
+        "/four/**",
+        &sub_info,
+    );
+
+    let resources = zenoh::net::routing::admin::resources(&tables);
+    let resource = resources
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|r| r["name"] == "/four/**")
+        .unwrap();
+    assert_eq!(resource["subscribers"].as_array().unwrap().len(), 1);
+    assert_eq!(resource["subscribers"][0]["mode"], "push");
+
+    let routes = zenoh::net::routing::admin::data_routes(&tables, "/four/five");
+    assert_eq!(routes["client_data_route"].as_array().unwrap().len(), 1);
+    let routes = zenoh::net::routing::admin::data_routes(&tables, "/five");
+    assert!(routes["client_data_route"].as_array().unwrap().is_empty());
+}
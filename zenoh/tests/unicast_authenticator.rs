@@ -13,6 +13,8 @@
 //
 use async_std::sync::Arc;
 use async_std::task;
+use ring::rand::SystemRandom;
+use ring::signature::{Ed25519KeyPair, KeyPair};
 use std::any::Any;
 use std::collections::{HashMap, HashSet};
 use std::iter::FromIterator;
@@ -22,7 +24,9 @@ use zenoh::net::protocol::core::{whatami, PeerId};
 use zenoh::net::protocol::proto::ZenohMessage;
 #[cfg(feature = "zero-copy")]
 use zenoh::net::transport::unicast::authenticator::SharedMemoryAuthenticator;
-use zenoh::net::transport::unicast::authenticator::UserPasswordAuthenticator;
+use zenoh::net::transport::unicast::authenticator::{
+    AuthenticatedPeerLink, PeerAuthenticatorTrait, PubKeyAuthenticator, UserPasswordAuthenticator,
+};
 use zenoh::net::transport::{
     DummyTransportPeerEventHandler, TransportEventHandler, TransportManager,
     TransportManagerConfig, TransportManagerConfigUnicast, TransportMulticast,
@@ -278,6 +282,137 @@ async fn authenticator_user_password(endpoint: &EndPoint) {
     task::sleep(SLEEP).await;
 }
 
+async fn authenticator_public_key(endpoint: &EndPoint) {
+    let rng = SystemRandom::new();
+    let generate = || {
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&rng).unwrap();
+        let keypair = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+        (
+            pkcs8.as_ref().to_vec(),
+            keypair.public_key().as_ref().to_vec(),
+        )
+    };
+    let (router_key, router_pub) = generate();
+    let (client01_key, client01_pub) = generate();
+    let (client02_key, client02_pub) = generate();
+    let (client03_key, client03_pub) = generate();
+
+    /* [CLIENT] */
+    let client01_id = PeerId::new(1, [1u8; PeerId::MAX_SIZE]);
+    let client02_id = PeerId::new(1, [2u8; PeerId::MAX_SIZE]);
+    let client03_id = client01_id;
+
+    /* [ROUTER] */
+    let router_id = PeerId::new(1, [0u8; PeerId::MAX_SIZE]);
+    let router_handler = Arc::new(SHRouterAuthenticator::new());
+    // Create the router transport manager trusting only client01 and client03
+    let trusted = HashSet::from_iter(vec![client01_pub, client03_pub]);
+    let peer_authenticator_router =
+        Arc::new(PubKeyAuthenticator::new(&router_key, trusted).unwrap());
+    let config = TransportManagerConfig::builder()
+        .whatami(whatami::ROUTER)
+        .pid(router_id)
+        .unicast(
+            TransportManagerConfigUnicast::builder()
+                .peer_authenticator(HashSet::from_iter(vec![peer_authenticator_router
+                    .clone()
+                    .into()]))
+                .build(),
+        )
+        .build(router_handler.clone());
+    let router_manager = TransportManager::new(config);
+
+    // Create the transport managers for the clients, all of them trusting the router
+    let make_client = |id: PeerId, key: &[u8]| {
+        let trusted = HashSet::from_iter(vec![router_pub.clone()]);
+        let peer_authenticator = PubKeyAuthenticator::new(key, trusted).unwrap();
+        let config = TransportManagerConfig::builder()
+            .whatami(whatami::CLIENT)
+            .pid(id)
+            .unicast(
+                TransportManagerConfigUnicast::builder()
+                    .peer_authenticator(HashSet::from_iter(vec![peer_authenticator.into()]))
+                    .build(),
+            )
+            .build(Arc::new(SHClientAuthenticator));
+        TransportManager::new(config)
+    };
+    let client01_manager = make_client(client01_id, &client01_key);
+    let client02_manager = make_client(client02_id, &client02_key);
+    let client03_manager = make_client(client03_id, &client03_key);
+
+    /* [1] */
+    println!("\nTransport Authenticator PublicKey [1a1]");
+    // Add the locator on the router
+    let res = router_manager.add_listener(endpoint.clone()).await;
+    println!("Transport Authenticator PublicKey [1a1]: {:?}", res);
+    assert!(res.is_ok());
+    println!("Transport Authenticator PublicKey [1a2]");
+    let locators = router_manager.get_listeners();
+    println!("Transport Authenticator PublicKey [1a2]: {:?}", locators);
+    assert_eq!(locators.len(), 1);
+
+    /* [2] */
+    // Open a first transport from a trusted client to the router
+    // -> This should be accepted
+    println!("Transport Authenticator PublicKey [2a1]");
+    let res = client01_manager.open_transport(endpoint.clone()).await;
+    println!("Transport Authenticator PublicKey [2a1]: {:?}", res);
+    assert!(res.is_ok());
+    let c_ses1 = res.unwrap();
+
+    /* [3] */
+    // Open a second transport from an untrusted client to the router
+    // -> This should be rejected
+    println!("Transport Authenticator PublicKey [3a1]");
+    let res = client02_manager.open_transport(endpoint.clone()).await;
+    println!("Transport Authenticator PublicKey [3a1]: {:?}", res);
+    assert!(res.is_err());
+
+    /* [4] */
+    // Trust the client02 key on the router
+    let res = peer_authenticator_router
+        .add_trusted_key(client02_pub)
+        .await;
+    assert!(res.is_ok());
+    // Open a third transport from the client to the router
+    // -> This should be accepted
+    println!("Transport Authenticator PublicKey [4a1]");
+    let res = client02_manager.open_transport(endpoint.clone()).await;
+    println!("Transport Authenticator PublicKey [4a1]: {:?}", res);
+    assert!(res.is_ok());
+    let c_ses2 = res.unwrap();
+
+    /* [5] */
+    // Open a fourth transport with the PeerId of client01 but a different key
+    // -> This should be rejected
+    println!("Transport Authenticator PublicKey [5a1]");
+    let res = client03_manager.open_transport(endpoint.clone()).await;
+    println!("Transport Authenticator PublicKey [5a1]: {:?}", res);
+    assert!(res.is_err());
+
+    /* [6] */
+    println!("Transport Authenticator PublicKey [6a1]");
+    let res = c_ses1.close().await;
+    println!("Transport Authenticator PublicKey [6a1]: {:?}", res);
+    assert!(res.is_ok());
+    println!("Transport Authenticator PublicKey [6a2]");
+    let res = c_ses2.close().await;
+    println!("Transport Authenticator PublicKey [6a2]: {:?}", res);
+    assert!(res.is_ok());
+
+    task::sleep(SLEEP).await;
+
+    /* [7] */
+    // Perform clean up of the open locators
+    println!("Transport Authenticator PublicKey [7a1]");
+    let res = router_manager.del_listener(endpoint).await;
+    println!("Transport Authenticator PublicKey [7a2]: {:?}", res);
+    assert!(res.is_ok());
+
+    task::sleep(SLEEP).await;
+}
+
 #[cfg(feature = "zero-copy")]
 async fn authenticator_shared_memory(endpoint: &EndPoint) {
     /* [CLIENT] */
@@ -351,6 +486,70 @@ async fn authenticator_shared_memory(endpoint: &EndPoint) {
     task::sleep(SLEEP).await;
 }
 
+#[test]
+fn authenticator_public_key_peer_id() {
+    task::block_on(async {
+        zasync_executor_init!();
+
+        let rng = SystemRandom::new();
+        let generate = || {
+            let pkcs8 = Ed25519KeyPair::generate_pkcs8(&rng).unwrap();
+            let keypair = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).unwrap();
+            (
+                pkcs8.as_ref().to_vec(),
+                keypair.public_key().as_ref().to_vec(),
+            )
+        };
+        let (initiator_key, initiator_pub) = generate();
+        let (responder_key, responder_pub) = generate();
+        let initiator =
+            PubKeyAuthenticator::new(&initiator_key, HashSet::from_iter(vec![responder_pub]))
+                .unwrap();
+        let responder =
+            PubKeyAuthenticator::new(&responder_key, HashSet::from_iter(vec![initiator_pub]))
+                .unwrap();
+
+        let initiator_id = PeerId::new(1, [1u8; PeerId::MAX_SIZE]);
+        let responder_id = PeerId::new(1, [2u8; PeerId::MAX_SIZE]);
+        let other_id = PeerId::new(1, [3u8; PeerId::MAX_SIZE]);
+        let sn_resolution = 128;
+        let endpoint: EndPoint = "tcp/127.0.0.1:11448".parse().unwrap();
+        let link = AuthenticatedPeerLink {
+            src: endpoint.locator.clone(),
+            dst: endpoint.locator,
+            peer_id: None,
+        };
+
+        // The responder signs the InitAck for its own PeerId: the InitAck must be rejected
+        // when it is presented with a PeerId that does not belong to the responder
+        for (claimed_id, accepted) in [(other_id, false), (responder_id, true)].iter() {
+            let init_syn = initiator
+                .get_init_syn_properties(&link, &initiator_id)
+                .await
+                .unwrap();
+            let init_ack = responder
+                .handle_init_syn(
+                    &link,
+                    &initiator_id,
+                    &responder_id,
+                    sn_resolution,
+                    &init_syn.properties,
+                )
+                .await
+                .unwrap();
+            let res = initiator
+                .handle_init_ack(&link, claimed_id, sn_resolution, &init_ack.properties)
+                .await;
+            println!(
+                "Transport Authenticator PublicKey PeerId {}: {:?}",
+                claimed_id,
+                res.as_ref().map(|_| ())
+            );
+            assert_eq!(res.is_ok(), *accepted);
+        }
+    });
+}
+
 #[cfg(feature = "transport_tcp")]
 #[test]
 fn authenticator_tcp() {
@@ -361,6 +560,7 @@ fn authenticator_tcp() {
     let endpoint: EndPoint = "tcp/127.0.0.1:11447".parse().unwrap();
     task::block_on(async {
         authenticator_user_password(&endpoint).await;
+        authenticator_public_key(&endpoint).await;
         #[cfg(feature = "zero-copy")]
         authenticator_shared_memory(&endpoint).await;
     });
@@ -376,6 +576,7 @@ fn authenticator_udp() {
     let endpoint: EndPoint = "udp/127.0.0.1:11447".parse().unwrap();
     task::block_on(async {
         authenticator_user_password(&endpoint).await;
+        authenticator_public_key(&endpoint).await;
         #[cfg(feature = "zero-copy")]
         authenticator_shared_memory(&endpoint).await;
     });
@@ -394,6 +595,7 @@ fn authenticator_unix() {
         .unwrap();
     task::block_on(async {
         authenticator_user_password(&endpoint).await;
+        authenticator_public_key(&endpoint).await;
         #[cfg(feature = "zero-copy")]
         authenticator_shared_memory(&endpoint).await;
     });
@@ -494,6 +696,7 @@ tOzot3pwe+3SJtpk90xAQrABEO0Zh2unrC8i83ySfg==
 
     task::block_on(async {
         authenticator_user_password(&endpoint).await;
+        authenticator_public_key(&endpoint).await;
         #[cfg(feature = "zero-copy")]
         authenticator_shared_memory(&endpoint).await;
     });
@@ -592,6 +795,7 @@ tOzot3pwe+3SJtpk90xAQrABEO0Zh2unrC8i83ySfg==
 
     task::block_on(async {
         authenticator_user_password(&endpoint).await;
+        authenticator_public_key(&endpoint).await;
         #[cfg(feature = "zero-copy")]
         authenticator_shared_memory(&endpoint).await;
     });
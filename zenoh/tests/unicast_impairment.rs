@@ -0,0 +1,224 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ADLINK zenoh team, <zenoh@adlink-labs.tech>
+//
+use async_std::prelude::*;
+use async_std::sync::Arc;
+use async_std::task;
+use std::any::Any;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use zenoh::net::link::{EndPoint, Link};
+use zenoh::net::protocol::core::{
+    whatami, Channel, CongestionControl, PeerId, Priority, Reliability, ResKey,
+};
+use zenoh::net::protocol::io::ZBuf;
+use zenoh::net::protocol::proto::ZenohMessage;
+use zenoh::net::transport::{
+    DummyTransportEventHandler, TransportEventHandler, TransportManager, TransportManagerConfig,
+    TransportMulticast, TransportMulticastEventHandler, TransportPeer, TransportPeerEventHandler,
+    TransportUnicast,
+};
+use zenoh_util::core::ZResult;
+use zenoh_util::zasync_executor_init;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_secs(1);
+const SLEEP_COUNT: Duration = Duration::from_millis(10);
+
+const MSG_COUNT: usize = 1_000;
+const MSG_SIZE: usize = 1_024;
+
+// Transport Handler for the router
+#[derive(Default)]
+struct SHRouter {
+    count: Arc<AtomicUsize>,
+}
+
+impl TransportEventHandler for SHRouter {
+    fn new_unicast(
+        &self,
+        _peer: TransportPeer,
+        _transport: TransportUnicast,
+    ) -> ZResult<Arc<dyn TransportPeerEventHandler>> {
+        Ok(Arc::new(SCRouter {
+            count: self.count.clone(),
+        }))
+    }
+
+    fn new_multicast(
+        &self,
+        _transport: TransportMulticast,
+    ) -> ZResult<Arc<dyn TransportMulticastEventHandler>> {
+        panic!();
+    }
+}
+
+// Transport Callback for the router
+struct SCRouter {
+    count: Arc<AtomicUsize>,
+}
+
+impl TransportPeerEventHandler for SCRouter {
+    fn handle_message(&self, _message: ZenohMessage) -> ZResult<()> {
+        self.count.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn new_link(&self, _link: Link) {}
+    fn del_link(&self, _link: Link) {}
+    fn closing(&self) {}
+    fn closed(&self) {}
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+// Sends MSG_COUNT messages from a client opening an impaired link to a router,
+// and returns the number of messages received by the router.
+async fn run(listener: &EndPoint, endpoint: &EndPoint, channel: Channel) -> usize {
+    // Define client and router IDs
+    let client_id = PeerId::new(1, [0u8; PeerId::MAX_SIZE]);
+    let router_id = PeerId::new(1, [1u8; PeerId::MAX_SIZE]);
+
+    // Create the router transport manager
+    let router_handler = Arc::new(SHRouter::default());
+    let config = TransportManagerConfig::builder()
+        .pid(router_id)
+        .whatami(whatami::ROUTER)
+        .build(router_handler.clone());
+    let router_manager = TransportManager::new(config);
+
+    // Create the client transport manager
+    let config = TransportManagerConfig::builder()
+        .whatami(whatami::CLIENT)
+        .pid(client_id)
+        .build(Arc::new(DummyTransportEventHandler));
+    let client_manager = TransportManager::new(config);
+
+    // Create the listener on the router
+    println!("Add locator: {}", listener);
+    let _ = router_manager
+        .add_listener(listener.clone())
+        .timeout(TIMEOUT)
+        .await
+        .unwrap()
+        .unwrap();
+
+    // Open an impaired transport with the router
+    println!("Opening transport with {:?}", endpoint);
+    let client_transport = client_manager
+        .open_transport(endpoint.clone())
+        .timeout(TIMEOUT)
+        .await
+        .unwrap()
+        .unwrap();
+
+    // Send the messages
+    let key = ResKey::RName("/test".to_string());
+    let payload = ZBuf::from(vec![0u8; MSG_SIZE]);
+    let message = ZenohMessage::make_data(
+        key,
+        payload,
+        channel,
+        CongestionControl::Block,
+        None,
+        None,
+        None,
+        None,
+    );
+    for i in 0..MSG_COUNT {
+        client_transport.schedule(message.clone()).unwrap();
+        // Let the messages be sent in several batches
+        if i % 10 == 0 {
+            task::sleep(SLEEP_COUNT).await;
+        }
+    }
+
+    // Wait for the router to stop receiving messages
+    let count = async {
+        let mut count = 0;
+        loop {
+            task::sleep(SLEEP).await;
+            let c = router_handler.count.load(Ordering::SeqCst);
+            println!("Received {}/{}", c, MSG_COUNT);
+            if c == MSG_COUNT || (c > 0 && c == count) {
+                break c;
+            }
+            count = c;
+        }
+    };
+    let count = count.timeout(TIMEOUT).await.unwrap();
+
+    // Close the transport
+    client_transport
+        .close()
+        .timeout(TIMEOUT)
+        .await
+        .unwrap()
+        .unwrap();
+
+    // Stop the locators on the manager
+    println!("Del locator: {}", listener);
+    router_manager
+        .del_listener(listener)
+        .timeout(TIMEOUT)
+        .await
+        .unwrap()
+        .unwrap();
+
+    // Wait a little bit
+    task::sleep(SLEEP).await;
+
+    count
+}
+
+#[cfg(feature = "transport_inproc")]
+#[test]
+fn transport_unicast_impairment_inproc() {
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+
+    // Delayed writes are still received in order on a reliable link
+    let listener: EndPoint = "inproc/zenoh-test-impairment-0".parse().unwrap();
+    let endpoint: EndPoint = "inproc/zenoh-test-impairment-0#delay=20ms;jitter=5ms;seed=1"
+        .parse()
+        .unwrap();
+    let channel = Channel {
+        priority: Priority::default(),
+        reliability: Reliability::Reliable,
+    };
+    let count = task::block_on(run(&listener, &endpoint, channel));
+    assert_eq!(count, MSG_COUNT);
+}
+
+#[cfg(feature = "transport_udp")]
+#[test]
+fn transport_unicast_impairment_udp() {
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+
+    // Lost and reordered writes are missing on a best effort link
+    let listener: EndPoint = "udp/127.0.0.1:16447".parse().unwrap();
+    let endpoint: EndPoint = "udp/127.0.0.1:16447?loss=0.1;reorder=0.05;seed=1"
+        .parse()
+        .unwrap();
+    let channel = Channel {
+        priority: Priority::default(),
+        reliability: Reliability::BestEffort,
+    };
+    let count = task::block_on(run(&listener, &endpoint, channel));
+    assert!(count > 0 && count < MSG_COUNT);
+}
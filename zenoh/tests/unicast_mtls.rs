@@ -0,0 +1,176 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ADLINK zenoh team, <zenoh@adlink-labs.tech>
+//
+#[cfg(feature = "transport_tls")]
+mod tests {
+    use async_std::prelude::*;
+    use async_std::sync::Arc;
+    use async_std::task;
+    use rcgen::{BasicConstraints, Certificate, CertificateParams, DnType, IsCa};
+    use std::time::Duration;
+    use zenoh::net::link::tls::config::*;
+    use zenoh::net::link::EndPoint;
+    use zenoh::net::protocol::core::{whatami, PeerId};
+    use zenoh::net::transport::{
+        DummyTransportPeerEventHandler, TransportEventHandler, TransportManager,
+        TransportManagerConfig, TransportMulticast, TransportMulticastEventHandler, TransportPeer,
+        TransportPeerEventHandler, TransportUnicast,
+    };
+    use zenoh_util::core::ZResult;
+    use zenoh_util::properties::Properties;
+    use zenoh_util::zasync_executor_init;
+
+    const TIMEOUT: Duration = Duration::from_secs(60);
+    const SLEEP: Duration = Duration::from_millis(100);
+
+    struct SHMutualTls;
+
+    impl TransportEventHandler for SHMutualTls {
+        fn new_unicast(
+            &self,
+            _peer: TransportPeer,
+            _transport: TransportUnicast,
+        ) -> ZResult<Arc<dyn TransportPeerEventHandler>> {
+            Ok(Arc::new(DummyTransportPeerEventHandler))
+        }
+
+        fn new_multicast(
+            &self,
+            _transport: TransportMulticast,
+        ) -> ZResult<Arc<dyn TransportMulticastEventHandler>> {
+            panic!();
+        }
+    }
+
+    fn make_cert(cn: &str, ca: Option<&Certificate>) -> (Certificate, String) {
+        let mut params = CertificateParams::new(vec!["localhost".to_string()]);
+        params.distinguished_name.push(DnType::CommonName, cn);
+        if ca.is_none() {
+            params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        }
+        let cert = Certificate::from_params(params).unwrap();
+        let pem = match ca {
+            Some(ca) => cert.serialize_pem_with_signer(ca).unwrap(),
+            None => cert.serialize_pem().unwrap(),
+        };
+        (cert, pem)
+    }
+
+    fn make_manager(pid: PeerId, whatami: whatami::Type) -> TransportManager {
+        let config = TransportManagerConfig::builder()
+            .whatami(whatami)
+            .pid(pid)
+            .build(Arc::new(SHMutualTls));
+        TransportManager::new(config)
+    }
+
+    fn make_endpoint(locator: &str, properties: Vec<(&str, String)>) -> EndPoint {
+        let mut endpoint: EndPoint = locator.parse().unwrap();
+        let mut config = Properties::default();
+        for (k, v) in properties.into_iter() {
+            config.insert(k.to_string(), v);
+        }
+        endpoint.config = Some(Arc::new(config));
+        endpoint
+    }
+
+    async fn run(locator: &str) {
+        let router_id = PeerId::rand();
+        let client01_id = PeerId::rand();
+        let client02_id = PeerId::rand();
+
+        let (ca, ca_pem) = make_cert("zenoh test CA", None);
+        let (server, server_pem) = make_cert(&router_id.to_string(), Some(&ca));
+        let (client01, client01_pem) = make_cert(&client01_id.to_string(), Some(&ca));
+
+        // Create the router with a listener requiring client certificates
+        let router_manager = make_manager(router_id, whatami::ROUTER);
+        let endpoint = make_endpoint(
+            locator,
+            vec![
+                (
+                    TLS_SERVER_PRIVATE_KEY_RAW,
+                    server.serialize_private_key_pem(),
+                ),
+                (TLS_SERVER_CERTIFICATE_RAW, server_pem),
+                (TLS_CLIENT_CA_CERTIFICATE_RAW, ca_pem.clone()),
+            ],
+        );
+        let res = router_manager.add_listener(endpoint).await;
+        println!("Mutual TLS [1a]: {:?}", res);
+        assert!(res.is_ok());
+
+        // A client whose certificate matches its PeerId is accepted
+        let client01_manager = make_manager(client01_id, whatami::CLIENT);
+        let endpoint = make_endpoint(
+            locator,
+            vec![
+                (TLS_ROOT_CA_CERTIFICATE_RAW, ca_pem.clone()),
+                (
+                    TLS_CLIENT_PRIVATE_KEY_RAW,
+                    client01.serialize_private_key_pem(),
+                ),
+                (TLS_CLIENT_CERTIFICATE_RAW, client01_pem.clone()),
+            ],
+        );
+        let res = client01_manager.open_transport(endpoint).await;
+        println!("Mutual TLS [2a]: {:?}", res);
+        assert!(res.is_ok());
+        let c_ses1 = res.unwrap();
+        let check = async {
+            while router_manager.get_transport(&client01_id).is_none() {
+                task::sleep(SLEEP).await;
+            }
+        };
+        check.timeout(TIMEOUT).await.unwrap();
+
+        // A client presenting the certificate of another PeerId is rejected
+        let client02_manager = make_manager(client02_id, whatami::CLIENT);
+        let endpoint = make_endpoint(
+            locator,
+            vec![
+                (TLS_ROOT_CA_CERTIFICATE_RAW, ca_pem.clone()),
+                (
+                    TLS_CLIENT_PRIVATE_KEY_RAW,
+                    client01.serialize_private_key_pem(),
+                ),
+                (TLS_CLIENT_CERTIFICATE_RAW, client01_pem),
+            ],
+        );
+        let res = client02_manager.open_transport(endpoint).await;
+        println!("Mutual TLS [3a]: {:?}", res);
+        assert!(res.is_err());
+
+        // A client without certificate is rejected
+        let endpoint = make_endpoint(locator, vec![(TLS_ROOT_CA_CERTIFICATE_RAW, ca_pem)]);
+        let res = client02_manager.open_transport(endpoint).await;
+        println!("Mutual TLS [4a]: {:?}", res);
+        assert!(res.is_err());
+        assert!(router_manager.get_transport(&client02_id).is_none());
+
+        let res = c_ses1.close().await;
+        println!("Mutual TLS [5a]: {:?}", res);
+        assert!(res.is_ok());
+        let res = router_manager.del_listener(&locator.parse().unwrap()).await;
+        println!("Mutual TLS [5b]: {:?}", res);
+        assert!(res.is_ok());
+    }
+
+    #[test]
+    fn mutual_tls() {
+        task::block_on(async {
+            zasync_executor_init!();
+        });
+        task::block_on(run("tls/localhost:8450"));
+    }
+}
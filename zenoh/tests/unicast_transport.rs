@@ -420,6 +420,148 @@ fn transport_unicast_unix_only() {
     let _ = std::fs::remove_file("zenoh-test-unix-socket-5.sock.lock");
 }
 
+#[cfg(all(feature = "transport_unixsock-dgram", target_family = "unix"))]
+#[test]
+fn transport_unicast_unix_dgram_only() {
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+
+    let _ = std::fs::remove_file("zenoh-test-unix-dgram-2.sock");
+    let _ = std::fs::remove_file("zenoh-test-unix-dgram-3.sock");
+    // Define the locators
+    let endpoints: Vec<EndPoint> = vec![
+        "unixsock-dgram/zenoh-test-unix-dgram-2.sock"
+            .parse()
+            .unwrap(),
+        "unixsock-dgram/zenoh-test-unix-dgram-3.sock#mtu=4096"
+            .parse()
+            .unwrap(),
+    ];
+    // Define the reliability and congestion control
+    let channel = [
+        Channel {
+            priority: Priority::default(),
+            reliability: Reliability::BestEffort,
+        },
+        Channel {
+            priority: Priority::RealTime,
+            reliability: Reliability::BestEffort,
+        },
+    ];
+    // Run
+    task::block_on(run(&endpoints, &channel, &MSG_SIZE_NOFRAG));
+    let _ = std::fs::remove_file("zenoh-test-unix-dgram-2.sock");
+    let _ = std::fs::remove_file("zenoh-test-unix-dgram-3.sock");
+    let _ = std::fs::remove_file("zenoh-test-unix-dgram-2.sock.lock");
+    let _ = std::fs::remove_file("zenoh-test-unix-dgram-3.sock.lock");
+}
+
+#[cfg(all(
+    feature = "transport_unixsock-stream",
+    feature = "transport_unixsock-dgram",
+    target_os = "linux"
+))]
+#[test]
+fn transport_unicast_unix_abstract() {
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+
+    // Define the locators in the abstract namespace
+    let endpoints: Vec<EndPoint> = vec![
+        "unixsock-stream/@zenoh-test-unix-socket-abstract-1"
+            .parse()
+            .unwrap(),
+        "unixsock-dgram/@zenoh-test-unix-dgram-abstract-1"
+            .parse()
+            .unwrap(),
+    ];
+    // Define the reliability and congestion control
+    let channel = [
+        Channel {
+            priority: Priority::default(),
+            reliability: Reliability::BestEffort,
+        },
+        Channel {
+            priority: Priority::RealTime,
+            reliability: Reliability::BestEffort,
+        },
+    ];
+    // Run
+    task::block_on(run(&endpoints, &channel, &MSG_SIZE_NOFRAG));
+}
+
+#[cfg(feature = "transport_ws")]
+#[test]
+fn transport_unicast_ws_only() {
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+
+    // Define the locators
+    let endpoints: Vec<EndPoint> = vec![
+        "ws/127.0.0.1:10460".parse().unwrap(),
+        "ws/[::1]:10460".parse().unwrap(),
+    ];
+    // Define the reliability and congestion control
+    let channel = [
+        Channel {
+            priority: Priority::default(),
+            reliability: Reliability::Reliable,
+        },
+        Channel {
+            priority: Priority::default(),
+            reliability: Reliability::BestEffort,
+        },
+        Channel {
+            priority: Priority::RealTime,
+            reliability: Reliability::Reliable,
+        },
+        Channel {
+            priority: Priority::RealTime,
+            reliability: Reliability::BestEffort,
+        },
+    ];
+    // Run
+    task::block_on(run(&endpoints, &channel, &MSG_SIZE_ALL));
+}
+
+#[cfg(feature = "transport_inproc")]
+#[test]
+fn transport_unicast_inproc_only() {
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+
+    // Define the locators
+    let endpoints: Vec<EndPoint> = vec![
+        "inproc/zenoh-test-inproc-2".parse().unwrap(),
+        "inproc/zenoh-test-inproc-3#mtu=1024".parse().unwrap(),
+    ];
+    // Define the reliability and congestion control
+    let channel = [
+        Channel {
+            priority: Priority::default(),
+            reliability: Reliability::Reliable,
+        },
+        Channel {
+            priority: Priority::default(),
+            reliability: Reliability::BestEffort,
+        },
+        Channel {
+            priority: Priority::RealTime,
+            reliability: Reliability::Reliable,
+        },
+        Channel {
+            priority: Priority::RealTime,
+            reliability: Reliability::BestEffort,
+        },
+    ];
+    // Run
+    task::block_on(run(&endpoints, &channel, &MSG_SIZE_ALL));
+}
+
 #[cfg(all(feature = "transport_tcp", feature = "transport_udp"))]
 #[test]
 fn transport_unicast_tcp_udp() {
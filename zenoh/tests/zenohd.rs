@@ -0,0 +1,94 @@
+//
+// Copyright (c) 2017, 2020 ADLINK Technology Inc.
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ADLINK zenoh team, <zenoh@adlink-labs.tech>
+//
+#![cfg(unix)]
+use async_std::prelude::*;
+use async_std::task;
+use std::process::{Command, Stdio};
+use std::time::Duration;
+use zenoh::net::info::ZN_INFO_ROUTER_PID_KEY;
+use zenoh::net::*;
+use zenoh_util::zasync_executor_init;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_millis(100);
+
+async fn router_pids(session: &Session) -> String {
+    session
+        .info()
+        .await
+        .get(&ZN_INFO_ROUTER_PID_KEY)
+        .cloned()
+        .unwrap_or_default()
+}
+
+#[test]
+fn zenohd_graceful_shutdown() {
+    task::block_on(async {
+        zasync_executor_init!();
+
+        let socket = std::env::temp_dir().join(format!("zenoh-test-zenohd-{}", std::process::id()));
+        let _ = std::fs::remove_file(&socket);
+        let locator = format!("unixsock-stream/{}", socket.display());
+        let mut zenohd = Command::new(env!("CARGO_BIN_EXE_zenohd"))
+            .args([
+                "--plugin-nolookup",
+                "--no-multicast-scouting",
+                "-l",
+                &locator,
+            ])
+            .stdout(Stdio::null())
+            .spawn()
+            .unwrap();
+
+        let mut config = config::client(Some(locator));
+        config.insert(config::ZN_MULTICAST_SCOUTING_KEY, "false".to_string());
+        let session = async {
+            loop {
+                match open(config.clone()).await {
+                    Ok(session) => break session,
+                    Err(_) => task::sleep(SLEEP).await,
+                }
+            }
+        }
+        .timeout(TIMEOUT)
+        .await
+        .unwrap();
+        assert!(!router_pids(&session).await.is_empty());
+
+        // On SIGTERM, zenohd closes its sessions and exits successfully
+        let status = Command::new("kill")
+            .args(["-TERM", &zenohd.id().to_string()])
+            .status()
+            .unwrap();
+        assert!(status.success());
+        let status = task::spawn_blocking(move || zenohd.wait())
+            .timeout(TIMEOUT)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(status.success(), "{:?}", status);
+
+        async {
+            while !router_pids(&session).await.is_empty() {
+                task::sleep(SLEEP).await;
+            }
+        }
+        .timeout(TIMEOUT)
+        .await
+        .unwrap();
+
+        session.close().await.unwrap();
+        let _ = std::fs::remove_file(&socket);
+    });
+}